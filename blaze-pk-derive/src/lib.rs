@@ -2,8 +2,12 @@ use darling::FromAttributes;
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
-    parse_macro_input, punctuated::Punctuated, token::Comma, Data, DataEnum, DeriveInput, Field,
-    Fields, Ident, 
+    braced,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    token::Comma,
+    Data, DataEnum, DeriveInput, Expr, Field, Fields, Ident, Token,
 };
 
 /// Options for a component field on the components enum
@@ -19,7 +23,7 @@ struct ComponentOpts {
 /// these traits are required for routing
 ///
 /// ```
-/// use blaze_pk::{PacketComponents, PacketComponent}
+/// use blaze_pk::{PacketComponents, PacketComponent};
 ///
 /// #[derive(Debug, Hash, PartialEq, Eq, PacketComponents)]
 /// pub enum Components {
@@ -27,7 +31,7 @@ struct ComponentOpts {
 ///     Component1(Component1)
 /// }
 ///
-/// #[derive(Debug, Hash, PartialEq, Eq, PacketComponents)]
+/// #[derive(Debug, Hash, PartialEq, Eq, PacketComponent)]
 /// pub enum Component1 {
 ///     #[command(target = 0x14)]
 ///     Value,
@@ -126,9 +130,9 @@ struct CommandOpts {
 /// these traits are required for routing
 ///
 /// ```
-/// use blaze_pk::{PacketComponent}
+/// use blaze_pk::{PacketComponent};
 ///
-/// #[derive(Debug, Hash, PartialEq, Eq, PacketComponents)]
+/// #[derive(Debug, Hash, PartialEq, Eq, PacketComponent)]
 /// pub enum Component1 {
 ///     #[command(target = 0x14)]
 ///     Value,
@@ -221,3 +225,137 @@ pub fn derive_component(input: TokenStream) -> TokenStream {
     }
     .into()
 }
+
+/// Single route entry within the routes macro
+struct RouteEntry {
+    /// The component expression for the route
+    component: Expr,
+    /// The handler expression for the route
+    handler: Expr,
+}
+
+impl Parse for RouteEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let component: Expr = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let handler: Expr = input.parse()?;
+        Ok(Self { component, handler })
+    }
+}
+
+/// Input for the routes macro the router followed by
+/// the braced route entries
+struct RoutesInput {
+    /// The router expression
+    router: Expr,
+    /// The routes to add to the router
+    routes: Punctuated<RouteEntry, Comma>,
+}
+
+impl Parse for RoutesInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let router: Expr = input.parse()?;
+        input.parse::<Comma>()?;
+        let content;
+        braced!(content in input);
+        let routes = content.parse_terminated(RouteEntry::parse, Comma)?;
+        Ok(Self { router, routes })
+    }
+}
+
+/// Macro for adding many routes to a router at once. Registering
+/// the same component more than once is a compile error rather
+/// than the later route silently replacing the earlier one
+///
+/// ```
+/// use blaze_pk::{router::Router, routes, PacketComponent, PacketComponents};
+///
+/// #[derive(Debug, Hash, PartialEq, Eq, PacketComponents)]
+/// enum Components {
+///     #[component(target = 0x9)]
+///     Util(Util),
+/// }
+///
+/// #[derive(Debug, Hash, PartialEq, Eq, PacketComponent)]
+/// enum Util {
+///     #[command(target = 0x2)]
+///     Ping,
+///     #[command(target = 0x3)]
+///     Pong,
+/// }
+///
+/// async fn handle_ping(_state: &mut ()) {}
+/// async fn handle_pong(_state: &mut ()) {}
+///
+/// let mut router: Router<Components, ()> = Router::new();
+/// routes!(router, {
+///     Components::Util(Util::Ping) => handle_ping,
+///     Components::Util(Util::Pong) => handle_pong,
+/// });
+/// ```
+///
+/// Duplicates are detected by comparing the tokens of the component
+/// expressions so only components written identically are caught.
+/// The same component written differently (i.e. through a `use` or
+/// a constant) is not detected by the macro but is still caught by
+/// the debug assertion in `Router::route` when the routes are added
+///
+/// ```compile_fail
+/// # use blaze_pk::{router::Router, routes, PacketComponent, PacketComponents};
+/// #
+/// # #[derive(Debug, Hash, PartialEq, Eq, PacketComponents)]
+/// # enum Components {
+/// #     #[component(target = 0x9)]
+/// #     Util(Util),
+/// # }
+/// #
+/// # #[derive(Debug, Hash, PartialEq, Eq, PacketComponent)]
+/// # enum Util {
+/// #     #[command(target = 0x2)]
+/// #     Ping,
+/// # }
+/// #
+/// # async fn handle_ping(_state: &mut ()) {}
+/// #
+/// let mut router: Router<Components, ()> = Router::new();
+/// routes!(router, {
+///     Components::Util(Util::Ping) => handle_ping,
+///     Components::Util(Util::Ping) => handle_ping,
+/// });
+/// ```
+#[proc_macro]
+pub fn routes(input: TokenStream) -> TokenStream {
+    let RoutesInput { router, routes } = parse_macro_input!(input);
+
+    let mut seen: Vec<String> = Vec::with_capacity(routes.len());
+    let mut errors = Vec::new();
+    let mut calls = Vec::with_capacity(routes.len());
+
+    for RouteEntry { component, handler } in routes {
+        // Components are compared using their tokens so only
+        // identically written components are detected
+        let key = quote!(#component).to_string();
+        if seen.contains(&key) {
+            errors.push(
+                syn::Error::new_spanned(&component, "Duplicate route for component")
+                    .to_compile_error(),
+            );
+        } else {
+            seen.push(key);
+        }
+
+        calls.push(quote! { router.route(#component, #handler); });
+    }
+
+    if !errors.is_empty() {
+        return quote! { #(#errors)* }.into();
+    }
+
+    quote! {
+        {
+            let router: &mut blaze_pk::router::Router<_, _> = &mut #router;
+            #(#calls)*
+        }
+    }
+    .into()
+}
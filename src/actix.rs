@@ -0,0 +1,129 @@
+//! Integration with actix for framing packets over actix connections
+//! ([`PacketFramed`]). The actix codec traits are the same tokio-util
+//! traits implemented by [`PacketCodec`] so the same codec is used for
+//! both runtimes
+//!
+//! ```
+//! use actix_codec::{AsyncRead, AsyncWrite};
+//! use blaze_pk::actix::{framed, PacketFramed};
+//!
+//! fn accept<T: AsyncRead + AsyncWrite>(io: T) -> PacketFramed<T> {
+//!     framed(io)
+//! }
+//! ```
+
+use crate::packet::PacketCodec;
+use actix_codec::{AsyncRead, AsyncWrite, Framed};
+
+/// Actix framed connection of packets
+pub type PacketFramed<T> = Framed<T, PacketCodec>;
+
+/// Wraps the provided actix IO in a framed connection
+/// for reading and writing packets
+///
+/// `io` The underlying IO of the connection
+pub fn framed<T>(io: T) -> PacketFramed<T>
+where
+    T: AsyncRead + AsyncWrite,
+{
+    Framed::new(io, PacketCodec)
+}
+
+#[cfg(test)]
+mod test {
+    use super::framed;
+    use crate::packet::{
+        test::{check_framing, framing_packets},
+        Packet, PacketCodec,
+    };
+    use actix_codec::{AsyncRead, AsyncWrite, ReadBuf};
+    use std::{
+        io,
+        pin::{pin, Pin},
+        task::{Context, Poll, Waker},
+    };
+
+    /// In memory IO reading from a fixed buffer and
+    /// collecting the written bytes
+    #[derive(Default)]
+    struct MemoryIo {
+        /// The bytes available for reading
+        read: Vec<u8>,
+        /// The bytes written
+        written: Vec<u8>,
+    }
+
+    impl AsyncRead for MemoryIo {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            let length = buf.remaining().min(self.read.len());
+            buf.put_slice(&self.read[..length]);
+            self.read.drain(..length);
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl AsyncWrite for MemoryIo {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.written.extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Tests the codec framing through the actix codec traits
+    #[test]
+    fn test_actix_framing() {
+        fn assert_actix_codec<C>(codec: C) -> C
+        where
+            C: actix_codec::Decoder + actix_codec::Encoder<Packet>,
+        {
+            codec
+        }
+        check_framing(assert_actix_codec(PacketCodec));
+    }
+
+    /// Tests packets written to an actix framed connection
+    /// can be read back from the written bytes
+    #[test]
+    fn test_actix_framed() {
+        let packets = framing_packets();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut writer = pin!(framed(MemoryIo::default()));
+        for packet in &packets {
+            writer.as_mut().write(packet.clone()).unwrap();
+        }
+        assert!(writer.as_mut().flush::<Packet>(&mut cx).is_ready());
+        let written = std::mem::take(&mut writer.io_mut().written);
+
+        let mut reader = pin!(framed(MemoryIo {
+            read: written,
+            written: Vec::new(),
+        }));
+        for packet in &packets {
+            let Poll::Ready(Some(Ok(read))) = reader.as_mut().next_item(&mut cx) else {
+                panic!("Expected packet to be read");
+            };
+            assert_eq!(&read, packet);
+        }
+        assert!(matches!(
+            reader.as_mut().next_item(&mut cx),
+            Poll::Ready(None)
+        ));
+    }
+}
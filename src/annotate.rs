@@ -0,0 +1,467 @@
+//! Annotating decoder ([`annotate_frame`]) which records the byte range of
+//! every part of a packet frame that it visits. The annotations are kept
+//! even when decoding stops at an error so that hex view debuggers can
+//! highlight which bytes correspond to which field and where decoding
+//! went wrong.
+//!
+//! ```
+//! use blaze_pk::{
+//!     annotate::{annotate, AnnotationKind},
+//!     tag::TdfType,
+//!     writer::TdfWriter,
+//! };
+//!
+//! let mut writer = TdfWriter::default();
+//! writer.tag_str(b"NAME", "Test");
+//! writer.tag(b"ID", TdfType::VarInt);
+//!
+//! let annotated = annotate(&writer.buffer);
+//! // The ID value is missing
+//! assert!(annotated.error.is_some());
+//!
+//! let value = annotated.at(5).last().unwrap();
+//! assert_eq!(value.kind, AnnotationKind::Value(TdfType::String));
+//! assert_eq!(value.path.to_string(), "NAME");
+//! assert_eq!(value.range, 4..10);
+//! ```
+
+use crate::{
+    diff::{DiffPath, PathSegment},
+    error::{DecodeError, DecodeResult},
+    reader::TdfReader,
+    tag::{Tag, TdfType},
+    types::UNION_UNSET,
+};
+use std::{cmp::Reverse, ops::Range};
+
+/// Fields of the packet header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderField {
+    /// Length of the contents
+    Length,
+    /// Component of the packet
+    Component,
+    /// Command of the packet
+    Command,
+    /// Error code of the packet
+    Error,
+    /// Type of the packet
+    Type,
+    /// Options byte (Whether the length is extended)
+    Options,
+    /// ID of the packet
+    Id,
+    /// Extended upper bytes of the length
+    ExtendedLength,
+}
+
+/// The part of the frame an annotation describes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnnotationKind {
+    /// Field of the packet header
+    Header(HeaderField),
+    /// Complete tagged field including the tag and value
+    Field,
+    /// Encoded tag and type of a field
+    Tag {
+        /// The decoded tag
+        tag: Tag,
+        /// The type of the field
+        ty: TdfType,
+    },
+    /// Value of the provided type
+    Value(TdfType),
+    /// Key of a map entry
+    MapKey(TdfType),
+    /// Type of the values in a list or the keys and values of a map
+    ElementType,
+    /// Length prefix of a blob, string, list, map or var int list
+    Length,
+    /// The 2 byte at the start of some groups
+    GroupStart,
+    /// The zero byte terminating a group
+    GroupEnd,
+    /// Key byte of a union
+    UnionKey,
+    /// Byte marking whether a generic value is set
+    GenericSet,
+    /// Type id of a generic value
+    GenericId,
+}
+
+/// Annotated range of bytes within the frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    /// The range of bytes within the frame
+    pub range: Range<usize>,
+    /// The path to the field the bytes belong to
+    pub path: DiffPath,
+    /// The part of the frame the bytes are
+    pub kind: AnnotationKind,
+    /// Whether the part was completely decoded, false for the
+    /// parts that were being decoded when an error occurred
+    pub complete: bool,
+}
+
+/// Annotations for a frame along with the error that stopped decoding
+#[derive(Debug)]
+pub struct Annotated {
+    /// The annotations ordered by their start offset with the
+    /// outer annotations before the annotations within them
+    pub annotations: Vec<Annotation>,
+    /// The error that stopped decoding if decoding failed
+    pub error: Option<DecodeError>,
+}
+
+impl Annotated {
+    /// Returns the annotations containing the provided offset
+    /// from the outermost annotation to the innermost annotation
+    ///
+    /// `offset` The offset within the frame
+    pub fn at(&self, offset: usize) -> impl Iterator<Item = &Annotation> {
+        self.annotations
+            .iter()
+            .filter(move |annotation| annotation.range.contains(&offset))
+    }
+}
+
+/// Annotates a complete packet frame including the packet header
+///
+/// `bytes` The bytes of the frame
+pub fn annotate_frame(bytes: &[u8]) -> Annotated {
+    let mut annotator = Annotator::new(bytes);
+    let result = annotator.header().and_then(|length| {
+        let start = annotator.reader.cursor;
+        // Truncated contents are still annotated up to the end of the bytes
+        let end = start.saturating_add(length).min(bytes.len());
+        annotator.reader = TdfReader::new(&bytes[..end]);
+        annotator.reader.cursor = start;
+        annotator.fields()?;
+        if end - start < length {
+            return Err(DecodeError::UnexpectedEof {
+                cursor: end,
+                wanted: length - (end - start),
+                remaining: 0,
+            });
+        }
+        Ok(())
+    });
+    annotator.finish(result)
+}
+
+/// Annotates encoded packet contents without a header
+///
+/// `bytes` The encoded contents
+pub fn annotate(bytes: &[u8]) -> Annotated {
+    let mut annotator = Annotator::new(bytes);
+    let result = annotator.fields();
+    annotator.finish(result)
+}
+
+/// Decoder recording annotations for the parts it visits
+struct Annotator<'a> {
+    /// The reader for the frame
+    reader: TdfReader<'a>,
+    /// The path to the current value
+    path: Vec<PathSegment>,
+    /// The recorded annotations
+    annotations: Vec<Annotation>,
+}
+
+impl<'a> Annotator<'a> {
+    /// Creates a new annotator for the provided bytes
+    ///
+    /// `bytes` The bytes to annotate
+    fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            reader: TdfReader::new(bytes),
+            path: Vec::new(),
+            annotations: Vec::new(),
+        }
+    }
+
+    /// Sorts the annotations and creates the annotated output
+    ///
+    /// `result` The result of decoding
+    fn finish(mut self, result: DecodeResult<()>) -> Annotated {
+        self.annotations
+            .sort_by_key(|annotation| (annotation.range.start, Reverse(annotation.range.end)));
+        Annotated {
+            annotations: self.annotations,
+            error: result.err(),
+        }
+    }
+
+    /// Runs the provided decode function annotating the bytes it
+    /// consumes. The annotation is marked incomplete if it fails
+    ///
+    /// `kind`   The kind of annotation
+    /// `decode` The decode function
+    fn annotated<T>(
+        &mut self,
+        kind: AnnotationKind,
+        decode: impl FnOnce(&mut Self) -> DecodeResult<T>,
+    ) -> DecodeResult<T> {
+        let start = self.reader.cursor;
+        let result = decode(self);
+        self.push(start, kind, result.is_ok());
+        result
+    }
+
+    /// Adds an annotation for the bytes from the provided
+    /// start offset to the current cursor position
+    ///
+    /// `start`    The start offset of the annotation
+    /// `kind`     The kind of annotation
+    /// `complete` Whether the part was completely decoded
+    fn push(&mut self, start: usize, kind: AnnotationKind, complete: bool) {
+        self.annotations.push(Annotation {
+            range: start..self.reader.cursor.max(start),
+            path: DiffPath(self.path.clone()),
+            kind,
+            complete,
+        });
+    }
+
+    /// Annotates the packet header returning the length of the contents
+    fn header(&mut self) -> DecodeResult<usize> {
+        let length = self.header_u16(HeaderField::Length)?;
+        self.header_u16(HeaderField::Component)?;
+        self.header_u16(HeaderField::Command)?;
+        self.header_u16(HeaderField::Error)?;
+        self.annotated(AnnotationKind::Header(HeaderField::Type), |this| {
+            this.reader.read_byte()
+        })?;
+        let options = self.annotated(AnnotationKind::Header(HeaderField::Options), |this| {
+            this.reader.read_byte()
+        })?;
+        self.header_u16(HeaderField::Id)?;
+
+        if options & 0x10 == 0 {
+            return Ok(length);
+        }
+        let extended = self.header_u16(HeaderField::ExtendedLength)?;
+        Ok(length | (extended << 16))
+    }
+
+    /// Annotates a big endian u16 header field
+    ///
+    /// `field` The header field
+    fn header_u16(&mut self, field: HeaderField) -> DecodeResult<usize> {
+        self.annotated(AnnotationKind::Header(field), |this| {
+            let bytes = this.reader.read_slice(2)?;
+            Ok(u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+        })
+    }
+
+    /// Annotates fields until the end of the bytes
+    fn fields(&mut self) -> DecodeResult<()> {
+        while !self.reader.is_empty() {
+            self.field()?;
+        }
+        Ok(())
+    }
+
+    /// Annotates a tagged field
+    fn field(&mut self) -> DecodeResult<()> {
+        let start = self.reader.cursor;
+        let tagged = match self.reader.read_tag() {
+            Ok(tagged) => tagged,
+            Err(err) => {
+                self.push(start, AnnotationKind::Field, false);
+                return Err(err);
+            }
+        };
+
+        self.path.push(PathSegment::Tag(tagged.tag));
+        let kind = AnnotationKind::Tag {
+            tag: tagged.tag,
+            ty: tagged.ty,
+        };
+        self.push(start, kind, true);
+        let result = self.value(tagged.ty, AnnotationKind::Value(tagged.ty));
+        self.push(start, AnnotationKind::Field, result.is_ok());
+        self.path.pop();
+        result
+    }
+
+    /// Annotates a value of the provided type
+    ///
+    /// `ty`   The type of value
+    /// `kind` The kind of annotation for the value
+    fn value(&mut self, ty: TdfType, kind: AnnotationKind) -> DecodeResult<()> {
+        self.annotated(kind, |this| this.value_contents(ty))
+    }
+
+    /// Annotates the parts of a value of the provided type
+    ///
+    /// `ty` The type of value
+    fn value_contents(&mut self, ty: TdfType) -> DecodeResult<()> {
+        match ty {
+            TdfType::VarInt | TdfType::TimeValue => {
+                self.reader.read_u64()?;
+            }
+            TdfType::String | TdfType::Blob => {
+                let length = self.length()?;
+                self.reader.read_slice(length)?;
+            }
+            TdfType::Group => {
+                if self.reader.buffer.get(self.reader.cursor) == Some(&2) {
+                    self.annotated(AnnotationKind::GroupStart, |this| this.reader.read_byte())?;
+                }
+                loop {
+                    let next = self.reader.buffer.get(self.reader.cursor).copied();
+                    match next {
+                        Some(0) => {
+                            self.annotated(AnnotationKind::GroupEnd, |this| {
+                                this.reader.read_byte()
+                            })?;
+                            break;
+                        }
+                        Some(_) => self.field()?,
+                        None => {
+                            return Err(DecodeError::UnexpectedEof {
+                                cursor: self.reader.cursor,
+                                wanted: 1,
+                                remaining: 0,
+                            })
+                        }
+                    }
+                }
+            }
+            TdfType::List => {
+                let ty = self.element_type()?;
+                let length = self.length()?;
+                for index in 0..length {
+                    self.path.push(PathSegment::Index(index));
+                    let result = self.value(ty, AnnotationKind::Value(ty));
+                    self.path.pop();
+                    result?;
+                }
+            }
+            TdfType::Map => {
+                let key_ty = self.element_type()?;
+                let value_ty = self.element_type()?;
+                let length = self.length()?;
+                for index in 0..length {
+                    self.path.push(PathSegment::Index(index));
+                    let result = self
+                        .value(key_ty, AnnotationKind::MapKey(key_ty))
+                        .and_then(|_| self.value(value_ty, AnnotationKind::Value(value_ty)));
+                    self.path.pop();
+                    result?;
+                }
+            }
+            TdfType::Union => {
+                let key =
+                    self.annotated(AnnotationKind::UnionKey, |this| this.reader.read_byte())?;
+                if key != UNION_UNSET {
+                    self.field()?;
+                }
+            }
+            TdfType::VarIntList => {
+                let length = self.length()?;
+                for index in 0..length {
+                    self.path.push(PathSegment::Index(index));
+                    let result =
+                        self.value(TdfType::VarInt, AnnotationKind::Value(TdfType::VarInt));
+                    self.path.pop();
+                    result?;
+                }
+            }
+            TdfType::Pair => {
+                self.reader.read_u64()?;
+                self.reader.read_u64()?;
+            }
+            TdfType::Triple => {
+                self.reader.read_u64()?;
+                self.reader.read_u64()?;
+                self.reader.read_u64()?;
+            }
+            TdfType::Float => {
+                self.reader.read_f32()?;
+            }
+            TdfType::Generic => {
+                let set =
+                    self.annotated(AnnotationKind::GenericSet, |this| this.reader.read_byte())?;
+                if set != 0 {
+                    self.annotated(AnnotationKind::GenericId, |this| this.reader.read_u64())?;
+                    self.field()?;
+                }
+                self.annotated(AnnotationKind::GroupEnd, |this| this.reader.read_byte())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Annotates a length prefix
+    fn length(&mut self) -> DecodeResult<usize> {
+        self.annotated(AnnotationKind::Length, |this| this.reader.read_usize())
+    }
+
+    /// Annotates the type of list or map elements
+    fn element_type(&mut self) -> DecodeResult<TdfType> {
+        self.annotated(AnnotationKind::ElementType, |this| this.reader.read_type())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{annotate_frame, AnnotationKind, HeaderField};
+    use crate::{
+        packet::{Packet, PacketHeader},
+        tag::{Tag, TdfType},
+        writer::TdfWriter,
+    };
+    use bytes::BytesMut;
+
+    /// Tests annotating a frame with nested values
+    #[test]
+    fn test_annotate_frame() {
+        let mut writer = TdfWriter::default();
+        writer.group(b"USER", |writer| {
+            writer.tag_str(b"NAME", "Test");
+        });
+        writer.tag_value(b"IDS", &vec![1u32, 2]);
+
+        let mut frame = BytesMut::new();
+        Packet::raw(PacketHeader::request(1, 0x1, 0x2), writer.into()).write(&mut frame);
+
+        let annotated = annotate_frame(&frame);
+        assert!(annotated.error.is_none());
+
+        let kinds: Vec<&AnnotationKind> = annotated.at(2).map(|value| &value.kind).collect();
+        assert_eq!(kinds, [&AnnotationKind::Header(HeaderField::Component)]);
+
+        // Character within the NAME string
+        let at: Vec<_> = annotated.at(22).collect();
+        assert_eq!(at.len(), 4);
+        assert_eq!(at[0].kind, AnnotationKind::Field);
+        assert_eq!(at[1].kind, AnnotationKind::Value(TdfType::Group));
+        assert_eq!(at[2].kind, AnnotationKind::Field);
+        assert_eq!(at[3].kind, AnnotationKind::Value(TdfType::String));
+        assert_eq!(at[3].path.to_string(), "USER.NAME");
+        assert_eq!(at[3].range, 20..26);
+
+        let second = annotated.annotations.last().unwrap();
+        assert_eq!(second.path.to_string(), "IDS[1]");
+        assert_eq!(second.range, frame.len() - 1..frame.len());
+
+        // Truncated frame keeps the annotations before the error
+        let annotated = annotate_frame(&frame[..22]);
+        assert!(annotated.error.is_some());
+        let tag = annotated
+            .annotations
+            .iter()
+            .find(|value| matches!(value.kind, AnnotationKind::Tag { .. }))
+            .unwrap();
+        assert_eq!(
+            tag.kind,
+            AnnotationKind::Tag {
+                tag: Tag::from(b"USER"),
+                ty: TdfType::Group
+            }
+        );
+        assert!(annotated.annotations.iter().any(|value| !value.complete));
+    }
+}
@@ -0,0 +1,439 @@
+//! Anonymization of packet contents ([`Anonymizer`]) so that captures can
+//! be shared without leaking personal data. Packets are decoded into dynamic
+//! values, the fields matching the configured tag paths are scrubbed and the
+//! contents are re-encoded.
+//!
+//! Tag paths are the tags of the fields separated by `.` (e.g. `USER.MAIL`)
+//! where `*` matches any single tag and `**` matches any number of tags. Lists
+//! and map values are transparent so `USER.MAIL` also matches the `MAIL`
+//! field of each group within a `USER` list
+//!
+//! ```
+//! use blaze_pk::{
+//!     anonymize::{Anonymizer, Scrub},
+//!     packet::{Packet, PacketHeader},
+//!     value::{TdfFields, TdfValue},
+//!     writer::TdfWriter,
+//! };
+//!
+//! let anonymizer = Anonymizer::new()
+//!     .rule("**.MAIL", Scrub::Hash)
+//!     .rule("AUTH", Scrub::Redact)
+//!     .rule("USER.ADDR", Scrub::Remove);
+//!
+//! let mut writer = TdfWriter::default();
+//! writer.tag_str(b"AUTH", "secret-token");
+//! writer.group(b"USER", |writer| {
+//!     writer.tag_str(b"MAIL", "test@example.com");
+//!     writer.tag_u32(b"ADDR", 0x7F000001);
+//! });
+//!
+//! let packet = Packet::raw(PacketHeader::notify(0x1, 0x2), writer.into());
+//! let packet = anonymizer.anonymize_packet(&packet).unwrap();
+//! let fields: TdfFields = packet.decode().unwrap();
+//!
+//! assert_eq!(fields.get(b"AUTH"), Some(&TdfValue::String(String::new())));
+//! let Some(TdfValue::Group { fields: user, .. }) = fields.get(b"USER") else { panic!() };
+//! assert_eq!(user.len(), 1);
+//! assert_ne!(user[0].value, TdfValue::String("test@example.com".to_string()));
+//! ```
+
+use crate::{
+    codec::{Decodable, Encodable},
+    error::DecodeResult,
+    packet::Packet,
+    reader::TdfReader,
+    tag::Tag,
+    types::UNION_UNSET,
+    value::{TdfField, TdfFields, TdfValue},
+};
+use std::{collections::hash_map::RandomState, hash::BuildHasher};
+
+/// Action taken on the values of matching fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scrub {
+    /// Replaces the value with an empty value of the same type
+    /// (zero, empty string, empty list, etc)
+    Redact,
+    /// Replaces the value with a hash of the value so that equal
+    /// values remain equal across packets without revealing the value
+    Hash,
+    /// Removes the field entirely
+    Remove,
+}
+
+/// Segment of a tag path
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// Field with a specific tag
+    Tag(Tag),
+    /// Field with any tag
+    Any,
+    /// Any number of fields with any tag
+    AnyDepth,
+}
+
+/// Rule scrubbing the fields matching a tag path
+#[derive(Debug, Clone)]
+struct Rule {
+    /// The tag path segments
+    path: Vec<Segment>,
+    /// The action to take on matching fields
+    scrub: Scrub,
+}
+
+/// Transform scrubbing configured fields from packet contents. Hashes
+/// are keyed with random keys created with the anonymizer so the same
+/// anonymizer must be used for all the packets of a capture for their
+/// hashes to be consistent
+#[derive(Debug, Clone, Default)]
+pub struct Anonymizer {
+    /// The scrubbing rules
+    rules: Vec<Rule>,
+    /// The random keys for hashing values
+    keys: RandomState,
+}
+
+impl Anonymizer {
+    /// Creates a new anonymizer without any rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule scrubbing the fields matching the provided tag path.
+    /// When multiple rules match a field the first rule is used
+    ///
+    /// `path`  The tag path of the fields (e.g. `USER.MAIL` or `**.MAIL`)
+    /// `scrub` The action to take on matching fields
+    pub fn rule(mut self, path: &str, scrub: Scrub) -> Self {
+        let path = path
+            .split('.')
+            .map(|segment| match segment {
+                "*" => Segment::Any,
+                "**" => Segment::AnyDepth,
+                tag => Segment::Tag(Tag::from(tag.as_bytes())),
+            })
+            .collect();
+        self.rules.push(Rule { path, scrub });
+        self
+    }
+
+    /// Anonymizes the contents of the provided packet returning
+    /// a new packet with the same header and scrubbed contents
+    ///
+    /// `packet` The packet to anonymize
+    pub fn anonymize_packet(&self, packet: &Packet) -> DecodeResult<Packet> {
+        let mut fields = TdfFields::decode(&mut TdfReader::new(&packet.contents))?;
+        self.anonymize_fields(&mut fields);
+        Ok(Packet::raw(packet.header, fields.encode_bytes()))
+    }
+
+    /// Anonymizes the provided captured packets. The IP addresses of the
+    /// packet sources and destinations are replaced with hashed addresses
+    /// in private ranges
+    ///
+    /// `packets` The captured packets to anonymize
+    #[cfg(feature = "pcap")]
+    pub fn anonymize_captured(
+        &self,
+        packets: &[crate::capture::CapturedPacket],
+    ) -> DecodeResult<Vec<crate::capture::CapturedPacket>> {
+        packets
+            .iter()
+            .map(|captured| {
+                Ok(crate::capture::CapturedPacket {
+                    timestamp: captured.timestamp,
+                    source: self.anonymize_addr(captured.source),
+                    destination: self.anonymize_addr(captured.destination),
+                    packet: self.anonymize_packet(&captured.packet)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Replaces the IP of the provided address with a hashed address in
+    /// a private range (10.0.0.0/8 or fd00::/8) keeping the port
+    ///
+    /// `addr` The address to anonymize
+    #[cfg(feature = "pcap")]
+    fn anonymize_addr(&self, addr: std::net::SocketAddr) -> std::net::SocketAddr {
+        use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+        let hash = self.keys.hash_one(addr.ip());
+        let ip = match addr.ip() {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from((hash as u32 & 0x00FF_FFFF) | 0x0A00_0000)),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(
+                (hash as u128 & 0x00FF_FFFF_FFFF_FFFF) | (0xFD << 120),
+            )),
+        };
+        std::net::SocketAddr::new(ip, addr.port())
+    }
+
+    /// Anonymizes the provided fields in place
+    ///
+    /// `fields` The fields to anonymize
+    pub fn anonymize_fields(&self, fields: &mut TdfFields) {
+        let mut path = Vec::new();
+        self.fields(&mut fields.0, &mut path);
+    }
+
+    /// Scrubs the matching fields within the provided fields
+    ///
+    /// `fields` The fields to scrub
+    /// `path`   The tag path to the fields
+    fn fields(&self, fields: &mut Vec<TdfField>, path: &mut Vec<Tag>) {
+        fields.retain_mut(|field| {
+            path.push(field.tag);
+            let scrub = self
+                .rules
+                .iter()
+                .find(|rule| matches_path(&rule.path, path))
+                .map(|rule| rule.scrub);
+            let keep = match scrub {
+                Some(Scrub::Remove) => false,
+                Some(scrub) => {
+                    self.scrub(&mut field.value, scrub);
+                    true
+                }
+                None => {
+                    self.nested(&mut field.value, path);
+                    true
+                }
+            };
+            path.pop();
+            keep
+        });
+    }
+
+    /// Scrubs the matching fields nested within the provided value
+    ///
+    /// `value` The value to search
+    /// `path`  The tag path to the value
+    fn nested(&self, value: &mut TdfValue, path: &mut Vec<Tag>) {
+        match value {
+            TdfValue::Group { fields, .. } => self.fields(fields, path),
+            TdfValue::List { values, .. } => {
+                values.iter_mut().for_each(|value| self.nested(value, path))
+            }
+            TdfValue::Map { entries, .. } => entries
+                .iter_mut()
+                .for_each(|(_, value)| self.nested(value, path)),
+            TdfValue::Union {
+                value: Some(field), ..
+            } => {
+                let mut fields = vec![(**field).clone()];
+                self.fields(&mut fields, path);
+                match fields.pop() {
+                    Some(scrubbed) => **field = scrubbed,
+                    // Removing the field of a union makes the union unset
+                    None => {
+                        *value = TdfValue::Union {
+                            key: UNION_UNSET,
+                            value: None,
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Scrubs the provided value. Hashing nested values hashes
+    /// each of the values within
+    ///
+    /// `value` The value to scrub
+    /// `scrub` The action to take
+    fn scrub(&self, value: &mut TdfValue, scrub: Scrub) {
+        let hash = scrub == Scrub::Hash;
+        match value {
+            TdfValue::VarInt(value) => *value = if hash { self.hash_int(*value) } else { 0 },
+            TdfValue::String(value) => {
+                *value = if hash {
+                    format!("{:016x}", self.keys.hash_one(value.as_str()))
+                } else {
+                    String::new()
+                }
+            }
+            TdfValue::Blob(value) => {
+                if hash {
+                    let bytes = self.keys.hash_one(value.as_slice()).to_be_bytes();
+                    let length = value.len();
+                    *value = bytes.iter().copied().cycle().take(length).collect();
+                } else {
+                    value.clear();
+                }
+            }
+            TdfValue::Group { fields, .. } => {
+                if hash {
+                    fields
+                        .iter_mut()
+                        .for_each(|field| self.scrub(&mut field.value, scrub));
+                } else {
+                    fields.clear();
+                }
+            }
+            TdfValue::List { values, .. } => {
+                if hash {
+                    values.iter_mut().for_each(|value| self.scrub(value, scrub));
+                } else {
+                    values.clear();
+                }
+            }
+            TdfValue::Map { entries, .. } => {
+                if hash {
+                    entries
+                        .iter_mut()
+                        .for_each(|(_, value)| self.scrub(value, scrub));
+                } else {
+                    entries.clear();
+                }
+            }
+            TdfValue::Union { key, value } => match value {
+                Some(field) if hash => self.scrub(&mut field.value, scrub),
+                _ => {
+                    *key = UNION_UNSET;
+                    *value = None;
+                }
+            },
+            TdfValue::VarIntList(values) => {
+                if hash {
+                    values
+                        .iter_mut()
+                        .for_each(|value| *value = self.hash_int(*value));
+                } else {
+                    values.clear();
+                }
+            }
+            TdfValue::Pair(a, b) => {
+                for value in [a, b] {
+                    *value = if hash { self.hash_int(*value) } else { 0 };
+                }
+            }
+            TdfValue::Triple(a, b, c) => {
+                for value in [a, b, c] {
+                    *value = if hash { self.hash_int(*value) } else { 0 };
+                }
+            }
+            TdfValue::Float(value) => {
+                *value = if hash {
+                    (self.keys.hash_one(value.to_bits()) as u32 as f32) / u32::MAX as f32
+                } else {
+                    0.0
+                }
+            }
+            TdfValue::TimeValue(value) => *value = if hash { self.hash_int(*value) } else { 0 },
+            TdfValue::Generic(value) => match value {
+                Some((_, field)) if hash => self.scrub(&mut field.value, scrub),
+                _ => *value = None,
+            },
+        }
+    }
+
+    /// Hashes an integer value keeping values that fit in 32 bits
+    /// within 32 bits so they can still be decoded as 32 bit values
+    ///
+    /// `value` The value to hash
+    fn hash_int(&self, value: u64) -> u64 {
+        let hash = self.keys.hash_one(value);
+        if value <= u32::MAX as u64 {
+            hash & u32::MAX as u64
+        } else {
+            hash
+        }
+    }
+}
+
+/// Checks whether the provided tag path matches the path segments
+///
+/// `segments` The segments to match
+/// `path`     The tag path
+fn matches_path(segments: &[Segment], path: &[Tag]) -> bool {
+    match segments.split_first() {
+        None => path.is_empty(),
+        Some((Segment::AnyDepth, rest)) => {
+            (0..=path.len()).any(|skip| matches_path(rest, &path[skip..]))
+        }
+        Some((segment, rest)) => match path.split_first() {
+            Some((tag, path)) => {
+                let matches = match segment {
+                    Segment::Tag(expected) => expected == tag,
+                    _ => true,
+                };
+                matches && matches_path(rest, path)
+            }
+            None => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Anonymizer, Scrub};
+    use crate::{
+        tag::{Tag, TdfType},
+        value::{TdfField, TdfFields, TdfValue},
+    };
+
+    /// Tests hashed values are consistent and that rules match nested fields
+    #[test]
+    fn test_anonymize_fields() {
+        let anonymizer = Anonymizer::new()
+            .rule("PLYR.MAIL", Scrub::Hash)
+            .rule("*.ADDR", Scrub::Redact)
+            .rule("TOKN", Scrub::Remove);
+
+        let field = |tag: &[u8], value: TdfValue| TdfField {
+            tag: Tag::from(tag),
+            value,
+        };
+        let player = |mail: &str| TdfValue::Group {
+            two: false,
+            fields: vec![
+                field(b"MAIL", TdfValue::String(mail.to_string())),
+                field(b"ADDR", TdfValue::Pair(0x7F000001, 3659)),
+            ],
+        };
+        let mut fields = TdfFields(vec![
+            field(b"TOKN", TdfValue::String("secret".to_string())),
+            field(
+                b"PLYR",
+                TdfValue::Map {
+                    key_ty: TdfType::VarInt,
+                    value_ty: TdfType::Group,
+                    entries: vec![
+                        (TdfValue::VarInt(1), player("a@example.com")),
+                        (TdfValue::VarInt(2), player("a@example.com")),
+                    ],
+                },
+            ),
+            field(b"MAIL", TdfValue::String("kept@example.com".to_string())),
+        ]);
+
+        anonymizer.anonymize_fields(&mut fields);
+
+        assert!(fields.get(b"TOKN").is_none());
+        assert_eq!(
+            fields.get(b"MAIL"),
+            Some(&TdfValue::String("kept@example.com".to_string()))
+        );
+
+        let Some(TdfValue::Map { entries, .. }) = fields.get(b"PLYR") else {
+            panic!("Missing players")
+        };
+        let players: Vec<&Vec<TdfField>> = entries
+            .iter()
+            .map(|(_, value)| match value {
+                TdfValue::Group { fields, .. } => fields,
+                _ => panic!("Expected group"),
+            })
+            .collect();
+
+        // Map values are transparent so the path matches the MAIL field of each player
+        assert_eq!(players[0][0].value, players[1][0].value);
+        assert_ne!(
+            players[0][0].value,
+            TdfValue::String("a@example.com".to_string())
+        );
+        assert_eq!(players[0][1].value, TdfValue::Pair(0, 0));
+    }
+}
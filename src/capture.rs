@@ -0,0 +1,816 @@
+//! Reading Blaze packets from network captures ([`CaptureReader`]) saved
+//! by tools such as Wireshark or tcpdump in the pcap or pcapng formats.
+//!
+//! The TCP streams within the capture are reassembled and decoded into
+//! [`Packet`]s for each direction of the stream. Only plaintext streams
+//! can be decoded, streams using SSL must be decrypted before capturing.
+//!
+//! Supported link types are Ethernet, Linux cooked capture (v1 and v2),
+//! BSD loopback and raw IP over both IPv4 and IPv6
+//!
+//! Captures containing multiple clients can be split into the packets of
+//! each connection using [`split_connections`]
+
+use crate::packet::{Packet, PacketCodec, PacketType};
+use bytes::BytesMut;
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    path::Path,
+    time::Duration,
+};
+use tokio_util::codec::Decoder;
+
+/// Error type for errors that can occur while reading a capture
+#[derive(Debug)]
+pub enum CaptureError {
+    /// IO error while reading the capture file
+    Io(io::Error),
+    /// The capture was not a valid pcap or pcapng file
+    InvalidFormat(&'static str),
+}
+
+/// Type alias for result which could result in a Capture Error
+pub type CaptureResult<T> = Result<T, CaptureError>;
+
+impl From<io::Error> for CaptureError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl Error for CaptureError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CaptureError::Io(err) => Some(err),
+            CaptureError::InvalidFormat(_) => None,
+        }
+    }
+}
+
+impl Display for CaptureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CaptureError::Io(err) => write!(f, "Failed to read capture: {}", err),
+            CaptureError::InvalidFormat(err) => write!(f, "Invalid capture: {}", err),
+        }
+    }
+}
+
+/// Packet decoded from a capture along with the details of the
+/// stream direction it was sent in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedPacket {
+    /// Time the frame completing the packet was captured at
+    /// relative to the unix epoch
+    pub timestamp: Duration,
+    /// The address of the sender
+    pub source: SocketAddr,
+    /// The address of the receiver
+    pub destination: SocketAddr,
+    /// The decoded packet
+    pub packet: Packet,
+}
+
+/// Reader for decoding the packets from captures
+#[derive(Debug, Default, Clone)]
+pub struct CaptureReader {
+    /// Ports to filter streams by, streams are only decoded if either
+    /// of their ports are in this list. Empty to decode all streams
+    ports: Vec<u16>,
+}
+
+impl CaptureReader {
+    /// Creates a new capture reader which decodes all TCP streams
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only decodes streams which have the provided port as their
+    /// source or destination. Can be called multiple times to allow
+    /// multiple ports
+    ///
+    /// `port` The port to allow
+    pub fn port(mut self, port: u16) -> Self {
+        self.ports.push(port);
+        self
+    }
+
+    /// Reads the packets from the capture file at the provided path
+    ///
+    /// `path` The path of the capture file
+    pub fn read_file(&self, path: impl AsRef<Path>) -> CaptureResult<Vec<CapturedPacket>> {
+        let bytes = std::fs::read(path)?;
+        self.read(&bytes)
+    }
+
+    /// Reads the packets from the provided pcap or pcapng capture bytes
+    /// returning the packets in the order they were completed
+    ///
+    /// `bytes` The capture bytes
+    pub fn read(&self, bytes: &[u8]) -> CaptureResult<Vec<CapturedPacket>> {
+        let mut streams = Streams {
+            ports: &self.ports,
+            streams: Vec::new(),
+            packets: Vec::new(),
+        };
+
+        let magic = bytes
+            .get(0..4)
+            .ok_or(CaptureError::InvalidFormat("Missing capture header"))?;
+        if magic == PCAPNG_SECTION_HEADER {
+            read_pcapng(bytes, &mut streams)?;
+        } else {
+            read_pcap(bytes, &mut streams)?;
+        }
+
+        Ok(streams.packets)
+    }
+}
+
+/// Direction of a packet within a connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionDirection {
+    /// Packet sent by the client to the server
+    ClientToServer,
+    /// Packet sent by the server to the client
+    ServerToClient,
+}
+
+/// Packet sent within a connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionPacket {
+    /// Time the frame completing the packet was captured at
+    /// relative to the unix epoch
+    pub timestamp: Duration,
+    /// The direction the packet was sent in
+    pub direction: ConnectionDirection,
+    /// The decoded packet
+    pub packet: Packet,
+}
+
+/// Packets sent between a client and server over a single connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+    /// The address of the client
+    pub client: SocketAddr,
+    /// The address of the server
+    pub server: SocketAddr,
+    /// The packets sent in both directions in the order they were sent
+    pub packets: Vec<ConnectionPacket>,
+}
+
+impl Connection {
+    /// Returns the packets sent in the provided direction
+    ///
+    /// `direction` The direction of the packets
+    pub fn packets_in(
+        &self,
+        direction: ConnectionDirection,
+    ) -> impl Iterator<Item = &ConnectionPacket> {
+        self.packets
+            .iter()
+            .filter(move |packet| packet.direction == direction)
+    }
+}
+
+/// Splits the packets of a capture containing multiple connections into
+/// the packets of each connection (identified by the addresses at either
+/// end) ordered by the first packet of each connection.
+///
+/// The client of each connection is the sender of the first request packet
+/// or the sender of the first packet when the connection has no requests
+///
+/// `packets` The captured packets in the order they were sent
+pub fn split_connections(packets: &[CapturedPacket]) -> Vec<Connection> {
+    let mut connections: Vec<(Vec<&CapturedPacket>, Option<SocketAddr>)> = Vec::new();
+    let mut lookup: HashMap<(SocketAddr, SocketAddr), usize> = HashMap::new();
+
+    for captured in packets {
+        let key = if captured.source <= captured.destination {
+            (captured.source, captured.destination)
+        } else {
+            (captured.destination, captured.source)
+        };
+        let index = *lookup.entry(key).or_insert_with(|| {
+            connections.push((Vec::new(), None));
+            connections.len() - 1
+        });
+
+        let (connection, client) = &mut connections[index];
+        if client.is_none() && captured.packet.header.ty == PacketType::Request {
+            *client = Some(captured.source);
+        }
+        connection.push(captured);
+    }
+
+    connections
+        .into_iter()
+        .filter_map(|(captured, client)| {
+            let first = captured.first()?;
+            let client = client.unwrap_or(first.source);
+            let server = if first.source == client {
+                first.destination
+            } else {
+                first.source
+            };
+            let packets = captured
+                .into_iter()
+                .map(|captured| ConnectionPacket {
+                    timestamp: captured.timestamp,
+                    direction: if captured.source == client {
+                        ConnectionDirection::ClientToServer
+                    } else {
+                        ConnectionDirection::ServerToClient
+                    },
+                    packet: captured.packet.clone(),
+                })
+                .collect();
+            Some(Connection {
+                client,
+                server,
+                packets,
+            })
+        })
+        .collect()
+}
+
+/// Block type of the pcapng section header block
+const PCAPNG_SECTION_HEADER: [u8; 4] = [0x0A, 0x0D, 0x0D, 0x0A];
+
+/// Byte order of values within a capture
+#[derive(Clone, Copy)]
+enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    /// Reads a u16 at the provided offset
+    fn u16(self, bytes: &[u8], offset: usize) -> Option<u16> {
+        let value: [u8; 2] = bytes.get(offset..offset + 2)?.try_into().ok()?;
+        Some(match self {
+            Endian::Little => u16::from_le_bytes(value),
+            Endian::Big => u16::from_be_bytes(value),
+        })
+    }
+
+    /// Reads a u32 at the provided offset
+    fn u32(self, bytes: &[u8], offset: usize) -> Option<u32> {
+        let value: [u8; 4] = bytes.get(offset..offset + 4)?.try_into().ok()?;
+        Some(match self {
+            Endian::Little => u32::from_le_bytes(value),
+            Endian::Big => u32::from_be_bytes(value),
+        })
+    }
+}
+
+/// Reads the frames from a classic pcap capture
+///
+/// `bytes`   The capture bytes
+/// `streams` The streams to add the frames to
+fn read_pcap(bytes: &[u8], streams: &mut Streams) -> CaptureResult<()> {
+    let invalid = CaptureError::InvalidFormat("Truncated pcap header");
+    let (endian, nanos) = match bytes.get(0..4).ok_or(invalid)? {
+        [0xD4, 0xC3, 0xB2, 0xA1] => (Endian::Little, false),
+        [0xA1, 0xB2, 0xC3, 0xD4] => (Endian::Big, false),
+        [0x4D, 0x3C, 0xB2, 0xA1] => (Endian::Little, true),
+        [0xA1, 0xB2, 0x3C, 0x4D] => (Endian::Big, true),
+        _ => return Err(CaptureError::InvalidFormat("Unknown capture magic")),
+    };
+    let link_type = endian
+        .u32(bytes, 20)
+        .ok_or(CaptureError::InvalidFormat("Truncated pcap header"))?;
+
+    let mut offset = 24;
+    while offset < bytes.len() {
+        let (Some(seconds), Some(fraction), Some(length)) = (
+            endian.u32(bytes, offset),
+            endian.u32(bytes, offset + 4),
+            endian.u32(bytes, offset + 8),
+        ) else {
+            return Err(CaptureError::InvalidFormat("Truncated pcap record"));
+        };
+        let start = offset + 16;
+        let end = start + length as usize;
+        let frame = bytes
+            .get(start..end)
+            .ok_or(CaptureError::InvalidFormat("Truncated pcap record"))?;
+
+        let timestamp = if nanos {
+            Duration::new(seconds as u64, fraction)
+        } else {
+            Duration::new(seconds as u64, 0) + Duration::from_micros(fraction as u64)
+        };
+        streams.push_frame(link_type, timestamp, frame);
+        offset = end;
+    }
+    Ok(())
+}
+
+/// Interface described by a pcapng interface description block
+struct Interface {
+    /// The link type of the interface
+    link_type: u32,
+    /// The number of timestamp units per second
+    units_per_second: u64,
+}
+
+/// Reads the frames from a pcapng capture
+///
+/// `bytes`   The capture bytes
+/// `streams` The streams to add the frames to
+fn read_pcapng(bytes: &[u8], streams: &mut Streams) -> CaptureResult<()> {
+    let truncated = || CaptureError::InvalidFormat("Truncated pcapng block");
+    let mut endian = Endian::Little;
+    let mut interfaces: Vec<Interface> = Vec::new();
+
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let block = bytes.get(offset..).ok_or_else(truncated)?;
+        if block.get(0..4) == Some(&PCAPNG_SECTION_HEADER) {
+            // Each section can change the byte order
+            endian = match block.get(8..12) {
+                Some([0x4D, 0x3C, 0x2B, 0x1A]) => Endian::Little,
+                Some([0x1A, 0x2B, 0x3C, 0x4D]) => Endian::Big,
+                _ => return Err(CaptureError::InvalidFormat("Unknown byte order magic")),
+            };
+            interfaces.clear();
+        }
+
+        let block_type = endian.u32(block, 0).ok_or_else(truncated)?;
+        let length = endian.u32(block, 4).ok_or_else(truncated)? as usize;
+        if length < 12 || length > block.len() {
+            return Err(truncated());
+        }
+        let body = &block[8..length - 4];
+
+        match block_type {
+            // Interface description block
+            0x1 => {
+                let link_type = endian.u16(body, 0).ok_or_else(truncated)? as u32;
+                let units_per_second = read_resolution(endian, body.get(8..).unwrap_or(&[]));
+                interfaces.push(Interface {
+                    link_type,
+                    units_per_second,
+                });
+            }
+            // Enhanced packet block
+            0x6 => {
+                let (Some(interface), Some(high), Some(low), Some(captured)) = (
+                    endian.u32(body, 0),
+                    endian.u32(body, 4),
+                    endian.u32(body, 8),
+                    endian.u32(body, 12),
+                ) else {
+                    return Err(truncated());
+                };
+                let interface = interfaces
+                    .get(interface as usize)
+                    .ok_or(CaptureError::InvalidFormat("Unknown interface"))?;
+                let frame = body.get(20..20 + captured as usize).ok_or_else(truncated)?;
+                let units = ((high as u64) << 32) | low as u64;
+                let timestamp = Duration::from_secs(units / interface.units_per_second)
+                    + Duration::from_nanos(
+                        ((units % interface.units_per_second) as u128 * 1_000_000_000
+                            / interface.units_per_second as u128) as u64,
+                    );
+                streams.push_frame(interface.link_type, timestamp, frame);
+            }
+            // Simple packet block (no timestamp, always interface zero)
+            0x3 => {
+                let interface = interfaces
+                    .first()
+                    .ok_or(CaptureError::InvalidFormat("Unknown interface"))?;
+                let original = endian.u32(body, 0).ok_or_else(truncated)? as usize;
+                let frame = &body[4..(4 + original).min(body.len())];
+                streams.push_frame(interface.link_type, Duration::ZERO, frame);
+            }
+            // Other blocks are not needed
+            _ => {}
+        }
+
+        offset += length;
+    }
+    Ok(())
+}
+
+/// Reads the timestamp resolution from the options of an interface
+/// description block returning the number of units per second
+///
+/// `endian`  The byte order of the section
+/// `options` The options of the block
+fn read_resolution(endian: Endian, options: &[u8]) -> u64 {
+    let mut offset = 0;
+    while let (Some(code), Some(length)) =
+        (endian.u16(options, offset), endian.u16(options, offset + 2))
+    {
+        // End of options
+        if code == 0 {
+            break;
+        }
+        if code == 9 {
+            if let Some(value) = options.get(offset + 4) {
+                let exponent = (value & 0x7F) as u32;
+                let base: u64 = if value & 0x80 == 0 { 10 } else { 2 };
+                return base.checked_pow(exponent).unwrap_or(1_000_000);
+            }
+        }
+        // Options are padded to 32 bits
+        offset += 4 + (length as usize).div_ceil(4) * 4;
+    }
+    // Default resolution is microseconds
+    1_000_000
+}
+
+/// TCP segment extracted from a captured frame
+struct Segment<'a> {
+    /// The address of the sender
+    source: SocketAddr,
+    /// The address of the receiver
+    destination: SocketAddr,
+    /// The sequence number of the segment
+    seq: u32,
+    /// Whether the SYN flag is set
+    syn: bool,
+    /// The segment payload
+    payload: &'a [u8],
+}
+
+/// Extracts the TCP segment from a frame returning None if the frame
+/// is not a TCP segment over IPv4 or IPv6
+///
+/// `link_type` The link type of the frame
+/// `frame`     The frame bytes
+fn parse_segment(link_type: u32, frame: &[u8]) -> Option<Segment<'_>> {
+    let ip = match link_type {
+        // BSD loopback
+        0 => frame.get(4..)?,
+        // Ethernet
+        1 => {
+            let mut offset = 12;
+            // Skip VLAN tags
+            while matches!(frame.get(offset..offset + 2)?, [0x81, 0x00] | [0x88, 0xA8]) {
+                offset += 4;
+            }
+            frame.get(offset + 2..)?
+        }
+        // Raw IP
+        101 | 228 | 229 => frame,
+        // Linux cooked capture
+        113 => frame.get(16..)?,
+        // Linux cooked capture v2
+        276 => frame.get(20..)?,
+        _ => return None,
+    };
+
+    let (source, destination, tcp) = match ip.first()? >> 4 {
+        4 => {
+            let header = ((ip.first()? & 0x0F) as usize) * 4;
+            let total = u16::from_be_bytes([*ip.get(2)?, *ip.get(3)?]) as usize;
+            let fragment = u16::from_be_bytes([*ip.get(6)?, *ip.get(7)?]);
+            // Only unfragmented TCP is supported
+            if *ip.get(9)? != 6 || fragment & 0x3FFF != 0 {
+                return None;
+            }
+            let source: [u8; 4] = ip.get(12..16)?.try_into().ok()?;
+            let destination: [u8; 4] = ip.get(16..20)?.try_into().ok()?;
+            (
+                IpAddr::V4(Ipv4Addr::from(source)),
+                IpAddr::V4(Ipv4Addr::from(destination)),
+                ip.get(header..total.min(ip.len()))?,
+            )
+        }
+        6 => {
+            let payload = u16::from_be_bytes([*ip.get(4)?, *ip.get(5)?]) as usize;
+            // Extension headers are not supported
+            if *ip.get(6)? != 6 {
+                return None;
+            }
+            let source: [u8; 16] = ip.get(8..24)?.try_into().ok()?;
+            let destination: [u8; 16] = ip.get(24..40)?.try_into().ok()?;
+            (
+                IpAddr::V6(Ipv6Addr::from(source)),
+                IpAddr::V6(Ipv6Addr::from(destination)),
+                ip.get(40..(40 + payload).min(ip.len()))?,
+            )
+        }
+        _ => return None,
+    };
+
+    let source_port = u16::from_be_bytes([*tcp.first()?, *tcp.get(1)?]);
+    let destination_port = u16::from_be_bytes([*tcp.get(2)?, *tcp.get(3)?]);
+    let seq = u32::from_be_bytes(tcp.get(4..8)?.try_into().ok()?);
+    let header = ((tcp.get(12)? >> 4) as usize) * 4;
+    let syn = tcp.get(13)? & 0x02 != 0;
+
+    Some(Segment {
+        source: SocketAddr::new(source, source_port),
+        destination: SocketAddr::new(destination, destination_port),
+        seq,
+        syn,
+        payload: tcp.get(header..)?,
+    })
+}
+
+/// One direction of a TCP stream being reassembled
+struct Stream {
+    /// The address of the sender
+    source: SocketAddr,
+    /// The address of the receiver
+    destination: SocketAddr,
+    /// The next expected sequence number
+    next_seq: Option<u32>,
+    /// Segments received ahead of the next expected sequence
+    pending: Vec<(u32, Vec<u8>)>,
+    /// The reassembled bytes that have not been decoded yet
+    buffer: BytesMut,
+}
+
+impl Stream {
+    /// Appends the provided segment to the stream returning whether
+    /// any new bytes were added to the buffer
+    ///
+    /// `seq`     The sequence number of the segment
+    /// `payload` The segment payload
+    fn push(&mut self, seq: u32, payload: &[u8]) -> bool {
+        let next = *self.next_seq.get_or_insert(seq);
+        let offset = seq.wrapping_sub(next) as i32;
+        if offset > 0 {
+            // Segment arrived early
+            self.pending.push((seq, payload.to_vec()));
+            return false;
+        }
+
+        let mut appended = self.append(seq, payload);
+        while let Some(index) = self
+            .pending
+            .iter()
+            .position(|(seq, _)| seq.wrapping_sub(self.next_seq.unwrap_or(*seq)) as i32 <= 0)
+        {
+            let (seq, payload) = self.pending.swap_remove(index);
+            appended |= self.append(seq, &payload);
+        }
+        appended
+    }
+
+    /// Appends the portion of the payload that is after the next
+    /// expected sequence number
+    ///
+    /// `seq`     The sequence number of the payload
+    /// `payload` The payload bytes
+    fn append(&mut self, seq: u32, payload: &[u8]) -> bool {
+        let next = self.next_seq.unwrap_or(seq);
+        // Skip bytes that were already received
+        let skip = next.wrapping_sub(seq) as usize;
+        let Some(payload) = payload.get(skip..).filter(|payload| !payload.is_empty()) else {
+            return false;
+        };
+        self.buffer.extend_from_slice(payload);
+        self.next_seq = Some(next.wrapping_add(payload.len() as u32));
+        true
+    }
+}
+
+/// Collection of the streams within a capture
+struct Streams<'a> {
+    /// Ports to filter streams by
+    ports: &'a [u16],
+    /// The streams being reassembled
+    streams: Vec<Stream>,
+    /// The packets decoded from the streams
+    packets: Vec<CapturedPacket>,
+}
+
+impl Streams<'_> {
+    /// Adds the provided frame to its stream decoding any packets
+    /// that have been completed
+    ///
+    /// `link_type` The link type of the frame
+    /// `timestamp` The time the frame was captured
+    /// `frame`     The frame bytes
+    fn push_frame(&mut self, link_type: u32, timestamp: Duration, frame: &[u8]) {
+        let Some(segment) = parse_segment(link_type, frame) else {
+            return;
+        };
+        if !self.ports.is_empty()
+            && !self.ports.contains(&segment.source.port())
+            && !self.ports.contains(&segment.destination.port())
+        {
+            return;
+        }
+
+        let index = match self.streams.iter().position(|stream| {
+            stream.source == segment.source && stream.destination == segment.destination
+        }) {
+            Some(index) => index,
+            None => {
+                self.streams.push(Stream {
+                    source: segment.source,
+                    destination: segment.destination,
+                    next_seq: None,
+                    pending: Vec::new(),
+                    buffer: BytesMut::new(),
+                });
+                self.streams.len() - 1
+            }
+        };
+        let stream = &mut self.streams[index];
+
+        if segment.syn {
+            // New connection on the same addresses
+            stream.next_seq = Some(segment.seq.wrapping_add(1));
+            stream.pending.clear();
+            stream.buffer.clear();
+        }
+
+        if !stream.push(segment.seq, segment.payload) {
+            return;
+        }
+
+        while let Ok(Some(packet)) = PacketCodec.decode(&mut stream.buffer) {
+            self.packets.push(CapturedPacket {
+                timestamp,
+                source: stream.source,
+                destination: stream.destination,
+                packet,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{split_connections, CaptureReader, CapturedPacket, ConnectionDirection};
+    use crate::packet::{Packet, PacketHeader};
+    use bytes::BytesMut;
+    use std::{net::SocketAddr, time::Duration};
+
+    /// Creates an ethernet frame containing a TCP segment
+    fn frame(source: u16, destination: u16, seq: u32, syn: bool, payload: &[u8]) -> Vec<u8> {
+        let mut out = vec![0u8; 12];
+        out.extend_from_slice(&[0x08, 0x00]);
+
+        let total = (20 + 20 + payload.len()) as u16;
+        out.extend_from_slice(&[0x45, 0x00]);
+        out.extend_from_slice(&total.to_be_bytes());
+        out.extend_from_slice(&[0, 0, 0x40, 0, 64, 6, 0, 0]);
+        out.extend_from_slice(&[127, 0, 0, 1, 127, 0, 0, 1]);
+
+        out.extend_from_slice(&source.to_be_bytes());
+        out.extend_from_slice(&destination.to_be_bytes());
+        out.extend_from_slice(&seq.to_be_bytes());
+        out.extend_from_slice(&[0, 0, 0, 0, 0x50, if syn { 0x02 } else { 0x18 }, 0xFF, 0xFF]);
+        out.extend_from_slice(&[0, 0, 0, 0]);
+        out.extend_from_slice(payload);
+        out
+    }
+
+    /// Creates the frames used by the tests which send a request with
+    /// its bytes split over two out of order segments and a response
+    fn frames() -> (Vec<Vec<u8>>, Packet, Packet) {
+        let request = Packet::raw(PacketHeader::request(1, 2, 3), vec![1, 2, 3, 4]);
+        let response = Packet::response_raw(&request, vec![5, 6]);
+
+        let mut bytes = BytesMut::new();
+        request.write(&mut bytes);
+        let (first, second) = bytes.split_at(5);
+
+        let mut bytes = BytesMut::new();
+        response.write(&mut bytes);
+
+        let frames = vec![
+            frame(50000, 14219, 99, true, &[]),
+            frame(50000, 14219, 105, false, second),
+            frame(50000, 14219, 100, false, first),
+            // Retransmission should be ignored
+            frame(50000, 14219, 100, false, first),
+            frame(14219, 50000, 500, false, &bytes),
+            // Stream on another port
+            frame(50001, 80, 1, false, &bytes),
+        ];
+        (frames, request, response)
+    }
+
+    /// Tests reading packets from a classic pcap capture
+    #[test]
+    fn test_pcap() {
+        let (frames, request, response) = frames();
+
+        let mut capture = vec![0xD4, 0xC3, 0xB2, 0xA1, 2, 0, 4, 0];
+        capture.extend_from_slice(&[0; 8]);
+        capture.extend_from_slice(&65535u32.to_le_bytes());
+        capture.extend_from_slice(&1u32.to_le_bytes());
+        for (index, frame) in frames.iter().enumerate() {
+            capture.extend_from_slice(&10u32.to_le_bytes());
+            capture.extend_from_slice(&(index as u32).to_le_bytes());
+            capture.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            capture.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            capture.extend_from_slice(frame);
+        }
+
+        let packets = CaptureReader::new().port(14219).read(&capture).unwrap();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].packet, request);
+        assert_eq!(packets[0].destination.port(), 14219);
+        assert_eq!(packets[0].timestamp, Duration::new(10, 2000));
+        assert_eq!(packets[1].packet, response);
+        assert_eq!(packets[1].source.port(), 14219);
+    }
+
+    /// Tests splitting packets from multiple connections
+    #[test]
+    fn test_split_connections() {
+        let addr = |port: u16| SocketAddr::from(([127, 0, 0, 1], port));
+        let captured = |source: u16, destination: u16, packet: &Packet| CapturedPacket {
+            timestamp: Duration::ZERO,
+            source: addr(source),
+            destination: addr(destination),
+            packet: packet.clone(),
+        };
+
+        let notify = Packet::raw_empty(PacketHeader::notify(1, 2));
+        let request = Packet::raw_empty(PacketHeader::request(1, 2, 3));
+        let response = Packet::response_empty(&request);
+
+        let connections = split_connections(&[
+            // Notification sent before the client sends a request
+            captured(14219, 50000, &notify),
+            captured(50001, 14219, &request),
+            captured(50000, 14219, &request),
+            captured(14219, 50000, &response),
+            captured(14219, 50001, &response),
+        ]);
+
+        assert_eq!(connections.len(), 2);
+        assert_eq!(connections[0].client, addr(50000));
+        assert_eq!(connections[0].server, addr(14219));
+        let directions: Vec<ConnectionDirection> = connections[0]
+            .packets
+            .iter()
+            .map(|packet| packet.direction)
+            .collect();
+        assert_eq!(
+            directions,
+            [
+                ConnectionDirection::ServerToClient,
+                ConnectionDirection::ClientToServer,
+                ConnectionDirection::ServerToClient
+            ]
+        );
+        assert_eq!(connections[1].client, addr(50001));
+        assert_eq!(
+            connections[1]
+                .packets_in(ConnectionDirection::ServerToClient)
+                .count(),
+            1
+        );
+    }
+
+    /// Tests reading packets from a pcapng capture
+    #[test]
+    fn test_pcapng() {
+        let (frames, request, response) = frames();
+
+        /// Appends a block to the capture
+        fn block(capture: &mut Vec<u8>, ty: u32, body: &[u8]) {
+            let length = (12 + body.len().div_ceil(4) * 4) as u32;
+            capture.extend_from_slice(&ty.to_le_bytes());
+            capture.extend_from_slice(&length.to_le_bytes());
+            capture.extend_from_slice(body);
+            capture.resize(capture.len() + (4 - body.len() % 4) % 4, 0);
+            capture.extend_from_slice(&length.to_le_bytes());
+        }
+
+        let mut capture = Vec::new();
+        let mut header = vec![0x4D, 0x3C, 0x2B, 0x1A, 1, 0, 0, 0];
+        header.extend_from_slice(&[0xFF; 8]);
+        block(&mut capture, 0x0A0D0D0A, &header);
+
+        // Interface with millisecond resolution
+        let mut interface = vec![1, 0, 0, 0, 0, 0, 0, 0];
+        interface.extend_from_slice(&[9, 0, 1, 0, 3, 0, 0, 0, 0, 0, 0, 0]);
+        block(&mut capture, 0x1, &interface);
+
+        for frame in &frames {
+            let mut body = vec![0; 4];
+            body.extend_from_slice(&0u32.to_le_bytes());
+            body.extend_from_slice(&1500u32.to_le_bytes());
+            body.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            body.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            body.extend_from_slice(frame);
+            block(&mut capture, 0x6, &body);
+        }
+
+        let packets = CaptureReader::new().read(&capture).unwrap();
+        assert_eq!(packets.len(), 3);
+        assert_eq!(packets[0].packet, request);
+        assert_eq!(packets[0].timestamp, Duration::from_millis(1500));
+        assert_eq!(packets[1].packet, response);
+        assert_eq!(packets[2].packet, response);
+        assert_eq!(packets[2].destination.port(), 80);
+    }
+}
@@ -0,0 +1,401 @@
+//! High level client connection ([`BlazeClient`]) which manages the framed
+//! connection, assigns packet IDs to requests, decodes typed responses and
+//! collects the notifications received while waiting for responses.
+//!
+//! ```no_run
+//! use blaze_pk::{client::{BlazeClient, ClientResult}, PacketComponent, PacketComponents};
+//! use tokio::io::{AsyncRead, AsyncWrite};
+//!
+//! #[derive(Debug, Hash, PartialEq, Eq, PacketComponents)]
+//! enum Components {
+//!     #[component(target = 0x9)]
+//!     Util(Util),
+//! }
+//!
+//! #[derive(Debug, Hash, PartialEq, Eq, PacketComponent)]
+//! enum Util {
+//!     #[command(target = 0x2)]
+//!     Ping,
+//! }
+//!
+//! async fn run<T: AsyncRead + AsyncWrite + Unpin>(io: T) -> ClientResult<()> {
+//!     let mut client = BlazeClient::new(io);
+//!     let time: u32 = client.send_empty(Components::Util(Util::Ping)).await?;
+//!
+//!     while let Some(notify) = client.next_notification().await? {
+//!         println!("{:?}", notify.header);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::{
+    codec::{Decodable, Encodable},
+    error::{CodecError, DecodeError, EncodeError},
+    packet::{Packet, PacketCodec, PacketComponents, PacketType},
+    timer::{timeout, Timer},
+};
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::Display,
+    future::poll_fn,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::codec::Framed;
+
+/// Error type for errors that can occur while using a client
+#[derive(Debug)]
+pub enum ClientError {
+    /// Error from the underlying connection
+    Codec(CodecError),
+    /// The request contents couldn't be encoded
+    Encode(EncodeError),
+    /// The response contents couldn't be decoded
+    Decode(DecodeError),
+    /// The server responded with an error packet
+    Response(Packet),
+    /// The connection was closed before the response was received
+    Closed,
+    /// The response wasn't received within the request timeout
+    Timeout,
+}
+
+/// Type alias for result which could result in a Client Error
+pub type ClientResult<T> = Result<T, ClientError>;
+
+impl From<CodecError> for ClientError {
+    fn from(value: CodecError) -> Self {
+        Self::Codec(value)
+    }
+}
+
+impl From<EncodeError> for ClientError {
+    fn from(value: EncodeError) -> Self {
+        Self::Encode(value)
+    }
+}
+
+impl From<DecodeError> for ClientError {
+    fn from(value: DecodeError) -> Self {
+        Self::Decode(value)
+    }
+}
+
+impl Error for ClientError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ClientError::Codec(err) => Some(err),
+            ClientError::Encode(err) => Some(err),
+            ClientError::Decode(err) => Some(err),
+            ClientError::Response(_) | ClientError::Closed | ClientError::Timeout => None,
+        }
+    }
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Codec(err) => write!(f, "Connection error: {}", err),
+            ClientError::Encode(err) => write!(f, "Failed to encode request: {}", err),
+            ClientError::Decode(err) => write!(f, "Failed to decode response: {}", err),
+            ClientError::Response(packet) => {
+                write!(
+                    f,
+                    "Server responded with error {}",
+                    packet.header.error_display()
+                )
+            }
+            ClientError::Closed => f.write_str("Connection closed"),
+            ClientError::Timeout => f.write_str("Request timed out"),
+        }
+    }
+}
+
+/// Client connection to a Blaze server
+pub struct BlazeClient<T> {
+    /// The framed connection to the server
+    framed: Framed<T, PacketCodec>,
+    /// The ID to assign to the next request
+    next_id: u16,
+    /// Notifications received while waiting for responses
+    notifications: VecDeque<Packet>,
+    /// Timer and duration requests must be responded to within
+    timeout: Option<(Arc<dyn Timer>, Duration)>,
+}
+
+impl<T> BlazeClient<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Creates a new client over the provided connection
+    ///
+    /// `io` The connection to the server
+    pub fn new(io: T) -> Self {
+        Self {
+            framed: Framed::new(io, PacketCodec),
+            next_id: 0,
+            notifications: VecDeque::new(),
+            timeout: None,
+        }
+    }
+
+    /// Sets the duration requests must be responded to within
+    /// before failing with [`ClientError::Timeout`]
+    ///
+    /// Responses that arrive after their request timed out are not
+    /// returned to any later request, they are read and discarded
+    /// while waiting for the response to the next request
+    ///
+    /// `timer`    The timer for the runtime the client is used on
+    /// `duration` The duration to wait for responses
+    pub fn timeout(mut self, timer: impl Timer + 'static, duration: Duration) -> Self {
+        self.timeout = Some((Arc::new(timer), duration));
+        self
+    }
+
+    /// Sends a request with the provided contents and decodes
+    /// the contents of the response
+    ///
+    /// `component` The component of the request
+    /// `req`       The request contents
+    pub async fn send<C, Req, Res>(&mut self, component: C, req: Req) -> ClientResult<Res>
+    where
+        C: PacketComponents,
+        Req: Encodable,
+        Res: Decodable,
+    {
+        let packet = Packet::try_request(0, component, req)?;
+        self.send_packet(packet).await
+    }
+
+    /// Sends a request without any contents and decodes the
+    /// contents of the response
+    ///
+    /// `component` The component of the request
+    pub async fn send_empty<C, Res>(&mut self, component: C) -> ClientResult<Res>
+    where
+        C: PacketComponents,
+        Res: Decodable,
+    {
+        let packet = Packet::request_empty(0, component);
+        self.send_packet(packet).await
+    }
+
+    /// Sends the provided request packet and decodes the contents of
+    /// the response. Error responses result in [`ClientError::Response`]
+    ///
+    /// `packet` The request packet, the ID is replaced with the next ID
+    async fn send_packet<Res: Decodable>(&mut self, packet: Packet) -> ClientResult<Res> {
+        let response = self.request(packet).await?;
+        if let PacketType::Error = response.header.ty {
+            return Err(ClientError::Response(response));
+        }
+        Ok(response.decode()?)
+    }
+
+    /// Sends the provided request packet returning the response or
+    /// error packet. Notifications received before the response are
+    /// kept for [`BlazeClient::next_notification`]
+    ///
+    /// Any response or error packets with a different ID received
+    /// while waiting (i.e. late responses to requests that timed out)
+    /// are discarded
+    ///
+    /// `packet` The request packet, the ID is replaced with the next ID
+    pub async fn request(&mut self, mut packet: Packet) -> ClientResult<Packet> {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        packet.header.id = id;
+
+        self.write(packet).await?;
+
+        match self.timeout.clone() {
+            Some((timer, duration)) => timeout(&timer, duration, self.read_response(id))
+                .await
+                .map_err(|_| ClientError::Timeout)?,
+            None => self.read_response(id).await,
+        }
+    }
+
+    /// Reads packets until the response or error packet for the
+    /// provided request ID is received
+    ///
+    /// `id` The ID of the request
+    async fn read_response(&mut self, id: u16) -> ClientResult<Packet> {
+        loop {
+            let packet = self.read().await?.ok_or(ClientError::Closed)?;
+            match packet.header.ty {
+                PacketType::Notify => self.notifications.push_back(packet),
+                PacketType::Response | PacketType::Error if packet.header.id == id => {
+                    return Ok(packet)
+                }
+                // Responses to other requests can't be matched so
+                // they are discarded
+                _ => {}
+            }
+        }
+    }
+
+    /// Sends the provided packet without waiting for any response
+    ///
+    /// `packet` The packet to send
+    pub async fn write(&mut self, packet: Packet) -> ClientResult<()> {
+        let mut framed = Pin::new(&mut self.framed);
+        poll_fn(|cx| Sink::<Packet>::poll_ready(framed.as_mut(), cx)).await?;
+        framed.as_mut().start_send(packet)?;
+        poll_fn(|cx| Sink::<Packet>::poll_flush(framed.as_mut(), cx)).await?;
+        Ok(())
+    }
+
+    /// Reads the next packet from the connection returning None
+    /// if the connection was closed
+    async fn read(&mut self) -> ClientResult<Option<Packet>> {
+        let packet = poll_fn(|cx| Pin::new(&mut self.framed).poll_next(cx)).await;
+        Ok(packet.transpose()?)
+    }
+
+    /// Waits for the next notification from the server returning
+    /// None if the connection was closed
+    pub async fn next_notification(&mut self) -> ClientResult<Option<Packet>> {
+        poll_fn(|cx| self.poll_notification(cx)).await.transpose()
+    }
+
+    /// Returns a stream of the notifications from the server
+    pub fn notifications(&mut self) -> ClientNotifications<'_, T> {
+        ClientNotifications { client: self }
+    }
+
+    /// Polls for the next notification from the server
+    ///
+    /// `cx` The task context
+    fn poll_notification(&mut self, cx: &mut Context<'_>) -> Poll<Option<ClientResult<Packet>>> {
+        if let Some(packet) = self.notifications.pop_front() {
+            return Poll::Ready(Some(Ok(packet)));
+        }
+        loop {
+            let packet = match std::task::ready!(Pin::new(&mut self.framed).poll_next(cx)) {
+                Some(Ok(packet)) => packet,
+                Some(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+                None => return Poll::Ready(None),
+            };
+            if let PacketType::Notify = packet.header.ty {
+                return Poll::Ready(Some(Ok(packet)));
+            }
+        }
+    }
+
+    /// Returns a reference to the underlying connection
+    pub fn get_ref(&self) -> &T {
+        self.framed.get_ref()
+    }
+
+    /// Returns a mutable reference to the underlying connection
+    pub fn get_mut(&mut self) -> &mut T {
+        self.framed.get_mut()
+    }
+
+    /// Consumes the client returning the underlying framed
+    /// connection and any notifications not yet taken
+    pub fn into_inner(self) -> (Framed<T, PacketCodec>, Vec<Packet>) {
+        (self.framed, self.notifications.into())
+    }
+}
+
+/// Stream of the notifications received by a client
+pub struct ClientNotifications<'a, T> {
+    /// The client receiving the notifications
+    client: &'a mut BlazeClient<T>,
+}
+
+impl<T> Stream for ClientNotifications<'_, T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    type Item = ClientResult<Packet>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().client.poll_notification(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BlazeClient, ClientError};
+    use crate::{
+        packet::{Packet, PacketComponents, PacketHeader, PacketType},
+        timer::test::{poll_now, TestTimer},
+    };
+    use std::time::Duration;
+
+    /// Component for the test requests
+    #[derive(Debug, Hash, PartialEq, Eq)]
+    struct Ping;
+
+    impl PacketComponents for Ping {
+        fn values(&self) -> (u16, u16) {
+            (0x9, 0x2)
+        }
+
+        fn from_values(_component: u16, _command: u16, _notify: bool) -> Option<Self> {
+            Some(Ping)
+        }
+    }
+
+    /// Tests requests are assigned IDs, responses are decoded and
+    /// notifications received before responses are kept
+    #[test]
+    fn test_client() {
+        let (client_io, mut server_io) = tokio::io::duplex(1024);
+        let mut client = BlazeClient::new(client_io);
+
+        let request = PacketHeader::request(0, 0x9, 0x2);
+        let responses = [
+            Packet::raw_empty(PacketHeader::notify(0x7802, 0x1)),
+            Packet::raw(request.response(), vec![0x05]),
+        ];
+        for packet in &responses {
+            poll_now(packet.write_async(&mut server_io)).unwrap();
+        }
+
+        let value: u32 = poll_now(client.send_empty(Ping)).unwrap();
+        assert_eq!(value, 5);
+
+        // Responses for other requests are skipped
+        let error = PacketHeader::request(1, 0x9, 0x2)
+            .with_type(PacketType::Error)
+            .with_error(0x4);
+        for header in [request.response(), error] {
+            poll_now(Packet::raw_empty(header).write_async(&mut server_io)).unwrap();
+        }
+        let result: Result<u32, _> = poll_now(client.send_empty(Ping));
+        assert!(matches!(result, Err(ClientError::Response(packet)) if packet.header.error == 0x4));
+
+        let sent = poll_now(Packet::read_async(&mut server_io)).unwrap();
+        assert_eq!(sent.header, PacketHeader::request(0, 0x9, 0x2));
+        let sent = poll_now(Packet::read_async(&mut server_io)).unwrap();
+        assert_eq!(sent.header.id, 1);
+
+        let notify = poll_now(client.next_notification()).unwrap().unwrap();
+        assert_eq!(notify.header, PacketHeader::notify(0x7802, 0x1));
+
+        drop(server_io);
+        assert!(poll_now(client.next_notification()).unwrap().is_none());
+    }
+
+    /// Tests requests without responses fail once the timeout elapses
+    #[test]
+    fn test_client_timeout() {
+        let (client_io, _server_io) = tokio::io::duplex(1024);
+        let mut client = BlazeClient::new(client_io)
+            .timeout(TestTimer { elapsed: true }, Duration::from_secs(5));
+        let result: Result<u32, _> = poll_now(client.send_empty(Ping));
+        assert!(matches!(result, Err(ClientError::Timeout)));
+    }
+}
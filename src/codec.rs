@@ -1,7 +1,13 @@
 //! Traits for implementing encoding ([`Encodable`]) and decoding ([`Decodable`])
-//! for different types and [`ValueType`] trait for specifying the Tdf type of a type
+//! for different types, decoding of values borrowing from the packet buffer
+//! ([`BorrowDecodable`]) and [`ValueType`] trait for specifying the Tdf type of a type
 
-use crate::{error::DecodeResult, reader::TdfReader, tag::TdfType, writer::TdfWriter};
+use crate::{
+    error::{DecodeResult, EncodeResult},
+    reader::TdfReader,
+    tag::TdfType,
+    writer::TdfWriter,
+};
 
 /// Trait for something that can be decoded from a TdfReader
 pub trait Decodable: Sized {
@@ -13,6 +19,46 @@ pub trait Decodable: Sized {
     fn decode(reader: &mut TdfReader) -> DecodeResult<Self>;
 }
 
+/// Trait for something that can be decoded from a TdfReader while
+/// borrowing from the buffer being read. Allows structures to contain
+/// `&'a str` and [`BlobRef`] values without allocating for each field.
+/// Every [`Decodable`] type is also borrow decodable
+///
+/// ```
+/// use blaze_pk::{codec::BorrowDecodable, error::DecodeResult, reader::TdfReader, types::BlobRef};
+///
+/// struct Message<'a> {
+///     name: &'a str,
+///     data: BlobRef<'a>,
+///     id: u32,
+/// }
+///
+/// impl<'a> BorrowDecodable<'a> for Message<'a> {
+///     fn decode_borrowed(reader: &mut TdfReader<'a>) -> DecodeResult<Self> {
+///         let data = reader.tag_borrowed(b"DATA")?;
+///         let id = reader.tag(b"ID")?;
+///         let name = reader.tag_borrowed(b"NAME")?;
+///         Ok(Self { name, data, id })
+///     }
+/// }
+/// ```
+///
+/// [`BlobRef`]: crate::types::BlobRef
+pub trait BorrowDecodable<'a>: Sized {
+    /// Function for implementing decoding of Self from the
+    /// provided Reader borrowing from its buffer
+    ///
+    /// `reader` The reader to decode from
+    fn decode_borrowed(reader: &mut TdfReader<'a>) -> DecodeResult<Self>;
+}
+
+impl<'a, T: Decodable> BorrowDecodable<'a> for T {
+    #[inline]
+    fn decode_borrowed(reader: &mut TdfReader<'a>) -> DecodeResult<Self> {
+        T::decode(reader)
+    }
+}
+
 /// Trait for something that can be encoded onto a TdfWriter
 pub trait Encodable: Sized {
     /// Function for implementing encoding of Self to the
@@ -21,13 +67,38 @@ pub trait Encodable: Sized {
     /// `writer` The output to encode to
     fn encode(&self, writer: &mut TdfWriter);
 
+    /// Estimated number of bytes this value encodes to which is used
+    /// to preallocate output buffers. Defaults to zero so only types
+    /// that can cheaply compute their size need to implement this
+    fn size_hint(&self) -> usize {
+        0
+    }
+
     /// Shortcut function for encoding self directly to
     /// a Vec of bytes
     fn encode_bytes(&self) -> Vec<u8> {
-        let mut output = TdfWriter::default();
+        let mut output = TdfWriter::with_capacity(self.size_hint());
         self.encode(&mut output);
         output.into()
     }
+
+    /// Fallible version of [Encodable::encode] for values that can
+    /// fail to encode. Defaults to the infallible encode function so
+    /// only types that can fail need to implement this
+    ///
+    /// `writer` The output to encode to
+    fn try_encode(&self, writer: &mut TdfWriter) -> EncodeResult<()> {
+        self.encode(writer);
+        Ok(())
+    }
+
+    /// Shortcut function for fallibly encoding self directly
+    /// to a Vec of bytes
+    fn try_encode_bytes(&self) -> EncodeResult<Vec<u8>> {
+        let mut output = TdfWriter::with_capacity(self.size_hint());
+        self.try_encode(&mut output)?;
+        Ok(output.into())
+    }
 }
 
 /// Trait for a type that conforms to one of the standard TdfTypes
@@ -49,3 +120,84 @@ macro_rules! value_type {
         }
     };
 }
+
+/// Macro for implementing [Encodable], [Decodable] and [ValueType] for
+/// `bitflags!` style flag types encoding them as a VarInt of their bits.
+/// The type must provide a `bits()` function and a `from_bits_retain`
+/// constructor (as generated by bitflags 2). Unknown bits are retained
+/// when decoding
+///
+/// ```
+/// use bitflags::bitflags;
+/// use blaze_pk::{codec::Encodable, flags_codec};
+///
+/// bitflags! {
+///     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///     pub struct GameSettings: u32 {
+///         const OPEN_TO_BROWSING = 0x1;
+///         const OPEN_TO_MATCHMAKING = 0x2;
+///     }
+/// }
+///
+/// flags_codec!(GameSettings, u32);
+///
+/// let settings = GameSettings::OPEN_TO_BROWSING | GameSettings::OPEN_TO_MATCHMAKING;
+/// assert_eq!(settings.encode_bytes(), 3u32.encode_bytes());
+/// ```
+#[macro_export]
+macro_rules! flags_codec {
+    ($for:ty, $bits:ty) => {
+        impl $crate::codec::Encodable for $for {
+            fn encode(&self, writer: &mut $crate::writer::TdfWriter) {
+                <$bits as $crate::codec::Encodable>::encode(&self.bits(), writer)
+            }
+        }
+
+        impl $crate::codec::Decodable for $for {
+            fn decode(reader: &mut $crate::reader::TdfReader) -> $crate::error::DecodeResult<Self> {
+                let bits = <$bits as $crate::codec::Decodable>::decode(reader)?;
+                Ok(<$for>::from_bits_retain(bits))
+            }
+        }
+
+        $crate::value_type!($for, $crate::tag::TdfType::VarInt);
+    };
+}
+
+/// Macro for implementing [Encodable], [Decodable] and [ValueType] for
+/// transparent newtype wrappers (single field tuple structs) by forwarding
+/// to the codec of the wrapped type
+///
+/// ```
+/// use blaze_pk::{codec::Encodable, newtype_codec};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// pub struct PlayerId(pub u64);
+///
+/// newtype_codec!(PlayerId, u64);
+///
+/// assert_eq!(PlayerId(5).encode_bytes(), 5u64.encode_bytes());
+/// ```
+#[macro_export]
+macro_rules! newtype_codec {
+    ($for:ty, $inner:ty) => {
+        impl $crate::codec::Encodable for $for {
+            fn encode(&self, writer: &mut $crate::writer::TdfWriter) {
+                <$inner as $crate::codec::Encodable>::encode(&self.0, writer)
+            }
+        }
+
+        impl $crate::codec::Decodable for $for {
+            fn decode(reader: &mut $crate::reader::TdfReader) -> $crate::error::DecodeResult<Self> {
+                let value = <$inner as $crate::codec::Decodable>::decode(reader)?;
+                Ok(Self(value))
+            }
+        }
+
+        impl $crate::codec::ValueType for $for {
+            fn value_type() -> $crate::tag::TdfType {
+                <$inner as $crate::codec::ValueType>::value_type()
+            }
+        }
+    };
+}
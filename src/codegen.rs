@@ -0,0 +1,536 @@
+//! Rust code generation from [`Schema`]s so that large protocol surfaces can
+//! be maintained as data. The generated code contains the components enum,
+//! an enum of commands for each component and a module for each component
+//! containing structures implementing [`Encodable`] and [`Decodable`] for the
+//! fields of each packet.
+//!
+//! Schemas can be written in any format supported by serde (e.g. TOML or RON)
+//! and the code generated from a build script:
+//!
+//! ```toml
+//! [[components]]
+//! id = 1
+//! name = "Authentication"
+//!
+//! [[components.commands]]
+//! id = 40
+//! name = "Login"
+//! request = [
+//!     { tag = "MAIL", name = "email", ty = "String" },
+//!     { tag = "PASS", name = "password", ty = "String" },
+//! ]
+//! ```
+//!
+//! ```ignore
+//! // build.rs
+//! let schema: Schema = toml::from_str(&std::fs::read_to_string("schema.toml")?)?;
+//! let out = std::path::Path::new(&std::env::var("OUT_DIR")?).join("packets.rs");
+//! std::fs::write(out, blaze_pk::codegen::generate(&schema))?;
+//!
+//! // lib.rs
+//! include!(concat!(env!("OUT_DIR"), "/packets.rs"));
+//! ```
+//!
+//! Fields map to the following types:
+//! - VarInt: `u64`
+//! - String: `String`
+//! - Blob: [`Blob`]
+//! - Group: [`Group`] of a generated structure for the nested fields
+//! - List: [`Vec`] of a [`Group`] of a generated structure for the nested fields
+//! - VarIntList: [`VarIntList`] of `u64`
+//! - Pair and Triple: tuples of `u64`
+//! - Float: `f32`
+//!
+//! Maps, unions and groups or lists without nested fields use [`TdfValue`].
+//! Fields without a type in the schema can't be generated and are skipped
+//!
+//! [`Encodable`]: crate::codec::Encodable
+//! [`Decodable`]: crate::codec::Decodable
+//! [`Blob`]: crate::types::Blob
+//! [`Group`]: crate::types::Group
+//! [`VarIntList`]: crate::types::VarIntList
+//! [`TdfValue`]: crate::value::TdfValue
+
+use crate::{
+    schema::{ComponentSchema, FieldSchema, Schema},
+    tag::TdfType,
+};
+use std::fmt::Write;
+
+/// Name of the generated enum of components
+const COMPONENTS_NAME: &str = "Components";
+
+/// Generates the Rust code for the provided schema
+///
+/// `schema` The schema to generate code for
+pub fn generate(schema: &Schema) -> String {
+    let mut out = String::new();
+    out.push_str("// Generated by blaze-pk codegen. Make changes to the schema instead\n\n");
+
+    let components: Vec<&ComponentSchema> = schema
+        .components
+        .iter()
+        .filter(|component| !component.commands.is_empty() || !component.notifications.is_empty())
+        .collect();
+
+    if !components.is_empty() {
+        out.push_str("#[derive(Debug, Hash, PartialEq, Eq, blaze_pk::PacketComponents)]\n");
+        let _ = writeln!(out, "pub enum {} {{", COMPONENTS_NAME);
+        for component in &components {
+            let name = pascal_case(&component.name);
+            let _ = writeln!(out, "    #[component(target = {:#x})]", component.id);
+            let _ = writeln!(out, "    {}({}),", name, name);
+        }
+        out.push_str("}\n");
+    }
+
+    for component in &components {
+        write_commands(&mut out, component);
+    }
+
+    for component in &schema.components {
+        write_module(&mut out, component);
+    }
+
+    out
+}
+
+/// Writes the enum of commands and notifications for a component
+///
+/// `out`       The output to write to
+/// `component` The component schema
+fn write_commands(out: &mut String, component: &ComponentSchema) {
+    out.push_str("\n#[derive(Debug, Hash, PartialEq, Eq, blaze_pk::PacketComponent)]\n");
+    let _ = writeln!(out, "pub enum {} {{", pascal_case(&component.name));
+    for command in &component.commands {
+        let _ = writeln!(out, "    #[command(target = {:#x})]", command.id);
+        let _ = writeln!(out, "    {},", pascal_case(&command.name));
+    }
+    for notification in &component.notifications {
+        let _ = writeln!(
+            out,
+            "    #[command(target = {:#x}, notify)]",
+            notification.id
+        );
+        let _ = writeln!(
+            out,
+            "    {},",
+            notification_name(component, &notification.name)
+        );
+    }
+    out.push_str("}\n");
+}
+
+/// Returns the variant name for a notification, notifications with
+/// the same name as a command are suffixed with Notify
+///
+/// `component` The component schema
+/// `name`      The name of the notification
+fn notification_name(component: &ComponentSchema, name: &str) -> String {
+    let name = pascal_case(name);
+    let conflicts = component
+        .commands
+        .iter()
+        .any(|command| pascal_case(&command.name) == name);
+    if conflicts {
+        format!("{}Notify", name)
+    } else {
+        name
+    }
+}
+
+/// Writes the module containing the packet structures of a component
+///
+/// `out`       The output to write to
+/// `component` The component schema
+fn write_module(out: &mut String, component: &ComponentSchema) {
+    let mut structs: Vec<(String, String, &[FieldSchema])> = Vec::new();
+    for command in &component.commands {
+        let name = pascal_case(&command.name);
+        for (suffix, fields) in [
+            ("Request", &command.request),
+            ("Response", &command.response),
+            ("Error", &command.error),
+        ] {
+            if !fields.is_empty() {
+                let doc = format!("{} for {}::{}", suffix, component.name, command.name);
+                structs.push((format!("{}{}", name, suffix), doc, fields));
+            }
+        }
+    }
+    for notification in &component.notifications {
+        if !notification.fields.is_empty() {
+            let name = format!("{}Notify", pascal_case(&notification.name));
+            let doc = format!("Notification {}::{}", component.name, notification.name);
+            structs.push((name, doc, &notification.fields));
+        }
+    }
+
+    if structs.is_empty() {
+        return;
+    }
+
+    let _ = writeln!(out, "\n/// Packets for the {} component", component.name);
+    let _ = writeln!(out, "pub mod {} {{", field_ident(&component.name));
+    out.push_str("    use blaze_pk::{\n");
+    out.push_str("        codec::{Decodable, Encodable},\n");
+    out.push_str("        error::DecodeResult,\n");
+    out.push_str("        reader::TdfReader,\n");
+    out.push_str("        writer::TdfWriter,\n");
+    out.push_str("    };\n");
+
+    // Nested structures are appended while writing so the length is checked each time
+    let mut index = 0;
+    while index < structs.len() {
+        let (name, doc, fields) = structs[index].clone();
+        write_struct(out, &name, &doc, fields, &mut structs);
+        index += 1;
+    }
+
+    out.push_str("}\n");
+}
+
+/// Representation of a field within a generated structure
+enum FieldKind {
+    /// Field with a type implementing the codec traits
+    Typed(String),
+    /// Field decoded as a dynamic value of the provided type
+    Dynamic(TdfType),
+}
+
+impl FieldKind {
+    /// Returns the Rust type of the field
+    fn rust_type(&self) -> &str {
+        match self {
+            FieldKind::Typed(ty) => ty,
+            FieldKind::Dynamic(_) => "blaze_pk::value::TdfValue",
+        }
+    }
+}
+
+/// Determines the kind of field for the provided field schema adding any
+/// nested structures that need to be generated. Returns None for fields
+/// that have no type in the schema
+///
+/// `parent`  The name of the structure containing the field
+/// `field`   The field schema
+/// `structs` The structures that still need to be generated
+fn field_kind<'a>(
+    parent: &str,
+    field: &'a FieldSchema,
+    structs: &mut Vec<(String, String, &'a [FieldSchema])>,
+) -> Option<FieldKind> {
+    let ty = field.ty?;
+    let mut nested = || {
+        let name = format!("{}{}", parent, pascal_case(&field_name(field)));
+        let doc = format!("Group within {}", parent);
+        structs.push((name.clone(), doc, &field.fields));
+        name
+    };
+
+    Some(match ty {
+        TdfType::VarInt => FieldKind::Typed("u64".to_string()),
+        TdfType::String => FieldKind::Typed("String".to_string()),
+        TdfType::Blob => FieldKind::Typed("blaze_pk::types::Blob".to_string()),
+        TdfType::Group if !field.fields.is_empty() => {
+            FieldKind::Typed(format!("blaze_pk::types::Group<{}>", nested()))
+        }
+        TdfType::List if !field.fields.is_empty() => {
+            FieldKind::Typed(format!("Vec<blaze_pk::types::Group<{}>>", nested()))
+        }
+        TdfType::VarIntList => FieldKind::Typed("blaze_pk::types::VarIntList<u64>".to_string()),
+        TdfType::Pair => FieldKind::Typed("(u64, u64)".to_string()),
+        TdfType::Triple => FieldKind::Typed("(u64, u64, u64)".to_string()),
+        TdfType::Float => FieldKind::Typed("f32".to_string()),
+        ty => FieldKind::Dynamic(ty),
+    })
+}
+
+/// Writes a structure for the provided fields along with its
+/// encoding and decoding implementations
+///
+/// `out`     The output to write to
+/// `name`    The name of the structure
+/// `doc`     Documentation for the structure
+/// `fields`  The fields of the structure
+/// `structs` The structures that still need to be generated
+fn write_struct<'a>(
+    out: &mut String,
+    name: &str,
+    doc: &str,
+    fields: &'a [FieldSchema],
+    structs: &mut Vec<(String, String, &'a [FieldSchema])>,
+) {
+    let fields: Vec<(&FieldSchema, String, Option<FieldKind>)> = fields
+        .iter()
+        .map(|field| {
+            let ident = field_ident(&field_name(field));
+            (field, ident, field_kind(name, field, structs))
+        })
+        .collect();
+
+    let _ = writeln!(out, "\n    /// {}", doc);
+    out.push_str("    #[derive(Debug, Clone, PartialEq)]\n");
+    let _ = writeln!(out, "    pub struct {} {{", name);
+    for (field, ident, kind) in &fields {
+        match kind {
+            Some(kind) => {
+                let _ = writeln!(out, "        /// {} ({})", field.name, field.tag);
+                let _ = writeln!(out, "        pub {}: {},", ident, kind.rust_type());
+            }
+            None => {
+                let _ = writeln!(
+                    out,
+                    "        // Skipped {} ({}) as its type is unknown",
+                    field_name(field),
+                    field.tag
+                );
+            }
+        }
+    }
+    out.push_str("    }\n");
+
+    let fields: Vec<(&FieldSchema, String, FieldKind)> = fields
+        .into_iter()
+        .filter_map(|(field, ident, kind)| kind.map(|kind| (field, ident, kind)))
+        .collect();
+
+    let _ = writeln!(out, "\n    impl Encodable for {} {{", name);
+    if fields.is_empty() {
+        out.push_str("        fn encode(&self, _writer: &mut TdfWriter) {}\n");
+    } else {
+        out.push_str("        fn encode(&self, writer: &mut TdfWriter) {\n");
+        for (field, ident, kind) in &fields {
+            let tag = tag_literal(&field.tag);
+            match kind {
+                FieldKind::Typed(_) => {
+                    let _ = writeln!(
+                        out,
+                        "            writer.tag_value({}, &self.{});",
+                        tag, ident
+                    );
+                }
+                FieldKind::Dynamic(ty) => {
+                    let _ = writeln!(
+                        out,
+                        "            writer.tag({}, blaze_pk::tag::TdfType::{:?});",
+                        tag, ty
+                    );
+                    let _ = writeln!(out, "            self.{}.encode(writer);", ident);
+                }
+            }
+        }
+        out.push_str("        }\n");
+
+        // Each field is written as a 4 byte tag followed by its value
+        out.push_str("\n        fn size_hint(&self) -> usize {\n");
+        let _ = write!(out, "            {}", fields.len() * 4);
+        for (_, ident, _) in &fields {
+            let _ = write!(out, " + self.{}.size_hint()", ident);
+        }
+        out.push_str("\n        }\n");
+    }
+    out.push_str("    }\n");
+
+    let _ = writeln!(out, "\n    impl Decodable for {} {{", name);
+    if fields.is_empty() {
+        out.push_str("        fn decode(_reader: &mut TdfReader) -> DecodeResult<Self> {\n");
+    } else {
+        out.push_str("        fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {\n");
+    }
+    for (field, ident, kind) in &fields {
+        let tag = tag_literal(&field.tag);
+        match kind {
+            FieldKind::Typed(_) => {
+                let _ = writeln!(out, "            let {} = reader.tag({})?;", ident, tag);
+            }
+            FieldKind::Dynamic(ty) => {
+                let _ = writeln!(
+                    out,
+                    "            reader.until_tag({}, blaze_pk::tag::TdfType::{:?})?;",
+                    tag, ty
+                );
+                let _ = writeln!(
+                    out,
+                    "            let {} = blaze_pk::value::TdfValue::decode_type(reader, blaze_pk::tag::TdfType::{:?})?;",
+                    ident, ty
+                );
+            }
+        }
+    }
+    let idents: Vec<&str> = fields.iter().map(|(_, ident, _)| ident.as_str()).collect();
+    if idents.is_empty() {
+        out.push_str("            Ok(Self {})\n");
+    } else {
+        let _ = writeln!(out, "            Ok(Self {{ {} }})", idents.join(", "));
+    }
+    out.push_str("        }\n");
+    out.push_str("    }\n");
+}
+
+/// Returns the name of a field falling back to its
+/// tag when the field has no name
+///
+/// `field` The field schema
+fn field_name(field: &FieldSchema) -> String {
+    if field.name.is_empty() {
+        field.tag.to_lowercase()
+    } else {
+        field.name.clone()
+    }
+}
+
+/// Creates a byte string literal for the provided tag
+///
+/// `tag` The tag
+fn tag_literal(tag: &str) -> String {
+    format!("b{:?}", tag)
+}
+
+/// Converts the provided name into PascalCase
+///
+/// `name` The name to convert
+fn pascal_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for part in name.split(|value: char| !value.is_ascii_alphanumeric()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            out.push(first.to_ascii_uppercase());
+            out.extend(chars);
+        }
+    }
+    if out.starts_with(|value: char| value.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+    out
+}
+
+/// Converts the provided name into a snake_case identifier
+/// escaping any keywords
+///
+/// `name` The name to convert
+fn field_ident(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::with_capacity(name.len() + 4);
+    for (index, value) in chars.iter().copied().enumerate() {
+        if !value.is_ascii_alphanumeric() {
+            if !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+            continue;
+        }
+
+        if value.is_ascii_uppercase() && index > 0 {
+            let previous = chars[index - 1];
+            let next_lower = chars
+                .get(index + 1)
+                .is_some_and(|value| value.is_ascii_lowercase());
+            let boundary = previous.is_ascii_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_ascii_uppercase() && next_lower);
+            if boundary && !out.is_empty() && !out.ends_with('_') {
+                out.push('_');
+            }
+        }
+        out.push(value.to_ascii_lowercase());
+    }
+
+    while out.ends_with('_') {
+        out.pop();
+    }
+
+    if out.is_empty() || out.starts_with(|value: char| value.is_ascii_digit()) {
+        out.insert(0, '_');
+    }
+
+    match out.as_str() {
+        "self" | "super" | "crate" => out.push('_'),
+        "as" | "async" | "await" | "break" | "const" | "continue" | "dyn" | "else" | "enum"
+        | "extern" | "false" | "fn" | "for" | "gen" | "if" | "impl" | "in" | "let" | "loop"
+        | "match" | "mod" | "move" | "mut" | "pub" | "ref" | "return" | "static" | "struct"
+        | "trait" | "true" | "try" | "type" | "unsafe" | "use" | "where" | "while" | "yield"
+        | "abstract" | "become" | "box" | "do" | "final" | "macro" | "override" | "priv"
+        | "typeof" | "unsized" | "virtual" => out.insert_str(0, "r#"),
+        _ => {}
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::{field_ident, generate, pascal_case};
+    use crate::{
+        schema::{CommandSchema, ComponentSchema, FieldSchema, NotificationSchema, Schema},
+        tag::TdfType,
+    };
+
+    /// Tests converting schema names into identifiers
+    #[test]
+    fn test_names() {
+        assert_eq!(field_ident("playerName"), "player_name");
+        assert_eq!(field_ident("HTTPServer"), "http_server");
+        assert_eq!(field_ident("type"), "r#type");
+        assert_eq!(field_ident("self"), "self_");
+        assert_eq!(field_ident("2fa"), "_2fa");
+        assert_eq!(pascal_case("user_added"), "UserAdded");
+        assert_eq!(pascal_case("login"), "Login");
+    }
+
+    /// Tests the generated code for a schema
+    #[test]
+    fn test_generate() {
+        let field = |tag: &str, name: &str, ty: Option<TdfType>| FieldSchema {
+            tag: tag.to_string(),
+            name: name.to_string(),
+            ty,
+            fields: Vec::new(),
+        };
+        let schema = Schema {
+            components: vec![ComponentSchema {
+                id: 0x1,
+                name: "Authentication".to_string(),
+                commands: vec![CommandSchema {
+                    id: 0x28,
+                    name: "Login".to_string(),
+                    request: vec![
+                        field("MAIL", "email", Some(TdfType::String)),
+                        field("UNKN", "unknown", None),
+                    ],
+                    response: vec![FieldSchema {
+                        fields: vec![field("ID", "id", Some(TdfType::VarInt))],
+                        ..field("USER", "user", Some(TdfType::Group))
+                    }],
+                    ..Default::default()
+                }],
+                notifications: vec![NotificationSchema {
+                    id: 0x1,
+                    name: "Login".to_string(),
+                    fields: vec![field("DATA", "data", Some(TdfType::Map))],
+                }],
+            }],
+        };
+
+        let code = generate(&schema);
+        for expected in [
+            "#[component(target = 0x1)]\n    Authentication(Authentication),",
+            "#[command(target = 0x28)]\n    Login,",
+            "#[command(target = 0x1, notify)]\n    LoginNotify,",
+            "pub mod authentication {",
+            "pub struct LoginRequest {",
+            "pub email: String,",
+            "// Skipped unknown (UNKN) as its type is unknown",
+            "pub user: blaze_pk::types::Group<LoginResponseUser>,",
+            "pub struct LoginResponseUser {",
+            "let email = reader.tag(b\"MAIL\")?;",
+            "writer.tag(b\"DATA\", blaze_pk::tag::TdfType::Map);",
+            "4 + self.email.size_hint()",
+        ] {
+            assert!(
+                code.contains(expected),
+                "Missing {:?} in:\n{}",
+                expected,
+                code
+            );
+        }
+    }
+}
@@ -0,0 +1,399 @@
+//! Structured comparison of packet contents ([`diff_packets`]) producing the
+//! tags that were added, removed or changed between two packets along with
+//! the path to each difference. Useful for comparing the responses of a
+//! server implementation against captures from an official server.
+//!
+//! Fields are matched by tag so differences in the order fields were
+//! written in are not reported
+//!
+//! ```
+//! use blaze_pk::{diff::diff_packets, packet::{Packet, PacketHeader}, writer::TdfWriter};
+//!
+//! let mut expected = TdfWriter::default();
+//! expected.tag_u32(b"ID", 1);
+//! expected.group(b"USER", |writer| writer.tag_str(b"NAME", "Test"));
+//!
+//! let mut actual = TdfWriter::default();
+//! actual.tag_u32(b"ID", 1);
+//! actual.group(b"USER", |writer| writer.tag_str(b"NAME", "Other"));
+//! actual.tag_u32(b"NEW", 5);
+//!
+//! let header = PacketHeader::notify(1, 1);
+//! let diff = diff_packets(
+//!     &Packet::raw(header, expected.into()),
+//!     &Packet::raw(header, actual.into()),
+//! )
+//! .unwrap();
+//!
+//! assert_eq!(diff.to_string(), "~ USER.NAME: String(\"Test\") -> String(\"Other\")\n+ NEW: VarInt(5)\n");
+//! ```
+
+use crate::{
+    codec::Decodable,
+    error::DecodeResult,
+    packet::{Packet, PacketHeader},
+    reader::TdfReader,
+    tag::Tag,
+    value::{TdfField, TdfFields, TdfValue},
+};
+use std::fmt::{Display, Write};
+
+/// Segment of the path to a difference
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathSegment {
+    /// Field with the provided tag
+    Tag(Tag),
+    /// Value at the provided index within a list
+    Index(usize),
+    /// Value for the provided key within a map
+    Key(TdfValue),
+}
+
+/// Path to a value within the packet contents
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiffPath(pub Vec<PathSegment>);
+
+impl DiffPath {
+    /// Creates a new path from this path with the
+    /// provided segment appended
+    ///
+    /// `segment` The segment to append
+    fn join(&self, segment: PathSegment) -> Self {
+        let mut path = self.clone();
+        path.0.push(segment);
+        path
+    }
+}
+
+impl Display for DiffPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, segment) in self.0.iter().enumerate() {
+            match segment {
+                PathSegment::Tag(tag) => {
+                    if index > 0 {
+                        f.write_char('.')?;
+                    }
+                    write!(f, "{}", tag)?;
+                }
+                PathSegment::Index(index) => write!(f, "[{}]", index)?,
+                PathSegment::Key(TdfValue::VarInt(key)) => write!(f, "[{}]", key)?,
+                PathSegment::Key(TdfValue::String(key)) => write!(f, "[{:?}]", key)?,
+                PathSegment::Key(key) => write!(f, "[{:?}]", key)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Single difference between two values
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// Value only present in the second packet
+    Added {
+        /// The path to the value
+        path: DiffPath,
+        /// The added value
+        value: TdfValue,
+    },
+    /// Value only present in the first packet
+    Removed {
+        /// The path to the value
+        path: DiffPath,
+        /// The removed value
+        value: TdfValue,
+    },
+    /// Value present in both packets with different values
+    Changed {
+        /// The path to the value
+        path: DiffPath,
+        /// The value from the first packet
+        old: TdfValue,
+        /// The value from the second packet
+        new: TdfValue,
+    },
+}
+
+impl Difference {
+    /// Returns the path to the difference
+    pub fn path(&self) -> &DiffPath {
+        match self {
+            Difference::Added { path, .. } => path,
+            Difference::Removed { path, .. } => path,
+            Difference::Changed { path, .. } => path,
+        }
+    }
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difference::Added { path, value } => write!(f, "+ {}: {:?}", path, value),
+            Difference::Removed { path, value } => write!(f, "- {}: {:?}", path, value),
+            Difference::Changed { path, old, new } => {
+                write!(f, "~ {}: {:?} -> {:?}", path, old, new)
+            }
+        }
+    }
+}
+
+/// Differences between two packets
+#[derive(Debug, Clone, PartialEq)]
+pub struct PacketDiff {
+    /// The headers of both packets if they differ
+    pub header: Option<(PacketHeader, PacketHeader)>,
+    /// The differences between the packet contents
+    pub differences: Vec<Difference>,
+}
+
+impl PacketDiff {
+    /// Checks whether the packets were equivalent
+    pub fn is_empty(&self) -> bool {
+        self.header.is_none() && self.differences.is_empty()
+    }
+}
+
+impl Display for PacketDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some((old, new)) = &self.header {
+            writeln!(f, "~ header: {:?} -> {:?}", old, new)?;
+        }
+        for difference in &self.differences {
+            writeln!(f, "{}", difference)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the contents of both packets and produces the
+/// differences between them
+///
+/// `old` The first packet (e.g. the expected packet)
+/// `new` The second packet (e.g. the actual packet)
+pub fn diff_packets(old: &Packet, new: &Packet) -> DecodeResult<PacketDiff> {
+    let header = (old.header != new.header).then_some((old.header, new.header));
+    let old_fields = TdfFields::decode(&mut TdfReader::new(&old.contents))?;
+    let new_fields = TdfFields::decode(&mut TdfReader::new(&new.contents))?;
+    Ok(PacketDiff {
+        header,
+        differences: diff_fields(&old_fields, &new_fields),
+    })
+}
+
+/// Produces the differences between two collections of fields
+///
+/// `old` The first fields
+/// `new` The second fields
+pub fn diff_fields(old: &TdfFields, new: &TdfFields) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    diff_field_list(&DiffPath::default(), &old.0, &new.0, &mut differences);
+    differences
+}
+
+/// Produces the differences between two values
+///
+/// `old` The first value
+/// `new` The second value
+pub fn diff_values(old: &TdfValue, new: &TdfValue) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    diff_value(DiffPath::default(), old, new, &mut differences);
+    differences
+}
+
+/// Appends the differences between two lists of fields. Fields are
+/// matched by tag with repeated tags matched in the order they appear
+///
+/// `path` The path to the fields
+/// `old`  The first fields
+/// `new`  The second fields
+/// `out`  The output differences
+fn diff_field_list(path: &DiffPath, old: &[TdfField], new: &[TdfField], out: &mut Vec<Difference>) {
+    let mut matched = vec![false; new.len()];
+
+    for field in old {
+        let path = path.join(PathSegment::Tag(field.tag));
+        let other = new
+            .iter()
+            .enumerate()
+            .find(|(index, other)| !matched[*index] && other.tag == field.tag);
+
+        match other {
+            Some((index, other)) => {
+                matched[index] = true;
+                diff_value(path, &field.value, &other.value, out);
+            }
+            None => out.push(Difference::Removed {
+                path,
+                value: field.value.clone(),
+            }),
+        }
+    }
+
+    new.iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .for_each(|(field, _)| {
+            out.push(Difference::Added {
+                path: path.join(PathSegment::Tag(field.tag)),
+                value: field.value.clone(),
+            })
+        });
+}
+
+/// Appends the differences between two values
+///
+/// `path` The path to the values
+/// `old`  The first value
+/// `new`  The second value
+/// `out`  The output differences
+fn diff_value(path: DiffPath, old: &TdfValue, new: &TdfValue, out: &mut Vec<Difference>) {
+    match (old, new) {
+        (
+            TdfValue::Group {
+                two: old_two,
+                fields: old_fields,
+            },
+            TdfValue::Group {
+                two: new_two,
+                fields: new_fields,
+            },
+        ) if old_two == new_two => diff_field_list(&path, old_fields, new_fields, out),
+        (
+            TdfValue::List {
+                ty: old_ty,
+                values: old_values,
+            },
+            TdfValue::List {
+                ty: new_ty,
+                values: new_values,
+            },
+        ) if old_ty == new_ty => {
+            for (index, (old, new)) in old_values.iter().zip(new_values).enumerate() {
+                diff_value(path.join(PathSegment::Index(index)), old, new, out);
+            }
+            for (index, value) in old_values.iter().enumerate().skip(new_values.len()) {
+                out.push(Difference::Removed {
+                    path: path.join(PathSegment::Index(index)),
+                    value: value.clone(),
+                });
+            }
+            for (index, value) in new_values.iter().enumerate().skip(old_values.len()) {
+                out.push(Difference::Added {
+                    path: path.join(PathSegment::Index(index)),
+                    value: value.clone(),
+                });
+            }
+        }
+        (
+            TdfValue::Map {
+                key_ty: old_key_ty,
+                value_ty: old_value_ty,
+                entries: old_entries,
+            },
+            TdfValue::Map {
+                key_ty: new_key_ty,
+                value_ty: new_value_ty,
+                entries: new_entries,
+            },
+        ) if old_key_ty == new_key_ty && old_value_ty == new_value_ty => {
+            for (key, old) in old_entries {
+                let path = path.join(PathSegment::Key(key.clone()));
+                match new_entries.iter().find(|(other, _)| other == key) {
+                    Some((_, new)) => diff_value(path, old, new, out),
+                    None => out.push(Difference::Removed {
+                        path,
+                        value: old.clone(),
+                    }),
+                }
+            }
+            for (key, new) in new_entries {
+                if !old_entries.iter().any(|(other, _)| other == key) {
+                    out.push(Difference::Added {
+                        path: path.join(PathSegment::Key(key.clone())),
+                        value: new.clone(),
+                    });
+                }
+            }
+        }
+        (
+            TdfValue::Union {
+                key: old_key,
+                value: Some(old_value),
+            },
+            TdfValue::Union {
+                key: new_key,
+                value: Some(new_value),
+            },
+        ) if old_key == new_key && old_value.tag == new_value.tag => diff_value(
+            path.join(PathSegment::Tag(old_value.tag)),
+            &old_value.value,
+            &new_value.value,
+            out,
+        ),
+        (old, new) if old != new => out.push(Difference::Changed {
+            path,
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{diff_fields, DiffPath, Difference, PathSegment};
+    use crate::{
+        codec::Decodable, reader::TdfReader, tag::Tag, types::TdfMap, value::TdfFields,
+        value::TdfValue, writer::TdfWriter,
+    };
+
+    /// Decodes the fields written by the provided function
+    fn fields(write: impl FnOnce(&mut TdfWriter)) -> TdfFields {
+        let mut writer = TdfWriter::default();
+        write(&mut writer);
+        TdfFields::decode(&mut TdfReader::new(&writer.buffer)).unwrap()
+    }
+
+    /// Tests differences are found within nested values
+    /// while field order is ignored
+    #[test]
+    fn test_diff_fields() {
+        let old = fields(|writer| {
+            let mut map = TdfMap::<String, u32>::new();
+            map.insert("A", 1u32);
+            map.insert("B", 2u32);
+
+            writer.tag_u32(b"SAME", 1);
+            writer.tag_value(b"LIST", &vec![1u32, 2, 3]);
+            writer.tag_value(b"MAP", &map);
+            writer.tag_str(b"GONE", "Test");
+        });
+        let new = fields(|writer| {
+            let mut map = TdfMap::<String, u32>::new();
+            map.insert("B", 3u32);
+            map.insert("A", 1u32);
+
+            writer.tag_value(b"MAP", &map);
+            writer.tag_value(b"LIST", &vec![1u32, 5]);
+            writer.tag_u32(b"SAME", 1);
+        });
+
+        let differences = diff_fields(&old, &new);
+        let paths: Vec<String> = differences
+            .iter()
+            .map(|difference| difference.path().to_string())
+            .collect();
+        assert_eq!(paths, ["LIST[1]", "LIST[2]", "MAP[\"B\"]", "GONE"]);
+        assert_eq!(
+            differences[2],
+            Difference::Changed {
+                path: DiffPath(vec![
+                    PathSegment::Tag(Tag::from(&b"MAP"[..])),
+                    PathSegment::Key(TdfValue::String("B".to_string()))
+                ]),
+                old: TdfValue::VarInt(2),
+                new: TdfValue::VarInt(3),
+            }
+        );
+        assert!(matches!(differences[3], Difference::Removed { .. }));
+    }
+}
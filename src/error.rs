@@ -1,8 +1,9 @@
-//! Error type used when decoding packets [`DecodeError`] and result
-//! type alias [`DecodeResult`]
+//! Error types used when decoding packets [`DecodeError`], encoding
+//! values [`EncodeError`] and the codec layer [`CodecError`] along with
+//! their result type aliases
 
 use crate::tag::{Tag, TdfType};
-use std::{error::Error, fmt::Display};
+use std::{error::Error, fmt::Display, io};
 
 /// Error type for errors that can occur while decoding a value
 /// using the tdf decode
@@ -50,15 +51,241 @@ pub enum DecodeError {
         remaining: usize,
     },
 
+    /// The value was decoded without reading all of the available
+    /// bytes. Created by [`crate::packet::Packet::decode_strict`]
+    TrailingBytes {
+        /// The reader cursor position after decoding
+        cursor: usize,
+        /// The number of bytes left unread
+        remaining: usize,
+    },
+
     /// Other error type with custom message
     Other(&'static str),
+
+    /// Custom error with an owned message for errors that need
+    /// to include dynamic context
+    Custom(String),
+
+    /// Error caused by another underlying error such as a parsing
+    /// failure from a user [`crate::codec::Decodable`] implementation
+    Source(Box<dyn Error + Send + Sync>),
+
+    /// Error with the bytes surrounding the reader cursor captured
+    /// for diagnostics. Created by [`crate::reader::TdfReader::decode_with_context`]
+    Context {
+        /// The underlying decoding error
+        error: Box<DecodeError>,
+        /// The bytes around the cursor when the error occurred
+        context: HexContext,
+    },
+}
+
+impl DecodeError {
+    /// Creates a custom decode error from the provided message
+    ///
+    /// `msg` The error message
+    pub fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+
+    /// Returns the underlying error removing any diagnostic
+    /// context that was wrapped around it
+    pub fn root(&self) -> &DecodeError {
+        match self {
+            DecodeError::Context { error, .. } => error.root(),
+            err => err,
+        }
+    }
+
+    /// Returns the kind of this error ignoring any diagnostic
+    /// context that was wrapped around it
+    pub fn kind(&self) -> DecodeErrorKind {
+        match self.root() {
+            DecodeError::MissingTag { .. } => DecodeErrorKind::MissingTag,
+            DecodeError::InvalidTagType { .. } => DecodeErrorKind::InvalidTagType,
+            DecodeError::InvalidType { .. } => DecodeErrorKind::InvalidType,
+            DecodeError::UnknownType { .. } => DecodeErrorKind::UnknownType,
+            DecodeError::UnexpectedEof { .. } => DecodeErrorKind::UnexpectedEof,
+            DecodeError::TrailingBytes { .. } => DecodeErrorKind::TrailingBytes,
+            DecodeError::Other(_) => DecodeErrorKind::Other,
+            DecodeError::Custom(_) => DecodeErrorKind::Custom,
+            DecodeError::Source(_) => DecodeErrorKind::Source,
+            // Context is removed by root
+            DecodeError::Context { error, .. } => error.kind(),
+        }
+    }
+
+    /// Checks if this is a missing tag error for the provided tag
+    ///
+    /// `tag` The tag that should be missing
+    pub fn is_missing_tag(&self, tag: &[u8]) -> bool {
+        match self.root() {
+            DecodeError::MissingTag { tag: missing, .. } => *missing == Tag::from(tag),
+            _ => false,
+        }
+    }
+
+    /// Checks if this is an invalid type error for the provided tag
+    /// regardless of the expected and actual types
+    ///
+    /// `tag` The tag with the invalid type
+    pub fn is_invalid_tag_type(&self, tag: &[u8]) -> bool {
+        match self.root() {
+            DecodeError::InvalidTagType { tag: invalid, .. } => *invalid == Tag::from(tag),
+            _ => false,
+        }
+    }
+
+    /// Checks if this is an unexpected end of file error
+    pub fn is_unexpected_eof(&self) -> bool {
+        matches!(self.kind(), DecodeErrorKind::UnexpectedEof)
+    }
+
+    /// Returns the underlying source error downcast to the provided
+    /// type if this error was caused by an error of that type
+    pub fn source_as<E: Error + 'static>(&self) -> Option<&E> {
+        match self.root() {
+            DecodeError::Source(err) => err.downcast_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Decode errors are compared by their semantic content. Diagnostic
+/// [HexContext]s are ignored and source errors are compared by their
+/// display message
+impl PartialEq for DecodeError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.root(), other.root()) {
+            (
+                DecodeError::MissingTag { tag, ty },
+                DecodeError::MissingTag {
+                    tag: other_tag,
+                    ty: other_ty,
+                },
+            ) => tag == other_tag && ty == other_ty,
+            (
+                DecodeError::InvalidTagType {
+                    tag,
+                    expected,
+                    actual,
+                },
+                DecodeError::InvalidTagType {
+                    tag: other_tag,
+                    expected: other_expected,
+                    actual: other_actual,
+                },
+            ) => tag == other_tag && expected == other_expected && actual == other_actual,
+            (
+                DecodeError::InvalidType { expected, actual },
+                DecodeError::InvalidType {
+                    expected: other_expected,
+                    actual: other_actual,
+                },
+            ) => expected == other_expected && actual == other_actual,
+            (DecodeError::UnknownType { ty }, DecodeError::UnknownType { ty: other_ty }) => {
+                ty == other_ty
+            }
+            (
+                DecodeError::UnexpectedEof {
+                    cursor,
+                    wanted,
+                    remaining,
+                },
+                DecodeError::UnexpectedEof {
+                    cursor: other_cursor,
+                    wanted: other_wanted,
+                    remaining: other_remaining,
+                },
+            ) => cursor == other_cursor && wanted == other_wanted && remaining == other_remaining,
+            (
+                DecodeError::TrailingBytes { cursor, remaining },
+                DecodeError::TrailingBytes {
+                    cursor: other_cursor,
+                    remaining: other_remaining,
+                },
+            ) => cursor == other_cursor && remaining == other_remaining,
+            (DecodeError::Other(err), DecodeError::Other(other)) => err == other,
+            (DecodeError::Custom(err), DecodeError::Custom(other)) => err == other,
+            (DecodeError::Source(err), DecodeError::Source(other)) => {
+                err.to_string() == other.to_string()
+            }
+            _ => false,
+        }
+    }
+}
+
+/// The kinds of [DecodeError] without their details for matching
+/// the kind of failure in tests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecodeErrorKind {
+    /// [DecodeError::MissingTag]
+    MissingTag,
+    /// [DecodeError::InvalidTagType]
+    InvalidTagType,
+    /// [DecodeError::InvalidType]
+    InvalidType,
+    /// [DecodeError::UnknownType]
+    UnknownType,
+    /// [DecodeError::UnexpectedEof]
+    UnexpectedEof,
+    /// [DecodeError::TrailingBytes]
+    TrailingBytes,
+    /// [DecodeError::Other]
+    Other,
+    /// [DecodeError::Custom]
+    Custom,
+    /// [DecodeError::Source]
+    Source,
+}
+
+/// Annotated window of the bytes surrounding the cursor position
+/// of a reader when decoding failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexContext {
+    /// The cursor position the window surrounds
+    pub cursor: usize,
+    /// The offset of the first byte in the window
+    pub start: usize,
+    /// The bytes within the window
+    pub bytes: Vec<u8>,
+}
+
+/// Formats the window as the offset of the window followed by the
+/// hex bytes with the byte at the cursor wrapped in brackets (empty brackets when the cursor is at the
+/// end of the window)
+impl Display for HexContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "near {:#06x}:", self.start)?;
+        let cursor = self.cursor.saturating_sub(self.start);
+        for (index, byte) in self.bytes.iter().enumerate() {
+            if index == cursor {
+                write!(f, " [{:02x}]", byte)?;
+            } else {
+                write!(f, " {:02x}", byte)?;
+            }
+        }
+        if cursor >= self.bytes.len() {
+            f.write_str(" []")?;
+        }
+        Ok(())
+    }
 }
 
 /// Type alias for result which could result in a Decode Error
 pub type DecodeResult<T> = Result<T, DecodeError>;
 
 /// Error implementation
-impl Error for DecodeError {}
+impl Error for DecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DecodeError::Source(err) => Some(err.as_ref()),
+            DecodeError::Context { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 /// Display formatting implementation
 impl Display for DecodeError {
@@ -99,7 +326,258 @@ impl Display for DecodeError {
                     cursor, wanted, remaining
                 )
             }
+            DecodeError::TrailingBytes { cursor, remaining } => {
+                write!(
+                    f,
+                    "Trailing bytes after decoding (cursor: {}, remaining: {})",
+                    cursor, remaining
+                )
+            }
             DecodeError::Other(err) => f.write_str(err),
+            DecodeError::Custom(err) => f.write_str(err),
+            DecodeError::Source(err) => Display::fmt(err, f),
+            DecodeError::Context { error, context } => write!(f, "{} {}", error, context),
         }
     }
 }
+
+/// Error type for errors that can occur while encoding a value
+/// using the fallible [`crate::codec::Encodable::try_encode`]
+#[derive(Debug)]
+pub enum EncodeError {
+    /// The tag label cannot be represented by the tag encoding
+    InvalidTag {
+        /// The invalid tag label
+        tag: String,
+    },
+    /// A value did not match the type declared by the list or
+    /// map that contained it
+    InvalidType {
+        /// The declared tdf type
+        expected: TdfType,
+        /// The actual tdf type
+        actual: TdfType,
+    },
+    /// A value was too large to be encoded
+    TooLarge {
+        /// The length of the value
+        length: usize,
+        /// The maximum length allowed
+        max: usize,
+    },
+    /// Other error type with custom message
+    Other(&'static str),
+}
+
+/// Type alias for result which could result in an Encode Error
+pub type EncodeResult<T> = Result<T, EncodeError>;
+
+/// Error implementation
+impl Error for EncodeError {}
+
+/// Display formatting implementation
+impl Display for EncodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodeError::InvalidTag { tag } => write!(f, "Invalid tag label '{}'", tag),
+            EncodeError::InvalidType { expected, actual } => {
+                write!(
+                    f,
+                    "Value type does not match declared type (expected: {:?}, got: {:?})",
+                    expected, actual
+                )
+            }
+            EncodeError::TooLarge { length, max } => {
+                write!(f, "Value too large (length: {}, max: {})", length, max)
+            }
+            EncodeError::Other(err) => f.write_str(err),
+        }
+    }
+}
+
+/// Decode errors are converted to [`io::ErrorKind::InvalidData`] errors
+/// which keep the decode error as the inner error so it can be recovered
+/// using [`io::Error::get_ref`] and downcasting
+impl From<DecodeError> for io::Error {
+    fn from(value: DecodeError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, value)
+    }
+}
+
+/// Encode errors are converted to [`io::ErrorKind::InvalidInput`] errors
+/// which keep the encode error as the inner error
+impl From<EncodeError> for io::Error {
+    fn from(value: EncodeError) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, value)
+    }
+}
+
+/// Error type for the packet codec layer which keeps the underlying
+/// failure rather than reducing it to an [`io::Error`]
+#[derive(Debug)]
+pub enum CodecError {
+    /// IO error from the underlying stream
+    Io(io::Error),
+    /// Error decoding a value
+    Decode(DecodeError),
+    /// Error encoding a value
+    Encode(EncodeError),
+}
+
+/// Type alias for result which could result in a Codec Error
+pub type CodecResult<T> = Result<T, CodecError>;
+
+impl From<io::Error> for CodecError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<DecodeError> for CodecError {
+    fn from(value: DecodeError) -> Self {
+        Self::Decode(value)
+    }
+}
+
+impl From<EncodeError> for CodecError {
+    fn from(value: EncodeError) -> Self {
+        Self::Encode(value)
+    }
+}
+
+/// Codec errors are converted to io errors by unwrapping IO errors
+/// and converting the other errors using their own conversions
+impl From<CodecError> for io::Error {
+    fn from(value: CodecError) -> Self {
+        match value {
+            CodecError::Io(err) => err,
+            CodecError::Decode(err) => err.into(),
+            CodecError::Encode(err) => err.into(),
+        }
+    }
+}
+
+/// Error implementation
+impl Error for CodecError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CodecError::Io(err) => Some(err),
+            CodecError::Decode(err) => Some(err),
+            CodecError::Encode(err) => Some(err),
+        }
+    }
+}
+
+/// Display formatting implementation
+impl Display for CodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodecError::Io(err) => write!(f, "IO error: {}", err),
+            CodecError::Decode(err) => write!(f, "Decode error: {}", err),
+            CodecError::Encode(err) => write!(f, "Encode error: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CodecError, DecodeError, DecodeErrorKind, EncodeError, HexContext};
+    use crate::tag::{Tag, TdfType};
+    use std::io;
+
+    /// Tests comparing errors ignores their diagnostic context
+    #[test]
+    fn test_error_matching() {
+        let err = DecodeError::MissingTag {
+            tag: Tag::from(b"TEST"),
+            ty: TdfType::VarInt,
+        };
+        let context = DecodeError::Context {
+            error: Box::new(DecodeError::MissingTag {
+                tag: Tag::from(b"TEST"),
+                ty: TdfType::VarInt,
+            }),
+            context: HexContext {
+                cursor: 0,
+                start: 0,
+                bytes: vec![0x1],
+            },
+        };
+        assert_eq!(err, context);
+        assert_eq!(context.kind(), DecodeErrorKind::MissingTag);
+        assert!(context.is_missing_tag(b"TEST"));
+        assert!(!context.is_missing_tag(b"VALU"));
+        assert_ne!(err, DecodeError::Other("Missing"));
+
+        let err = DecodeError::Source(Box::new(io::Error::other("Test")));
+        assert_eq!(err.source_as::<io::Error>().unwrap().to_string(), "Test");
+        assert_eq!(err, DecodeError::Source(Box::new(io::Error::other("Test"))));
+    }
+
+    /// Tests the decode error hook is called with failed packets
+    #[test]
+    fn test_decode_error_hook() {
+        use crate::{
+            packet::{set_decode_error_hook, Packet, PacketHeader},
+            partial::{PartialDecodable, PartialDecode, PartialReader},
+            reader::TdfReader,
+            tag::TdfType,
+            writer::TdfWriter,
+        };
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        /// Partially decoded value with a single field
+        struct Partial(Option<u32>);
+
+        impl PartialDecodable for Partial {
+            fn decode_partial(reader: &mut TdfReader) -> PartialDecode<Self> {
+                let mut reader = PartialReader::new(reader);
+                let value = reader.tag(b"VALU");
+                reader.finish(Partial(value))
+            }
+        }
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let hook_count = count.clone();
+        set_decode_error_hook(move |header, err| {
+            // Ignore errors from other tests decoding packets
+            if header.component == 0x7FFF {
+                assert!(err.is_unexpected_eof());
+                hook_count.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        let packet = Packet::raw(PacketHeader::request(1, 0x7FFF, 1), vec![0x80]);
+        assert!(packet.decode::<u32>().is_err());
+        assert!(packet.decode_with_context::<u32>().is_err());
+        assert!(Packet::raw(PacketHeader::request(1, 0x7FFF, 1), vec![0x1])
+            .decode::<u32>()
+            .is_ok());
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+
+        // Truncated var int value for the partial field
+        let mut writer = TdfWriter::default();
+        writer.tag(b"VALU", TdfType::VarInt);
+        writer.write_byte(0x80);
+        let packet = Packet::raw(PacketHeader::request(1, 0x7FFF, 1), writer.into());
+        assert!(packet.decode_partial::<Partial>().value.0.is_none());
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+
+    /// Tests converting errors to io errors keeps the original error
+    #[test]
+    fn test_io_conversion() {
+        let err: io::Error = DecodeError::Other("Test").into();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let inner = err.get_ref().unwrap().downcast_ref::<DecodeError>();
+        assert!(matches!(inner, Some(DecodeError::Other("Test"))));
+
+        let err: io::Error = CodecError::from(EncodeError::Other("Test")).into();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.get_ref().unwrap().is::<EncodeError>());
+
+        let err: io::Error = CodecError::from(io::Error::from(io::ErrorKind::BrokenPipe)).into();
+        assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    }
+}
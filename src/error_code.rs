@@ -0,0 +1,168 @@
+//! Registry of known Blaze error codes ([`ErrorRegistry`]) for resolving
+//! the error value of packet headers into names and descriptions.
+//!
+//! The global registry starts with the common framework error codes and
+//! can be extended at runtime with the codes specific to a game title
+//! using [`register_error`]
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::Display,
+    sync::{OnceLock, RwLock},
+};
+
+/// Known error code with its name and description
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorCode {
+    /// The error code value
+    pub code: u16,
+    /// The name of the error (e.g. ERR_SYSTEM)
+    pub name: Cow<'static, str>,
+    /// Description of what the error means
+    pub description: Cow<'static, str>,
+}
+
+/// Common error codes used by the Blaze framework across titles
+const FRAMEWORK_ERRORS: &[(u16, &str, &str)] = &[
+    (0x0001, "ERR_SYSTEM", "General system error"),
+    (0x0002, "ERR_COMPONENT_NOT_FOUND", "Component was not found"),
+    (0x0003, "ERR_COMMAND_NOT_FOUND", "Command was not found"),
+    (
+        0x0004,
+        "ERR_AUTHENTICATION_REQUIRED",
+        "Authentication is required",
+    ),
+    (0x0005, "ERR_TIMEOUT", "Request timed out"),
+    (0x0006, "ERR_DISCONNECTED", "Connection was lost"),
+    (0x0007, "ERR_DUPLICATE_LOGIN", "User is already logged in"),
+    (
+        0x0008,
+        "ERR_AUTHORIZATION_REQUIRED",
+        "User is not authorized",
+    ),
+    (0x0009, "ERR_CANCELED", "Request was canceled"),
+];
+
+/// Registry mapping error codes to their known names and descriptions
+#[derive(Debug, Clone, Default)]
+pub struct ErrorRegistry {
+    /// The registered error codes
+    codes: HashMap<u16, ErrorCode>,
+}
+
+impl ErrorRegistry {
+    /// Creates a new empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new registry containing the common framework
+    /// error codes
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        for (code, name, description) in FRAMEWORK_ERRORS {
+            registry.register(*code, *name, *description);
+        }
+        registry
+    }
+
+    /// Registers an error code replacing any existing entry
+    /// for the same code
+    ///
+    /// `code`        The error code value
+    /// `name`        The name of the error
+    /// `description` Description of the error
+    pub fn register(
+        &mut self,
+        code: u16,
+        name: impl Into<Cow<'static, str>>,
+        description: impl Into<Cow<'static, str>>,
+    ) {
+        self.codes.insert(
+            code,
+            ErrorCode {
+                code,
+                name: name.into(),
+                description: description.into(),
+            },
+        );
+    }
+
+    /// Looks up the error code with the provided value
+    ///
+    /// `code` The error code value
+    pub fn get(&self, code: u16) -> Option<&ErrorCode> {
+        self.codes.get(&code)
+    }
+}
+
+/// Returns the global error registry which is initialized with
+/// the framework error codes on first use
+pub fn global_registry() -> &'static RwLock<ErrorRegistry> {
+    static REGISTRY: OnceLock<RwLock<ErrorRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(ErrorRegistry::with_defaults()))
+}
+
+/// Registers an error code with the global registry
+///
+/// `code`        The error code value
+/// `name`        The name of the error
+/// `description` Description of the error
+pub fn register_error(
+    code: u16,
+    name: impl Into<Cow<'static, str>>,
+    description: impl Into<Cow<'static, str>>,
+) {
+    let mut registry = global_registry()
+        .write()
+        .unwrap_or_else(|err| err.into_inner());
+    registry.register(code, name, description);
+}
+
+/// Looks up an error code in the global registry
+///
+/// `code` The error code value
+pub fn lookup_error(code: u16) -> Option<ErrorCode> {
+    let registry = global_registry()
+        .read()
+        .unwrap_or_else(|err| err.into_inner());
+    registry.get(code).cloned()
+}
+
+/// Display wrapper for error codes which formats the code as hex
+/// followed by its name from the global registry when known
+/// (e.g. `0x0001 (ERR_SYSTEM)`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ErrorDisplay(pub u16);
+
+impl Display for ErrorDisplay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#06x}", self.0)?;
+        if let Some(error) = lookup_error(self.0) {
+            write!(f, " ({})", error.name)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{lookup_error, register_error, ErrorDisplay, ErrorRegistry};
+
+    /// Tests looking up default and registered error codes
+    #[test]
+    fn test_registry() {
+        let mut registry = ErrorRegistry::with_defaults();
+        assert_eq!(registry.get(0x1).unwrap().name, "ERR_SYSTEM");
+        assert!(registry.get(0x4000).is_none());
+
+        registry.register(0x4000, "GAME_ERR_FULL", "Game is full");
+        assert_eq!(registry.get(0x4000).unwrap().description, "Game is full");
+
+        register_error(0x4001, "TITLE_ERR_TEST", "Test error");
+        assert_eq!(lookup_error(0x4001).unwrap().name, "TITLE_ERR_TEST");
+        assert_eq!(ErrorDisplay(0x4001).to_string(), "0x4001 (TITLE_ERR_TEST)");
+        assert_eq!(ErrorDisplay(0x4fff).to_string(), "0x4fff");
+    }
+}
@@ -0,0 +1,250 @@
+//! Minimal C API for decoding packet frames into JSON strings and encoding
+//! JSON strings back into frames so tools written in other languages can
+//! link against this crate.
+//!
+//! The crate is only built as a Rust library by default, a shared or static
+//! library can be built by overriding the crate type from the repository:
+//!
+//! ```sh
+//! cargo rustc --lib --release --features ffi --crate-type cdylib
+//! cargo rustc --lib --release --features ffi --crate-type staticlib
+//! ```
+//!
+//! Which produces `target/release/libblaze_pk.so` (`blaze_pk.dll` on Windows,
+//! `libblaze_pk.dylib` on macOS) or `libblaze_pk.a` (`blaze_pk.lib` on Windows)
+//!
+//! ```c
+//! char *blaze_decode_frame_json(const uint8_t *frame, size_t length);
+//! uint8_t *blaze_encode_json_frame(const char *json, size_t *out_length);
+//! const char *blaze_last_error(void);
+//! void blaze_free_string(char *value);
+//! void blaze_free_frame(uint8_t *frame, size_t length);
+//! ```
+//!
+//! Functions that fail return null and store a message that can be read
+//! with `blaze_last_error` until the next call on the same thread. Panics
+//! are caught rather than unwinding into the caller and are reported the
+//! same way
+
+use crate::json::{decode_frame_to_json, encode_json_to_frame};
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::AssertUnwindSafe,
+    ptr, slice,
+};
+
+thread_local! {
+    /// The message of the last error on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Stores the provided error message as the last error
+/// for the current thread
+///
+/// `message` The error message
+fn set_last_error(message: impl ToString) {
+    // Messages containing nul bytes can't be represented
+    let message = CString::new(message.to_string()).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Calls the provided function catching any panic so that it doesn't
+/// unwind into the calling language. Panics are stored as the last
+/// error and null is returned instead
+///
+/// `fun` The function to call
+fn catch_panic<T>(fun: impl FnOnce() -> *mut T) -> *mut T {
+    match std::panic::catch_unwind(AssertUnwindSafe(fun)) {
+        Ok(value) => value,
+        Err(payload) => {
+            let message = if let Some(message) = payload.downcast_ref::<&'static str>() {
+                message
+            } else if let Some(message) = payload.downcast_ref::<String>() {
+                message.as_str()
+            } else {
+                "Unknown panic"
+            };
+            set_last_error(format!("Panicked: {message}"));
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Decodes the provided packet frame into a JSON string in the form
+/// produced by [`decode_frame_to_json`]. Returns null on failure.
+/// The returned string must be freed with [`blaze_free_string`]
+///
+/// # Safety
+///
+/// `frame` must point to `length` readable bytes
+///
+/// `frame`  Pointer to the bytes of the frame
+/// `length` The number of bytes in the frame
+#[no_mangle]
+pub unsafe extern "C" fn blaze_decode_frame_json(frame: *const u8, length: usize) -> *mut c_char {
+    if frame.is_null() {
+        set_last_error("Frame pointer was null");
+        return ptr::null_mut();
+    }
+    let frame = slice::from_raw_parts(frame, length);
+    catch_panic(|| {
+        let value = match decode_frame_to_json(frame) {
+            Ok(value) => value,
+            Err(err) => {
+                set_last_error(err);
+                return ptr::null_mut();
+            }
+        };
+        match CString::new(value.to_string()) {
+            Ok(value) => value.into_raw(),
+            Err(err) => {
+                set_last_error(err);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Encodes the provided JSON string in the form produced by
+/// [`blaze_decode_frame_json`] into a packet frame. Returns null
+/// on failure. The length of the frame is written to `out_length`
+/// and the frame must be freed with [`blaze_free_frame`]
+///
+/// # Safety
+///
+/// `json` must be a valid nul terminated string and `out_length`
+/// must be valid for writes
+///
+/// `json`       The nul terminated JSON string
+/// `out_length` Output for the length of the frame
+#[no_mangle]
+pub unsafe extern "C" fn blaze_encode_json_frame(
+    json: *const c_char,
+    out_length: *mut usize,
+) -> *mut u8 {
+    if json.is_null() || out_length.is_null() {
+        set_last_error("JSON or length pointer was null");
+        return ptr::null_mut();
+    }
+    let json = match CStr::from_ptr(json).to_str() {
+        Ok(value) => value,
+        Err(err) => {
+            set_last_error(err);
+            return ptr::null_mut();
+        }
+    };
+    catch_panic(|| {
+        let frame = serde_json::from_str(json)
+            .map_err(|err| err.to_string())
+            .and_then(|value| encode_json_to_frame(&value).map_err(|err| err.to_string()));
+        match frame {
+            Ok(frame) => {
+                let frame = frame.into_boxed_slice();
+                *out_length = frame.len();
+                Box::into_raw(frame) as *mut u8
+            }
+            Err(err) => {
+                set_last_error(err);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Returns the message of the last error on the current thread or null
+/// if there hasn't been an error. The message is owned by the library
+/// and is valid until the next call on the same thread
+#[no_mangle]
+pub extern "C" fn blaze_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Frees a string returned by this library
+///
+/// # Safety
+///
+/// `value` must be null or a string returned by this library
+/// that hasn't already been freed
+///
+/// `value` The string to free
+#[no_mangle]
+pub unsafe extern "C" fn blaze_free_string(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+/// Frees a frame returned by this library
+///
+/// # Safety
+///
+/// `frame` must be null or a frame returned by this library that
+/// hasn't already been freed along with its length
+///
+/// `frame`  The frame to free
+/// `length` The length of the frame
+#[no_mangle]
+pub unsafe extern "C" fn blaze_free_frame(frame: *mut u8, length: usize) {
+    if !frame.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(frame, length)));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        blaze_decode_frame_json, blaze_encode_json_frame, blaze_free_frame, blaze_free_string,
+        blaze_last_error, catch_panic,
+    };
+    use crate::{
+        packet::{Packet, PacketHeader},
+        writer::TdfWriter,
+    };
+    use bytes::BytesMut;
+    use std::{ffi::CStr, ptr, slice};
+
+    /// Tests frames decoded to JSON through the C API can
+    /// be encoded back into the same frames
+    #[test]
+    fn test_ffi_round_trip() {
+        let mut writer = TdfWriter::default();
+        writer.tag_str(b"NAME", "Test");
+        let mut frame = BytesMut::new();
+        Packet::raw(PacketHeader::request(1, 0x1, 0x2), writer.into()).write(&mut frame);
+
+        unsafe {
+            let json = blaze_decode_frame_json(frame.as_ptr(), frame.len());
+            assert!(!json.is_null());
+
+            let mut length = 0;
+            let encoded = blaze_encode_json_frame(json, &mut length);
+            assert!(!encoded.is_null());
+            assert_eq!(slice::from_raw_parts(encoded, length), &frame[..]);
+
+            blaze_free_string(json);
+            blaze_free_frame(encoded, length);
+
+            let json = blaze_decode_frame_json(frame.as_ptr(), frame.len() - 1);
+            assert!(json.is_null());
+            assert!(!CStr::from_ptr(blaze_last_error()).is_empty());
+        }
+    }
+
+    /// Tests panics are caught and stored as the last error
+    #[test]
+    fn test_ffi_catch_panic() {
+        let value = catch_panic::<u8>(|| panic!("Failed to encode"));
+        assert!(value.is_null());
+        let message = unsafe { CStr::from_ptr(blaze_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "Panicked: Failed to encode");
+
+        let mut value = 1u8;
+        let pointer = ptr::addr_of_mut!(value);
+        assert_eq!(catch_panic(|| pointer), pointer);
+    }
+}
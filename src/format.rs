@@ -0,0 +1,530 @@
+//! Configurable pretty printing of packet contents ([`TdfFormatter`]) with
+//! options for the indentation, truncating long strings and blobs, collapsing
+//! large lists and ANSI coloring for terminal output.
+//!
+//! The default formatter produces the same output as [`TdfReader::stringify`].
+//! [`PacketDebug`] and the replay mismatch output use the global default
+//! formatter which can be replaced using [`set_default_formatter`]
+//!
+//! ```
+//! use blaze_pk::{format::TdfFormatter, writer::TdfWriter};
+//!
+//! let mut writer = TdfWriter::default();
+//! writer.tag_str(b"NAME", "A very long name");
+//! writer.tag_value(b"IDS", &vec![1u32, 2, 3, 4]);
+//!
+//! let out = TdfFormatter::new()
+//!     .max_string(6)
+//!     .collapse_lists(2)
+//!     .format_contents(&writer.buffer, None);
+//! assert_eq!(
+//!     out,
+//!     "  \"NAME\": \"A very\"... (16 chars),\n  \"IDS\": [1, 2, ... (2 more)],\n"
+//! );
+//! ```
+//!
+//! [`TdfReader::stringify`]: crate::reader::TdfReader::stringify
+//! [`PacketDebug`]: crate::packet::PacketDebug
+
+use crate::{
+    error::DecodeResult, reader::TdfReader, tag::TdfType, tag_names::TagNames, types::UNION_UNSET,
+};
+use std::sync::{OnceLock, RwLock};
+
+/// ANSI color used for tags
+const TAG_COLOR: &str = "\x1b[36m";
+/// ANSI color used for strings
+const STRING_COLOR: &str = "\x1b[32m";
+/// ANSI color used for numbers
+const NUMBER_COLOR: &str = "\x1b[33m";
+/// ANSI color used for the names of value kinds (Blob, Map, etc)
+const KIND_COLOR: &str = "\x1b[35m";
+/// ANSI color used for tag names and truncation notes
+const NOTE_COLOR: &str = "\x1b[2m";
+/// ANSI code resetting the color
+const RESET: &str = "\x1b[0m";
+
+/// Formatter for converting encoded packet contents into
+/// a readable string representation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TdfFormatter {
+    /// The number of spaces for each indent level
+    indent: usize,
+    /// The maximum number of chars of strings to display
+    max_string: Option<usize>,
+    /// The maximum number of bytes of blobs to display
+    max_blob: Option<usize>,
+    /// The maximum number of list values to display
+    max_list: Option<usize>,
+    /// Whether to color the output using ANSI escape codes
+    color: bool,
+}
+
+impl Default for TdfFormatter {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            max_string: None,
+            max_blob: None,
+            max_list: None,
+            color: false,
+        }
+    }
+}
+
+impl TdfFormatter {
+    /// Creates a new formatter with the default options
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the number of spaces used for each indent level
+    ///
+    /// `indent` The number of spaces
+    pub fn indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Sets the maximum number of chars to display for strings
+    /// with longer strings being truncated
+    ///
+    /// `length` The maximum number of chars
+    pub fn max_string(mut self, length: usize) -> Self {
+        self.max_string = Some(length);
+        self
+    }
+
+    /// Sets the maximum number of bytes to display for blobs
+    /// with longer blobs being truncated
+    ///
+    /// `length` The maximum number of bytes
+    pub fn max_blob(mut self, length: usize) -> Self {
+        self.max_blob = Some(length);
+        self
+    }
+
+    /// Sets the maximum number of values to display for lists
+    /// with the remaining values of longer lists collapsed
+    ///
+    /// `length` The maximum number of values
+    pub fn collapse_lists(mut self, length: usize) -> Self {
+        self.max_list = Some(length);
+        self
+    }
+
+    /// Sets whether the output is colored using ANSI escape codes
+    ///
+    /// `color` Whether to color the output
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Formats the provided encoded contents into a string appending
+    /// the decoding error to the output if the contents are malformed
+    ///
+    /// `contents` The encoded contents
+    /// `names`    The known tag names
+    pub fn format_contents(&self, contents: &[u8], names: Option<&TagNames>) -> String {
+        let mut out = String::new();
+        // Errors are appended to the output so the result can be ignored
+        let _ = self.format(&mut TdfReader::new(contents), &mut out, names);
+        out
+    }
+
+    /// Formats all the remaining contents within the reader into the
+    /// output appending the decoding error to the output if the contents
+    /// are malformed
+    ///
+    /// `reader` The reader to format the contents of
+    /// `out`    The string output to append to
+    /// `names`  The known tag names
+    pub fn format(
+        &self,
+        reader: &mut TdfReader,
+        out: &mut String,
+        names: Option<&TagNames>,
+    ) -> DecodeResult<()> {
+        while reader.cursor < reader.buffer.len() {
+            if let Err(err) = self.format_tag(reader, out, 1, names) {
+                out.push_str(&format!(
+                    "... remaining {}, cause: {:?}",
+                    reader.buffer.len() - reader.cursor,
+                    err
+                ));
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Formats the next tag and its value into the output
+    ///
+    /// `reader` The reader to read the tag from
+    /// `out`    The string output to append to
+    /// `indent` The current indent level
+    /// `names`  The known tag names
+    pub fn format_tag(
+        &self,
+        reader: &mut TdfReader,
+        out: &mut String,
+        indent: usize,
+        names: Option<&TagNames>,
+    ) -> DecodeResult<()> {
+        let tag = reader.read_tag()?;
+        self.push_indent(out, indent);
+        self.push_colored(out, TAG_COLOR, &format!("\"{}\"", &tag.tag));
+        if let Some(name) = names.and_then(|names| names.get(&tag.tag)) {
+            self.push_colored(out, NOTE_COLOR, &format!(" ({})", name));
+        }
+        out.push_str(": ");
+        match self.format_type(reader, out, indent, &tag.ty, names) {
+            Ok(_) => {
+                out.push_str(",\n");
+                Ok(())
+            }
+            Err(err) => {
+                out.push_str("...");
+                Err(err)
+            }
+        }
+    }
+
+    /// Formats the next value of the provided type into the output
+    ///
+    /// `reader` The reader to read the value from
+    /// `out`    The string output to append to
+    /// `indent` The current indent level
+    /// `ty`     The type of the value
+    /// `names`  The known tag names
+    pub fn format_type(
+        &self,
+        reader: &mut TdfReader,
+        out: &mut String,
+        indent: usize,
+        ty: &TdfType,
+        names: Option<&TagNames>,
+    ) -> DecodeResult<()> {
+        match ty {
+            TdfType::VarInt => {
+                let value = reader.read_usize()?;
+                self.push_colored(out, NUMBER_COLOR, &value.to_string());
+            }
+            TdfType::String => {
+                let value = reader.read_str()?;
+                let length = value.chars().count();
+                match self.max_string {
+                    Some(max) if length > max => {
+                        let value: String = value.chars().take(max).collect();
+                        self.push_colored(out, STRING_COLOR, &format!("\"{}\"", value));
+                        self.push_colored(out, NOTE_COLOR, &format!("... ({} chars)", length));
+                    }
+                    _ => self.push_colored(out, STRING_COLOR, &format!("\"{}\"", value)),
+                }
+            }
+            TdfType::Blob => {
+                let value = reader.read_blob()?;
+                let shown = self
+                    .max_blob
+                    .map_or(value.len(), |max| max.min(value.len()));
+                self.push_colored(out, KIND_COLOR, "Blob");
+                out.push_str(" [");
+                for (i, byte) in value[..shown].iter().enumerate() {
+                    self.push_colored(out, NUMBER_COLOR, &format!("0x{:X}", byte));
+                    if i + 1 < value.len() {
+                        out.push_str(", ");
+                    }
+                }
+                if shown < value.len() {
+                    self.push_colored(
+                        out,
+                        NOTE_COLOR,
+                        &format!("... ({} more bytes)", value.len() - shown),
+                    );
+                }
+                out.push(']');
+            }
+            TdfType::Group => {
+                out.push_str("{\n");
+                let mut is_two: bool = false;
+                while reader.cursor < reader.buffer.len() {
+                    let byte: u8 = reader.buffer[reader.cursor];
+                    if byte == 0 {
+                        reader.cursor += 1;
+                        break;
+                    }
+                    if byte == 2 {
+                        is_two = true;
+                        reader.cursor += 1;
+                    }
+                    self.format_tag(reader, out, indent + 1, names)?;
+                }
+                self.push_indent(out, indent);
+                out.push('}');
+                if is_two {
+                    out.push_str(" (2)");
+                }
+            }
+            TdfType::List => {
+                let value_type: TdfType = reader.read_type()?;
+                let length: usize = reader.read_usize()?;
+                let shown = self.max_list.map_or(length, |max| max.min(length));
+                let expand = matches!(value_type, TdfType::Map | TdfType::Group);
+                out.push('[');
+                if expand {
+                    out.push('\n');
+                }
+
+                for i in 0..shown {
+                    if expand {
+                        self.push_indent(out, indent + 1);
+                    }
+                    self.format_type(reader, out, indent + 1, &value_type, names)?;
+                    if i + 1 < length {
+                        out.push_str(", ");
+                    }
+                    if expand {
+                        out.push('\n');
+                    }
+                }
+
+                if shown < length {
+                    // Collapsed values still need to be read past
+                    for _ in shown..length {
+                        reader.skip_type(&value_type)?;
+                    }
+                    if expand {
+                        self.push_indent(out, indent + 1);
+                    }
+                    self.push_colored(out, NOTE_COLOR, &format!("... ({} more)", length - shown));
+                    if expand {
+                        out.push('\n');
+                    }
+                }
+
+                if expand {
+                    self.push_indent(out, indent);
+                }
+                out.push(']');
+            }
+            TdfType::Map => {
+                let key_type: TdfType = reader.read_type()?;
+                let value_type: TdfType = reader.read_type()?;
+                let length: usize = reader.read_usize()?;
+                self.push_colored(
+                    out,
+                    KIND_COLOR,
+                    &format!("Map<{:?}, {:?}>", key_type, value_type),
+                );
+                out.push_str(" {\n");
+
+                for i in 0..length {
+                    self.push_indent(out, indent + 1);
+                    self.format_type(reader, out, indent + 1, &key_type, names)?;
+                    out.push_str(": ");
+                    self.format_type(reader, out, indent + 1, &value_type, names)?;
+                    if i + 1 < length {
+                        out.push(',');
+                    }
+                    out.push('\n')
+                }
+                self.push_indent(out, indent);
+                out.push('}');
+            }
+            TdfType::Union => {
+                let ty = reader.read_byte()?;
+                if ty == UNION_UNSET {
+                    self.push_colored(out, KIND_COLOR, "Union");
+                    out.push_str("(Unset)");
+                } else {
+                    let tag = reader.read_tag()?;
+                    self.push_colored(out, KIND_COLOR, "Union");
+                    out.push('(');
+                    self.push_colored(out, TAG_COLOR, &format!("\"{}\"", &tag.tag));
+                    out.push_str(&format!(", {}, ", ty));
+                    self.format_type(reader, out, indent + 1, &tag.ty, names)?;
+                    out.push(')')
+                }
+            }
+            TdfType::VarIntList => {
+                let length: usize = reader.read_usize()?;
+                let shown = self.max_list.map_or(length, |max| max.min(length));
+                self.push_colored(out, KIND_COLOR, "VarList");
+                out.push_str(" [");
+                for i in 0..length {
+                    let value = reader.read_usize()?;
+                    if i >= shown {
+                        continue;
+                    }
+                    self.push_colored(out, NUMBER_COLOR, &value.to_string());
+                    if i + 1 < length {
+                        out.push_str(", ");
+                    }
+                }
+                if shown < length {
+                    self.push_colored(out, NOTE_COLOR, &format!("... ({} more)", length - shown));
+                }
+                out.push(']');
+            }
+            TdfType::Pair => {
+                let a = reader.read_usize()?;
+                let b = reader.read_usize()?;
+                out.push('(');
+                self.push_colored(out, NUMBER_COLOR, &a.to_string());
+                out.push_str(", ");
+                self.push_colored(out, NUMBER_COLOR, &b.to_string());
+                out.push(')');
+            }
+            TdfType::Triple => {
+                let a = reader.read_usize()?;
+                let b = reader.read_usize()?;
+                let c = reader.read_usize()?;
+                out.push('(');
+                self.push_colored(out, NUMBER_COLOR, &a.to_string());
+                out.push_str(", ");
+                self.push_colored(out, NUMBER_COLOR, &b.to_string());
+                out.push_str(", ");
+                self.push_colored(out, NUMBER_COLOR, &c.to_string());
+                out.push(')');
+            }
+            TdfType::Float => {
+                let value = reader.read_f32()?;
+                self.push_colored(out, NUMBER_COLOR, &value.to_string());
+            }
+            TdfType::TimeValue => {
+                let value = reader.read_u64()?;
+                self.push_colored(out, NUMBER_COLOR, &value.to_string());
+                out.push_str("us");
+            }
+            TdfType::Generic => {
+                self.push_colored(out, KIND_COLOR, "Generic");
+                if reader.read_byte()? == 0 {
+                    out.push_str("(Unset)");
+                } else {
+                    let id = reader.read_u64()?;
+                    let tag = reader.read_tag()?;
+                    out.push('(');
+                    self.push_colored(out, TAG_COLOR, &format!("\"{}\"", &tag.tag));
+                    out.push_str(&format!(", {}, ", id));
+                    self.format_type(reader, out, indent + 1, &tag.ty, names)?;
+                    out.push(')');
+                }
+                reader.skip_group_end()?;
+            }
+        };
+        Ok(())
+    }
+
+    /// Appends the indentation for the provided indent level
+    ///
+    /// `out`    The string output to append to
+    /// `indent` The indent level
+    fn push_indent(&self, out: &mut String, indent: usize) {
+        out.extend(std::iter::repeat_n(' ', self.indent * indent));
+    }
+
+    /// Appends the provided text wrapped in the provided ANSI
+    /// color if coloring is enabled
+    ///
+    /// `out`   The string output to append to
+    /// `color` The ANSI color code
+    /// `text`  The text to append
+    fn push_colored(&self, out: &mut String, color: &str, text: &str) {
+        if self.color {
+            out.push_str(color);
+            out.push_str(text);
+            out.push_str(RESET);
+        } else {
+            out.push_str(text);
+        }
+    }
+}
+
+/// Returns the global default formatter
+fn global_formatter() -> &'static RwLock<TdfFormatter> {
+    static FORMATTER: OnceLock<RwLock<TdfFormatter>> = OnceLock::new();
+    FORMATTER.get_or_init(Default::default)
+}
+
+/// Replaces the global default formatter used by [`PacketDebug`]
+/// and the other tooling output
+///
+/// `formatter` The new default formatter
+///
+/// [`PacketDebug`]: crate::packet::PacketDebug
+pub fn set_default_formatter(formatter: TdfFormatter) {
+    let mut global = global_formatter()
+        .write()
+        .unwrap_or_else(|err| err.into_inner());
+    *global = formatter;
+}
+
+/// Returns a copy of the global default formatter
+pub fn default_formatter() -> TdfFormatter {
+    global_formatter()
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone()
+}
+
+#[cfg(test)]
+mod test {
+    use super::TdfFormatter;
+    use crate::{reader::TdfReader, writer::TdfWriter};
+
+    /// Tests the default formatter matches the reader stringify output
+    #[test]
+    fn test_default_matches_stringify() {
+        let mut writer = TdfWriter::default();
+        writer.group(b"USER", |writer| {
+            writer.tag_str(b"NAME", "Test");
+            writer.tag_value(b"IDS", &vec![1u32, 2, 3]);
+        });
+        writer.tag_value(b"BLOB", &crate::types::Blob(vec![0x1, 0xAB]));
+
+        let mut expected = String::new();
+        TdfReader::new(&writer.buffer)
+            .stringify(&mut expected)
+            .unwrap();
+        let out = TdfFormatter::new().format_contents(&writer.buffer, None);
+        assert_eq!(out, expected);
+        assert_eq!(
+            out,
+            "  \"USER\": {\n    \"NAME\": \"Test\",\n    \"IDS\": [1, 2, 3],\n  },\n  \"BLOB\": Blob [0x1, 0xAB],\n"
+        );
+    }
+
+    /// Tests the truncating, collapsing and indentation options
+    #[test]
+    fn test_options() {
+        let mut writer = TdfWriter::default();
+        writer.tag_value(b"BLOB", &crate::types::Blob(vec![0x1, 0x2, 0x3]));
+        writer.group(b"USER", |writer| {
+            writer.tag_value(b"IDS", &vec![1u32, 2, 3]);
+        });
+        writer.tag_u8(b"END", 1);
+
+        let out = TdfFormatter::new()
+            .indent(1)
+            .max_blob(1)
+            .collapse_lists(0)
+            .format_contents(&writer.buffer, None);
+        assert_eq!(
+            out,
+            " \"BLOB\": Blob [0x1, ... (2 more bytes)],\n \"USER\": {\n  \"IDS\": [... (3 more)],\n },\n \"END\": 1,\n"
+        );
+    }
+
+    /// Tests colored output wraps values in ANSI escape codes
+    #[test]
+    fn test_color() {
+        let mut writer = TdfWriter::default();
+        writer.tag_u8(b"VAL", 1);
+
+        let out = TdfFormatter::new()
+            .color(true)
+            .format_contents(&writer.buffer, None);
+        assert_eq!(out, "  \x1b[36m\"VAL\"\x1b[0m: \x1b[33m1\x1b[0m,\n");
+    }
+}
@@ -0,0 +1,260 @@
+//! Runtime agnostic framing of packets ([`PacketStream`]) implemented
+//! directly against the [`futures_io`] `AsyncRead` and `AsyncWrite` traits
+//! so async-std and smol connections can be used without going through
+//! `tokio_util::codec`
+//!
+//! ```
+//! use blaze_pk::futures::PacketStream;
+//! use futures_io::{AsyncRead, AsyncWrite};
+//!
+//! fn accept<T: AsyncRead + AsyncWrite + Unpin>(io: T) -> PacketStream<T> {
+//!     PacketStream::new(io)
+//! }
+//! ```
+
+use crate::{
+    error::CodecError,
+    packet::{Packet, PacketCodec},
+};
+use bytes::BytesMut;
+use futures_core::Stream;
+use futures_io::{AsyncRead, AsyncWrite};
+use futures_sink::Sink;
+use std::{
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// The number of bytes to read from the connection at a time
+const READ_CHUNK: usize = 8 * 1024;
+/// The number of buffered bytes at which the sink will flush
+/// before accepting more packets
+const BACKPRESSURE_BOUNDARY: usize = 8 * 1024;
+
+/// Stream of packets read from and sink of packets written
+/// to an underlying connection
+pub struct PacketStream<T> {
+    /// The underlying connection
+    io: T,
+    /// Buffer of bytes read but not yet decoded
+    read_buffer: BytesMut,
+    /// Buffer of encoded packets not yet written
+    write_buffer: BytesMut,
+    /// Whether the connection has reached the end
+    eof: bool,
+}
+
+impl<T> PacketStream<T> {
+    /// Creates a new packet stream over the provided connection
+    ///
+    /// `io` The underlying connection
+    pub fn new(io: T) -> Self {
+        Self {
+            io,
+            read_buffer: BytesMut::new(),
+            write_buffer: BytesMut::new(),
+            eof: false,
+        }
+    }
+
+    /// Returns a reference to the underlying connection
+    pub fn get_ref(&self) -> &T {
+        &self.io
+    }
+
+    /// Returns a mutable reference to the underlying connection
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.io
+    }
+
+    /// Consumes the stream returning the underlying connection. Any
+    /// buffered bytes that haven't been read or written are lost
+    pub fn into_inner(self) -> T {
+        self.io
+    }
+}
+
+impl<T> Stream for PacketStream<T>
+where
+    T: AsyncRead + Unpin,
+{
+    type Item = Result<Packet, CodecError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut chunk = [0u8; READ_CHUNK];
+        loop {
+            if let Some(packet) = PacketCodec.decode(&mut this.read_buffer)? {
+                return Poll::Ready(Some(Ok(packet)));
+            }
+
+            if this.eof {
+                if this.read_buffer.is_empty() {
+                    return Poll::Ready(None);
+                }
+                // Discard the partial packet so the stream ends afterwards
+                this.read_buffer.clear();
+                let err =
+                    io::Error::new(io::ErrorKind::UnexpectedEof, "Stream ended within packet");
+                return Poll::Ready(Some(Err(err.into())));
+            }
+
+            let count = ready!(Pin::new(&mut this.io).poll_read(cx, &mut chunk))?;
+            if count == 0 {
+                this.eof = true;
+            }
+            this.read_buffer.extend_from_slice(&chunk[..count]);
+        }
+    }
+}
+
+impl<T> PacketStream<T>
+where
+    T: AsyncWrite + Unpin,
+{
+    /// Polls writing all the buffered bytes to the connection
+    ///
+    /// `cx` The task context
+    fn poll_write_buffer(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), CodecError>> {
+        while !self.write_buffer.is_empty() {
+            let count = ready!(Pin::new(&mut self.io).poll_write(cx, &self.write_buffer))?;
+            if count == 0 {
+                let err = io::Error::new(io::ErrorKind::WriteZero, "Failed to write packet");
+                return Poll::Ready(Err(err.into()));
+            }
+            let _ = self.write_buffer.split_to(count);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T> Sink<Packet> for PacketStream<T>
+where
+    T: AsyncWrite + Unpin,
+{
+    type Error = CodecError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        if this.write_buffer.len() >= BACKPRESSURE_BOUNDARY {
+            ready!(this.poll_write_buffer(cx))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Packet) -> Result<(), Self::Error> {
+        PacketCodec.encode(item, &mut self.get_mut().write_buffer)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_buffer(cx))?;
+        ready!(Pin::new(&mut this.io).poll_flush(cx))?;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_buffer(cx))?;
+        ready!(Pin::new(&mut this.io).poll_close(cx))?;
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PacketStream;
+    use crate::packet::test::framing_packets;
+    use futures_core::Stream;
+    use futures_io::{AsyncRead, AsyncWrite};
+    use futures_sink::Sink;
+    use std::{
+        io,
+        pin::Pin,
+        task::{Context, Poll, Waker},
+    };
+
+    /// In memory connection reading from a fixed buffer in small
+    /// chunks and collecting the written bytes
+    #[derive(Default)]
+    struct MemoryIo {
+        /// The bytes available for reading
+        read: Vec<u8>,
+        /// The bytes written
+        written: Vec<u8>,
+    }
+
+    impl AsyncRead for MemoryIo {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let length = buf.len().min(self.read.len()).min(7);
+            buf[..length].copy_from_slice(&self.read[..length]);
+            self.read.drain(..length);
+            Poll::Ready(Ok(length))
+        }
+    }
+
+    impl AsyncWrite for MemoryIo {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            self.written.extend_from_slice(buf);
+            Poll::Ready(Ok(buf.len()))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Tests packets sent through the sink can be read back
+    /// through the stream
+    #[test]
+    fn test_sink_stream() {
+        let packets = framing_packets();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut writer = PacketStream::new(MemoryIo::default());
+        for packet in &packets {
+            assert!(Pin::new(&mut writer).poll_ready(&mut cx).is_ready());
+            Pin::new(&mut writer).start_send(packet.clone()).unwrap();
+        }
+        assert!(matches!(
+            Pin::new(&mut writer).poll_flush(&mut cx),
+            Poll::Ready(Ok(()))
+        ));
+        let mut written = writer.into_inner().written;
+        // Remove the last byte to end within a packet
+        written.pop();
+
+        let mut reader = PacketStream::new(MemoryIo {
+            read: written,
+            written: Vec::new(),
+        });
+        for packet in &packets[..packets.len() - 1] {
+            let Poll::Ready(Some(Ok(read))) = Pin::new(&mut reader).poll_next(&mut cx) else {
+                panic!("Expected packet to be read");
+            };
+            assert_eq!(&read, packet);
+        }
+        assert!(matches!(
+            Pin::new(&mut reader).poll_next(&mut cx),
+            Poll::Ready(Some(Err(_)))
+        ));
+        assert!(matches!(
+            Pin::new(&mut reader).poll_next(&mut cx),
+            Poll::Ready(None)
+        ));
+    }
+}
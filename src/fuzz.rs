@@ -0,0 +1,260 @@
+//! [Arbitrary] implementations for generating structured inputs when fuzzing.
+//!
+//! Generated dynamic values are always valid so they can be used for
+//! round-trip testing of the reader and writer: tags only use characters
+//! that can be encoded, list and map values always match their declared
+//! types and nesting is limited to [MAX_DEPTH]
+
+use crate::{
+    codec::Encodable,
+    packet::{Packet, PacketHeader, PacketType},
+    tag::{Tag, TdfType},
+    types::UNION_UNSET,
+    value::{TdfField, TdfFields, TdfValue},
+};
+use arbitrary::{Arbitrary, Result, Unstructured};
+use bytes::Bytes;
+
+/// The maximum depth of nested values that will be generated
+const MAX_DEPTH: usize = 4;
+
+/// All the value types
+const TYPES: &[TdfType] = &[
+    TdfType::VarInt,
+    TdfType::String,
+    TdfType::Blob,
+    TdfType::Group,
+    TdfType::List,
+    TdfType::Map,
+    TdfType::Union,
+    TdfType::VarIntList,
+    TdfType::Pair,
+    TdfType::Triple,
+    TdfType::Float,
+    TdfType::TimeValue,
+    TdfType::Generic,
+];
+
+/// Value types that cannot contain nested values
+const SCALAR_TYPES: &[TdfType] = &[
+    TdfType::VarInt,
+    TdfType::String,
+    TdfType::Blob,
+    TdfType::VarIntList,
+    TdfType::Pair,
+    TdfType::Triple,
+    TdfType::Float,
+    TdfType::TimeValue,
+];
+
+impl<'a> Arbitrary<'a> for PacketType {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.choose(&[
+            PacketType::Request,
+            PacketType::Response,
+            PacketType::Notify,
+            PacketType::Error,
+        ])
+        .copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for PacketHeader {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            component: u.arbitrary()?,
+            command: u.arbitrary()?,
+            error: u.arbitrary()?,
+            ty: u.arbitrary()?,
+            id: u.arbitrary()?,
+        })
+    }
+}
+
+/// Packets are generated with contents encoded from arbitrary fields
+impl<'a> Arbitrary<'a> for Packet {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let header = u.arbitrary()?;
+        let fields: TdfFields = u.arbitrary()?;
+        Ok(Self {
+            header,
+            contents: Bytes::from(fields.encode_bytes()),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for TdfType {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        u.choose(TYPES).copied()
+    }
+}
+
+/// Tags are generated with 1 to 4 uppercase letters
+impl<'a> Arbitrary<'a> for Tag {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut tag = [0u8; 4];
+        let length = u.int_in_range(1..=4)?;
+        for value in tag.iter_mut().take(length) {
+            *value = u.int_in_range(b'A'..=b'Z')?;
+        }
+        Ok(Self(tag))
+    }
+}
+
+impl<'a> Arbitrary<'a> for TdfValue {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let ty = u.arbitrary()?;
+        arbitrary_value(u, ty, 0)
+    }
+}
+
+impl<'a> Arbitrary<'a> for TdfField {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_field(u, 0)
+    }
+}
+
+impl<'a> Arbitrary<'a> for TdfFields {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        arbitrary_fields(u, 0).map(Self)
+    }
+}
+
+/// Generates a value type that can be used at the provided depth
+///
+/// `u`     The unstructured input
+/// `depth` The current nesting depth
+fn arbitrary_type(u: &mut Unstructured, depth: usize) -> Result<TdfType> {
+    let types = if depth >= MAX_DEPTH {
+        SCALAR_TYPES
+    } else {
+        TYPES
+    };
+    u.choose(types).copied()
+}
+
+/// Generates a field with a value at the provided depth
+///
+/// `u`     The unstructured input
+/// `depth` The current nesting depth
+fn arbitrary_field(u: &mut Unstructured, depth: usize) -> Result<TdfField> {
+    let tag = u.arbitrary()?;
+    let ty = arbitrary_type(u, depth)?;
+    let value = arbitrary_value(u, ty, depth)?;
+    Ok(TdfField { tag, value })
+}
+
+/// Generates a collection of fields at the provided depth
+///
+/// `u`     The unstructured input
+/// `depth` The current nesting depth
+fn arbitrary_fields(u: &mut Unstructured, depth: usize) -> Result<Vec<TdfField>> {
+    let length = u.arbitrary_len::<u8>()?;
+    (0..length).map(|_| arbitrary_field(u, depth + 1)).collect()
+}
+
+/// Generates a value of the provided type at the provided depth
+///
+/// `u`     The unstructured input
+/// `ty`    The type of value to generate
+/// `depth` The current nesting depth
+fn arbitrary_value(u: &mut Unstructured, ty: TdfType, depth: usize) -> Result<TdfValue> {
+    Ok(match ty {
+        TdfType::VarInt => TdfValue::VarInt(u.arbitrary()?),
+        TdfType::String => TdfValue::String(u.arbitrary()?),
+        TdfType::Blob => TdfValue::Blob(u.arbitrary()?),
+        TdfType::Group => TdfValue::Group {
+            two: u.arbitrary()?,
+            fields: arbitrary_fields(u, depth)?,
+        },
+        TdfType::List => {
+            let ty = arbitrary_type(u, depth + 1)?;
+            let length = u.arbitrary_len::<u8>()?;
+            let values = (0..length)
+                .map(|_| arbitrary_value(u, ty, depth + 1))
+                .collect::<Result<_>>()?;
+            TdfValue::List { ty, values }
+        }
+        TdfType::Map => {
+            let key_ty = *u.choose(&[TdfType::VarInt, TdfType::String])?;
+            let value_ty = arbitrary_type(u, depth + 1)?;
+            let length = u.arbitrary_len::<u8>()?;
+            let entries = (0..length)
+                .map(|_| {
+                    Ok((
+                        arbitrary_value(u, key_ty, depth + 1)?,
+                        arbitrary_value(u, value_ty, depth + 1)?,
+                    ))
+                })
+                .collect::<Result<_>>()?;
+            TdfValue::Map {
+                key_ty,
+                value_ty,
+                entries,
+            }
+        }
+        TdfType::Union => {
+            let key: u8 = u.arbitrary()?;
+            let value = if key == UNION_UNSET {
+                None
+            } else {
+                Some(Box::new(arbitrary_field(u, depth + 1)?))
+            };
+            TdfValue::Union { key, value }
+        }
+        TdfType::VarIntList => TdfValue::VarIntList(u.arbitrary()?),
+        TdfType::Pair => TdfValue::Pair(u.arbitrary()?, u.arbitrary()?),
+        TdfType::Triple => TdfValue::Triple(u.arbitrary()?, u.arbitrary()?, u.arbitrary()?),
+        TdfType::Float => TdfValue::Float(u.arbitrary()?),
+        TdfType::TimeValue => TdfValue::TimeValue(u.arbitrary()?),
+        TdfType::Generic => {
+            let value = if u.arbitrary()? {
+                Some((u.arbitrary()?, Box::new(arbitrary_field(u, depth + 1)?)))
+            } else {
+                None
+            };
+            TdfValue::Generic(value)
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        codec::{Decodable, Encodable},
+        packet::Packet,
+        reader::TdfReader,
+        value::TdfFields,
+    };
+    use arbitrary::{Arbitrary, Unstructured};
+    use bytes::BytesMut;
+
+    /// Tests generated values round-trip through the writer and reader
+    #[test]
+    fn test_arbitrary_round_trip() {
+        // Deterministic pseudo random input data
+        let mut state: u32 = 0x1234_5678;
+        let data: Vec<u8> = (0..8192)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+
+        for offset in (0..4096).step_by(128) {
+            let mut u = Unstructured::new(&data[offset..]);
+
+            let fields = TdfFields::arbitrary(&mut u).unwrap();
+            let bytes = fields.try_encode_bytes().unwrap();
+            let decoded = TdfFields::decode(&mut TdfReader::new(&bytes)).unwrap();
+            assert_eq!(decoded.encode_bytes(), bytes);
+
+            let packet = Packet::arbitrary(&mut u).unwrap();
+            let mut frame = BytesMut::new();
+            packet.write(&mut frame);
+            assert_eq!(Packet::read(&mut frame).unwrap(), packet);
+        }
+    }
+}
@@ -0,0 +1,369 @@
+//! Parsing of hex dumps ([`parse_hex`]) such as those copied from Wireshark,
+//! `hexdump -C`, `xxd` or `tcpdump -X` along with plain hex strings so that
+//! packets shared as text can be decoded in one call.
+//!
+//! ```
+//! use blaze_pk::hexdump::packet_from_hex;
+//!
+//! let packet = packet_from_hex(
+//!     "0000   00 02 00 01 00 02 00 00 00 00 00 01 d2 00   ..............",
+//! )
+//! .unwrap();
+//! assert_eq!(packet.header.component, 0x1);
+//! assert_eq!(&packet.contents[..], &[0xd2, 0x00]);
+//! ```
+
+use crate::packet::Packet;
+use bytes::{BufMut, BytesMut};
+use std::{error::Error, fmt::Display};
+
+/// Error type for errors that can occur while parsing hex
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HexDumpError {
+    /// The line contained characters that weren't valid hex
+    InvalidHex {
+        /// The line number (starting from 1)
+        line: usize,
+    },
+    /// The offset of a hex dump line didn't match the
+    /// number of bytes read before it
+    OffsetMismatch {
+        /// The line number (starting from 1)
+        line: usize,
+        /// The expected offset
+        expected: usize,
+        /// The offset of the line
+        actual: usize,
+    },
+    /// The bytes didn't contain a complete packet
+    IncompletePacket {
+        /// The number of bytes available
+        length: usize,
+    },
+    /// Bytes remained after reading the packet
+    TrailingBytes {
+        /// The number of remaining bytes
+        length: usize,
+    },
+}
+
+/// Type alias for result which could result in a Hex Dump Error
+pub type HexDumpResult<T> = Result<T, HexDumpError>;
+
+impl Error for HexDumpError {}
+
+impl Display for HexDumpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HexDumpError::InvalidHex { line } => write!(f, "Invalid hex on line {}", line),
+            HexDumpError::OffsetMismatch {
+                line,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Offset mismatch on line {} (expected: {:#06x}, actual: {:#06x})",
+                line, expected, actual
+            ),
+            HexDumpError::IncompletePacket { length } => {
+                write!(f, "Incomplete packet ({} bytes)", length)
+            }
+            HexDumpError::TrailingBytes { length } => {
+                write!(f, "{} bytes remaining after packet", length)
+            }
+        }
+    }
+}
+
+/// The number of bytes assumed per hex dump line when it can't be
+/// determined from the offset of the following line
+const DEFAULT_LINE_WIDTH: usize = 16;
+
+/// Parses the bytes from the provided hex. Lines starting with an offset
+/// (e.g. `0000`, `0x0010:` or `00000020`) followed by a gap are treated
+/// as hex dump lines where the ASCII column is ignored. Other lines are
+/// treated as plain hex where bytes can be separated by whitespace, commas,
+/// colons or dashes and can be prefixed with 0x
+///
+/// `input` The hex to parse
+pub fn parse_hex(input: &str) -> HexDumpResult<BytesMut> {
+    let lines: Vec<(usize, &str)> = input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line))
+        .filter(|(_, line)| !line.trim().is_empty())
+        .collect();
+
+    let mut output = BytesMut::new();
+    let mut base: Option<usize> = None;
+    let mut width = DEFAULT_LINE_WIDTH;
+
+    for (index, (line, value)) in lines.iter().enumerate() {
+        let Some((offset, rest)) = dump_offset(value, base.is_some()) else {
+            parse_plain_line(value, *line, &mut output)?;
+            continue;
+        };
+
+        let base = *base.get_or_insert(offset);
+        let expected = base + output.len();
+        if offset != expected {
+            return Err(HexDumpError::OffsetMismatch {
+                line: *line,
+                expected,
+                actual: offset,
+            });
+        }
+
+        // Lines are limited to the bytes before the next offset so the
+        // ASCII column isn't mistaken for bytes
+        let next = lines
+            .get(index + 1)
+            .and_then(|(_, value)| dump_offset(value, true))
+            .map(|(next, _)| next);
+        if let Some(next) = next {
+            width = next.saturating_sub(offset);
+        }
+
+        parse_dump_line(rest, width, *line, &mut output)?;
+    }
+
+    Ok(output)
+}
+
+/// Parses the provided hex into a single packet
+///
+/// `input` The hex to parse
+pub fn packet_from_hex(input: &str) -> HexDumpResult<Packet> {
+    let mut bytes = parse_hex(input)?;
+    let length = bytes.len();
+    let packet = Packet::read(&mut bytes).ok_or(HexDumpError::IncompletePacket { length })?;
+    if !bytes.is_empty() {
+        return Err(HexDumpError::TrailingBytes {
+            length: bytes.len(),
+        });
+    }
+    Ok(packet)
+}
+
+/// Parses the provided hex into all the packets it contains
+///
+/// `input` The hex to parse
+pub fn packets_from_hex(input: &str) -> HexDumpResult<Vec<Packet>> {
+    let mut bytes = parse_hex(input)?;
+    let mut packets = Vec::new();
+    while !bytes.is_empty() {
+        let length = bytes.len();
+        let packet = Packet::read(&mut bytes).ok_or(HexDumpError::IncompletePacket { length })?;
+        packets.push(packet);
+    }
+    Ok(packets)
+}
+
+/// Splits the offset from a hex dump line. Some tools end dumps with
+/// a line containing only the final offset which is only treated as an
+/// offset when already within a hex dump
+///
+/// `line`    The line to split
+/// `in_dump` Whether previous lines were hex dump lines
+fn dump_offset(line: &str, in_dump: bool) -> Option<(usize, &str)> {
+    split_offset(line).or_else(|| {
+        if !in_dump {
+            return None;
+        }
+        parse_offset(line.trim()).map(|(offset, _)| (offset, ""))
+    })
+}
+
+/// Splits the offset from the start of a hex dump line returning the
+/// offset and the remaining line. Returns None if the line doesn't
+/// start with an offset
+///
+/// `line` The line to split
+fn split_offset(line: &str) -> Option<(usize, &str)> {
+    let line = line.trim_start();
+    let end = line.find(char::is_whitespace)?;
+    let (token, rest) = line.split_at(end);
+    let (offset, colon) = parse_offset(token)?;
+
+    // Offsets are followed by a colon or a wider gap than plain hex
+    if !colon && !rest.starts_with("  ") && !rest.starts_with('\t') {
+        return None;
+    }
+
+    Some((offset, rest))
+}
+
+/// Parses a hex dump offset returning the offset and whether
+/// it was followed by a colon
+///
+/// `token` The offset token
+fn parse_offset(token: &str) -> Option<(usize, bool)> {
+    let token = token
+        .strip_prefix("0x")
+        .or_else(|| token.strip_prefix("0X"))
+        .unwrap_or(token);
+    let (token, colon) = match token.strip_suffix(':') {
+        Some(token) => (token, true),
+        None => (token, false),
+    };
+
+    if token.len() < 4 || !token.bytes().all(|value| value.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let offset = usize::from_str_radix(token, 16).ok()?;
+    Some((offset, colon))
+}
+
+/// Parses the bytes from a hex dump line with the offset removed. Parsing
+/// stops at the line width, at the first value that isn't hex or at a gap
+/// wider than the gap some tools place between the two halves of a line
+///
+/// `rest`   The line after the offset
+/// `width`  The maximum number of bytes on the line
+/// `line`   The line number
+/// `output` The output to write the bytes to
+fn parse_dump_line(
+    rest: &str,
+    width: usize,
+    line: usize,
+    output: &mut BytesMut,
+) -> HexDumpResult<()> {
+    let mut rest = rest.trim_start();
+    let mut count = 0;
+
+    while count < width && !rest.is_empty() {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (token, after) = rest.split_at(end);
+
+        // Values are single bytes or groups of bytes (e.g. xxd)
+        if token.len() > 8
+            || !token.len().is_multiple_of(2)
+            || !token.bytes().all(|value| value.is_ascii_hexdigit())
+            || count + token.len() / 2 > width
+        {
+            break;
+        }
+
+        put_hex(token, line, output)?;
+        count += token.len() / 2;
+
+        rest = after.trim_start();
+        let gap = after.len() - rest.len();
+        if gap > 2 || (gap == 2 && count != 8) {
+            break;
+        }
+    }
+
+    if count == 0 && !rest.is_empty() {
+        return Err(HexDumpError::InvalidHex { line });
+    }
+
+    Ok(())
+}
+
+/// Parses the bytes from a line of plain hex
+///
+/// `value`  The line to parse
+/// `line`   The line number
+/// `output` The output to write the bytes to
+fn parse_plain_line(value: &str, line: usize, output: &mut BytesMut) -> HexDumpResult<()> {
+    value
+        .split(|value: char| value.is_whitespace() || matches!(value, ',' | ':' | '-'))
+        .filter(|token| !token.is_empty())
+        .try_for_each(|token| {
+            let token = token
+                .strip_prefix("0x")
+                .or_else(|| token.strip_prefix("0X"))
+                .unwrap_or(token);
+            put_hex(token, line, output)
+        })
+}
+
+/// Writes the bytes from the provided hex digits to the output
+///
+/// `token`  The hex digits
+/// `line`   The line number
+/// `output` The output to write the bytes to
+fn put_hex(token: &str, line: usize, output: &mut BytesMut) -> HexDumpResult<()> {
+    if !token.len().is_multiple_of(2) {
+        return Err(HexDumpError::InvalidHex { line });
+    }
+
+    for pair in token.as_bytes().chunks(2) {
+        let value = std::str::from_utf8(pair)
+            .ok()
+            .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+            .ok_or(HexDumpError::InvalidHex { line })?;
+        output.put_u8(value);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{packet_from_hex, parse_hex, HexDumpError};
+    use crate::packet::{Packet, PacketHeader};
+
+    /// Tests parsing the hex dump formats of different tools
+    #[test]
+    fn test_parse_hex() {
+        let expected: Vec<u8> = (0u8..20).collect();
+
+        let wireshark = "\
+0000   00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f   ................
+0010   10 11 12 13                                       ....";
+        let hexdump = "\
+00000000  00 01 02 03 04 05 06 07  08 09 0a 0b 0c 0d 0e 0f  |................|
+00000010  10 11 12 13                                       |....|
+00000014";
+        let xxd = "\
+00000000: 0001 0203 0405 0607 0809 0a0b 0c0d 0e0f  ................
+00000010: 1011 1213                                ....";
+        let plain = "00 01 02 03 04 05 06 07 08 09\n0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f 10111213";
+
+        for input in [wireshark, hexdump, xxd, plain] {
+            assert_eq!(&parse_hex(input).unwrap()[..], &expected[..], "{}", input);
+        }
+
+        // ASCII columns that look like hex are ignored
+        let ascii = "0000   61 62 20 63 64   ab cd";
+        assert_eq!(&parse_hex(ascii).unwrap()[..], b"ab cd");
+
+        assert_eq!(
+            parse_hex("0000   00 01\n0004   02 03"),
+            Err(HexDumpError::OffsetMismatch {
+                line: 2,
+                expected: 2,
+                actual: 4
+            })
+        );
+        assert_eq!(
+            parse_hex("00 0g"),
+            Err(HexDumpError::InvalidHex { line: 1 })
+        );
+    }
+
+    /// Tests parsing a packet from a hex dump
+    #[test]
+    fn test_packet_from_hex() {
+        let packet = Packet::raw(PacketHeader::request(1, 2, 3), vec![1, 2, 3]);
+        let bytes: Vec<u8> = {
+            let mut bytes = bytes::BytesMut::new();
+            packet.write(&mut bytes);
+            bytes.to_vec()
+        };
+        let hex: String = bytes
+            .iter()
+            .map(|value| format!("{:02x} ", value))
+            .collect();
+
+        assert_eq!(packet_from_hex(&hex).unwrap(), packet);
+        assert_eq!(
+            packet_from_hex(&hex[..hex.len() - 3]),
+            Err(HexDumpError::IncompletePacket {
+                length: bytes.len() - 1
+            })
+        );
+    }
+}
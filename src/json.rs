@@ -0,0 +1,332 @@
+//! High level conversion of raw packet frames into JSON ([`decode_frame_to_json`])
+//! for tools such as command line utilities and web based debuggers that
+//! only need a readable tree of the packet. JSON in the same form can be
+//! encoded back into frames ([`encode_json_to_frame`]).
+//!
+//! ```
+//! use blaze_pk::{json::decode_frame_to_json, packet::{Packet, PacketHeader}, writer::TdfWriter};
+//! use bytes::BytesMut;
+//!
+//! let mut writer = TdfWriter::default();
+//! writer.tag_str(b"NAME", "Test");
+//!
+//! let mut frame = BytesMut::new();
+//! Packet::raw(PacketHeader::request(1, 0x1, 0x2), writer.into()).write(&mut frame);
+//!
+//! let value = decode_frame_to_json(&frame).unwrap();
+//! assert_eq!(value["header"]["component"], 0x1);
+//! assert_eq!(value["contents"]["NAME"], "Test");
+//! ```
+
+use crate::{
+    codec::Encodable,
+    error::{DecodeError, DecodeResult, EncodeError, EncodeResult},
+    error_code::lookup_error,
+    packet::{Packet, PacketHeader, PacketType},
+    schema::Schema,
+    tag::{Tag, TdfType},
+    types::UNION_UNSET,
+    value::{TdfField, TdfValue},
+    writer::TdfWriter,
+};
+use bytes::BytesMut;
+use serde_json::Value;
+
+/// Decodes a complete packet frame (header and contents) into a JSON
+/// value containing the `header` and the decoded `contents` tree
+///
+/// `bytes` The bytes of the packet frame
+pub fn decode_frame_to_json(bytes: &[u8]) -> DecodeResult<Value> {
+    decode_frame_to_json_with(bytes, None)
+}
+
+/// Decodes a complete packet frame into a JSON value the same as
+/// [decode_frame_to_json] using the provided schema (when present)
+/// to include the component, command and field names
+///
+/// `bytes`  The bytes of the packet frame
+/// `schema` Optional schema for naming the packet and its fields
+pub fn decode_frame_to_json_with(bytes: &[u8], schema: Option<&Schema>) -> DecodeResult<Value> {
+    let mut src = BytesMut::from(bytes);
+    let packet = Packet::read(&mut src).ok_or(DecodeError::UnexpectedEof {
+        cursor: 0,
+        wanted: bytes.len().max(12) + 1,
+        remaining: bytes.len(),
+    })?;
+    if !src.is_empty() {
+        return Err(DecodeError::Custom(format!(
+            "{} bytes remaining after packet frame",
+            src.len()
+        )));
+    }
+
+    packet_to_json(&packet, schema)
+}
+
+/// Converts a packet into a JSON value containing the header and
+/// the decoded contents
+///
+/// `packet` The packet to convert
+/// `schema` Optional schema for naming the packet and its fields
+pub fn packet_to_json(packet: &Packet, schema: Option<&Schema>) -> DecodeResult<Value> {
+    let header = &packet.header;
+    let mut header_value = to_json(header)?;
+
+    let contents = match schema {
+        Some(schema) => {
+            let decoded = schema.decode(packet)?;
+            if let Value::Object(object) = &mut header_value {
+                if let Some(component) = decoded.component {
+                    object.insert("component_name".to_string(), component.into());
+                }
+                if let Some(command) = decoded.command {
+                    object.insert("command_name".to_string(), command.into());
+                }
+            }
+            to_json(&SchemaFields(&decoded.fields))?
+        }
+        None => packet.contents_as_json()?,
+    };
+
+    if let (PacketType::Error, Value::Object(object)) = (&header.ty, &mut header_value) {
+        if let Some(error) = lookup_error(header.error) {
+            object.insert("error_name".to_string(), error.name.into());
+        }
+    }
+
+    let mut out = serde_json::Map::new();
+    out.insert("header".to_string(), header_value);
+    out.insert("contents".to_string(), contents);
+    Ok(Value::Object(out))
+}
+
+/// Encodes a JSON value in the form produced by [decode_frame_to_json]
+/// back into a complete packet frame. See [json_to_packet] for how
+/// the contents are encoded
+///
+/// `value` The JSON value containing the `header` and `contents`
+pub fn encode_json_to_frame(value: &Value) -> EncodeResult<Vec<u8>> {
+    let packet = json_to_packet(value)?;
+    let mut frame = BytesMut::new();
+    packet.try_write(&mut frame)?;
+    Ok(frame.to_vec())
+}
+
+/// Converts a JSON value containing the `header` and `contents` into
+/// a packet. JSON doesn't describe the Tdf types so they are chosen
+/// from the JSON values:
+///
+/// - Objects are groups of tagged fields, except for objects with a
+///   `key` and one tagged value which are unions and objects with an
+///   `id` and one tagged value which are generics
+/// - Arrays are lists of the type of their first value
+/// - Integers and booleans are var ints, other numbers are floats
+/// - Null is an unset union
+///
+/// Fields are written in the order of the JSON objects which are
+/// sorted by their tags
+///
+/// `value` The JSON value containing the `header` and `contents`
+pub fn json_to_packet(value: &Value) -> EncodeResult<Packet> {
+    let header = value
+        .get("header")
+        .ok_or(EncodeError::Other("Missing packet header"))?;
+    let header: PacketHeader = serde::Deserialize::deserialize(header)
+        .map_err(|_| EncodeError::Other("Invalid packet header"))?;
+
+    let mut writer = TdfWriter::default();
+    match value.get("contents") {
+        Some(Value::Object(object)) => {
+            for (key, value) in object {
+                json_to_field(key, value)?.try_encode(&mut writer)?;
+            }
+        }
+        None | Some(Value::Null) => {}
+        Some(_) => return Err(EncodeError::Other("Packet contents must be an object")),
+    }
+    Ok(Packet::raw(header, writer.into()))
+}
+
+/// Converts a JSON object entry into a tagged field
+///
+/// `key`   The tag of the field
+/// `value` The value of the field
+fn json_to_field(key: &str, value: &Value) -> EncodeResult<TdfField> {
+    if key.len() > 4 {
+        return Err(EncodeError::InvalidTag {
+            tag: key.to_string(),
+        });
+    }
+    Ok(TdfField {
+        tag: Tag::from(key.as_bytes()),
+        value: json_to_value(value)?,
+    })
+}
+
+/// Converts a JSON value into a dynamic Tdf value
+///
+/// `value` The value to convert
+fn json_to_value(value: &Value) -> EncodeResult<TdfValue> {
+    Ok(match value {
+        Value::Null => TdfValue::Union {
+            key: UNION_UNSET,
+            value: None,
+        },
+        Value::Bool(value) => TdfValue::VarInt(*value as u64),
+        Value::Number(number) => match number.as_u64() {
+            Some(value) => TdfValue::VarInt(value),
+            None if number.is_i64() => {
+                return Err(EncodeError::Other("Negative integers can't be encoded"))
+            }
+            None => TdfValue::Float(number.as_f64().unwrap_or_default() as f32),
+        },
+        Value::String(value) => TdfValue::String(value.clone()),
+        Value::Array(values) => {
+            let values = values
+                .iter()
+                .map(json_to_value)
+                .collect::<EncodeResult<Vec<_>>>()?;
+            let ty = values.first().map_or(TdfType::VarInt, TdfValue::ty);
+            TdfValue::List { ty, values }
+        }
+        Value::Object(object) if object.len() == 2 && object.contains_key("id") => {
+            let id = object
+                .get("id")
+                .and_then(Value::as_u64)
+                .ok_or(EncodeError::Other("Invalid generic id"))?;
+            let (tag, value) = object
+                .iter()
+                .find(|(tag, _)| *tag != "id")
+                .ok_or(EncodeError::Other("Missing generic value"))?;
+            TdfValue::Generic(Some((id, Box::new(json_to_field(tag, value)?))))
+        }
+        Value::Object(object) => match (object.get("key"), object.len()) {
+            (Some(Value::Number(key)), 2) => {
+                let key = key
+                    .as_u64()
+                    .and_then(|key| u8::try_from(key).ok())
+                    .ok_or(EncodeError::Other("Invalid union key"))?;
+                let (tag, value) = object
+                    .iter()
+                    .find(|(tag, _)| *tag != "key")
+                    .ok_or(EncodeError::Other("Missing union value"))?;
+                TdfValue::Union {
+                    key,
+                    value: Some(Box::new(json_to_field(tag, value)?)),
+                }
+            }
+            _ => TdfValue::Group {
+                two: false,
+                fields: object
+                    .iter()
+                    .map(|(key, value)| json_to_field(key, value))
+                    .collect::<EncodeResult<_>>()?,
+            },
+        },
+    })
+}
+
+/// Wrapper for serializing schema decoded fields as a map
+struct SchemaFields<'a>(&'a [crate::schema::NamedField]);
+
+impl serde::Serialize for SchemaFields<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for field in self.0 {
+            map.serialize_entry(&field.key(), &field.value)?;
+        }
+        map.end()
+    }
+}
+
+/// Converts the provided value to JSON
+///
+/// `value` The value to convert
+fn to_json<T: serde::Serialize>(value: &T) -> DecodeResult<Value> {
+    serde_json::to_value(value).map_err(|err| DecodeError::Source(Box::new(err)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode_frame_to_json, decode_frame_to_json_with, encode_json_to_frame};
+    use crate::{
+        packet::{Packet, PacketHeader},
+        schema::{CommandSchema, ComponentSchema, FieldSchema, Schema},
+        tag::TdfType,
+        writer::TdfWriter,
+    };
+    use bytes::BytesMut;
+
+    /// Tests decoding a frame using a schema for names
+    #[test]
+    fn test_decode_frame_with_schema() {
+        let schema = Schema {
+            components: vec![ComponentSchema {
+                id: 0x1,
+                name: "Authentication".to_string(),
+                commands: vec![CommandSchema {
+                    id: 0x28,
+                    name: "Login".to_string(),
+                    request: vec![FieldSchema {
+                        tag: "MAIL".to_string(),
+                        name: "email".to_string(),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+
+        let mut writer = TdfWriter::default();
+        writer.tag_str(b"MAIL", "test@example.com");
+        writer.tag_u32(b"TYPE", 1);
+
+        let mut frame = BytesMut::new();
+        Packet::raw(PacketHeader::request(1, 0x1, 0x28), writer.into()).write(&mut frame);
+
+        let value = decode_frame_to_json_with(&frame, Some(&schema)).unwrap();
+        assert_eq!(value["header"]["component_name"], "Authentication");
+        assert_eq!(value["header"]["command_name"], "Login");
+        assert_eq!(value["contents"]["email"], "test@example.com");
+        assert_eq!(value["contents"]["TYPE"], 1);
+
+        assert!(decode_frame_to_json_with(&frame[..frame.len() - 1], None).is_err());
+    }
+
+    /// Tests JSON decoded from frames can be encoded back
+    /// into the same frames
+    #[test]
+    fn test_encode_json_to_frame() {
+        let mut writer = TdfWriter::default();
+        writer.tag_union_unset(b"ADDR");
+        writer.tag_group(b"DATA");
+        writer.tag_slice_list(b"IDS", &[1u32, 2, 3]);
+        writer.tag_group_end();
+        writer.tag(b"GEN", TdfType::Generic);
+        writer.write_byte(1);
+        writer.write_u64(12);
+        writer.tag_str(b"VAL", "Value");
+        writer.tag_group_end();
+        writer.tag_str(b"NAME", "Test");
+        writer.tag_u32(b"TYPE", 5);
+
+        let mut frame = BytesMut::new();
+        Packet::raw(PacketHeader::request(7, 0x1, 0x28), writer.into()).write(&mut frame);
+
+        let value = decode_frame_to_json(&frame).unwrap();
+        let encoded = encode_json_to_frame(&value).unwrap();
+        assert_eq!(&encoded, &frame[..]);
+
+        let value = serde_json::json!({
+            "header": { "component": 1, "command": 2, "error": 0, "ty": 0, "id": 1 },
+            "contents": { "lowercase": 1 }
+        });
+        assert!(encode_json_to_frame(&value).is_err());
+    }
+}
@@ -0,0 +1,233 @@
+//! Latency analysis of captures ([`analyze_capture`]) which pairs requests
+//! with their responses and errors by packet ID within each connection and
+//! reports latency percentiles for each component and command. Used for
+//! comparing the timing of server implementations against official servers.
+//!
+//! Latencies are measured using the capture timestamps of the frames that
+//! completed each packet
+//!
+//! ```no_run
+//! use blaze_pk::{capture::CaptureReader, latency::analyze_capture};
+//!
+//! let packets = CaptureReader::new().port(14219).read_file("session.pcapng").unwrap();
+//! let report = analyze_capture(&packets);
+//! println!("{}", report);
+//! ```
+
+use crate::{
+    capture::{split_connections, CapturedPacket, Connection, ConnectionDirection},
+    packet::PacketType,
+};
+use std::{collections::HashMap, fmt::Display, time::Duration};
+
+/// Latency statistics for a specific component and command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// The component of the requests
+    pub component: u16,
+    /// The command of the requests
+    pub command: u16,
+    /// The number of requests answered with error packets
+    pub errors: usize,
+    /// The latencies of the answered requests in ascending order
+    pub samples: Vec<Duration>,
+}
+
+impl LatencyStats {
+    /// Returns the number of answered requests
+    pub fn count(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns the smallest latency
+    pub fn min(&self) -> Duration {
+        self.samples.first().copied().unwrap_or_default()
+    }
+
+    /// Returns the largest latency
+    pub fn max(&self) -> Duration {
+        self.samples.last().copied().unwrap_or_default()
+    }
+
+    /// Returns the mean latency
+    pub fn mean(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        let total: Duration = self.samples.iter().sum();
+        total / self.samples.len() as u32
+    }
+
+    /// Returns the latency at the provided percentile using the
+    /// nearest rank method
+    ///
+    /// `percentile` The percentile between 0 and 100
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * self.samples.len() as f64).ceil();
+        let index = (rank as usize).saturating_sub(1);
+        self.samples[index.min(self.samples.len() - 1)]
+    }
+}
+
+/// Latency statistics for all the requests within a capture
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyReport {
+    /// The stats for each component and command ordered by
+    /// component then command
+    pub commands: Vec<LatencyStats>,
+    /// The number of requests that were never answered
+    pub unanswered: usize,
+}
+
+impl LatencyReport {
+    /// Finds the stats for the provided component and command
+    ///
+    /// `component` The component of the requests
+    /// `command`   The command of the requests
+    pub fn get(&self, component: u16, command: u16) -> Option<&LatencyStats> {
+        self.commands
+            .iter()
+            .find(|stats| stats.component == component && stats.command == command)
+    }
+}
+
+impl Display for LatencyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<6} {:<6} {:>6} {:>6} {:>10} {:>10} {:>10} {:>10}",
+            "COMP", "CMD", "COUNT", "ERRORS", "P50", "P90", "P99", "MAX"
+        )?;
+        for stats in &self.commands {
+            writeln!(
+                f,
+                "{:<#6x} {:<#6x} {:>6} {:>6} {:>10.2?} {:>10.2?} {:>10.2?} {:>10.2?}",
+                stats.component,
+                stats.command,
+                stats.count(),
+                stats.errors,
+                stats.percentile(50.0),
+                stats.percentile(90.0),
+                stats.percentile(99.0),
+                stats.max()
+            )?;
+        }
+        write!(f, "Unanswered requests: {}", self.unanswered)
+    }
+}
+
+/// Analyzes the latency of the requests within the provided capture
+/// packets which can contain packets from multiple connections
+///
+/// `packets` The captured packets in the order they were sent
+pub fn analyze_capture(packets: &[CapturedPacket]) -> LatencyReport {
+    analyze_connections(&split_connections(packets))
+}
+
+/// Analyzes the latency of the requests within the provided connections
+///
+/// `connections` The connections to analyze
+pub fn analyze_connections(connections: &[Connection]) -> LatencyReport {
+    let mut commands: HashMap<(u16, u16), LatencyStats> = HashMap::new();
+    let mut unanswered = 0;
+
+    for connection in connections {
+        // Requests waiting for a response by packet ID
+        let mut pending: HashMap<u16, Duration> = HashMap::new();
+
+        for packet in &connection.packets {
+            let header = &packet.packet.header;
+            match (packet.direction, header.ty) {
+                (ConnectionDirection::ClientToServer, PacketType::Request) => {
+                    // Reused IDs replace requests that were never answered
+                    let replaced = pending.insert(header.id, packet.timestamp);
+                    unanswered += replaced.is_some() as usize;
+                }
+                (ConnectionDirection::ServerToClient, PacketType::Response | PacketType::Error) => {
+                    let Some(sent) = pending.remove(&header.id) else {
+                        continue;
+                    };
+                    let stats = commands
+                        .entry((header.component, header.command))
+                        .or_insert_with(|| LatencyStats {
+                            component: header.component,
+                            command: header.command,
+                            errors: 0,
+                            samples: Vec::new(),
+                        });
+                    if let PacketType::Error = header.ty {
+                        stats.errors += 1;
+                    }
+                    stats.samples.push(packet.timestamp.saturating_sub(sent));
+                }
+                _ => {}
+            }
+        }
+
+        unanswered += pending.len();
+    }
+
+    let mut commands: Vec<LatencyStats> = commands.into_values().collect();
+    commands.sort_by_key(|stats| (stats.component, stats.command));
+    commands.iter_mut().for_each(|stats| stats.samples.sort());
+    LatencyReport {
+        commands,
+        unanswered,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::analyze_capture;
+    use crate::{
+        capture::CapturedPacket,
+        packet::{Packet, PacketHeader},
+    };
+    use std::{net::SocketAddr, time::Duration};
+
+    /// Tests requests are paired with responses within each connection
+    #[test]
+    fn test_analyze_capture() {
+        let captured =
+            |ms: u64, source: u16, destination: u16, header: PacketHeader| CapturedPacket {
+                timestamp: Duration::from_millis(ms),
+                source: SocketAddr::from(([127, 0, 0, 1], source)),
+                destination: SocketAddr::from(([127, 0, 0, 1], destination)),
+                packet: Packet::raw_empty(header),
+            };
+
+        let mut packets = Vec::new();
+        for (index, latency) in [10u64, 20, 30, 40].into_iter().enumerate() {
+            let request = PacketHeader::request(index as u16, 0x1, 0x2);
+            packets.push(captured(100, 50000, 14219, request));
+            packets.push(captured(100 + latency, 14219, 50000, request.response()));
+        }
+        // Same ID on another connection answered with an error
+        let request = PacketHeader::request(0, 0x1, 0x2);
+        packets.push(captured(500, 50001, 14219, request));
+        packets.push(captured(505, 14219, 50001, request.with_error(0x4)));
+        // Request without a response
+        packets.push(captured(
+            600,
+            50001,
+            14219,
+            PacketHeader::request(1, 0x1, 0x3),
+        ));
+
+        let report = analyze_capture(&packets);
+        assert_eq!(report.unanswered, 1);
+        assert_eq!(report.commands.len(), 1);
+
+        let stats = report.get(0x1, 0x2).unwrap();
+        assert_eq!(stats.count(), 5);
+        assert_eq!(stats.errors, 1);
+        assert_eq!(stats.min(), Duration::from_millis(5));
+        assert_eq!(stats.percentile(50.0), Duration::from_millis(20));
+        assert_eq!(stats.percentile(99.0), Duration::from_millis(40));
+        assert_eq!(stats.mean(), Duration::from_millis(21));
+        assert!(report.to_string().contains("Unanswered requests: 1"));
+    }
+}
@@ -1,18 +1,92 @@
 //! Rust library for working with the Blaze packet system this is the networking solution used by games such as
 //! Mass Effect 3, Battlefield 3, another Other EA games.
 
+pub mod annotate;
+pub mod anonymize;
 pub mod codec;
+pub mod diff;
 pub mod error;
+pub mod error_code;
+pub mod format;
+pub mod hexdump;
+/// Rate limiting, unavailable on wasm32-unknown-unknown where
+/// [`std::time::Instant`] is unsupported
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub mod limit;
+pub mod marker;
 pub mod packet;
+pub mod partial;
 pub mod reader;
+pub mod record;
+pub mod replay;
 pub mod router;
+pub mod schema;
+pub mod session;
+pub mod sniff;
+/// Packet statistics, unavailable on wasm32-unknown-unknown where
+/// [`std::time::Instant`] is unsupported
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub mod stats;
+pub mod sync;
 pub mod tag;
+pub mod tag_names;
+pub mod timer;
 pub mod types;
+pub mod validate;
+pub mod value;
 pub mod writer;
 
 /// Serde serialization
 #[cfg(feature = "serde")]
 pub mod serialize;
 
+/// JSON conversion of packet frames
+#[cfg(feature = "json")]
+pub mod json;
+
+/// Packet capture reading
+#[cfg(feature = "pcap")]
+pub mod capture;
+
+/// Latency analysis of captures
+#[cfg(feature = "pcap")]
+pub mod latency;
+
+/// Arbitrary implementations for fuzzing
+#[cfg(feature = "arbitrary")]
+mod fuzz;
+
+/// Round-trip testing utilities
+#[cfg(feature = "test-util")]
+pub mod test_util;
+
+/// Code generation from schemas
+#[cfg(feature = "codegen")]
+pub mod codegen;
+
+/// Name mapping files
+#[cfg(feature = "mappings")]
+pub mod mapping;
+
+/// Actix codec integration
+#[cfg(feature = "actix")]
+pub mod actix;
+
+/// Runtime agnostic futures framing
+#[cfg(feature = "futures")]
+pub mod futures;
+
+/// C API for frame conversion
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+/// Client connections
+#[cfg(feature = "client")]
+pub mod client;
+
+/// Tower service integration
+#[cfg(feature = "tower")]
+pub mod service;
+
 /// Re-exports for derive macros
-pub use blaze_pk_derive::{PacketComponent, PacketComponents};
+pub use blaze_pk_derive::{routes, PacketComponent, PacketComponents};
@@ -0,0 +1,204 @@
+//! Token bucket rate limiting for packets routed through the [`Router`]
+//! protecting servers from sessions flooding requests
+//!
+//! [`Router`]: crate::router::Router
+
+use crate::packet::{Packet, PacketComponents};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Error code used for rate limit error responses when one
+/// is not specified
+pub const DEFAULT_RATE_LIMIT_ERROR: u16 = 0x1;
+
+/// Configuration for a token bucket rate limit. Allows `capacity`
+/// packets to be handled in a burst with the tokens being refilled
+/// over `interval`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The maximum number of tokens the bucket can hold
+    pub capacity: u32,
+    /// The amount of time taken to completely refill the bucket
+    pub interval: Duration,
+}
+
+impl RateLimit {
+    /// Creates a new rate limit allowing `capacity` packets
+    /// over the provided `interval`
+    ///
+    /// `capacity` The maximum number of tokens
+    /// `interval` The time taken to refill all the tokens
+    pub const fn new(capacity: u32, interval: Duration) -> Self {
+        Self { capacity, interval }
+    }
+
+    /// Creates a new rate limit allowing `capacity` packets
+    /// every second
+    ///
+    /// `capacity` The maximum number of tokens
+    pub const fn per_second(capacity: u32) -> Self {
+        Self::new(capacity, Duration::from_secs(1))
+    }
+}
+
+/// Token bucket storing the current number of available tokens
+/// for a rate limit
+#[derive(Debug)]
+struct TokenBucket {
+    /// The rate limit for this bucket
+    limit: RateLimit,
+    /// The current number of tokens available
+    tokens: f64,
+    /// The last time the tokens were refilled
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a new full token bucket for the provided limit
+    ///
+    /// `limit` The rate limit
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: limit.capacity as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    /// Refills the bucket based on the time elapsed since the last
+    /// refill and attempts to take a token. Returns whether a token
+    /// was available
+    ///
+    /// `now` The current time
+    fn try_take(&mut self, now: Instant) -> bool {
+        let capacity = self.limit.capacity as f64;
+        let interval = self.limit.interval.as_secs_f64();
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.last_refill = now;
+
+        if interval > 0.0 {
+            self.tokens = (self.tokens + elapsed * (capacity / interval)).min(capacity);
+        } else {
+            self.tokens = capacity;
+        }
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Per-session rate limiter. Each session should own its own limiter
+/// which is checked before handing packets to the router. Packets are
+/// checked against an overall session limit and then against any limit
+/// specific to the packet component
+#[derive(Debug)]
+pub struct RateLimiter {
+    /// Bucket for the limit applied to all packets in the session
+    session: Option<TokenBucket>,
+    /// Buckets for the limits on specific components keyed by the
+    /// component and command values
+    components: HashMap<(u16, u16), TokenBucket>,
+    /// The error code to respond with when a limit is exceeded
+    error: u16,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self {
+            session: None,
+            components: HashMap::new(),
+            error: DEFAULT_RATE_LIMIT_ERROR,
+        }
+    }
+}
+
+impl RateLimiter {
+    /// Creates a new rate limiter with no limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the limit applied to every packet in the session
+    ///
+    /// `limit` The session rate limit
+    pub fn session(mut self, limit: RateLimit) -> Self {
+        self.session = Some(TokenBucket::new(limit));
+        self
+    }
+
+    /// Sets the limit applied to packets for the provided
+    /// component
+    ///
+    /// `component` The component to limit
+    /// `limit`     The component rate limit
+    pub fn component<C: PacketComponents>(mut self, component: C, limit: RateLimit) -> Self {
+        self.components
+            .insert(component.values(), TokenBucket::new(limit));
+        self
+    }
+
+    /// Sets the error code used for the error response packet
+    /// when a rate limit is exceeded
+    ///
+    /// `error` The error code
+    pub fn error(mut self, error: u16) -> Self {
+        self.error = error;
+        self
+    }
+
+    /// Checks whether the provided packet is within the rate limits
+    /// taking a token from the relevant buckets. When a limit has been
+    /// exceeded the error response packet is returned instead
+    ///
+    /// `packet` The packet to check
+    pub fn check(&mut self, packet: &Packet) -> Result<(), Packet> {
+        let now = Instant::now();
+        let header = &packet.header;
+
+        if let Some(bucket) = &mut self.session {
+            if !bucket.try_take(now) {
+                return Err(packet.respond_error_empty(self.error));
+            }
+        }
+
+        if let Some(bucket) = self.components.get_mut(&(header.component, header.command)) {
+            if !bucket.try_take(now) {
+                return Err(packet.respond_error_empty(self.error));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RateLimit, TokenBucket};
+    use std::time::{Duration, Instant};
+
+    /// Tests that the bucket is exhausted after taking all its
+    /// tokens and is refilled over time
+    #[test]
+    fn test_token_bucket() {
+        let mut bucket = TokenBucket::new(RateLimit::new(3, Duration::from_secs(3)));
+        let now = bucket.last_refill;
+
+        assert!(bucket.try_take(now));
+        assert!(bucket.try_take(now));
+        assert!(bucket.try_take(now));
+        assert!(!bucket.try_take(now));
+
+        // A single token should be refilled after one second
+        let later: Instant = now + Duration::from_secs(1);
+        assert!(bucket.try_take(later));
+        assert!(!bucket.try_take(later));
+    }
+}
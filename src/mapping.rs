@@ -0,0 +1,327 @@
+//! Loading of component, command and tag name mappings ([`Mappings`]) from
+//! TOML or JSON files at runtime. Loaded mappings are merged into the global
+//! [`TagNameRegistry`] used by [`PacketDebug`] and the stringify functions so
+//! newly reverse-engineered names can be used without recompiling.
+//!
+//! Mapping files have the following format:
+//!
+//! ```toml
+//! [[components]]
+//! id = 0x1
+//! name = "Authentication"
+//!
+//! [[components.commands]]
+//! id = 0x28
+//! name = "Login"
+//! tags = { MAIL = "email", PASS = "password" }
+//!
+//! [[components.notifications]]
+//! id = 0x1
+//! name = "UserAuthenticated"
+//! ```
+//!
+//! ```
+//! use blaze_pk::{mapping::Mappings, tag::Tag, tag_names::lookup_tag_names};
+//!
+//! let mappings = Mappings::from_json(
+//!     r#"{ "components": [{ "id": 1, "commands": [{ "id": 40, "tags": { "MAIL": "email" } }] }] }"#,
+//! )
+//! .unwrap();
+//! mappings.register();
+//!
+//! let names = lookup_tag_names(1, 40).unwrap();
+//! assert_eq!(names.get(&Tag::from(b"MAIL")), Some("email"));
+//! ```
+//!
+//! [`TagNameRegistry`]: crate::tag_names::TagNameRegistry
+//! [`PacketDebug`]: crate::packet::PacketDebug
+
+use crate::tag_names::{global_registry, TagNameRegistry, TagNames};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, error::Error, fmt::Display, fs, io, path::Path};
+
+/// Error type for errors that can occur while loading mappings
+#[derive(Debug)]
+pub enum MappingError {
+    /// IO error while reading the mapping file
+    Io(io::Error),
+    /// The mapping file extension wasn't a known format
+    UnknownFormat,
+    /// The mappings couldn't be parsed as TOML
+    Toml(toml::de::Error),
+    /// The mappings couldn't be parsed as JSON
+    Json(serde_json::Error),
+    /// A tag in the mappings couldn't be encoded as a tag
+    InvalidTag {
+        /// The component the tag was mapped for
+        component: u16,
+        /// The command the tag was mapped for
+        command: u16,
+        /// The invalid tag
+        tag: String,
+    },
+}
+
+/// Type alias for result which could result in a Mapping Error
+pub type MappingResult<T> = Result<T, MappingError>;
+
+impl From<io::Error> for MappingError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<toml::de::Error> for MappingError {
+    fn from(value: toml::de::Error) -> Self {
+        Self::Toml(value)
+    }
+}
+
+impl From<serde_json::Error> for MappingError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+impl Error for MappingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            MappingError::Io(err) => Some(err),
+            MappingError::Toml(err) => Some(err),
+            MappingError::Json(err) => Some(err),
+            MappingError::UnknownFormat | MappingError::InvalidTag { .. } => None,
+        }
+    }
+}
+
+impl Display for MappingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MappingError::Io(err) => write!(f, "Failed to read mappings: {}", err),
+            MappingError::UnknownFormat => {
+                f.write_str("Unknown mapping format, expected .toml or .json")
+            }
+            MappingError::Toml(err) => write!(f, "Invalid TOML mappings: {}", err),
+            MappingError::Json(err) => write!(f, "Invalid JSON mappings: {}", err),
+            MappingError::InvalidTag {
+                component,
+                command,
+                tag,
+            } => write!(
+                f,
+                "Invalid tag \"{}\" for component {:#06x} command {:#06x}",
+                tag, component, command
+            ),
+        }
+    }
+}
+
+/// Collection of name mappings for components
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Mappings {
+    /// The mappings for each component
+    #[serde(default)]
+    pub components: Vec<ComponentMapping>,
+}
+
+/// Name mappings for a component and its commands
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentMapping {
+    /// The ID of the component
+    pub id: u16,
+    /// The name of the component
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The mappings for the commands of the component
+    #[serde(default)]
+    pub commands: Vec<CommandMapping>,
+    /// The mappings for the notifications of the component
+    #[serde(default)]
+    pub notifications: Vec<CommandMapping>,
+}
+
+/// Name mappings for a command or notification and its tags
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandMapping {
+    /// The ID of the command
+    pub id: u16,
+    /// The name of the command
+    #[serde(default)]
+    pub name: Option<String>,
+    /// The field names for the tags used in the command contents
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+impl Mappings {
+    /// Parses mappings from a TOML string
+    ///
+    /// `value` The TOML string
+    pub fn from_toml(value: &str) -> MappingResult<Self> {
+        let mappings: Self = toml::from_str(value)?;
+        mappings.validate()?;
+        Ok(mappings)
+    }
+
+    /// Parses mappings from a JSON string
+    ///
+    /// `value` The JSON string
+    pub fn from_json(value: &str) -> MappingResult<Self> {
+        let mappings: Self = serde_json::from_str(value)?;
+        mappings.validate()?;
+        Ok(mappings)
+    }
+
+    /// Loads mappings from a file using the format matching the
+    /// file extension (.toml or .json)
+    ///
+    /// `path` The path to the mapping file
+    pub fn load_file(path: impl AsRef<Path>) -> MappingResult<Self> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("toml") => Self::from_toml(&fs::read_to_string(path)?),
+            Some("json") => Self::from_json(&fs::read_to_string(path)?),
+            _ => Err(MappingError::UnknownFormat),
+        }
+    }
+
+    /// Checks that all the mapped tags can be encoded as tags
+    fn validate(&self) -> MappingResult<()> {
+        for component in &self.components {
+            let commands = component.commands.iter().chain(&component.notifications);
+            for command in commands {
+                let invalid = command.tags.keys().find(|tag| {
+                    !(1..=4).contains(&tag.len())
+                        || !tag
+                            .bytes()
+                            .all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit())
+                });
+                if let Some(tag) = invalid {
+                    return Err(MappingError::InvalidTag {
+                        component: component.id,
+                        command: command.id,
+                        tag: tag.clone(),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Merges the mappings into the provided registry replacing any
+    /// existing names that are also present in the mappings
+    ///
+    /// `registry` The registry to merge into
+    pub fn apply(&self, registry: &mut TagNameRegistry) {
+        for component in &self.components {
+            if let Some(name) = &component.name {
+                registry.register_component(component.id, name.clone());
+            }
+
+            let commands = component
+                .commands
+                .iter()
+                .map(|command| (command, false))
+                .chain(
+                    component
+                        .notifications
+                        .iter()
+                        .map(|command| (command, true)),
+                );
+
+            for (command, notify) in commands {
+                if let Some(name) = &command.name {
+                    registry.register_command(component.id, command.id, notify, name.clone());
+                }
+                if !command.tags.is_empty() {
+                    let names: TagNames = command
+                        .tags
+                        .iter()
+                        .map(|(tag, name)| (tag.as_bytes(), name.clone()))
+                        .collect();
+                    registry.merge(component.id, command.id, names);
+                }
+            }
+        }
+    }
+
+    /// Merges the mappings into the global registry
+    pub fn register(&self) {
+        let mut registry = global_registry()
+            .write()
+            .unwrap_or_else(|err| err.into_inner());
+        self.apply(&mut registry);
+    }
+}
+
+/// Loads the mappings from the provided file and merges them into
+/// the global registry
+///
+/// `path` The path to the mapping file (.toml or .json)
+pub fn load_mappings(path: impl AsRef<Path>) -> MappingResult<()> {
+    Mappings::load_file(path)?.register();
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MappingError, Mappings};
+    use crate::{
+        tag::Tag,
+        tag_names::{TagNameRegistry, TagNames},
+    };
+
+    /// Tests TOML mappings are merged with the existing names
+    #[test]
+    fn test_toml_mappings() {
+        let mappings = Mappings::from_toml(
+            r#"
+            [[components]]
+            id = 0x1
+            name = "Authentication"
+
+            [[components.commands]]
+            id = 0x28
+            name = "Login"
+            tags = { MAIL = "email", PASS = "password" }
+
+            [[components.notifications]]
+            id = 0x28
+            name = "LoginNotify"
+            "#,
+        )
+        .unwrap();
+
+        let mut registry = TagNameRegistry::new();
+        let existing: TagNames = [(&b"MAIL"[..], "mail"), (&b"PNAM"[..], "playerName")]
+            .into_iter()
+            .collect();
+        registry.register(0x1, 0x28, existing);
+        mappings.apply(&mut registry);
+
+        assert_eq!(registry.component_name(0x1), Some("Authentication"));
+        assert_eq!(registry.command_name(0x1, 0x28, false), Some("Login"));
+        assert_eq!(registry.command_name(0x1, 0x28, true), Some("LoginNotify"));
+
+        let names = registry.get(0x1, 0x28).unwrap();
+        assert_eq!(names.get(&Tag::from(b"MAIL")), Some("email"));
+        assert_eq!(names.get(&Tag::from(b"PASS")), Some("password"));
+        assert_eq!(names.get(&Tag::from(b"PNAM")), Some("playerName"));
+    }
+
+    /// Tests tags that can't be encoded are rejected
+    #[test]
+    fn test_invalid_tag() {
+        let result = Mappings::from_json(
+            r#"{ "components": [{ "id": 1, "commands": [{ "id": 2, "tags": { "mail": "email" } }] }] }"#,
+        );
+        assert!(matches!(
+            result,
+            Err(MappingError::InvalidTag { component: 1, command: 2, ref tag }) if tag == "mail"
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+//! Marker traits ([`MaybeSend`], [`MaybeSync`]) and boxed future types used
+//! for the bounds on handlers, requests and the futures produced by routers.
+//!
+//! By default the markers require [`Send`] and [`Sync`] so routers can be used
+//! from multi-threaded runtimes. With the `unsend` feature the markers are
+//! implemented for every type so handlers holding types that aren't thread
+//! safe (such as the JavaScript futures on wasm32) can be routed
+//!
+//! The `unsend` feature is not additive. Features are unified across the
+//! dependency graph so if any crate enables it the Send and Sync bounds are
+//! removed for every crate using blaze-pk, and the futures produced by
+//! routers can no longer be spawned on multi-threaded runtimes. Only the
+//! final binary for a single threaded target should enable it
+//!
+//! ```
+//! use blaze_pk::marker::{BoxFuture, MaybeSend};
+//!
+//! fn boxed<F: std::future::Future<Output = u32> + MaybeSend + 'static>(
+//!     fut: F,
+//! ) -> BoxFuture<'static, u32> {
+//!     Box::pin(fut)
+//! }
+//! ```
+
+use futures_core::Stream;
+use std::{future::Future, pin::Pin};
+
+/// Marker for types that are [`Send`] unless the
+/// `unsend` feature is enabled
+#[cfg(not(feature = "unsend"))]
+pub trait MaybeSend: Send {}
+
+#[cfg(not(feature = "unsend"))]
+impl<T: Send + ?Sized> MaybeSend for T {}
+
+/// Marker for types that are [`Send`] unless the
+/// `unsend` feature is enabled
+#[cfg(feature = "unsend")]
+pub trait MaybeSend {}
+
+#[cfg(feature = "unsend")]
+impl<T: ?Sized> MaybeSend for T {}
+
+/// Marker for types that are [`Sync`] unless the
+/// `unsend` feature is enabled
+#[cfg(not(feature = "unsend"))]
+pub trait MaybeSync: Sync {}
+
+#[cfg(not(feature = "unsend"))]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+
+/// Marker for types that are [`Sync`] unless the
+/// `unsend` feature is enabled
+#[cfg(feature = "unsend")]
+pub trait MaybeSync {}
+
+#[cfg(feature = "unsend")]
+impl<T: ?Sized> MaybeSync for T {}
+
+/// Pin boxed future type that is Send unless the `unsend`
+/// feature is enabled and lives for 'a
+#[cfg(not(feature = "unsend"))]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Pin boxed future type that is Send unless the `unsend`
+/// feature is enabled and lives for 'a
+#[cfg(feature = "unsend")]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// Pin boxed stream type that is Send unless the `unsend`
+/// feature is enabled
+#[cfg(not(feature = "unsend"))]
+pub type BoxStream<T> = Pin<Box<dyn Stream<Item = T> + Send>>;
+
+/// Pin boxed stream type that is Send unless the `unsend`
+/// feature is enabled
+#[cfg(feature = "unsend")]
+pub type BoxStream<T> = Pin<Box<dyn Stream<Item = T>>>;
+
+#[cfg(all(test, feature = "unsend"))]
+mod test {
+    use super::BoxFuture;
+    use std::{
+        future::Future,
+        pin::pin,
+        rc::Rc,
+        task::{Context, Poll, Waker},
+    };
+
+    /// Tests futures that aren't Send can be boxed when
+    /// the unsend feature is enabled
+    #[test]
+    fn test_unsend_future() {
+        let value = Rc::new(1);
+        let fut: BoxFuture<'static, u32> = Box::pin(async move { *value + 1 });
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(pin!(fut).poll(&mut cx), Poll::Ready(2));
+    }
+}
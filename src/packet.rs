@@ -5,13 +5,30 @@
 //! [`PacketCodec`]
 
 use crate::{
-    codec::{Decodable, Encodable},
-    error::DecodeResult,
+    codec::{BorrowDecodable, Decodable, Encodable},
+    error::{CodecError, DecodeError, DecodeResult, EncodeError, EncodeResult},
+    error_code::ErrorDisplay,
+    format::default_formatter,
+    marker::{BoxStream, MaybeSend},
+    partial::{PartialDecodable, PartialDecode},
     reader::TdfReader,
+    session::SessionContext,
+    tag::TdfType,
+    tag_names::{lookup_command_names, lookup_tag_names},
+    writer::TdfWriter,
 };
-use bytes::{Buf, BufMut, Bytes, BytesMut};
-use std::{fmt::Debug, hash::Hash, sync::Arc};
-use std::{io, ops::Deref};
+use bytes::{buf::Reader, Buf, BufMut, Bytes, BytesMut};
+use futures_core::Stream;
+use std::ops::Deref;
+use std::{
+    fmt::Debug,
+    future::poll_fn,
+    hash::Hash,
+    io::{self, IoSlice},
+    pin::Pin,
+    sync::{Arc, OnceLock, RwLock},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 use tokio_util::codec::{Decoder, Encoder};
 
 /// Trait implemented by structures that can be used as packet components
@@ -55,7 +72,7 @@ pub trait PacketComponent: Debug + Hash + Eq + Sized {
 }
 
 /// The different types of packets
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum PacketType {
     /// ID counted request packets (0x00)
@@ -85,7 +102,7 @@ impl From<u8> for PacketType {
 
 /// Structure of packet header which comes before the
 /// packet content and describes it.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct PacketHeader {
     /// The component of this packet
     pub component: u16,
@@ -160,6 +177,13 @@ impl PacketHeader {
         }
     }
 
+    /// Returns a display wrapper for the error value of this header
+    /// which includes the error name when it is known by the global
+    /// [crate::error_code] registry
+    pub const fn error_display(&self) -> ErrorDisplay {
+        ErrorDisplay(self.error)
+    }
+
     /// Checks if the component and command of this packet header matches
     /// that of the other packet header
     ///
@@ -174,17 +198,32 @@ impl PacketHeader {
     /// `dst`    The dst to append the bytes to
     /// `length` The length of the content after the header
     pub fn write(&self, dst: &mut BytesMut, length: usize) {
+        let (header, header_length) = self.encode(length);
+        dst.extend_from_slice(&header[..header_length]);
+    }
+
+    /// Encodes the header for contents of the provided length returning
+    /// the header bytes along with the number of bytes used which is
+    /// larger when the length needs to be extended
+    ///
+    /// `length` The length of the packet contents
+    fn encode(&self, length: usize) -> ([u8; HEADER_LENGTH + 2], usize) {
         let is_extended = length > 0xFFFF;
+        let mut header = [0u8; HEADER_LENGTH + 2];
+        let mut dst = &mut header[..];
         dst.put_u16(length as u16);
         dst.put_u16(self.component);
         dst.put_u16(self.command);
         dst.put_u16(self.error);
         dst.put_u8(self.ty as u8);
-        dst.put_u8(if is_extended { 0x10 } else { 0x00 });
+        dst.put_u8(if is_extended { EXTENDED_FLAG } else { 0x00 });
         dst.put_u16(self.id);
         if is_extended {
             dst.put_u8(((length & 0xFF000000) >> 24) as u8);
             dst.put_u8(((length & 0x00FF0000) >> 16) as u8);
+            (header, HEADER_LENGTH + 2)
+        } else {
+            (header, HEADER_LENGTH)
         }
     }
 
@@ -193,7 +232,7 @@ impl PacketHeader {
     ///
     /// `src` The bytes to read from
     pub fn read(src: &mut BytesMut) -> Option<(PacketHeader, usize)> {
-        if src.len() < 12 {
+        if src.len() < HEADER_LENGTH {
             return None;
         }
 
@@ -202,9 +241,9 @@ impl PacketHeader {
         let command = src.get_u16();
         let error = src.get_u16();
         let ty = src.get_u8();
-        // If we encounter 0x10 here then the packet contains extended length
-        // bytes so its longer than a u16::MAX length
-        let is_extended = src.get_u8() == 0x10;
+        // If we encounter the extended flag here then the packet contains
+        // extended length bytes so its longer than a u16::MAX length
+        let is_extended = src.get_u8() == EXTENDED_FLAG;
         let id = src.get_u16();
 
         if is_extended {
@@ -212,7 +251,8 @@ impl PacketHeader {
             if src.len() < 2 {
                 return None;
             }
-            length += src.get_u16() as usize;
+            // The extended bytes are the upper bytes of the length
+            length |= (src.get_u16() as usize) << 16;
         }
 
         let ty = PacketType::from(ty);
@@ -227,12 +267,62 @@ impl PacketHeader {
     }
 }
 
+/// Type of the hook function called with packet decoding errors
+pub type DecodeErrorHook = dyn Fn(&PacketHeader, &DecodeError) + Send + Sync;
+
+/// The global hook called when decoding the contents of a packet fails
+static DECODE_ERROR_HOOK: RwLock<Option<Arc<DecodeErrorHook>>> = RwLock::new(None);
+
+/// Sets the global hook which is called with the packet header and
+/// error whenever decoding packet contents using [Packet::decode] fails.
+/// This allows servers to count and sample malformed traffic without
+/// wrapping every decode call. Replaces any previously set hook
+///
+/// `hook` The hook function
+pub fn set_decode_error_hook(hook: impl Fn(&PacketHeader, &DecodeError) + Send + Sync + 'static) {
+    let mut current = DECODE_ERROR_HOOK
+        .write()
+        .unwrap_or_else(|err| err.into_inner());
+    *current = Some(Arc::new(hook));
+}
+
+/// Removes the global decode error hook
+pub fn clear_decode_error_hook() {
+    let mut current = DECODE_ERROR_HOOK
+        .write()
+        .unwrap_or_else(|err| err.into_inner());
+    *current = None;
+}
+
+/// Calls the global decode error hook if one is set
+///
+/// `header` The header of the packet that failed to decode
+/// `err`    The decoding error
+fn on_decode_error(header: &PacketHeader, err: &DecodeError) {
+    let hook = DECODE_ERROR_HOOK
+        .read()
+        .unwrap_or_else(|err| err.into_inner())
+        .clone();
+    if let Some(hook) = hook {
+        hook(header, err);
+    }
+}
+
+/// The maximum length of packet contents that can be described by
+/// the extended length of a packet header
+pub const MAX_CONTENTS_LENGTH: usize = 0xFFFF_FFFF;
+
+/// The length of a packet header without the extended length bytes
+pub(crate) const HEADER_LENGTH: usize = 12;
+/// The flag byte value marking headers followed by extended length bytes
+pub(crate) const EXTENDED_FLAG: u8 = 0x10;
+
 /// Structure for Blaze packets contains the contents of the packet
 /// and the header for identification.
 ///
 /// Packets can be cloned with little memory usage increase because
 /// the content is stored as Bytes.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Packet {
     /// The packet header
     pub header: PacketHeader,
@@ -276,6 +366,18 @@ impl Packet {
         }
     }
 
+    /// Fallible version of [Packet::response] which returns an error
+    /// if the contents fail to encode
+    ///
+    /// `packet`   The packet to respond to
+    /// `contents` The contents to encode for the packet
+    pub fn try_response<C: Encodable>(packet: &Packet, contents: C) -> EncodeResult<Self> {
+        Ok(Self {
+            header: packet.header.response(),
+            contents: Bytes::from(contents.try_encode_bytes()?),
+        })
+    }
+
     /// Creates a packet responding to the current packet.
     /// Clones the header of the request packet and changes
     /// the type to repsonse
@@ -331,6 +433,19 @@ impl Packet {
         }
     }
 
+    /// Fallible version of [Packet::error] which returns an error
+    /// if the contents fail to encode
+    ///
+    /// `packet`   The packet to respond to
+    /// `error`    The response error value
+    /// `contents` The response contents
+    pub fn try_error<C: Encodable>(packet: &Packet, error: u16, contents: C) -> EncodeResult<Self> {
+        Ok(Self {
+            header: packet.header.with_error(error),
+            contents: Bytes::from(contents.try_encode_bytes()?),
+        })
+    }
+
     /// Creates a error respond packet responding to the provided
     /// packet with the provided error and contents
     ///
@@ -388,6 +503,22 @@ impl Packet {
         }
     }
 
+    /// Fallible version of [Packet::notify] which returns an error
+    /// if the contents fail to encode
+    ///
+    /// `component` The packet component to use for the header
+    /// `contents`  The contents of the packet to encode
+    pub fn try_notify<C: Encodable, T: PacketComponents>(
+        component: T,
+        contents: C,
+    ) -> EncodeResult<Packet> {
+        let (component, command) = component.values();
+        Ok(Self {
+            header: PacketHeader::notify(component, command),
+            contents: Bytes::from(contents.try_encode_bytes()?),
+        })
+    }
+
     /// Creates a notify packet for the provided component with the
     /// provided raw encoded contents.
     ///
@@ -430,6 +561,24 @@ impl Packet {
         }
     }
 
+    /// Fallible version of [Packet::request] which returns an error
+    /// if the contents fail to encode
+    ///
+    /// `id`        The packet id
+    /// `component` The packet component
+    /// `contents`  The packet contents
+    pub fn try_request<C: Encodable, T: PacketComponents>(
+        id: u16,
+        component: T,
+        contents: C,
+    ) -> EncodeResult<Packet> {
+        let (component, command) = component.values();
+        Ok(Self {
+            header: PacketHeader::request(id, component, command),
+            contents: Bytes::from(contents.try_encode_bytes()?),
+        })
+    }
+
     /// Creates a new request packet from the provided id, component
     /// with raw encoded contents
     ///
@@ -461,8 +610,89 @@ impl Packet {
     /// Attempts to decode the contents bytes of this packet into the
     /// provided Codec type value.
     pub fn decode<C: Decodable>(&self) -> DecodeResult<C> {
+        let mut reader = TdfReader::new(&self.contents);
+        C::decode(&mut reader).inspect_err(|err| on_decode_error(&self.header, err))
+    }
+
+    /// Strict version of [Packet::decode] which fails with a
+    /// [DecodeError::TrailingBytes] error if decoding didn't
+    /// consume all of the packet contents
+    pub fn decode_strict<C: Decodable>(&self) -> DecodeResult<C> {
         let mut reader = TdfReader::new(&self.contents);
         C::decode(&mut reader)
+            .and_then(|value| {
+                if reader.is_empty() {
+                    Ok(value)
+                } else {
+                    Err(DecodeError::TrailingBytes {
+                        cursor: reader.cursor,
+                        remaining: reader.len(),
+                    })
+                }
+            })
+            .inspect_err(|err| on_decode_error(&self.header, err))
+    }
+
+    /// Attempts to decode the contents bytes of this packet into the
+    /// provided type borrowing from the packet contents
+    pub fn decode_borrowed<'a, C: BorrowDecodable<'a>>(&'a self) -> DecodeResult<C> {
+        let mut reader = TdfReader::new(&self.contents);
+        C::decode_borrowed(&mut reader).inspect_err(|err| on_decode_error(&self.header, err))
+    }
+
+    /// Reads the contents of this packet up to the blob with the provided
+    /// tag returning a handle to the blob bytes within the contents without
+    /// copying them. The tags after the blob are not decoded so handlers
+    /// can forward large payloads without decoding the rest of the packet
+    ///
+    /// `tag` The tag of the blob
+    pub fn blob(&self, tag: &[u8]) -> DecodeResult<Bytes> {
+        let mut reader = TdfReader::new(&self.contents);
+        reader
+            .until_tag(tag, TdfType::Blob)
+            .and_then(|_| reader.read_blob())
+            .map(|blob| self.contents.slice_ref(blob))
+            .inspect_err(|err| on_decode_error(&self.header, err))
+    }
+
+    /// Reader version of [Packet::blob] which provides the blob bytes
+    /// through [std::io::Read] without copying them
+    ///
+    /// `tag` The tag of the blob
+    pub fn blob_reader(&self, tag: &[u8]) -> DecodeResult<Reader<Bytes>> {
+        self.blob(tag).map(Buf::reader)
+    }
+
+    /// Attempts to decode the contents bytes of this packet into the
+    /// provided Codec type value. Errors include a [crate::error::HexContext]
+    /// of the bytes surrounding the failure
+    pub fn decode_with_context<C: Decodable>(&self) -> DecodeResult<C> {
+        let mut reader = TdfReader::new(&self.contents);
+        reader
+            .decode_with_context()
+            .inspect_err(|err| on_decode_error(&self.header, err))
+    }
+
+    /// Partially decodes the contents of this packet returning the
+    /// decoded value along with the errors for any fields that failed
+    pub fn decode_partial<C: PartialDecodable>(&self) -> PartialDecode<C> {
+        let mut reader = TdfReader::new(&self.contents);
+        let decoded = C::decode_partial(&mut reader);
+        decoded
+            .errors
+            .iter()
+            .for_each(|err| on_decode_error(&self.header, &err.error));
+        decoded
+    }
+
+    /// Decodes the contents of this packet into a JSON value without
+    /// needing the structure of the contents. The contents are converted
+    /// to an object of tags to their values
+    #[cfg(feature = "json")]
+    pub fn contents_as_json(&self) -> DecodeResult<serde_json::Value> {
+        let fields: crate::value::TdfFields = self.decode()?;
+        serde_json::to_value(&fields)
+            .map_err(|err| crate::error::DecodeError::Source(Box::new(err)))
     }
 
     /// Attempts to read a packet from the provided
@@ -489,17 +719,163 @@ impl Packet {
     /// `dst` The destination buffer
     pub fn write(&self, dst: &mut BytesMut) {
         let contents = &self.contents;
+        dst.reserve(HEADER_LENGTH + 2 + contents.len());
         self.header.write(dst, contents.len());
         dst.extend_from_slice(contents);
     }
+
+    /// Writes the contents and header of the packet onto the dst
+    /// source of bytes returning an error instead if the contents
+    /// are too long to be described by the header
+    ///
+    /// `dst` The destination buffer
+    pub fn try_write(&self, dst: &mut BytesMut) -> EncodeResult<()> {
+        self.check_length()?;
+        self.write(dst);
+        Ok(())
+    }
+
+    /// Ensures the contents of the packet aren't too
+    /// long to be described by the header
+    fn check_length(&self) -> EncodeResult<()> {
+        let length = self.contents.len();
+        if length > MAX_CONTENTS_LENGTH {
+            return Err(EncodeError::TooLarge {
+                length,
+                max: MAX_CONTENTS_LENGTH,
+            });
+        }
+        Ok(())
+    }
+
+    /// Writes this packet to the provided writer using vectored writes
+    /// so the contents are written straight from the packet instead of
+    /// being copied into a buffer after the header. Packets with contents
+    /// too long to be described by the header result in an error
+    ///
+    /// `writer` The writer to write to
+    pub fn write_vectored<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.check_length().map_err(CodecError::from)?;
+        let (header, header_length) = self.header.encode(self.contents.len());
+        let header = &header[..header_length];
+
+        let total = header.len() + self.contents.len();
+        let mut written = 0;
+        while written < total {
+            let slices = remaining_slices(header, &self.contents, written);
+            match writer.write_vectored(&slices) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(count) => written += count,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+        writer.flush()
+    }
+
+    /// Reads a single packet from the provided async reader without
+    /// any buffering beyond the packet itself. Reaching the end of
+    /// the reader before a whole packet is read results in an error
+    ///
+    /// `reader` The reader to read from
+    pub async fn read_async<R>(reader: &mut R) -> io::Result<Packet>
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut header = [0u8; HEADER_LENGTH + 2];
+        read_exact_async(reader, &mut header[..HEADER_LENGTH]).await?;
+        let header_length = if header[9] == EXTENDED_FLAG {
+            read_exact_async(reader, &mut header[HEADER_LENGTH..]).await?;
+            HEADER_LENGTH + 2
+        } else {
+            HEADER_LENGTH
+        };
+
+        let mut src = BytesMut::from(&header[..header_length]);
+        let (header, length) = PacketHeader::read(&mut src).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "Incomplete packet header")
+        })?;
+
+        let mut contents = vec![0u8; length];
+        read_exact_async(reader, &mut contents).await?;
+        Ok(Packet {
+            header,
+            contents: Bytes::from(contents),
+        })
+    }
+
+    /// Writes this packet to the provided async writer and flushes
+    /// the writer. The header and contents are written as separate
+    /// slices using vectored writes so the contents aren't copied.
+    /// Packets with contents too long to be described by the header
+    /// result in an error
+    ///
+    /// `writer` The writer to write to
+    pub async fn write_async<W>(&self, writer: &mut W) -> io::Result<()>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        self.check_length().map_err(CodecError::from)?;
+        let (header, header_length) = self.header.encode(self.contents.len());
+        let header = &header[..header_length];
+
+        let total = header.len() + self.contents.len();
+        let mut written = 0;
+        while written < total {
+            let slices = remaining_slices(header, &self.contents, written);
+            let count =
+                poll_fn(|cx| Pin::new(&mut *writer).poll_write_vectored(cx, &slices)).await?;
+            if count == 0 {
+                return Err(io::ErrorKind::WriteZero.into());
+            }
+            written += count;
+        }
+        poll_fn(|cx| Pin::new(&mut *writer).poll_flush(cx)).await
+    }
+}
+
+/// Creates the slices for the portions of the header and
+/// contents that haven't been written yet
+///
+/// `header`   The encoded header bytes
+/// `contents` The packet contents
+/// `written`  The number of bytes already written
+fn remaining_slices<'a>(header: &'a [u8], contents: &'a [u8], written: usize) -> [IoSlice<'a>; 2] {
+    match header.get(written..) {
+        Some(header) => [IoSlice::new(header), IoSlice::new(contents)],
+        None => [
+            IoSlice::new(&[]),
+            IoSlice::new(&contents[written - header.len()..]),
+        ],
+    }
 }
 
-/// Tokio codec for encoding and decoding packets
+/// Reads from the provided async reader until the buffer is filled
+///
+/// `reader` The reader to read from
+/// `buf`    The buffer to fill
+async fn read_exact_async<R>(reader: &mut R, buf: &mut [u8]) -> io::Result<()>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buf = ReadBuf::new(buf);
+    while buf.remaining() > 0 {
+        let filled = buf.filled().len();
+        poll_fn(|cx| Pin::new(&mut *reader).poll_read(cx, &mut buf)).await?;
+        if buf.filled().len() == filled {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+    }
+    Ok(())
+}
+
+/// Tokio codec for encoding and decoding packets. Errors are reported
+/// as [CodecError] which can be converted into [std::io::Error]
 pub struct PacketCodec;
 
 /// Decoder implementation
 impl Decoder for PacketCodec {
-    type Error = io::Error;
+    type Error = CodecError;
     type Item = Packet;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
@@ -516,34 +892,156 @@ impl Decoder for PacketCodec {
 
 /// Encoder implementation for owned packets
 impl Encoder<Packet> for PacketCodec {
-    type Error = io::Error;
+    type Error = CodecError;
 
     fn encode(&mut self, item: Packet, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        item.write(dst);
+        item.try_write(dst)?;
         Ok(())
     }
 }
 
 /// Encoder implementation for borrowed packets
 impl Encoder<&Packet> for PacketCodec {
-    type Error = io::Error;
+    type Error = CodecError;
 
     fn encode(&mut self, item: &Packet, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        item.write(dst);
+        item.try_write(dst)?;
         Ok(())
     }
 }
 
 /// Encoder implementation for arc reference packets
 impl Encoder<Arc<Packet>> for PacketCodec {
-    type Error = io::Error;
+    type Error = CodecError;
 
     fn encode(&mut self, item: Arc<Packet>, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        item.write(dst);
+        item.try_write(dst)?;
+        Ok(())
+    }
+}
+
+/// Encoder implementation for packets with contents that are
+/// encoded directly into the frame buffer
+impl<C: Encodable> Encoder<EncodePacket<C>> for PacketCodec {
+    type Error = CodecError;
+
+    fn encode(&mut self, item: EncodePacket<C>, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        item.write(dst)?;
         Ok(())
     }
 }
 
+/// Packet with contents that haven't been encoded yet. When written
+/// to a frame buffer with no pending frames the contents are encoded
+/// straight into the allocation of the frame buffer after a reserved
+/// header slot which is filled in once the length is known, instead
+/// of being encoded into their own buffer and then copied
+#[derive(Debug, Clone)]
+pub struct EncodePacket<C> {
+    /// The packet header
+    pub header: PacketHeader,
+    /// The contents to encode
+    pub contents: C,
+}
+
+impl<C: Encodable> EncodePacket<C> {
+    /// Creates a packet from the header and contents to encode
+    ///
+    /// `header`   The packet header
+    /// `contents` The contents to encode for the packet
+    pub const fn new(header: PacketHeader, contents: C) -> Self {
+        Self { header, contents }
+    }
+
+    /// Creates a packet responding to the provided packet
+    ///
+    /// `packet`   The packet to respond to
+    /// `contents` The contents to encode for the packet
+    pub const fn response(packet: &Packet, contents: C) -> Self {
+        Self::new(packet.header.response(), contents)
+    }
+
+    /// Creates an error response to the provided packet
+    ///
+    /// `packet`   The packet to respond to
+    /// `error`    The response error value
+    /// `contents` The contents to encode for the packet
+    pub const fn error(packet: &Packet, error: u16, contents: C) -> Self {
+        Self::new(packet.header.with_error(error), contents)
+    }
+
+    /// Creates a notify packet for the provided component
+    ///
+    /// `component` The packet component
+    /// `contents`  The contents to encode for the packet
+    pub fn notify<T: PacketComponents>(component: T, contents: C) -> Self {
+        let (component, command) = component.values();
+        Self::new(PacketHeader::notify(component, command), contents)
+    }
+
+    /// Writes the header and encodes the contents onto the dst
+    /// buffer. Nothing is written if the contents fail to encode
+    /// or are too long to be described by the header.
+    ///
+    /// Handing the buffer to the writer would move any frames that
+    /// are still pending in the buffer so when there are pending
+    /// frames the packet is encoded into its own buffer and appended
+    ///
+    /// `dst` The destination buffer
+    pub fn write(&self, dst: &mut BytesMut) -> EncodeResult<()> {
+        if !dst.is_empty() {
+            let mut frame = BytesMut::new();
+            self.write(&mut frame)?;
+            dst.extend_from_slice(&frame);
+            return Ok(());
+        }
+
+        // The slot always has room for the extended length so the contents
+        // never have to be moved, shorter headers are written at an offset
+        // into the slot which is then skipped
+        let slot = HEADER_LENGTH + 2;
+
+        // The buffer is empty so its allocation is moved into the writer
+        // and back without copying any bytes
+        let mut writer = TdfWriter::from(Vec::from(std::mem::take(dst)));
+        writer.buffer.reserve(slot + self.contents.size_hint());
+        writer.buffer.resize(slot, 0);
+        let result = self.contents.try_encode(&mut writer);
+        let mut buffer: Vec<u8> = writer.into();
+
+        let length = buffer.len() - slot;
+        let result = result.and_then(|_| {
+            if length > MAX_CONTENTS_LENGTH {
+                return Err(EncodeError::TooLarge {
+                    length,
+                    max: MAX_CONTENTS_LENGTH,
+                });
+            }
+            Ok(())
+        });
+        if result.is_err() {
+            buffer.clear();
+            *dst = BytesMut::from(Bytes::from(buffer));
+            return result;
+        }
+
+        let (header, header_length) = self.header.encode(length);
+        let offset = slot - header_length;
+        buffer[offset..slot].copy_from_slice(&header[..header_length]);
+        *dst = BytesMut::from(Bytes::from(buffer));
+        dst.advance(offset);
+        Ok(())
+    }
+
+    /// Encodes the contents into a regular packet
+    pub fn into_packet(self) -> EncodeResult<Packet> {
+        Ok(Packet {
+            header: self.header,
+            contents: Bytes::from(self.contents.try_encode_bytes()?),
+        })
+    }
+}
+
 /// Structure wrapping a from request type to include a packet
 /// header to allow the response type to be created
 pub struct Request<T: FromRequest> {
@@ -601,15 +1099,15 @@ pub struct Response(Packet);
 
 impl IntoResponse for Response {
     /// Simply provide the already compute response
-    fn into_response(self, _req: &Packet) -> Packet {
+    fn into_response(self, _req: &PacketHeader) -> Packet {
         self.0
     }
 }
 
 impl IntoResponse for PacketBody {
-    fn into_response(self, req: &Packet) -> Packet {
+    fn into_response(self, req: &PacketHeader) -> Packet {
         Packet {
-            header: req.header.response(),
+            header: req.response(),
             contents: self.0,
         }
     }
@@ -621,50 +1119,166 @@ impl<T: FromRequest> FromRequest for Request<T> {
         let header = req.header;
         Ok(Self { req: inner, header })
     }
+
+    fn from_request_context(req: &Packet, ctx: Option<&SessionContext>) -> DecodeResult<Self> {
+        let inner = T::from_request_context(req, ctx)?;
+        let header = req.header;
+        Ok(Self { req: inner, header })
+    }
 }
 
 /// Trait implementing by structures which can be created from a request
 /// packet and is used for the arguments on routing functions
-pub trait FromRequest: Sized + Send + 'static {
+pub trait FromRequest: Sized + MaybeSend + 'static {
     /// Takes the value from the request returning a decode result of
     /// whether the value could be created
     ///
     /// `req` The request packet
     fn from_request(req: &Packet) -> DecodeResult<Self>;
+
+    /// Takes the value from the request using the context of the
+    /// session the request is from if one is available. Defaults
+    /// to [FromRequest::from_request] ignoring the context
+    ///
+    /// `req`  The request packet
+    /// `_ctx` The context of the session the request is from
+    fn from_request_context(req: &Packet, _ctx: Option<&SessionContext>) -> DecodeResult<Self> {
+        Self::from_request(req)
+    }
 }
 
 impl<D> FromRequest for D
 where
-    D: Decodable + Send + 'static,
+    D: Decodable + MaybeSend + 'static,
 {
     fn from_request(req: &Packet) -> DecodeResult<Self> {
         req.decode()
     }
 }
 
+/// From request implementation for taking the entire raw request
+/// packet without decoding its contents. Useful for low level handlers
+/// such as proxies and loggers. (Cloning the packet is cheap as the
+/// contents are stored as Bytes)
+impl FromRequest for Packet {
+    fn from_request(req: &Packet) -> DecodeResult<Self> {
+        Ok(req.clone())
+    }
+}
+
+/// From request implementation for taking the raw encoded contents
+/// of the request packet without decoding them
+impl FromRequest for Bytes {
+    fn from_request(req: &Packet) -> DecodeResult<Self> {
+        Ok(req.contents.clone())
+    }
+}
+
 /// Trait for a type that can be converted into a packet
-/// response using the header from the request packet
+/// response using the header from the request packet. Only
+/// the header is required so the request packet contents
+/// don't need to be kept around while the handler runs
 pub trait IntoResponse: 'static {
     /// Into packet conversion
-    fn into_response(self, req: &Packet) -> Packet;
+    ///
+    /// `req` The header of the request packet
+    fn into_response(self, req: &PacketHeader) -> Packet;
+
+    /// Converts this value into the response packet along with any
+    /// additional notification packets that should be sent after it.
+    /// By default this is just the response from `into_response`
+    fn into_responses(self, req: &PacketHeader) -> Responses
+    where
+        Self: Sized,
+    {
+        Responses::new(self.into_response(req))
+    }
+}
+
+/// Boxed stream of notification packets produced by a handler
+pub type NotifyStream = BoxStream<Packet>;
+
+/// Collection of packets produced when responding to a request. Contains
+/// the response packet itself along with any notification packets that
+/// should be sent to the session after the response
+pub struct Responses {
+    /// The response packet for the request
+    pub response: Packet,
+    /// Additional notification packets to send after the response
+    pub notify: Vec<Packet>,
+    /// Stream of notification packets produced over time by long running
+    /// handlers. The packets from this stream should be forwarded to the
+    /// session after the response and notification packets
+    pub stream: Option<NotifyStream>,
+}
+
+impl Responses {
+    /// Creates a new collection of responses containing only
+    /// the provided response packet
+    ///
+    /// `response` The response packet
+    pub fn new(response: Packet) -> Self {
+        Self {
+            response,
+            notify: Vec::new(),
+            stream: None,
+        }
+    }
+
+    /// Appends an additional notification packet to be sent
+    /// after the response
+    ///
+    /// `packet` The notification packet
+    pub fn push_notify(&mut self, packet: Packet) {
+        self.notify.push(packet);
+    }
+}
+
+impl Debug for Responses {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Responses")
+            .field("response", &self.response)
+            .field("notify", &self.notify)
+            .field("stream", &self.stream.is_some())
+            .finish()
+    }
+}
+
+impl From<Packet> for Responses {
+    fn from(value: Packet) -> Self {
+        Self::new(value)
+    }
+}
+
+/// Into iterator implementation for iterating all the packets in
+/// the order they should be written (response first then notifications).
+/// The notification stream is not included and must be taken before
+/// iterating
+impl IntoIterator for Responses {
+    type Item = Packet;
+    type IntoIter = std::iter::Chain<std::iter::Once<Packet>, std::vec::IntoIter<Packet>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self.response).chain(self.notify)
+    }
 }
 
 /// Empty response implementation for unit types to allow
 /// functions to have no return type
 impl IntoResponse for () {
-    fn into_response(self, req: &Packet) -> Packet {
-        req.respond_empty()
+    fn into_response(self, req: &PacketHeader) -> Packet {
+        Packet::raw_empty(req.response())
     }
 }
 
 /// Into response imeplementation for encodable responses
-/// which just calls res.respond
+/// which encodes the value as the response contents
 impl<E> IntoResponse for E
 where
     E: Encodable + 'static,
 {
-    fn into_response(self, req: &Packet) -> Packet {
-        req.respond(self)
+    fn into_response(self, req: &PacketHeader) -> Packet {
+        Packet::raw(req.response(), self.encode_bytes())
     }
 }
 
@@ -675,12 +1289,19 @@ where
     S: IntoResponse,
     E: IntoResponse,
 {
-    fn into_response(self, req: &Packet) -> Packet {
+    fn into_response(self, req: &PacketHeader) -> Packet {
         match self {
             Ok(value) => value.into_response(req),
             Err(value) => value.into_response(req),
         }
     }
+
+    fn into_responses(self, req: &PacketHeader) -> Responses {
+        match self {
+            Ok(value) => value.into_responses(req),
+            Err(value) => value.into_responses(req),
+        }
+    }
 }
 
 /// Into response implementation for option type turning
@@ -689,12 +1310,172 @@ impl<S> IntoResponse for Option<S>
 where
     S: IntoResponse,
 {
-    fn into_response(self, req: &Packet) -> Packet {
+    fn into_response(self, req: &PacketHeader) -> Packet {
         match self {
             Some(value) => value.into_response(req),
-            None => req.respond_empty(),
+            None => Packet::raw_empty(req.response()),
         }
     }
+
+    fn into_responses(self, req: &PacketHeader) -> Responses {
+        match self {
+            Some(value) => value.into_responses(req),
+            None => Responses::new(Packet::raw_empty(req.response())),
+        }
+    }
+}
+
+/// Into response implementation for a response along with a list of
+/// additional notification packets that should be sent after the
+/// response
+///
+/// ```
+/// use blaze_pk::packet::{Packet, Response};
+///
+/// async fn test() -> (Response, Vec<Packet>) {
+///     # unimplemented!()
+/// }
+/// ```
+impl<R> IntoResponse for (R, Vec<Packet>)
+where
+    R: IntoResponse,
+{
+    fn into_response(self, req: &PacketHeader) -> Packet {
+        self.0.into_response(req)
+    }
+
+    fn into_responses(self, req: &PacketHeader) -> Responses {
+        let mut responses = self.0.into_responses(req);
+        responses.notify.extend(self.1);
+        responses
+    }
+}
+
+/// Wrapper over a stream of notification packets for responding
+/// with a stream alongside a response
+pub struct Notifications<S>(pub S);
+
+/// Into response implementation for a response along with a stream of
+/// notification packets that should be forwarded to the session after
+/// the response. Used by long running commands that emit progress
+/// notifications
+///
+/// ```
+/// use blaze_pk::packet::{Notifications, NotifyStream, Response};
+///
+/// async fn test() -> (Response, Notifications<NotifyStream>) {
+///     # unimplemented!()
+/// }
+/// ```
+impl<R, S> IntoResponse for (R, Notifications<S>)
+where
+    R: IntoResponse,
+    S: Stream<Item = Packet> + MaybeSend + 'static,
+{
+    fn into_response(self, req: &PacketHeader) -> Packet {
+        self.0.into_response(req)
+    }
+
+    fn into_responses(self, req: &PacketHeader) -> Responses {
+        let mut responses = self.0.into_responses(req);
+        responses.stream = Some(Box::pin(self.1 .0));
+        responses
+    }
+}
+
+/// Packet along with its typed component which is resolved from the
+/// header the first time it's needed and then cached so the component
+/// values only need to be matched once per packet
+pub struct ComponentPacket<C> {
+    /// The underlying packet
+    packet: Packet,
+    /// The cached component resolved from the packet header
+    component: OnceLock<Option<C>>,
+}
+
+impl<C: PacketComponents> ComponentPacket<C> {
+    /// Creates a new wrapper over the provided packet
+    ///
+    /// `packet` The packet to wrap
+    pub const fn new(packet: Packet) -> Self {
+        Self {
+            packet,
+            component: OnceLock::new(),
+        }
+    }
+
+    /// Returns the component of the packet resolving it from the
+    /// packet header if it hasn't already been resolved. Returns
+    /// None if the header doesn't match a known component
+    pub fn component(&self) -> Option<&C> {
+        self.component
+            .get_or_init(|| C::from_header(&self.packet.header))
+            .as_ref()
+    }
+
+    /// Returns a reference to the underlying packet
+    pub fn packet(&self) -> &Packet {
+        &self.packet
+    }
+
+    /// Consumes the wrapper returning the underlying packet
+    pub fn into_inner(self) -> Packet {
+        self.packet
+    }
+
+    /// Creates a debug wrapper for the packet using the cached component
+    ///
+    /// `minified` Whether to exclude the contents of the packet
+    pub fn debug(&self, minified: bool) -> PacketDebug<'_, C> {
+        PacketDebug {
+            packet: &self.packet,
+            component: self.component(),
+            minified,
+        }
+    }
+
+    /// Creates a JSON debug wrapper for the packet using the cached component
+    ///
+    /// `minified` Whether to exclude the contents of the packet
+    #[cfg(feature = "json")]
+    pub fn json_debug(&self, minified: bool) -> PacketJsonDebug<'_, C> {
+        PacketJsonDebug {
+            packet: &self.packet,
+            component: self.component(),
+            minified,
+        }
+    }
+}
+
+impl<C: PacketComponents> From<Packet> for ComponentPacket<C> {
+    fn from(packet: Packet) -> Self {
+        Self::new(packet)
+    }
+}
+
+impl<C> Deref for ComponentPacket<C> {
+    type Target = Packet;
+
+    fn deref(&self) -> &Self::Target {
+        &self.packet
+    }
+}
+
+impl<C: PacketComponents> Debug for ComponentPacket<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ComponentPacket")
+            .field("packet", &self.packet)
+            .field("component", &self.component())
+            .finish()
+    }
+}
+
+/// From request implementation for taking the request packet along
+/// with its resolved component
+impl<C: PacketComponents + MaybeSend + 'static> FromRequest for ComponentPacket<C> {
+    fn from_request(req: &Packet) -> DecodeResult<Self> {
+        Ok(Self::new(req.clone()))
+    }
 }
 
 /// Wrapper over a packet structure to provde debug logging
@@ -718,8 +1499,19 @@ where
         if let Some(component) = self.component {
             writeln!(f, "Component: {:?}", component)?;
         } else {
-            writeln!(f, "Component: {:#06x}", header.component)?;
-            writeln!(f, "Command: {:#06x}", header.command)?;
+            // Fallback to the names from the registry for unknown components
+            let notify = matches!(&header.ty, PacketType::Notify);
+            let (component, command) =
+                lookup_command_names(header.component, header.command, notify);
+            write!(f, "Component: {:#06x}", header.component)?;
+            if let Some(component) = component {
+                write!(f, " ({})", component)?;
+            }
+            write!(f, "\nCommand: {:#06x}", header.command)?;
+            if let Some(command) = command {
+                write!(f, " ({})", command)?;
+            }
+            writeln!(f)?;
         }
 
         writeln!(f, "Type: {:?}", header.ty)?;
@@ -729,7 +1521,7 @@ where
         }
 
         if let PacketType::Error = &header.ty {
-            writeln!(f, "Error: {:#06x}", &header.error)?;
+            writeln!(f, "Error: {}", header.error_display())?;
         }
 
         // Skip remaining if the message shouldn't contain its content
@@ -739,11 +1531,12 @@ where
 
         let mut reader = TdfReader::new(&self.packet.contents);
         let mut out = String::new();
+        let names = lookup_tag_names(header.component, header.command);
 
         out.push_str("{\n");
 
-        // Stringify the content or append error instead
-        if let Err(err) = reader.stringify(&mut out) {
+        // Format the content or append error instead
+        if let Err(err) = default_formatter().format(&mut reader, &mut out, names.as_deref()) {
             writeln!(f, "Content: Content was malformed")?;
             writeln!(f, "Error: {:?}", err)?;
             writeln!(f, "Partial Content: {}", out)?;
@@ -761,3 +1554,474 @@ where
         write!(f, "Content: {}", out)
     }
 }
+
+/// Wrapper over a packet structure to provide debug logging as
+/// pretty-printed JSON with names resolved for the component. The
+/// packet contents are converted using [Packet::contents_as_json]
+#[cfg(feature = "json")]
+pub struct PacketJsonDebug<'a, C> {
+    /// Reference to the packet itself
+    pub packet: &'a Packet,
+    /// The component derived from the packet header
+    pub component: Option<&'a C>,
+    /// Decide whether to include the contents of the packet
+    pub minified: bool,
+}
+
+#[cfg(feature = "json")]
+impl<'a, C> PacketJsonDebug<'a, C>
+where
+    C: PacketComponents,
+{
+    /// Creates a JSON object describing the packet header and
+    /// its contents. Malformed contents are included as a hex
+    /// string along with the decoding error
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::{Map, Value};
+        use std::fmt::Write;
+
+        let header = &self.packet.header;
+        let mut out = Map::new();
+
+        if let Some(component) = self.component {
+            out.insert("component".to_string(), format!("{:?}", component).into());
+        } else {
+            out.insert("component".to_string(), header.component.into());
+            out.insert("command".to_string(), header.command.into());
+        }
+
+        out.insert("type".to_string(), format!("{:?}", header.ty).into());
+
+        if !matches!(&header.ty, PacketType::Notify) {
+            out.insert("id".to_string(), header.id.into());
+        }
+
+        if let PacketType::Error = &header.ty {
+            out.insert("error".to_string(), header.error.into());
+            if let Some(error) = crate::error_code::lookup_error(header.error) {
+                out.insert("error_name".to_string(), error.name.into());
+            }
+        }
+
+        if !self.minified {
+            match self.packet.contents_as_json() {
+                Ok(contents) => {
+                    out.insert("contents".to_string(), contents);
+                }
+                Err(err) => {
+                    let mut raw = String::with_capacity(self.packet.contents.len() * 2);
+                    for byte in self.packet.contents.iter() {
+                        let _ = write!(raw, "{:02x}", byte);
+                    }
+                    out.insert("malformed".to_string(), err.to_string().into());
+                    out.insert("raw".to_string(), raw.into());
+                }
+            }
+        }
+
+        Value::Object(out)
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'a, C> Debug for PacketJsonDebug<'a, C>
+where
+    C: PacketComponents,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = self.to_json();
+        let out = serde_json::to_string_pretty(&value).map_err(|_| std::fmt::Error)?;
+        f.write_str(&out)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{
+        ComponentPacket, EncodePacket, Packet, PacketCodec, PacketComponents, PacketHeader,
+        HEADER_LENGTH,
+    };
+    use crate::{
+        codec::{Decodable, Encodable},
+        error::{CodecError, DecodeError, DecodeResult, EncodeError, EncodeResult},
+        reader::TdfReader,
+        sync::read_packet,
+        types::Blob,
+        writer::TdfWriter,
+    };
+    use bytes::{Buf, BytesMut};
+    use std::{
+        future::Future,
+        io::{self, Read},
+        pin::pin,
+        sync::atomic::{AtomicUsize, Ordering},
+        task::{Context, Poll, Waker},
+    };
+    use tokio_util::codec::{Decoder, Encoder};
+
+    /// Creates the packets used for testing framing
+    pub(crate) fn framing_packets() -> Vec<Packet> {
+        let mut writer = TdfWriter::default();
+        writer.tag_str(b"NAME", "Test");
+        writer.tag_u32(b"ID", 1);
+        let request = PacketHeader::request(1, 0x1, 0x28);
+        vec![
+            Packet::raw(request, writer.buffer.clone()),
+            Packet::raw_empty(request.response()),
+            Packet::raw_empty(request.with_error(0x4)),
+            Packet::raw(PacketHeader::notify(0x7802, 0x1), vec![0xAB; 0x1000]),
+            Packet::raw(PacketHeader::notify(0x7802, 0x2), extended_contents()),
+        ]
+    }
+
+    /// Creates contents long enough to need the extended header length
+    /// with a varying pattern so misplaced bytes are noticed
+    pub(crate) fn extended_contents() -> Vec<u8> {
+        (0..0x12345u32).map(|value| value as u8).collect()
+    }
+
+    /// Tests frames with contents longer than 0xFFFF bytes can be read
+    /// back through each of the readers with the extended length
+    #[test]
+    fn test_extended_length() {
+        let packet = Packet::raw(PacketHeader::request(1, 0x1, 0x2), extended_contents());
+        let mut frame = BytesMut::new();
+        packet.write(&mut frame);
+        assert_eq!(frame.len(), HEADER_LENGTH + 2 + 0x12345);
+
+        let (_, length) = PacketHeader::read(&mut frame.clone()).unwrap();
+        assert_eq!(length, 0x12345);
+
+        let mut src = frame.clone();
+        assert_eq!(PacketCodec.decode(&mut src).unwrap(), Some(packet.clone()));
+        assert!(src.is_empty());
+
+        let mut reader = &frame[..];
+        assert_eq!(read_packet(&mut reader).unwrap(), packet);
+        assert!(reader.is_empty());
+
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut reader = &frame[..];
+        let Poll::Ready(Ok(read)) = pin!(Packet::read_async(&mut reader)).poll(&mut cx) else {
+            panic!("Expected packet to be read");
+        };
+        assert_eq!(read, packet);
+        assert!(reader.is_empty());
+    }
+
+    /// Checks that the provided codec frames packets correctly. Used
+    /// for testing each of the codec integrations share the same framing
+    ///
+    /// `codec` The codec to check
+    pub(crate) fn check_framing<C>(mut codec: C)
+    where
+        C: Decoder<Item = Packet, Error = CodecError> + Encoder<Packet, Error = CodecError>,
+    {
+        let packets = framing_packets();
+        let mut buffer = BytesMut::new();
+        for packet in &packets {
+            codec.encode(packet.clone(), &mut buffer).unwrap();
+        }
+        let encoded = buffer.clone();
+
+        // Partial frames must not consume any bytes
+        let mut partial = BytesMut::from(&encoded[..encoded.len() - 1]);
+        for packet in &packets[..packets.len() - 1] {
+            assert_eq!(codec.decode(&mut partial).unwrap().as_ref(), Some(packet));
+        }
+        let remaining = partial.len();
+        assert!(codec.decode(&mut partial).unwrap().is_none());
+        assert_eq!(partial.len(), remaining);
+
+        for packet in &packets {
+            assert_eq!(codec.decode(&mut buffer).unwrap().as_ref(), Some(packet));
+        }
+        assert!(codec.decode(&mut buffer).unwrap().is_none());
+        assert!(buffer.is_empty());
+    }
+
+    /// Tests the tokio codec framing
+    #[test]
+    fn test_codec_framing() {
+        check_framing(PacketCodec);
+    }
+
+    /// Tests packets written with write_async can be read back
+    /// using read_async
+    #[test]
+    fn test_read_write_async() {
+        let packets = framing_packets();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut buffer: Vec<u8> = Vec::new();
+        for packet in &packets {
+            let future = pin!(packet.write_async(&mut buffer));
+            assert!(matches!(future.poll(&mut cx), Poll::Ready(Ok(()))));
+        }
+
+        let mut reader = &buffer[..buffer.len() - 1];
+        for packet in &packets[..packets.len() - 1] {
+            let future = pin!(Packet::read_async(&mut reader));
+            let Poll::Ready(Ok(read)) = future.poll(&mut cx) else {
+                panic!("Expected packet to be read");
+            };
+            assert_eq!(&read, packet);
+        }
+        let future = pin!(Packet::read_async(&mut reader));
+        let Poll::Ready(Err(err)) = future.poll(&mut cx) else {
+            panic!("Expected partial packet to fail");
+        };
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    /// Contents with a single ID field
+    struct IdContents {
+        /// The decoded ID
+        id: u32,
+    }
+
+    impl Decodable for IdContents {
+        fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+            let id = reader.tag(b"ID")?;
+            Ok(Self { id })
+        }
+    }
+
+    /// Tests strict decoding fails when contents are left unread
+    #[test]
+    fn test_decode_strict() {
+        let mut writer = TdfWriter::default();
+        writer.tag_u32(b"ID", 5);
+        let packet = Packet::raw(PacketHeader::notify(0x1, 0x2), writer.buffer.clone());
+        assert_eq!(packet.decode_strict::<IdContents>().unwrap().id, 5);
+
+        let length = writer.buffer.len();
+        writer.tag_str(b"NAME", "Test");
+        let packet = Packet::raw(PacketHeader::notify(0x1, 0x2), writer.buffer.clone());
+        assert_eq!(packet.decode::<IdContents>().unwrap().id, 5);
+        assert_eq!(
+            packet.decode_strict::<IdContents>().err(),
+            Some(DecodeError::TrailingBytes {
+                cursor: length,
+                remaining: writer.buffer.len() - length,
+            })
+        );
+    }
+
+    /// Tests blobs can be taken from packet contents without
+    /// decoding the tags after them
+    #[test]
+    fn test_blob() {
+        let mut writer = TdfWriter::default();
+        writer.tag_u32(b"ID", 1);
+        writer.tag_value(b"DATA", &Blob(vec![1, 2, 3, 4]));
+        // Invalid tag type which would fail to decode
+        writer.write_slice(&[0xFF; 5]);
+        let packet = Packet::raw(PacketHeader::notify(0x1, 0x2), writer.into());
+
+        let blob = packet.blob(b"DATA").unwrap();
+        assert_eq!(&blob[..], &[1, 2, 3, 4]);
+        // The blob shares the packet contents
+        assert!(packet.contents.as_ptr_range().contains(&blob.as_ptr()));
+
+        let mut read = Vec::new();
+        packet
+            .blob_reader(b"DATA")
+            .unwrap()
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(read, [1, 2, 3, 4]);
+
+        assert!(packet.blob(b"ID").is_err());
+        assert!(packet.blob(b"NONE").is_err());
+    }
+
+    /// The number of times [TestComponent] has been resolved
+    static RESOLVED: AtomicUsize = AtomicUsize::new(0);
+
+    /// Component for testing component resolution
+    #[derive(Debug, Hash, PartialEq, Eq)]
+    struct TestComponent;
+
+    impl PacketComponents for TestComponent {
+        fn values(&self) -> (u16, u16) {
+            (0x1, 0x2)
+        }
+
+        fn from_values(component: u16, command: u16, _notify: bool) -> Option<Self> {
+            RESOLVED.fetch_add(1, Ordering::SeqCst);
+            (component == 0x1 && command == 0x2).then_some(TestComponent)
+        }
+    }
+
+    /// Tests the component of a packet is only resolved once
+    #[test]
+    fn test_component_packet() {
+        let packet: ComponentPacket<TestComponent> =
+            Packet::raw_empty(PacketHeader::notify(0x1, 0x2)).into();
+        assert_eq!(packet.component(), Some(&TestComponent));
+        assert_eq!(packet.component(), Some(&TestComponent));
+        assert!(format!("{:?}", packet.debug(true)).contains("TestComponent"));
+        assert_eq!(RESOLVED.load(Ordering::SeqCst), 1);
+        assert_eq!(packet.header.component, 0x1);
+
+        let packet = ComponentPacket::<TestComponent>::new(Packet::raw_empty(
+            PacketHeader::notify(0x1, 0x3),
+        ));
+        assert_eq!(packet.component(), None);
+        assert_eq!(packet.component(), None);
+        assert_eq!(RESOLVED.load(Ordering::SeqCst), 2);
+    }
+
+    /// Writer which only accepts a few bytes at a time
+    struct ShortWriter(Vec<u8>);
+
+    impl io::Write for ShortWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let length = buf.len().min(5);
+            self.0.extend_from_slice(&buf[..length]);
+            Ok(length)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Tests packets written with vectored writes match the
+    /// packets written into a buffer and can be read back
+    #[test]
+    fn test_write_vectored() {
+        // Includes a packet with extended length contents
+        for packet in &framing_packets() {
+            let mut expected = BytesMut::new();
+            packet.write(&mut expected);
+
+            let mut buffer = Vec::new();
+            packet.write_vectored(&mut buffer).unwrap();
+            assert_eq!(&buffer[..], &expected[..]);
+            assert_eq!(
+                Packet::read(&mut BytesMut::from(&buffer[..])).as_ref(),
+                Some(packet)
+            );
+
+            let mut writer = ShortWriter(Vec::new());
+            packet.write_vectored(&mut writer).unwrap();
+            assert_eq!(&writer.0[..], &expected[..]);
+        }
+    }
+
+    /// Contents for testing encoding directly into frame buffers
+    struct TestContents {
+        /// The name to encode
+        name: String,
+        /// Whether encoding should fail after writing the name
+        fail: bool,
+    }
+
+    impl Encodable for TestContents {
+        fn encode(&self, writer: &mut TdfWriter) {
+            writer.tag_str(b"NAME", &self.name);
+        }
+
+        fn try_encode(&self, writer: &mut TdfWriter) -> EncodeResult<()> {
+            self.encode(writer);
+            if self.fail {
+                return Err(EncodeError::Other("Test failure"));
+            }
+            Ok(())
+        }
+    }
+
+    /// Tests packets encoded directly into the frame buffer match
+    /// packets encoded into their own buffer and that failures
+    /// leave the frame buffer unchanged
+    #[test]
+    fn test_encode_packet() {
+        let request = Packet::raw_empty(PacketHeader::request(1, 0x1, 0x28));
+        let existing = [0xAB, 0xCD];
+
+        // Both normal and extended length contents
+        for length in [4, 0x10000] {
+            let contents = || TestContents {
+                name: "A".repeat(length),
+                fail: false,
+            };
+            let packet = Packet::response(&request, contents());
+            let mut expected = BytesMut::from(&existing[..]);
+            packet.write(&mut expected);
+
+            let mut buffer = BytesMut::from(&existing[..]);
+            PacketCodec
+                .encode(EncodePacket::response(&request, contents()), &mut buffer)
+                .unwrap();
+            assert_eq!(buffer, expected);
+
+            // Decode the frame to check the header describes the contents
+            let mut frame = buffer.split_off(existing.len());
+            assert_eq!(Packet::read(&mut frame), Some(packet));
+            assert!(frame.is_empty());
+        }
+
+        let contents = TestContents {
+            name: "Test".to_string(),
+            fail: true,
+        };
+        let mut buffer = BytesMut::from(&existing[..]);
+        assert!(EncodePacket::response(&request, contents)
+            .write(&mut buffer)
+            .is_err());
+        assert_eq!(&buffer[..], &existing);
+    }
+
+    /// Tests encoding into buffers that have been advanced past frames
+    /// that were already written, both with and without frames that are
+    /// still pending
+    #[test]
+    fn test_encode_packet_advanced() {
+        let request = Packet::raw_empty(PacketHeader::request(1, 0x1, 0x28));
+        let contents = || TestContents {
+            name: "A".repeat(0x10000),
+            fail: false,
+        };
+        let packet = Packet::response(&request, contents());
+        let written = Packet::raw(PacketHeader::notify(0x7802, 0x1), vec![0xAB; 0x100]);
+
+        // Buffer with the start of a frame pending
+        let mut buffer = BytesMut::with_capacity(0x20000);
+        written.write(&mut buffer);
+        buffer.advance(0x80);
+        let pending = buffer.clone();
+        PacketCodec
+            .encode(EncodePacket::response(&request, contents()), &mut buffer)
+            .unwrap();
+        assert_eq!(&buffer[..pending.len()], &pending[..]);
+        let mut frame = buffer.split_off(pending.len());
+        assert_eq!(Packet::read(&mut frame), Some(packet.clone()));
+        assert!(frame.is_empty());
+
+        // Buffer with every frame written, the allocation is reused
+        let mut buffer = BytesMut::with_capacity(0x20000);
+        let allocation = buffer.as_ptr();
+        written.write(&mut buffer);
+        buffer.advance(buffer.len());
+        PacketCodec
+            .encode(EncodePacket::response(&request, contents()), &mut buffer)
+            .unwrap();
+        assert_eq!(buffer.as_ptr(), allocation);
+        assert_eq!(Packet::read(&mut buffer), Some(packet));
+        assert!(buffer.is_empty());
+
+        // Short headers skip the unused part of the header slot
+        let mut buffer = BytesMut::with_capacity(0x100);
+        let allocation = buffer.as_ptr();
+        PacketCodec
+            .encode(EncodePacket::response(&request, 1u32), &mut buffer)
+            .unwrap();
+        assert_eq!(buffer.as_ptr(), allocation.wrapping_add(2));
+        assert_eq!(
+            Packet::read(&mut buffer),
+            Some(Packet::response(&request, 1u32))
+        );
+    }
+}
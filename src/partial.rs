@@ -0,0 +1,229 @@
+//! Tolerant decoding which attempts to decode every field of a value
+//! collecting the errors for fields that failed ([`PartialDecode`])
+//! instead of failing the whole value over one bad field.
+//!
+//! Types implement [`PartialDecodable`] using a [`PartialReader`]:
+//!
+//! ```
+//! use blaze_pk::{
+//!     partial::{PartialDecodable, PartialDecode, PartialReader},
+//!     reader::TdfReader,
+//!     writer::TdfWriter,
+//! };
+//!
+//! struct Stats {
+//!     name: Option<String>,
+//!     score: Option<u32>,
+//! }
+//!
+//! impl PartialDecodable for Stats {
+//!     fn decode_partial(reader: &mut TdfReader) -> PartialDecode<Self> {
+//!         let mut reader = PartialReader::new(reader);
+//!         let name = reader.tag(b"NAME");
+//!         let score = reader.tag(b"SCOR");
+//!         reader.finish(Stats { name, score })
+//!     }
+//! }
+//!
+//! let mut writer = TdfWriter::default();
+//! writer.tag_u32(b"NAME", 1);
+//! writer.tag_u32(b"SCOR", 5);
+//!
+//! let decoded = Stats::decode_partial(&mut TdfReader::new(&writer.buffer));
+//! assert_eq!(decoded.value.name, None);
+//! assert_eq!(decoded.value.score, Some(5));
+//! assert_eq!(decoded.errors.len(), 1);
+//! ```
+
+use crate::{
+    codec::{Decodable, ValueType},
+    error::{DecodeError, DecodeResult},
+    reader::TdfReader,
+    tag::Tag,
+};
+
+/// Error for a single field that failed to decode
+#[derive(Debug)]
+pub struct FieldError {
+    /// The tag of the field
+    pub tag: Tag,
+    /// The error that occurred decoding the field
+    pub error: DecodeError,
+}
+
+/// Result of partially decoding a value containing the value
+/// made from the fields that could be decoded along with the
+/// errors for the fields that could not
+#[derive(Debug)]
+pub struct PartialDecode<T> {
+    /// The decoded value
+    pub value: T,
+    /// The errors for each field that failed to decode
+    pub errors: Vec<FieldError>,
+}
+
+impl<T> PartialDecode<T> {
+    /// Checks whether every field was decoded successfully
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Converts this partial decode into a result which is
+    /// the error of the first failed field if any failed
+    pub fn into_result(self) -> DecodeResult<T> {
+        match self.errors.into_iter().next() {
+            Some(FieldError { error, .. }) => Err(error),
+            None => Ok(self.value),
+        }
+    }
+}
+
+/// Trait for types that can be partially decoded
+pub trait PartialDecodable: Sized {
+    /// Decodes as many of the fields of Self as possible from
+    /// the provided reader
+    ///
+    /// `reader` The reader to decode from
+    fn decode_partial(reader: &mut TdfReader) -> PartialDecode<Self>;
+}
+
+/// Wrapper around a reader which reads tagged fields recording the
+/// errors for fields that fail and skipping over their values so the
+/// following fields can still be read
+pub struct PartialReader<'r, 'a> {
+    /// The underlying reader
+    reader: &'r mut TdfReader<'a>,
+    /// The errors from the fields that failed
+    errors: Vec<FieldError>,
+}
+
+impl<'r, 'a> PartialReader<'r, 'a> {
+    /// Creates a new partial reader from the provided reader
+    ///
+    /// `reader` The reader to read from
+    pub fn new(reader: &'r mut TdfReader<'a>) -> Self {
+        Self {
+            reader,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Reads the provided tag returning None and recording the
+    /// error if the tag was missing or failed to decode
+    ///
+    /// `tag` The tag name to read
+    pub fn tag<C: Decodable + ValueType>(&mut self, tag: &[u8]) -> Option<C> {
+        match self.read(tag) {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.errors.push(FieldError {
+                    tag: Tag::from(tag),
+                    error,
+                });
+                None
+            }
+        }
+    }
+
+    /// Reads the provided optional tag returning None without recording
+    /// an error if the tag is missing. Other errors are still recorded
+    ///
+    /// `tag` The tag name to read
+    pub fn try_tag<C: Decodable + ValueType>(&mut self, tag: &[u8]) -> Option<C> {
+        match self.read(tag) {
+            Ok(value) => Some(value),
+            Err(DecodeError::MissingTag { .. }) => None,
+            Err(error) => {
+                self.errors.push(FieldError {
+                    tag: Tag::from(tag),
+                    error,
+                });
+                None
+            }
+        }
+    }
+
+    /// Reads the value of the provided tag. Missing tags and malformed
+    /// fields reset the cursor, fields with the wrong type or values that
+    /// fail to decode are skipped
+    ///
+    /// `tag` The tag name to read
+    fn read<C: Decodable + ValueType>(&mut self, tag: &[u8]) -> DecodeResult<C> {
+        let start = self.reader.cursor;
+        let ty = C::value_type();
+
+        if let Err(err) = self.reader.until_tag(tag, ty) {
+            match &err {
+                DecodeError::InvalidTagType { actual, .. }
+                    if self.reader.skip_type(actual).is_ok() => {}
+                _ => self.reader.cursor = start,
+            }
+            return Err(err);
+        }
+
+        let value_start = self.reader.cursor;
+        C::decode(self.reader).inspect_err(|_| {
+            self.reader.cursor = value_start;
+            if self.reader.skip_type(&ty).is_err() {
+                self.reader.cursor = start;
+            }
+        })
+    }
+
+    /// Returns the errors recorded so far
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    /// Completes the partial decode with the provided value
+    ///
+    /// `value` The value created from the decoded fields
+    pub fn finish<T>(self, value: T) -> PartialDecode<T> {
+        PartialDecode {
+            value,
+            errors: self.errors,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PartialReader;
+    use crate::{error::DecodeErrorKind, reader::TdfReader, tag::Tag, writer::TdfWriter};
+    use std::num::NonZeroU32;
+
+    /// Tests fields after failed fields are still decoded
+    #[test]
+    fn test_partial_reader() {
+        let mut writer = TdfWriter::default();
+        writer.tag_str(b"NAME", "Test");
+        writer.tag_u32(b"NZ", 0);
+        writer.tag_str(b"WRNG", "Test");
+        writer.tag_u32(b"SCOR", 5);
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        let mut partial = PartialReader::new(&mut reader);
+        assert_eq!(partial.tag::<String>(b"NAME").as_deref(), Some("Test"));
+        assert_eq!(partial.tag::<NonZeroU32>(b"NZ"), None);
+        assert_eq!(partial.tag::<u32>(b"WRNG"), None);
+        assert_eq!(partial.try_tag::<u32>(b"OPT"), None);
+        assert_eq!(partial.tag::<u32>(b"SCOR"), Some(5));
+        assert_eq!(partial.tag::<u32>(b"MISS"), None);
+
+        let decoded = partial.finish(());
+        let errors: Vec<(Tag, DecodeErrorKind)> = decoded
+            .errors
+            .iter()
+            .map(|err| (err.tag, err.error.kind()))
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                (Tag::from(&b"NZ"[..]), DecodeErrorKind::Other),
+                (Tag::from(b"WRNG"), DecodeErrorKind::InvalidTagType),
+                (Tag::from(b"MISS"), DecodeErrorKind::MissingTag),
+            ]
+        );
+        assert!(decoded.into_result().is_err());
+    }
+}
@@ -2,12 +2,14 @@
 //! packet buffers provides easy functions for all the different tdf types
 
 use crate::{
-    codec::{Decodable, ValueType},
-    error::{DecodeError, DecodeResult},
+    codec::{BorrowDecodable, Decodable, ValueType},
+    error::{DecodeError, DecodeResult, HexContext},
+    format::TdfFormatter,
     tag::{Tag, Tagged, TdfType},
-    types::{TdfMap, UNION_UNSET},
+    tag_names::TagNames,
+    types::{TdfMap, VarInt, UNION_UNSET},
 };
-use std::borrow::Cow;
+use std::{borrow::Cow, marker::PhantomData};
 
 /// Buffered readable implementation. Allows reading through the
 /// underlying slice using a cursor and with a position that can
@@ -21,29 +23,6 @@ pub struct TdfReader<'a> {
     pub cursor: usize,
 }
 
-/// Macro for implementing VarInt decoding for a specific number type
-/// to prevent allocating for a u64 for every other number type
-macro_rules! impl_decode_var {
-    ($ty:ty, $reader:ident) => {{
-        let first: u8 = $reader.read_byte()?;
-        let mut result: $ty = (first & 63) as $ty;
-        if first < 128 {
-            return Ok(result);
-        }
-        let mut shift: u8 = 6;
-        let mut byte: u8;
-        loop {
-            byte = $reader.read_byte()?;
-            result |= ((byte & 127) as $ty) << shift;
-            if byte < 128 {
-                break;
-            }
-            shift += 7;
-        }
-        Ok(result)
-    }};
-}
-
 impl<'a> TdfReader<'a> {
     /// Creates a new reader over the provided slice of bytes with
     /// the default cursor position at zero
@@ -51,6 +30,38 @@ impl<'a> TdfReader<'a> {
         Self { buffer, cursor: 0 }
     }
 
+    /// Decodes a value from the reader wrapping any errors with a
+    /// [HexContext] of the bytes surrounding the failure so that the
+    /// error can be diagnosed without a full capture
+    pub fn decode_with_context<C: Decodable>(&mut self) -> DecodeResult<C> {
+        C::decode(self).map_err(|error| {
+            let cursor = match &error {
+                DecodeError::UnexpectedEof { cursor, .. } => *cursor,
+                _ => self.cursor,
+            };
+            DecodeError::Context {
+                context: self.context(cursor, 16),
+                error: Box::new(error),
+            }
+        })
+    }
+
+    /// Captures a window of bytes surrounding the provided cursor
+    /// position
+    ///
+    /// `cursor` The position to capture around
+    /// `radius` The maximum number of bytes to capture either side
+    pub fn context(&self, cursor: usize, radius: usize) -> HexContext {
+        let cursor = cursor.min(self.buffer.len());
+        let start = cursor.saturating_sub(radius);
+        let end = cursor.saturating_add(radius).min(self.buffer.len());
+        HexContext {
+            cursor,
+            start,
+            bytes: self.buffer[start..end].to_vec(),
+        }
+    }
+
     /// Takes a single byte from the underlying buffer moving
     /// the cursor over by one. Will return UnexpectedEof error
     /// if there are no bytes left
@@ -80,7 +91,7 @@ impl<'a> TdfReader<'a> {
     /// buffer that is after the cursor position
     ///
     /// `length` The length of the slice to take
-    pub fn read_slice(&mut self, length: usize) -> DecodeResult<&[u8]> {
+    pub fn read_slice(&mut self, length: usize) -> DecodeResult<&'a [u8]> {
         // Ensure we have the required number of bytes
         if self.cursor + length > self.buffer.len() {
             return Err(DecodeError::UnexpectedEof {
@@ -89,7 +100,7 @@ impl<'a> TdfReader<'a> {
                 remaining: self.len(),
             });
         }
-        let slice: &[u8] = &self.buffer[self.cursor..self.cursor + length];
+        let slice: &'a [u8] = &self.buffer[self.cursor..self.cursor + length];
         self.cursor += length;
         Ok(slice)
     }
@@ -127,69 +138,106 @@ impl<'a> TdfReader<'a> {
         self.cursor >= self.buffer.len()
     }
 
-    /// Decodes a u8 value using the VarInt encoding
-    pub fn read_u8(&mut self) -> DecodeResult<u8> {
-        let first: u8 = self.read_byte()?;
-        let mut result: u8 = first & 63;
+    /// Returns the bytes after the cursor that haven't been read without
+    /// moving the cursor. Allows decoding to stop early and the rest of
+    /// the contents to be forwarded as is
+    pub fn remaining(&self) -> &'a [u8] {
+        &self.buffer[self.cursor.min(self.buffer.len())..]
+    }
+
+    /// Decodes a VarInt value from the buffer. The value is decoded in
+    /// a single pass over the buffer and the cursor is only moved once
+    /// the whole value is read. Bits beyond the 64 bits of the value
+    /// are discarded
+    fn read_var_int(&mut self) -> DecodeResult<u64> {
+        let bytes: &[u8] = &self.buffer[self.cursor.min(self.buffer.len())..];
+        let Some(&first) = bytes.first() else {
+            return Err(DecodeError::UnexpectedEof {
+                cursor: self.cursor,
+                wanted: 1,
+                remaining: 0,
+            });
+        };
+        let mut result: u64 = (first & 63) as u64;
         // Values less than 128 are already complete and don't need more reading
         if first < 128 {
+            self.cursor += 1;
             return Ok(result);
         }
 
-        let byte: u8 = self.read_byte()?;
-        result |= (byte & 127) << 6;
-
-        // Consume remaining unused VarInt data. We only wanted a u8
-        if byte >= 128 {
-            self.skip_var_int();
+        let mut shift: u32 = 6;
+        for (index, &byte) in bytes.iter().enumerate().skip(1) {
+            if shift < u64::BITS {
+                result |= ((byte & 127) as u64) << shift;
+            }
+            if byte < 128 {
+                self.cursor += index + 1;
+                return Ok(result);
+            }
+            shift += 7;
         }
-        Ok(result)
+
+        // Reached the end of the buffer within the value
+        self.cursor = self.buffer.len();
+        Err(DecodeError::UnexpectedEof {
+            cursor: self.cursor,
+            wanted: 1,
+            remaining: 0,
+        })
+    }
+
+    /// Decodes a u8 value using the VarInt encoding. Bits
+    /// beyond the width of the value are discarded
+    pub fn read_u8(&mut self) -> DecodeResult<u8> {
+        self.read_var_int().map(|value| value as u8)
     }
 
-    /// Decodes a u16 value using hte VarInt encoding. This uses
-    /// the impl_decode_var macro so its implementation is the
-    /// same as others
+    /// Decodes a u16 value using the VarInt encoding. Bits
+    /// beyond the width of the value are discarded
     pub fn read_u16(&mut self) -> DecodeResult<u16> {
-        impl_decode_var!(u16, self)
+        self.read_var_int().map(|value| value as u16)
     }
 
-    /// Decodes a u32 value using hte VarInt encoding. This uses
-    /// the impl_decode_var macro so its implementation is the
-    /// same as others
+    /// Decodes a u32 value using the VarInt encoding. Bits
+    /// beyond the width of the value are discarded
     pub fn read_u32(&mut self) -> DecodeResult<u32> {
-        impl_decode_var!(u32, self)
+        self.read_var_int().map(|value| value as u32)
     }
 
-    /// Decodes a u64 value using hte VarInt encoding. This uses
-    /// the impl_decode_var macro so its implementation is the
-    /// same as others
+    /// Decodes a u64 value using the VarInt encoding
     pub fn read_u64(&mut self) -> DecodeResult<u64> {
-        impl_decode_var!(u64, self)
+        self.read_var_int()
     }
 
-    /// Decodes a u64 value using hte VarInt encoding. This uses
-    /// the impl_decode_var macro so its implementation is the
-    /// same as others
+    /// Decodes a usize value using the VarInt encoding. Bits
+    /// beyond the width of the value are discarded
     pub fn read_usize(&mut self) -> DecodeResult<usize> {
-        impl_decode_var!(usize, self)
+        self.read_var_int().map(|value| value as usize)
     }
 
     /// Reads a blob from the buffer. The blob is a slice prefixed
     /// by a length value
-    pub fn read_blob(&mut self) -> DecodeResult<&[u8]> {
+    pub fn read_blob(&mut self) -> DecodeResult<&'a [u8]> {
         let length: usize = self.read_usize()?;
-        let bytes: &[u8] = self.read_slice(length)?;
+        let bytes: &'a [u8] = self.read_slice(length)?;
         Ok(bytes)
     }
 
+    /// Reads a string from the underlying buffer borrowing the
+    /// string from the buffer when it is valid UTF-8 otherwise
+    /// the invalid characters are replaced
+    pub fn read_str(&mut self) -> DecodeResult<Cow<'a, str>> {
+        let mut bytes: &'a [u8] = self.read_blob()?;
+        // Remove null terminator
+        if let Some((0, rest)) = bytes.split_last() {
+            bytes = rest;
+        }
+        Ok(String::from_utf8_lossy(bytes))
+    }
+
     /// Reads a string from the underlying buffer
     pub fn read_string(&mut self) -> DecodeResult<String> {
-        let bytes: &[u8] = self.read_blob()?;
-        let text: Cow<str> = String::from_utf8_lossy(bytes);
-        let mut text: String = text.to_string();
-        // Remove null terminator
-        text.pop();
-        Ok(text)
+        self.read_str().map(Cow::into_owned)
     }
 
     /// Reads a boolean value this is encoded using the
@@ -327,6 +375,11 @@ impl<'a> TdfReader<'a> {
     /// reaches the correct value. If the tag is missing the cursor is reset
     /// back to where it was
     ///
+    /// Optional values are represented by the absence of their tag, this
+    /// is the reading counterpart for [`TdfWriter::tag_option`]
+    ///
+    /// [`TdfWriter::tag_option`]: crate::writer::TdfWriter::tag_option
+    ///
     /// `tag` The tag name to read
     pub fn try_tag<C: Decodable + ValueType>(&mut self, tag: &[u8]) -> DecodeResult<Option<C>> {
         let start = self.cursor;
@@ -340,6 +393,37 @@ impl<'a> TdfReader<'a> {
         }
     }
 
+    /// Reads the provided tag from the buffer the same as [`TdfReader::tag`]
+    /// for values which borrow from the buffer
+    ///
+    /// `tag` The tag name to read
+    pub fn tag_borrowed<C: BorrowDecodable<'a> + ValueType>(
+        &mut self,
+        tag: &[u8],
+    ) -> DecodeResult<C> {
+        self.until_tag(tag, C::value_type())?;
+        C::decode_borrowed(self)
+    }
+
+    /// Reads the provided tag from the buffer the same as [`TdfReader::try_tag`]
+    /// for values which borrow from the buffer
+    ///
+    /// `tag` The tag name to read
+    pub fn try_tag_borrowed<C: BorrowDecodable<'a> + ValueType>(
+        &mut self,
+        tag: &[u8],
+    ) -> DecodeResult<Option<C>> {
+        let start = self.cursor;
+        match self.tag_borrowed(tag) {
+            Ok(value) => Ok(Some(value)),
+            Err(DecodeError::MissingTag { .. }) => {
+                self.cursor = start;
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
     /// Reads the next TdfType value after the cursor
     pub fn read_type(&mut self) -> DecodeResult<TdfType> {
         let value = self.read_byte()?;
@@ -424,6 +508,18 @@ impl<'a> TdfReader<'a> {
         Ok(())
     }
 
+    /// Skips any remaining values within the current group up to
+    /// and including the zero byte that marks the end of the group
+    pub fn skip_group_end(&mut self) -> DecodeResult<()> {
+        loop {
+            if self.read_byte()? == 0 {
+                return Ok(());
+            }
+            self.cursor -= 1;
+            self.skip()?;
+        }
+    }
+
     /// Skips a list of items
     pub fn skip_list(&mut self) -> DecodeResult<()> {
         let ty: TdfType = self.read_type()?;
@@ -455,6 +551,17 @@ impl<'a> TdfReader<'a> {
         Ok(())
     }
 
+    /// Skips a generic value which is a byte for whether the value is set
+    /// followed by the type id and tagged value when set and then the
+    /// zero byte that ends the generic
+    pub fn skip_generic(&mut self) -> DecodeResult<()> {
+        if self.read_byte()? != 0 {
+            self.skip_var_int();
+            self.skip()?;
+        }
+        self.skip_group_end()
+    }
+
     /// Skips a var int list
     pub fn skip_var_int_list(&mut self) -> DecodeResult<()> {
         let length: usize = self.read_usize()?;
@@ -475,7 +582,7 @@ impl<'a> TdfReader<'a> {
     /// `ty` The type of data to skip
     pub fn skip_type(&mut self, ty: &TdfType) -> DecodeResult<()> {
         match ty {
-            TdfType::VarInt => self.skip_var_int(),
+            TdfType::VarInt | TdfType::TimeValue => self.skip_var_int(),
             TdfType::String | TdfType::Blob => self.skip_blob()?,
             TdfType::Group => self.skip_group()?,
             TdfType::List => self.skip_list()?,
@@ -492,6 +599,7 @@ impl<'a> TdfReader<'a> {
                 self.skip_var_int();
             }
             TdfType::Float => self.skip_f32()?,
+            TdfType::Generic => self.skip_generic()?,
         }
         Ok(())
     }
@@ -501,17 +609,21 @@ impl<'a> TdfReader<'a> {
     ///
     /// `out` The string output to append to
     pub fn stringify(&mut self, out: &mut String) -> DecodeResult<()> {
-        while self.cursor < self.buffer.len() {
-            if let Err(err) = self.stringify_tag(out, 1) {
-                out.push_str(&format!(
-                    "... remaining {}, cause: {:?}",
-                    self.buffer.len() - self.cursor,
-                    err
-                ));
-                break;
-            }
-        }
-        Ok(())
+        self.stringify_with_names(out, None)
+    }
+
+    /// Decodes all the contents within the reader into a string
+    /// representation including the names of tags that are known
+    /// from the provided tag names
+    ///
+    /// `out`   The string output to append to
+    /// `names` The known tag names
+    pub fn stringify_with_names(
+        &mut self,
+        out: &mut String,
+        names: Option<&TagNames>,
+    ) -> DecodeResult<()> {
+        TdfFormatter::default().format(self, out, names)
     }
 
     /// Decodes and converts the next tag into
@@ -520,19 +632,7 @@ impl<'a> TdfReader<'a> {
     /// `out`    The string output to append to
     /// `indent` The current indent level
     pub fn stringify_tag(&mut self, out: &mut String, indent: usize) -> DecodeResult<()> {
-        let tag = self.read_tag()?;
-        out.push_str(&"  ".repeat(indent));
-        out.push_str(&format!("\"{}\": ", &tag.tag));
-        match self.stringify_type(out, indent, &tag.ty) {
-            Ok(_) => {
-                out.push_str(",\n");
-                Ok(())
-            }
-            Err(err) => {
-                out.push_str("...");
-                Err(err)
-            }
-        }
+        TdfFormatter::default().format_tag(self, out, indent, None)
     }
 
     /// Decodes and converts the next value of the provided type
@@ -547,138 +647,7 @@ impl<'a> TdfReader<'a> {
         indent: usize,
         ty: &TdfType,
     ) -> DecodeResult<()> {
-        match ty {
-            TdfType::VarInt => {
-                let value = self.read_usize()?;
-                out.push_str(&value.to_string());
-            }
-            TdfType::String => {
-                let value = self.read_string()?;
-                out.push('"');
-                out.push_str(&value);
-                out.push('"');
-            }
-            TdfType::Blob => {
-                let value = self.read_blob()?;
-                let length = value.len();
-                out.push_str("Blob [");
-                for (i, value) in value.iter().enumerate() {
-                    out.push_str(&format!("0x{:X}", value));
-                    if i < length - 1 {
-                        out.push_str(", ");
-                    }
-                }
-                out.push(']');
-            }
-            TdfType::Group => {
-                out.push_str("{\n");
-                let mut is_two: bool = false;
-                while self.cursor < self.buffer.len() {
-                    let byte: u8 = self.buffer[self.cursor];
-                    if byte == 0 {
-                        self.cursor += 1;
-                        break;
-                    }
-                    if byte == 2 {
-                        is_two = true;
-                        self.cursor += 1;
-                    }
-                    self.stringify_tag(out, indent + 1)?;
-                }
-                out.push_str(&"  ".repeat(indent));
-                out.push('}');
-                if is_two {
-                    out.push_str(" (2)");
-                }
-            }
-            TdfType::List => {
-                let value_type: TdfType = self.read_type()?;
-                let length: usize = self.read_usize()?;
-                let expand = matches!(value_type, TdfType::Map | TdfType::Group);
-                out.push('[');
-                if expand {
-                    out.push('\n');
-                }
-
-                for i in 0..length {
-                    if expand {
-                        out.push_str(&"  ".repeat(indent + 1));
-                    }
-                    self.stringify_type(out, indent + 1, &value_type)?;
-                    if i < length - 1 {
-                        out.push_str(", ");
-                    }
-                    if expand {
-                        out.push('\n');
-                    }
-                }
-                if expand {
-                    out.push_str(&"  ".repeat(indent));
-                }
-                out.push(']');
-            }
-            TdfType::Map => {
-                let key_type: TdfType = self.read_type()?;
-                let value_type: TdfType = self.read_type()?;
-                let length: usize = self.read_usize()?;
-                out.push_str(&format!("Map<{:?}, {:?}> ", key_type, value_type));
-                out.push_str("{\n");
-
-                for i in 0..length {
-                    out.push_str(&"  ".repeat(indent + 1));
-                    self.stringify_type(out, indent + 1, &key_type)?;
-                    out.push_str(": ");
-                    self.stringify_type(out, indent + 1, &value_type)?;
-                    if i < length - 1 {
-                        out.push(',');
-                    }
-                    out.push('\n')
-                }
-                out.push_str(&"  ".repeat(indent));
-                out.push('}');
-            }
-            TdfType::Union => {
-                let ty = self.read_byte()?;
-                if ty == UNION_UNSET {
-                    out.push_str("Union(Unset)")
-                } else {
-                    let tag = self.read_tag()?;
-                    out.push_str(&format!("Union(\"{}\", {}, ", &tag.tag, ty));
-                    self.stringify_type(out, indent + 1, &tag.ty)?;
-                    out.push(')')
-                }
-            }
-            TdfType::VarIntList => {
-                let length: usize = self.read_usize()?;
-                out.push_str("VarList [");
-                for i in 0..length {
-                    let value = self.read_usize()?;
-                    out.push_str(&value.to_string());
-                    if i < length - 1 {
-                        out.push_str(", ");
-                    }
-                }
-                out.push(']');
-            }
-            TdfType::Pair => {
-                let a = self.read_usize()?;
-                let b = self.read_usize()?;
-
-                out.push_str(&format!("({}, {})", a, b))
-            }
-            TdfType::Triple => {
-                let a = self.read_usize()?;
-                let b = self.read_usize()?;
-                let c = self.read_usize()?;
-
-                out.push_str(&format!("({}, {}, {})", a, b, c))
-            }
-            TdfType::Float => {
-                let value = self.read_f32()?;
-                out.push_str(&value.to_string());
-            }
-        };
-        Ok(())
+        TdfFormatter::default().format_type(self, out, indent, ty, None)
     }
 
     /// Reads until the next list values selection for the provided
@@ -735,12 +704,146 @@ impl<'a> TdfReader<'a> {
         let count = self.read_usize()?;
         Ok(count)
     }
+
+    /// Reads the header of a list returning an iterator which lazily
+    /// decodes the values of the list from this reader instead of
+    /// collecting them into a Vec. The values must be fully consumed
+    /// before reading anything after the list
+    pub fn iter_list<C: Decodable + ValueType>(&mut self) -> DecodeResult<ListIter<'_, 'a, C>> {
+        let value_type: TdfType = self.read_type()?;
+        let expected_type = C::value_type();
+        if value_type != expected_type {
+            return Err(DecodeError::InvalidType {
+                expected: expected_type,
+                actual: value_type,
+            });
+        }
+        let length: usize = self.read_usize()?;
+        Ok(ListIter::new(self, length))
+    }
+
+    /// Reads the length of a var int list returning an iterator which
+    /// lazily decodes the values of the list from this reader instead
+    /// of collecting them into a Vec. The values must be fully consumed
+    /// before reading anything after the list
+    pub fn iter_var_int_list<C: VarInt>(&mut self) -> DecodeResult<ListIter<'_, 'a, C>> {
+        let length: usize = self.read_usize()?;
+        Ok(ListIter::new(self, length))
+    }
+}
+
+/// Iterator lazily decoding the values of a list from a reader created
+/// using [TdfReader::iter_list] or [TdfReader::iter_var_int_list].
+/// Iteration stops after the first value that fails to decode
+pub struct ListIter<'r, 'a, C> {
+    /// The reader to decode the values from
+    reader: &'r mut TdfReader<'a>,
+    /// The number of values left to decode
+    remaining: usize,
+    /// Marker for the value type
+    _marker: PhantomData<C>,
+}
+
+impl<'r, 'a, C> ListIter<'r, 'a, C> {
+    /// Creates a new list iterator for the provided reader
+    ///
+    /// `reader`    The reader to decode from
+    /// `remaining` The number of values in the list
+    fn new(reader: &'r mut TdfReader<'a>, remaining: usize) -> Self {
+        Self {
+            reader,
+            remaining,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of values that are yet to be decoded
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<C: Decodable> Iterator for ListIter<'_, '_, C> {
+    type Item = DecodeResult<C>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let value = C::decode(self.reader);
+        if value.is_err() {
+            self.remaining = 0;
+        }
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
 }
 
 /// Majority of reading tests are merged into the writing tests
 #[cfg(test)]
 mod test {
     use super::TdfReader;
+    use crate::{error::DecodeError, types::VarIntList, writer::TdfWriter};
+
+    /// Tests errors are wrapped with the bytes surrounding the cursor
+    #[test]
+    fn test_decode_with_context() {
+        let mut writer = TdfWriter::default();
+        writer.tag_str(b"TEXT", "Test");
+        let bytes = &writer.buffer[..writer.buffer.len() - 2];
+
+        let mut reader = TdfReader::new(bytes);
+        reader.cursor = 4;
+        let err = reader.decode_with_context::<String>().unwrap_err();
+        let DecodeError::Context { error, context } = &err else {
+            panic!("Expected context error");
+        };
+        assert!(matches!(
+            **error,
+            DecodeError::UnexpectedEof { cursor: 5, .. }
+        ));
+        assert_eq!(context.start, 0);
+        assert_eq!(context.bytes, bytes);
+        assert!(err
+            .to_string()
+            .ends_with("near 0x0000: d2 5e 34 01 05 [54] 65 73"));
+
+        reader.cursor = bytes.len();
+        let err = reader.decode_with_context::<u8>().unwrap_err();
+        assert!(err.to_string().ends_with("73 []"));
+    }
+
+    /// Tests lazily decoding the values of lists
+    #[test]
+    fn test_iter_list() {
+        let mut writer = TdfWriter::default();
+        writer.tag_value(b"LIST", &vec![1u32, 2, 3]);
+        writer.tag_value(b"VARS", &VarIntList(vec![4u32, 5]));
+        writer.tag_u8(b"NEXT", 6);
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        reader.read_tag().unwrap();
+        let sum: u32 = reader
+            .iter_list::<u32>()
+            .unwrap()
+            .map(|value| value.unwrap())
+            .sum();
+        assert_eq!(sum, 6);
+
+        reader.read_tag().unwrap();
+        let values: Vec<u32> = reader
+            .iter_var_int_list()
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(values, vec![4, 5]);
+
+        assert_eq!(reader.tag::<u8>(b"NEXT").unwrap(), 6);
+    }
 
     /// Tests reading a byte from the reader
     #[test]
@@ -752,4 +855,34 @@ mod test {
             assert_eq!(value, read_value);
         }
     }
+
+    /// Tests VarInt values with more bytes than fit in a u64 are
+    /// consumed without overflowing and truncated values error
+    #[test]
+    fn test_read_var_int_overlong() {
+        let mut bytes = vec![0xFF; 12];
+        bytes.push(0x01);
+        bytes.push(0x05);
+        let mut reader = TdfReader::new(&bytes);
+        assert_eq!(reader.read_u64().unwrap(), u64::MAX);
+        assert_eq!(reader.read_u8().unwrap(), 5);
+
+        let bytes = [0x80, 0x80];
+        let mut reader = TdfReader::new(&bytes);
+        assert!(matches!(
+            reader.read_u32(),
+            Err(DecodeError::UnexpectedEof { cursor: 2, .. })
+        ));
+    }
+
+    /// Tests the remaining bytes can be taken without moving the cursor
+    #[test]
+    fn test_remaining() {
+        let bytes = [0x05, 0x01, 0x02];
+        let mut reader = TdfReader::new(&bytes);
+        assert_eq!(reader.remaining(), &bytes);
+        reader.read_u8().unwrap();
+        assert_eq!(reader.remaining(), &[0x01, 0x02]);
+        assert_eq!(reader.cursor, 1);
+    }
 }
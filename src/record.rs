@@ -0,0 +1,387 @@
+//! Recording of the frames passing through a codec ([`RecordingCodec`]) to a
+//! file so that traffic from production sessions can be captured and later
+//! replayed locally (See [`Replay::from_recording`]).
+//!
+//! Recording is controlled at runtime through a shared [`Recorder`] handle
+//! which can be started and stopped without replacing the codec.
+//!
+//! Recordings start with the [`RECORDING_MAGIC`] bytes and a version byte
+//! followed by a record for each frame:
+//!
+//! | Field     | Size     | Description                                       |
+//! |-----------|----------|---------------------------------------------------|
+//! | Timestamp | 8 bytes  | Microseconds since the unix epoch (big endian)    |
+//! | Direction | 1 byte   | 0 for inbound frames and 1 for outbound frames    |
+//! | Length    | 4 bytes  | The length of the frame (big endian)              |
+//! | Frame     | Variable | The complete packet frame including the header    |
+//!
+//! ```
+//! use blaze_pk::{
+//!     packet::{Packet, PacketCodec, PacketHeader},
+//!     record::{read_recording, Direction, Recorder, RecordingCodec},
+//! };
+//! use bytes::BytesMut;
+//! use std::sync::{Arc, Mutex};
+//! use tokio_util::codec::Encoder;
+//!
+//! # #[derive(Clone, Default)]
+//! # struct Shared(Arc<Mutex<Vec<u8>>>);
+//! # impl std::io::Write for Shared {
+//! #     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> { self.0.lock().unwrap().write(buf) }
+//! #     fn flush(&mut self) -> std::io::Result<()> { Ok(()) }
+//! # }
+//! let recorder = Recorder::new();
+//! let mut codec = RecordingCodec::new(PacketCodec, recorder.clone());
+//!
+//! // Usually a file using `recorder.start_file("session.bpkrec")`
+//! let output = Shared::default();
+//! recorder.start(output.clone()).unwrap();
+//!
+//! let packet = Packet::raw_empty(PacketHeader::notify(0x1, 0x2));
+//! codec.encode(&packet, &mut BytesMut::new()).unwrap();
+//! recorder.stop().unwrap();
+//!
+//! let frames = read_recording(&output.0.lock().unwrap()).unwrap();
+//! assert_eq!(frames[0].direction, Direction::Outbound);
+//! assert_eq!(frames[0].packet, packet);
+//! ```
+//!
+//! [`Replay::from_recording`]: crate::replay::Replay::from_recording
+
+use crate::{
+    error::CodecError,
+    packet::{Packet, PacketCodec},
+};
+use bytes::{Buf, BytesMut};
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Magic bytes at the start of recordings
+pub const RECORDING_MAGIC: &[u8; 6] = b"BPKREC";
+
+/// The current version of the recording format
+pub const RECORDING_VERSION: u8 = 1;
+
+/// The size of the fields at the start of each record
+const RECORD_HEADER_LENGTH: usize = 13;
+
+/// The direction a frame passed through the codec
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Frame decoded from the connection
+    Inbound = 0,
+    /// Frame encoded to be sent over the connection
+    Outbound = 1,
+}
+
+/// Type of the recording output
+type Output = Box<dyn Write + Send>;
+
+/// Shared handle for controlling the recording of frames. Clones
+/// of the recorder control the same recording
+#[derive(Clone, Default)]
+pub struct Recorder {
+    /// Whether frames are currently being recorded
+    enabled: Arc<AtomicBool>,
+    /// The output frames are written to
+    output: Arc<Mutex<Option<Output>>>,
+}
+
+impl Recorder {
+    /// Creates a new recorder which is not recording
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts recording to a new file at the provided path replacing
+    /// any existing file
+    ///
+    /// `path` The path of the recording file
+    pub fn start_file(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let file = File::create(path)?;
+        self.start(BufWriter::new(file))
+    }
+
+    /// Starts recording to the provided output. Any existing
+    /// recording is stopped first
+    ///
+    /// `output` The output to write the recording to
+    pub fn start(&self, output: impl Write + Send + 'static) -> io::Result<()> {
+        let mut output: Output = Box::new(output);
+        output.write_all(RECORDING_MAGIC)?;
+        output.write_all(&[RECORDING_VERSION])?;
+        output.flush()?;
+
+        let mut current = self.output.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(mut previous) = current.replace(output) {
+            previous.flush()?;
+        }
+        self.enabled.store(true, Ordering::Release);
+        Ok(())
+    }
+
+    /// Stops recording flushing and closing the output
+    pub fn stop(&self) -> io::Result<()> {
+        self.enabled.store(false, Ordering::Release);
+        let output = self
+            .output
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .take();
+        match output {
+            Some(mut output) => output.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks whether frames are currently being recorded
+    pub fn is_recording(&self) -> bool {
+        self.enabled.load(Ordering::Acquire)
+    }
+
+    /// Records the provided frame if the recorder is recording.
+    /// Recording is stopped if writing to the output fails
+    ///
+    /// `direction` The direction of the frame
+    /// `frame`     The bytes of the complete frame
+    pub fn record(&self, direction: Direction, frame: &[u8]) {
+        if !self.is_recording() {
+            return;
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let mut record = Vec::with_capacity(RECORD_HEADER_LENGTH + frame.len());
+        record.extend_from_slice(&(timestamp.as_micros() as u64).to_be_bytes());
+        record.push(direction as u8);
+        record.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+        record.extend_from_slice(frame);
+
+        let mut output = self.output.lock().unwrap_or_else(|err| err.into_inner());
+        let Some(writer) = output.as_mut() else {
+            return;
+        };
+        // Flushed after every record so recordings survive crashes
+        if let Err(_err) = writer.write_all(&record).and_then(|_| writer.flush()) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!("Stopping recording after failing to write: {}", _err);
+            self.enabled.store(false, Ordering::Release);
+            *output = None;
+        }
+    }
+}
+
+impl std::fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Recorder")
+            .field("recording", &self.is_recording())
+            .finish()
+    }
+}
+
+/// Codec wrapper which records every frame decoded and
+/// encoded by the underlying codec using a [`Recorder`]
+#[derive(Debug)]
+pub struct RecordingCodec<C = PacketCodec> {
+    /// The underlying codec
+    inner: C,
+    /// The recorder controlling the recording
+    recorder: Recorder,
+}
+
+impl<C> RecordingCodec<C> {
+    /// Creates a new recording codec wrapping the provided codec
+    ///
+    /// `inner`    The underlying codec
+    /// `recorder` The recorder controlling the recording
+    pub fn new(inner: C, recorder: Recorder) -> Self {
+        Self { inner, recorder }
+    }
+
+    /// Returns the recorder controlling the recording
+    pub fn recorder(&self) -> &Recorder {
+        &self.recorder
+    }
+
+    /// Unwraps the underlying codec
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C> Decoder for RecordingCodec<C>
+where
+    C: Decoder<Item = Packet, Error = CodecError>,
+{
+    type Error = CodecError;
+    type Item = Packet;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let packet = self.inner.decode(src)?;
+        if let Some(packet) = &packet {
+            if self.recorder.is_recording() {
+                let mut frame = BytesMut::new();
+                packet.write(&mut frame);
+                self.recorder.record(Direction::Inbound, &frame);
+            }
+        }
+        Ok(packet)
+    }
+}
+
+impl<C, I> Encoder<I> for RecordingCodec<C>
+where
+    C: Encoder<I, Error = CodecError>,
+{
+    type Error = CodecError;
+
+    fn encode(&mut self, item: I, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let start = dst.len();
+        self.inner.encode(item, dst)?;
+        self.recorder.record(Direction::Outbound, &dst[start..]);
+        Ok(())
+    }
+}
+
+/// Frame read from a recording
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedFrame {
+    /// Time the frame was recorded at relative to the unix epoch
+    pub timestamp: Duration,
+    /// The direction of the frame
+    pub direction: Direction,
+    /// The decoded packet
+    pub packet: Packet,
+}
+
+/// Reads the frames from the recording file at the provided path
+///
+/// `path` The path of the recording file
+pub fn read_recording_file(path: impl AsRef<Path>) -> io::Result<Vec<RecordedFrame>> {
+    let bytes = std::fs::read(path)?;
+    read_recording(&bytes)
+}
+
+/// Reads the frames from the provided recording bytes
+///
+/// `bytes` The recording bytes
+pub fn read_recording(mut bytes: &[u8]) -> io::Result<Vec<RecordedFrame>> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+
+    if bytes.len() < RECORDING_MAGIC.len() + 1 || !bytes.starts_with(RECORDING_MAGIC) {
+        return Err(invalid("Not a packet recording"));
+    }
+    bytes.advance(RECORDING_MAGIC.len());
+    if bytes.get_u8() != RECORDING_VERSION {
+        return Err(invalid("Unsupported recording version"));
+    }
+
+    let mut frames = Vec::new();
+    while !bytes.is_empty() {
+        if bytes.len() < RECORD_HEADER_LENGTH {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let timestamp = Duration::from_micros(bytes.get_u64());
+        let direction = match bytes.get_u8() {
+            0 => Direction::Inbound,
+            1 => Direction::Outbound,
+            _ => return Err(invalid("Unknown frame direction")),
+        };
+        let length = bytes.get_u32() as usize;
+        if bytes.len() < length {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let mut frame = BytesMut::from(&bytes[..length]);
+        bytes.advance(length);
+        let packet = Packet::read(&mut frame)
+            .filter(|_| frame.is_empty())
+            .ok_or_else(|| invalid("Invalid packet frame"))?;
+
+        frames.push(RecordedFrame {
+            timestamp,
+            direction,
+            packet,
+        });
+    }
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_recording, Direction, Recorder, RecordingCodec};
+    use crate::packet::{Packet, PacketCodec, PacketHeader};
+    use bytes::BytesMut;
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+    use tokio_util::codec::{Decoder, Encoder};
+
+    /// Output shared with the test for reading the recording
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Tests frames are only recorded while recording and
+    /// that they can be read back
+    #[test]
+    fn test_recording_codec() {
+        let recorder = Recorder::new();
+        let mut codec = RecordingCodec::new(PacketCodec, recorder.clone());
+
+        let request = Packet::raw(PacketHeader::request(1, 0x1, 0x28), vec![1, 2, 3]);
+        let response = Packet::raw_empty(request.header.response());
+
+        let mut src = BytesMut::new();
+        request.write(&mut src);
+        request.write(&mut src);
+        let mut dst = BytesMut::new();
+
+        // Not recording
+        assert_eq!(codec.decode(&mut src).unwrap(), Some(request.clone()));
+
+        let output = Shared::default();
+        recorder.start(output.clone()).unwrap();
+        assert!(recorder.is_recording());
+
+        assert_eq!(codec.decode(&mut src).unwrap(), Some(request.clone()));
+        codec.encode(&response, &mut dst).unwrap();
+        recorder.stop().unwrap();
+
+        // Stopped recording
+        codec.encode(&response, &mut dst).unwrap();
+
+        let frames = read_recording(&output.0.lock().unwrap()).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].direction, Direction::Inbound);
+        assert_eq!(frames[0].packet, request);
+        assert_eq!(frames[1].direction, Direction::Outbound);
+        assert_eq!(frames[1].packet, response);
+        assert!(frames[0].timestamp <= frames[1].timestamp);
+
+        let bytes = output.0.lock().unwrap().clone();
+        assert!(read_recording(&bytes[..bytes.len() - 1]).is_err());
+        assert!(read_recording(b"NOTREC\x01").is_err());
+    }
+}
@@ -0,0 +1,448 @@
+//! Harness for replaying recorded sessions against a [`Router`] to check
+//! the responses it produces still match the recorded responses, allowing
+//! regression suites to be built from real traffic.
+//!
+//! Packet contents are compared by their fields ignoring the order the
+//! fields were written in (See [`contents_match`])
+//!
+//! ```
+//! use blaze_pk::{
+//!     packet::Packet, replay::Replay, router::Router, session::SessionContext,
+//!     PacketComponent, PacketComponents,
+//! };
+//! use std::{future::Future, pin::pin, task::{Context, Poll, Waker}};
+//!
+//! #[derive(Debug, Hash, PartialEq, Eq, PacketComponents)]
+//! enum Components {
+//!     #[component(target = 0x1)]
+//!     Counter(Counter),
+//! }
+//!
+//! #[derive(Debug, Hash, PartialEq, Eq, PacketComponent)]
+//! enum Counter {
+//!     #[command(target = 0x1)]
+//!     Increment,
+//! }
+//!
+//! async fn increment(state: &mut u32) -> u32 {
+//!     *state += 1;
+//!     *state
+//! }
+//!
+//! let mut router: Router<Components, u32> = Router::new();
+//! router.route(Components::Counter(Counter::Increment), increment);
+//!
+//! let first = Packet::request_empty(1, Components::Counter(Counter::Increment));
+//! let second = Packet::request_empty(2, Components::Counter(Counter::Increment));
+//!
+//! let replay = Replay::new()
+//!     .exchange(first.clone(), [Packet::response(&first, 1u32)])
+//!     .exchange(second.clone(), [Packet::response(&second, 2u32)]);
+//!
+//! let mut state = 0;
+//! let ctx = SessionContext::new(1);
+//!
+//! // Replays are run using whichever async runtime is available
+//! let mut future = pin!(replay.run(&router, &mut state, &ctx));
+//! let mut cx = Context::from_waker(Waker::noop());
+//! let report = loop {
+//!     if let Poll::Ready(report) = future.as_mut().poll(&mut cx) {
+//!         break report;
+//!     }
+//! };
+//! report.assert_success();
+//! ```
+//!
+//! [`Router`]: crate::router::Router
+
+use crate::{
+    codec::Decodable,
+    error::DecodeResult,
+    format::default_formatter,
+    packet::{Packet, PacketComponents, PacketHeader},
+    reader::TdfReader,
+    router::{HandleError, Router},
+    session::SessionContext,
+    tag_names::lookup_tag_names,
+    value::TdfFields,
+};
+use std::fmt::Display;
+
+/// Single recorded request along with the packets that were
+/// sent in response to it
+#[derive(Debug, Clone)]
+pub struct ReplayExchange {
+    /// The request packet sent to the router
+    pub request: Packet,
+    /// The response packet followed by any notification
+    /// packets that were sent for the request
+    pub expected: Vec<Packet>,
+}
+
+/// Recorded sequence of requests and expected responses
+#[derive(Debug, Clone, Default)]
+pub struct Replay {
+    /// The recorded exchanges in the order they occurred
+    exchanges: Vec<ReplayExchange>,
+}
+
+impl Replay {
+    /// Creates a new empty replay
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an exchange to the replay
+    ///
+    /// `request`  The request packet
+    /// `expected` The response packet followed by any notification packets
+    pub fn exchange(mut self, request: Packet, expected: impl IntoIterator<Item = Packet>) -> Self {
+        self.exchanges.push(ReplayExchange {
+            request,
+            expected: expected.into_iter().collect(),
+        });
+        self
+    }
+
+    /// Creates a replay from captured packets. Requests sent to the
+    /// server port start a new exchange and the packets sent by the
+    /// server are expected in response to the latest request
+    ///
+    /// `packets` The captured packets in the order they were sent
+    /// `port`    The port of the server
+    #[cfg(feature = "pcap")]
+    pub fn from_captured(packets: &[crate::capture::CapturedPacket], port: u16) -> Self {
+        use crate::packet::PacketType;
+
+        let mut exchanges: Vec<ReplayExchange> = Vec::new();
+        for captured in packets {
+            if captured.destination.port() == port {
+                if let PacketType::Request = captured.packet.header.ty {
+                    exchanges.push(ReplayExchange {
+                        request: captured.packet.clone(),
+                        expected: Vec::new(),
+                    });
+                }
+            } else if captured.source.port() == port {
+                // Packets sent before the first request are not part of an exchange
+                if let Some(exchange) = exchanges.last_mut() {
+                    exchange.expected.push(captured.packet.clone());
+                }
+            }
+        }
+        Self { exchanges }
+    }
+
+    /// Creates a replay from the frames of a recording made on the server
+    /// side of a connection. Each inbound request starts a new exchange and
+    /// the outbound packets are expected in response to the latest request
+    ///
+    /// `frames` The recorded frames in the order they were recorded
+    pub fn from_recording(frames: &[crate::record::RecordedFrame]) -> Self {
+        use crate::{packet::PacketType, record::Direction};
+
+        let mut exchanges: Vec<ReplayExchange> = Vec::new();
+        for frame in frames {
+            match frame.direction {
+                Direction::Inbound => {
+                    if let PacketType::Request = frame.packet.header.ty {
+                        exchanges.push(ReplayExchange {
+                            request: frame.packet.clone(),
+                            expected: Vec::new(),
+                        });
+                    }
+                }
+                Direction::Outbound => {
+                    // Packets sent before the first request are not part of an exchange
+                    if let Some(exchange) = exchanges.last_mut() {
+                        exchange.expected.push(frame.packet.clone());
+                    }
+                }
+            }
+        }
+        Self { exchanges }
+    }
+
+    /// Returns the recorded exchanges
+    pub fn exchanges(&self) -> &[ReplayExchange] {
+        &self.exchanges
+    }
+
+    /// Sends each of the recorded requests to the router in order comparing
+    /// the response and notification packets produced against the expected
+    /// packets. Notification streams produced by handlers are not compared
+    ///
+    /// `router` The router to handle the requests
+    /// `state`  The state provided to the handlers
+    /// `ctx`    The context of the session being replayed
+    pub async fn run<C, S>(
+        &self,
+        router: &Router<C, S>,
+        state: &mut S,
+        ctx: &SessionContext,
+    ) -> ReplayReport
+    where
+        C: PacketComponents,
+        S: Send + 'static,
+    {
+        let mut mismatches = Vec::new();
+
+        for (index, exchange) in self.exchanges.iter().enumerate() {
+            let responses = match router.handle(state, ctx, &exchange.request) {
+                Ok(future) => future.await,
+                Err(err) => {
+                    mismatches.push(ReplayMismatch {
+                        index,
+                        request: exchange.request.header,
+                        reason: MismatchReason::Handle(err),
+                    });
+                    continue;
+                }
+            };
+
+            let mut actual = Vec::with_capacity(1 + responses.notify.len());
+            actual.push(responses.response);
+            actual.extend(responses.notify);
+
+            if let Some(reason) = compare_packets(&exchange.expected, actual) {
+                mismatches.push(ReplayMismatch {
+                    index,
+                    request: exchange.request.header,
+                    reason,
+                });
+            }
+        }
+
+        ReplayReport {
+            exchanges: self.exchanges.len(),
+            mismatches,
+        }
+    }
+}
+
+/// Compares the expected packets against the actual packets
+/// returning the reason for the first difference found
+///
+/// `expected` The expected packets
+/// `actual`   The actual packets
+fn compare_packets(expected: &[Packet], actual: Vec<Packet>) -> Option<MismatchReason> {
+    if expected.len() != actual.len() {
+        return Some(MismatchReason::Count {
+            expected: expected.len(),
+            actual: actual.len(),
+        });
+    }
+
+    expected
+        .iter()
+        .zip(actual)
+        .enumerate()
+        .find_map(|(position, (expected, actual))| {
+            if expected.header != actual.header {
+                Some(MismatchReason::Header {
+                    position,
+                    expected: expected.header,
+                    actual: actual.header,
+                })
+            } else if !contents_match(expected, &actual) {
+                Some(MismatchReason::Contents {
+                    position,
+                    expected: expected.clone(),
+                    actual,
+                })
+            } else {
+                None
+            }
+        })
+}
+
+/// Checks whether the contents of the two packets are equal. Contents
+/// that differ byte for byte are still considered equal if they decode
+/// to the same fields regardless of the order of the fields
+///
+/// `a` The first packet
+/// `b` The second packet
+pub fn contents_match(a: &Packet, b: &Packet) -> bool {
+    if a.contents == b.contents {
+        return true;
+    }
+
+    let (Ok(mut a), Ok(mut b)) = (decode_fields(a), decode_fields(b)) else {
+        return false;
+    };
+    a.normalize();
+    b.normalize();
+    a == b
+}
+
+/// Decodes the contents of the provided packet as dynamic fields
+///
+/// `packet` The packet to decode
+fn decode_fields(packet: &Packet) -> DecodeResult<TdfFields> {
+    TdfFields::decode(&mut TdfReader::new(&packet.contents))
+}
+
+/// Result of running a replay
+#[derive(Debug)]
+pub struct ReplayReport {
+    /// The number of exchanges that were replayed
+    pub exchanges: usize,
+    /// The exchanges that didn't produce the expected packets
+    pub mismatches: Vec<ReplayMismatch>,
+}
+
+impl ReplayReport {
+    /// Checks whether every exchange produced the expected packets
+    pub fn is_success(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Asserts that every exchange produced the expected packets
+    /// panicking with the details of each mismatch otherwise
+    #[track_caller]
+    pub fn assert_success(&self) {
+        if !self.is_success() {
+            panic!("{}", self);
+        }
+    }
+}
+
+impl Display for ReplayReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} of {} exchanges mismatched",
+            self.mismatches.len(),
+            self.exchanges
+        )?;
+        for mismatch in &self.mismatches {
+            write!(f, "\n{}", mismatch)?;
+        }
+        Ok(())
+    }
+}
+
+/// Exchange that didn't produce the expected packets
+#[derive(Debug)]
+pub struct ReplayMismatch {
+    /// The index of the exchange within the replay
+    pub index: usize,
+    /// The header of the request packet
+    pub request: PacketHeader,
+    /// The reason the exchange didn't match
+    pub reason: MismatchReason,
+}
+
+impl Display for ReplayMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Exchange {} (component: {:#06x}, command: {:#06x}, id: {}): {}",
+            self.index, self.request.component, self.request.command, self.request.id, self.reason
+        )
+    }
+}
+
+/// Reason an exchange didn't match the expected packets
+#[derive(Debug)]
+pub enum MismatchReason {
+    /// The router failed to handle the request
+    Handle(HandleError),
+    /// A different number of packets were produced
+    Count {
+        /// The number of expected packets
+        expected: usize,
+        /// The number of packets produced
+        actual: usize,
+    },
+    /// The header of a produced packet differed
+    Header {
+        /// The position of the packet within the exchange
+        position: usize,
+        /// The expected header
+        expected: PacketHeader,
+        /// The header produced
+        actual: PacketHeader,
+    },
+    /// The contents of a produced packet differed
+    Contents {
+        /// The position of the packet within the exchange
+        position: usize,
+        /// The expected packet
+        expected: Packet,
+        /// The packet produced
+        actual: Packet,
+    },
+}
+
+impl Display for MismatchReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MismatchReason::Handle(err) => write!(f, "Failed to handle request: {}", err),
+            MismatchReason::Count { expected, actual } => {
+                write!(f, "Expected {} packets but got {}", expected, actual)
+            }
+            MismatchReason::Header {
+                position,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Header of packet {} differs (expected: {:?}, actual: {:?})",
+                position, expected, actual
+            ),
+            MismatchReason::Contents {
+                position,
+                expected,
+                actual,
+            } => {
+                let formatter = default_formatter();
+                let names = lookup_tag_names(expected.header.component, expected.header.command);
+                let names = names.as_deref();
+                write!(
+                    f,
+                    "Contents of packet {} differ\nExpected:\n{}Actual:\n{}",
+                    position,
+                    formatter.format_contents(&expected.contents, names),
+                    formatter.format_contents(&actual.contents, names)
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::contents_match;
+    use crate::{
+        packet::{Packet, PacketHeader},
+        writer::TdfWriter,
+    };
+
+    /// Tests contents with fields in a different order still match
+    #[test]
+    fn test_contents_match() {
+        let packet =
+            |writer: &TdfWriter| Packet::raw(PacketHeader::notify(1, 1), writer.buffer.clone());
+
+        let mut a = TdfWriter::default();
+        a.tag_u32(b"A", 1);
+        a.group(b"GRP", |writer| {
+            writer.tag_str(b"B", "Test");
+            writer.tag_u32(b"C", 2);
+        });
+
+        let mut b = TdfWriter::default();
+        b.group(b"GRP", |writer| {
+            writer.tag_u32(b"C", 2);
+            writer.tag_str(b"B", "Test");
+        });
+        b.tag_u32(b"A", 1);
+
+        let mut c = TdfWriter::default();
+        c.tag_u32(b"A", 2);
+
+        assert!(contents_match(&packet(&a), &packet(&b)));
+        assert!(!contents_match(&packet(&a), &packet(&c)));
+    }
+}
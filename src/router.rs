@@ -3,13 +3,24 @@
 
 use crate::{
     error::{DecodeError, DecodeResult},
-    packet::{FromRequest, IntoResponse, Packet, PacketComponents},
+    marker::{BoxFuture, MaybeSend, MaybeSync},
+    packet::{
+        FromRequest, IntoResponse, NotifyStream, Packet, PacketComponents, PacketHeader,
+        PacketType, Responses,
+    },
+    session::SessionContext,
 };
+use futures_core::Stream;
 use std::{
+    any::Any,
     collections::HashMap,
+    error::Error,
+    fmt::Display,
     future::Future,
     marker::PhantomData,
+    panic::AssertUnwindSafe,
     pin::Pin,
+    sync::Arc,
     task::{ready, Context, Poll},
 };
 
@@ -30,26 +41,28 @@ pub struct FormatB;
 /// Wrapper over the [FromRequest] type to support the unit type
 /// to differentiate
 pub trait FromRequestInternal: Sized + 'static {
-    fn from_request(req: &Packet) -> DecodeResult<Self>;
+    /// Creates the value from the request packet and the context of
+    /// the session if one is available
+    ///
+    /// `req` The request packet
+    /// `ctx` The context of the session the request is from
+    fn from_request(req: &Packet, ctx: Option<&SessionContext>) -> DecodeResult<Self>;
 }
 
 /// Unit type implementation for handlers that don't take a req type
 impl FromRequestInternal for () {
-    fn from_request(_req: &Packet) -> DecodeResult<Self> {
+    fn from_request(_req: &Packet, _ctx: Option<&SessionContext>) -> DecodeResult<Self> {
         Ok(())
     }
 }
 
 /// Implementation for normal [FromRequest] implementations
 impl<F: FromRequest> FromRequestInternal for F {
-    fn from_request(req: &Packet) -> DecodeResult<Self> {
-        F::from_request(req)
+    fn from_request(req: &Packet, ctx: Option<&SessionContext>) -> DecodeResult<Self> {
+        F::from_request_context(req, ctx)
     }
 }
 
-/// Pin boxed future type that is Send and lives for 'a
-type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
-
 /// Trait implemented by handlers which can provided a boxed future
 /// to a response type which can be turned into a response
 ///
@@ -57,7 +70,7 @@ type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
 /// `Format` The format of the handler function (FormatA, FormatB)
 /// `Req`    The request value type for the handler
 /// `Res`    The response type for the handler
-pub trait Handler<'a, State, Format, Req, Res>: Send + Sync + 'static {
+pub trait Handler<'a, State, Format, Req, Res>: MaybeSend + MaybeSync + 'static {
     /// Handle function for calling the underlying handle logic using
     /// the proivded state and packet
     ///
@@ -66,9 +79,9 @@ pub trait Handler<'a, State, Format, Req, Res>: Send + Sync + 'static {
     fn handle(&self, state: &'a mut State, req: Req) -> BoxFuture<'a, Res>;
 }
 
-/// Future which results in a response packet being produced that can
+/// Future which results in the response packets being produced that can
 /// only live for the lifetime of 'a which is the state lifetime
-type PacketFuture<'a> = BoxFuture<'a, Packet>;
+type ResponseFuture<'a> = BoxFuture<'a, Responses>;
 
 /// Handler implementation for async functions that take the state as well
 /// as a request type
@@ -84,11 +97,11 @@ type PacketFuture<'a> = BoxFuture<'a, Packet>;
 /// ```
 impl<'a, State, Fun, Fut, Req, Res> Handler<'a, State, FormatA, Req, Res> for Fun
 where
-    Fun: Fn(&'a mut State, Req) -> Fut + Send + Sync + 'static,
-    Fut: Future<Output = Res> + Send + 'a,
+    Fun: Fn(&'a mut State, Req) -> Fut + MaybeSend + MaybeSync + 'static,
+    Fut: Future<Output = Res> + MaybeSend + 'a,
     Req: FromRequest,
     Res: IntoResponse,
-    State: Send + 'static,
+    State: MaybeSend + 'static,
 {
     fn handle(&self, state: &'a mut State, req: Req) -> BoxFuture<'a, Res> {
         Box::pin(self(state, req))
@@ -108,11 +121,11 @@ where
 /// ```
 impl<State, Fun, Fut, Req, Res> Handler<'_, State, FormatB, Req, Res> for Fun
 where
-    Fun: Fn(Req) -> Fut + Send + Sync + 'static,
-    Fut: Future<Output = Res> + Send + 'static,
+    Fun: Fn(Req) -> Fut + MaybeSend + MaybeSync + 'static,
+    Fut: Future<Output = Res> + MaybeSend + 'static,
     Req: FromRequest,
     Res: IntoResponse,
-    State: Send + 'static,
+    State: MaybeSend + 'static,
 {
     fn handle(&self, _state: &mut State, req: Req) -> BoxFuture<'static, Res> {
         Box::pin(self(req))
@@ -132,10 +145,10 @@ where
 /// ```
 impl<'a, State, Fun, Fut, Res> Handler<'a, State, FormatA, (), Res> for Fun
 where
-    Fun: Fn(&'a mut State) -> Fut + Send + Sync + 'static,
-    Fut: Future<Output = Res> + Send + 'a,
+    Fun: Fn(&'a mut State) -> Fut + MaybeSend + MaybeSync + 'static,
+    Fut: Future<Output = Res> + MaybeSend + 'a,
     Res: IntoResponse,
-    State: Send + 'static,
+    State: MaybeSend + 'static,
 {
     fn handle(&self, state: &'a mut State, _: ()) -> BoxFuture<'a, Res> {
         Box::pin(self(state))
@@ -153,10 +166,10 @@ where
 /// ```
 impl<State, Fun, Fut, Res> Handler<'_, State, FormatB, (), Res> for Fun
 where
-    Fun: Fn() -> Fut + Send + Sync + 'static,
-    Fut: Future<Output = Res> + Send + 'static,
+    Fun: Fn() -> Fut + MaybeSend + MaybeSync + 'static,
+    Fut: Future<Output = Res> + MaybeSend + 'static,
     Res: IntoResponse,
-    State: Send + 'static,
+    State: MaybeSend + 'static,
 {
     fn handle(&self, _state: &mut State, _: ()) -> BoxFuture<'static, Res> {
         Box::pin(self())
@@ -165,43 +178,60 @@ where
 
 /// Future wrapper that wraps a future from a handler in order
 /// to poll the underlying future and then transform the future
-/// result into the response packet
+/// result into the response packets
 ///
 /// 'a:   The lifetime of the session
 /// `Res` The response type for the handler
 struct HandlerFuture<'a, Res> {
     /// The future from the hanlder
     fut: BoxFuture<'a, Res>,
-    /// The packet the handler is responding to
-    packet: Packet,
+    /// The header of the packet the handler is responding to
+    header: PacketHeader,
+    /// The time the handler started handling the packet. Not tracked on
+    /// wasm32-unknown-unknown where [`std::time::Instant`] is unsupported
+    #[cfg(all(
+        feature = "tracing",
+        not(all(target_arch = "wasm32", target_os = "unknown"))
+    ))]
+    start: std::time::Instant,
 }
 
 impl<'a, Res> Future for HandlerFuture<'a, Res>
 where
     Res: IntoResponse,
 {
-    type Output = Packet;
+    type Output = Responses;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
         // Poll the underlying future
         let fut = Pin::new(&mut this.fut);
         let res = ready!(fut.poll(cx));
+        #[cfg(all(
+            feature = "tracing",
+            not(all(target_arch = "wasm32", target_os = "unknown"))
+        ))]
+        tracing::Span::current().record("handle_time", tracing::field::debug(this.start.elapsed()));
         // Transform the result
-        let packet = res.into_response(&this.packet);
-        Poll::Ready(packet)
+        let responses = res.into_responses(&this.header);
+        Poll::Ready(responses)
     }
 }
 
 /// Trait for erasing the inner types of the handler routes
-trait Route<S>: Send + Sync {
+trait Route<S>: MaybeSend + MaybeSync {
     /// Handle function for calling the handler logic on the actual implementation
     /// producing a future that lives as long as the state
     ///
     /// `state`  The state provided
+    /// `ctx`    The context of the session
     /// `packet` The packet to handle with the route
-    fn handle<'s>(&self, state: &'s mut S, packet: Packet)
-        -> Result<PacketFuture<'s>, HandleError>;
+    fn handle<'s>(
+        &self,
+        state: &'s mut S,
+        ctx: &SessionContext,
+        packet: &Packet,
+    ) -> Result<ResponseFuture<'s>, HandleError>;
 }
 
 /// Route wrapper over a handler for storing the phantom type data
@@ -220,33 +250,547 @@ where
     Req: FromRequestInternal,
     Res: IntoResponse,
     Format: 'static,
-    State: Send + 'static,
+    State: MaybeSend + 'static,
 {
     fn handle<'s>(
         &self,
         state: &'s mut State,
-        packet: Packet,
-    ) -> Result<PacketFuture<'s>, HandleError> {
-        let req = match Req::from_request(&packet) {
-            Ok(value) => value,
-            Err(err) => return Err(HandleError::Decoding(err)),
-        };
+        ctx: &SessionContext,
+        packet: &Packet,
+    ) -> Result<ResponseFuture<'s>, HandleError> {
+        let req = decode_request(packet, Some(ctx))?;
         let fut = self.handler.handle(state, req);
-        Ok(Box::pin(HandlerFuture { fut, packet }))
+        Ok(Box::pin(HandlerFuture {
+            fut,
+            header: packet.header,
+            #[cfg(all(
+                feature = "tracing",
+                not(all(target_arch = "wasm32", target_os = "unknown"))
+            ))]
+            start: std::time::Instant::now(),
+        }))
+    }
+}
+
+/// Future which completes once a notification handler has finished
+/// handling a notify packet. Notify packets don't produce a response
+type NotifyFuture<'a> = BoxFuture<'a, ()>;
+
+/// Trait for erasing the inner types of notification handler routes
+trait NotifyRoute<S>: MaybeSend + MaybeSync {
+    /// Handle function for calling the notification handler logic on the
+    /// actual implementation producing a future that lives as long as the state
+    ///
+    /// `state`  The state provided
+    /// `ctx`    The context of the session
+    /// `packet` The notify packet to handle with the route
+    fn handle<'s>(
+        &self,
+        state: &'s mut S,
+        ctx: &SessionContext,
+        packet: &Packet,
+    ) -> Result<NotifyFuture<'s>, HandleError>;
+}
+
+/// Notify route implementation for handlers wrapped by handler routes
+/// that don't produce a response
+impl<H, State, Format, Req> NotifyRoute<State> for HandlerRoute<H, Format, Req, ()>
+where
+    for<'a> H: Handler<'a, State, Format, Req, ()>,
+    Req: FromRequestInternal,
+    Format: 'static,
+    State: MaybeSend + 'static,
+{
+    fn handle<'s>(
+        &self,
+        state: &'s mut State,
+        ctx: &SessionContext,
+        packet: &Packet,
+    ) -> Result<NotifyFuture<'s>, HandleError> {
+        let req = decode_request(packet, Some(ctx))?;
+        Ok(self.handler.handle(state, req))
+    }
+}
+
+/// Trait implemented by handlers which can be run concurrently. Rather than
+/// borrowing the state mutably these handlers are given their own clone of
+/// the state (i.e. an `Arc<RwLock<T>>`) so the futures they produce don't
+/// borrow anything and multiple requests can be handled at once
+pub trait ConcurrentHandler<State, Format, Req, Res>: MaybeSend + MaybeSync + 'static {
+    /// Handle function for calling the underlying handle logic using
+    /// the proivded state and request
+    ///
+    /// `state` The cloned state
+    /// `req`   The request extracted from the packet
+    fn handle(&self, state: State, req: Req) -> BoxFuture<'static, Res>;
+}
+
+/// Concurrent handler implementation for async functions that take the state
+/// as well as a request type
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::RwLock;
+///
+/// struct Data;
+/// struct Req;
+/// struct Res;
+///
+/// async fn test(state: Arc<RwLock<Data>>, req: Req) -> Res {
+///     Res {}
+/// }
+/// ```
+impl<State, Fun, Fut, Req, Res> ConcurrentHandler<State, FormatA, Req, Res> for Fun
+where
+    Fun: Fn(State, Req) -> Fut + MaybeSend + MaybeSync + 'static,
+    Fut: Future<Output = Res> + MaybeSend + 'static,
+    Req: FromRequest,
+    Res: IntoResponse,
+    State: MaybeSend + 'static,
+{
+    fn handle(&self, state: State, req: Req) -> BoxFuture<'static, Res> {
+        Box::pin(self(state, req))
+    }
+}
+
+/// Concurrent handler implementation for async functions that take the
+/// request type without the state
+///
+/// ```
+/// struct Req;
+/// struct Res;
+///
+/// async fn test(req: Req) -> Res {
+///     Res {}
+/// }
+/// ```
+impl<State, Fun, Fut, Req, Res> ConcurrentHandler<State, FormatB, Req, Res> for Fun
+where
+    Fun: Fn(Req) -> Fut + MaybeSend + MaybeSync + 'static,
+    Fut: Future<Output = Res> + MaybeSend + 'static,
+    Req: FromRequest,
+    Res: IntoResponse,
+    State: MaybeSend + 'static,
+{
+    fn handle(&self, _state: State, req: Req) -> BoxFuture<'static, Res> {
+        Box::pin(self(req))
+    }
+}
+
+/// Concurrent handler implementation for async functions that take the
+/// state with no request type
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::sync::RwLock;
+///
+/// struct Data;
+/// struct Res;
+///
+/// async fn test(state: Arc<RwLock<Data>>) -> Res {
+///     Res {}
+/// }
+/// ```
+impl<State, Fun, Fut, Res> ConcurrentHandler<State, FormatA, (), Res> for Fun
+where
+    Fun: Fn(State) -> Fut + MaybeSend + MaybeSync + 'static,
+    Fut: Future<Output = Res> + MaybeSend + 'static,
+    Res: IntoResponse,
+    State: MaybeSend + 'static,
+{
+    fn handle(&self, state: State, _: ()) -> BoxFuture<'static, Res> {
+        Box::pin(self(state))
+    }
+}
+
+/// Trait for erasing the inner types of concurrent handler routes
+trait ConcurrentRoute<S>: MaybeSend + MaybeSync {
+    /// Handle function for calling the handler logic on the actual
+    /// implementation producing a future that owns its own state
+    ///
+    /// `state`  The state provided
+    /// `ctx`    The context of the session
+    /// `packet` The packet to handle with the route
+    fn handle(
+        &self,
+        state: S,
+        ctx: &SessionContext,
+        packet: &Packet,
+    ) -> Result<ResponseFuture<'static>, HandleError>;
+}
+
+/// Concurrent route implementation for handlers wrapped by handler routes
+impl<H, State, Format, Req, Res> ConcurrentRoute<State> for HandlerRoute<H, Format, Req, Res>
+where
+    H: ConcurrentHandler<State, Format, Req, Res>,
+    Req: FromRequestInternal,
+    Res: IntoResponse,
+    Format: 'static,
+    State: MaybeSend + 'static,
+{
+    fn handle(
+        &self,
+        state: State,
+        ctx: &SessionContext,
+        packet: &Packet,
+    ) -> Result<ResponseFuture<'static>, HandleError> {
+        let req = decode_request(packet, Some(ctx))?;
+        let fut = self.handler.handle(state, req);
+        Ok(Box::pin(HandlerFuture {
+            fut,
+            header: packet.header,
+            #[cfg(all(
+                feature = "tracing",
+                not(all(target_arch = "wasm32", target_os = "unknown"))
+            ))]
+            start: std::time::Instant::now(),
+        }))
+    }
+}
+
+/// Decodes the request type for a route from the provided packet
+/// recording the time taken when tracing is enabled
+///
+/// `packet` The packet to decode the request from
+/// `ctx`    The context of the session if available
+fn decode_request<Req: FromRequestInternal>(
+    packet: &Packet,
+    ctx: Option<&SessionContext>,
+) -> Result<Req, HandleError> {
+    #[cfg(all(
+        feature = "tracing",
+        not(all(target_arch = "wasm32", target_os = "unknown"))
+    ))]
+    let start = std::time::Instant::now();
+    let req = Req::from_request(packet, ctx).map_err(|error| HandleError::Decoding {
+        header: packet.header,
+        error,
+    })?;
+    #[cfg(all(
+        feature = "tracing",
+        not(all(target_arch = "wasm32", target_os = "unknown"))
+    ))]
+    tracing::Span::current().record("decode_time", tracing::field::debug(start.elapsed()));
+    Ok(req)
+}
+
+/// Guard which is checked before a route is decoded and handled. If the
+/// guard rejects the packet the route is skipped and an error response
+/// with the guard error code is produced instead
+///
+/// ```
+/// use blaze_pk::router::Guard;
+///
+/// struct State {
+///     authenticated: bool,
+/// }
+///
+/// let guard: Guard<State> = Guard::new(0x4001, |state: &State, _packet| state.authenticated);
+/// ```
+pub struct Guard<S> {
+    /// The error code to respond with when the guard rejects
+    error: u16,
+    /// The predicate deciding whether the packet is allowed
+    check: Box<GuardFn<S>>,
+}
+
+/// Type of the predicate function used by guards
+#[cfg(not(feature = "unsend"))]
+type GuardFn<S> = dyn Fn(&S, &Packet) -> bool + Send + Sync;
+
+/// Type of the predicate function used by guards
+#[cfg(feature = "unsend")]
+type GuardFn<S> = dyn Fn(&S, &Packet) -> bool;
+
+impl<S> Guard<S> {
+    /// Creates a new guard from the provided predicate function
+    ///
+    /// `error` The error code to respond with on rejection
+    /// `check` The predicate function returning whether the packet is allowed
+    pub fn new<F>(error: u16, check: F) -> Self
+    where
+        F: Fn(&S, &Packet) -> bool + MaybeSend + MaybeSync + 'static,
+    {
+        Self {
+            error,
+            check: Box::new(check),
+        }
+    }
+
+    /// Checks the guard against the provided state and packet returning
+    /// the error response packet if the guard rejected the packet
+    ///
+    /// `state`  The current state
+    /// `packet` The packet to check
+    fn check(&self, state: &S, packet: &Packet) -> Result<(), Packet> {
+        if (self.check)(state, packet) {
+            Ok(())
+        } else {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(
+                component = packet.header.component,
+                command = packet.header.command,
+                id = packet.header.id,
+                error = %crate::error_code::ErrorDisplay(self.error),
+                "Guard rejected packet"
+            );
+            Err(packet.respond_error_empty(self.error))
+        }
+    }
+}
+
+/// Route wrapper which checks a guard before calling the
+/// underlying route
+struct GuardedRoute<S> {
+    /// The guard to check
+    guard: Guard<S>,
+    /// The underlying route
+    route: Arc<dyn Route<S>>,
+}
+
+impl<S> Route<S> for GuardedRoute<S>
+where
+    S: MaybeSend + 'static,
+{
+    fn handle<'s>(
+        &self,
+        state: &'s mut S,
+        ctx: &SessionContext,
+        packet: &Packet,
+    ) -> Result<ResponseFuture<'s>, HandleError> {
+        if let Err(response) = self.guard.check(state, packet) {
+            return Ok(Box::pin(std::future::ready(Responses::new(response))));
+        }
+        self.route.handle(state, ctx, packet)
+    }
+}
+
+/// Type of the hook functions called with packets
+#[cfg(not(feature = "unsend"))]
+type PacketHook = dyn Fn(&Packet) + Send + Sync;
+
+/// Type of the hook functions called with packets
+#[cfg(feature = "unsend")]
+type PacketHook = dyn Fn(&Packet);
+
+/// Type of the hook functions called with handling errors
+#[cfg(not(feature = "unsend"))]
+type ErrorHook = dyn Fn(&HandleError) + Send + Sync;
+
+/// Type of the hook functions called with handling errors
+#[cfg(feature = "unsend")]
+type ErrorHook = dyn Fn(&HandleError);
+
+/// Collection of lifecycle hooks called by the router for
+/// every packet that it handles
+#[derive(Default, Clone)]
+struct Hooks {
+    /// Hooks called with every packet before it is routed
+    on_request: Vec<Arc<PacketHook>>,
+    /// Hooks called with every packet produced by routes
+    on_response: Vec<Arc<PacketHook>>,
+    /// Hooks called with every error produced while routing
+    on_error: Vec<Arc<ErrorHook>>,
+}
+
+impl Hooks {
+    /// Calls the request hooks with the provided packet then calls the
+    /// provided handle function calling the error hooks if it fails
+    ///
+    /// `packet` The packet being handled
+    /// `handle` The function handling the packet
+    fn run<T>(
+        &self,
+        packet: &Packet,
+        handle: impl FnOnce() -> Result<T, HandleError>,
+    ) -> Result<T, HandleError> {
+        self.on_request.iter().for_each(|hook| hook(packet));
+        let result = handle();
+        if let Err(err) = &result {
+            self.on_error.iter().for_each(|hook| hook(err));
+        }
+        result
+    }
+
+    /// Wraps the provided response future so that the response
+    /// hooks are called with the packets it produces
+    ///
+    /// `fut` The response future to wrap
+    fn responses<'a>(&self, fut: ResponseFuture<'a>) -> ResponseFuture<'a> {
+        if self.on_response.is_empty() {
+            return fut;
+        }
+        let hooks = self.on_response.clone();
+        Box::pin(async move {
+            let mut responses = fut.await;
+            for hook in &hooks {
+                hook(&responses.response);
+                responses.notify.iter().for_each(|packet| hook(packet));
+            }
+            if let Some(stream) = responses.stream.take() {
+                responses.stream = Some(Box::pin(HookedStream { stream, hooks }));
+            }
+            responses
+        })
+    }
+}
+
+/// Stream wrapper which calls the response hooks with each
+/// packet produced by a notification stream
+struct HookedStream {
+    /// The underlying notification stream
+    stream: NotifyStream,
+    /// The response hooks to call
+    hooks: Vec<Arc<PacketHook>>,
+}
+
+impl Stream for HookedStream {
+    type Item = Packet;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let packet = ready!(this.stream.as_mut().poll_next(cx));
+        if let Some(packet) = &packet {
+            this.hooks.iter().for_each(|hook| hook(packet));
+        }
+        Poll::Ready(packet)
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.stream.size_hint()
+    }
+}
+
+/// Future wrapper which catches panics from the underlying response
+/// future converting them into error responses
+struct CatchPanic<'a> {
+    /// The underlying response future
+    fut: ResponseFuture<'a>,
+    /// The header of the packet being responded to
+    header: PacketHeader,
+    /// The error code to respond with if the future panics
+    error: u16,
+    /// The error hooks to report panics to
+    hooks: Vec<Arc<ErrorHook>>,
 }
 
+impl Future for CatchPanic<'_> {
+    type Output = Responses;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let fut = &mut this.fut;
+        match std::panic::catch_unwind(AssertUnwindSafe(|| fut.as_mut().poll(cx))) {
+            Ok(poll) => poll,
+            Err(payload) => Poll::Ready(Responses::new(panic_response(
+                &this.header,
+                this.error,
+                &this.hooks,
+                payload,
+            ))),
+        }
+    }
+}
+
+/// Creates the error response for a handler that panicked reporting
+/// the panic to the error hooks and logging the panic message when
+/// tracing is enabled
+///
+/// `header`  The header of the packet being responded to
+/// `error`   The error code to respond with
+/// `hooks`   The error hooks to report the panic to
+/// `payload` The payload of the panic
+fn panic_response(
+    header: &PacketHeader,
+    error: u16,
+    hooks: &[Arc<ErrorHook>],
+    payload: Box<dyn Any + Send>,
+) -> Packet {
+    let message = if let Some(message) = payload.downcast_ref::<&'static str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.as_str()
+    } else {
+        "Unknown panic"
+    };
+    #[cfg(feature = "tracing")]
+    tracing::error!(
+        component = header.component,
+        command = header.command,
+        id = header.id,
+        error = %crate::error_code::ErrorDisplay(error),
+        message,
+        "Handler panicked"
+    );
+    if !hooks.is_empty() {
+        let err = HandleError::Panic {
+            header: *header,
+            message: message.to_string(),
+        };
+        hooks.iter().for_each(|hook| hook(&err));
+    }
+    Packet::raw_empty(header.with_error(error))
+}
+
+/// Key used for looking up routes consisting of the raw
+/// component and command values from the packet header
+type RouteKey = (u16, u16);
+
 /// Route implementation for storing components mapped to route
-/// handlers
+/// handlers. Routes are stored behind [Arc]s so the router can be
+/// cheaply cloned and shared across session tasks.
+///
+/// Routes are keyed by the raw component and command values so that
+/// packets don't need to be converted into the component type `C`
+/// in order to be routed
 pub struct Router<C, S> {
-    /// The map of components to routes
-    routes: HashMap<C, Box<dyn Route<S>>>,
+    /// The map of component keys to routes
+    routes: HashMap<RouteKey, Arc<dyn Route<S>>>,
+    /// The map of component keys to notification routes
+    notify_routes: HashMap<RouteKey, Arc<dyn NotifyRoute<S>>>,
+    /// The map of component keys to concurrent routes
+    concurrent_routes: HashMap<RouteKey, Arc<dyn ConcurrentRoute<S>>>,
+    /// The map of component values to guards applied to every
+    /// route within that component
+    guards: HashMap<u16, Arc<Guard<S>>>,
+    /// The lifecycle hooks called while handling packets
+    hooks: Hooks,
+    /// Whether to check that responses match their requests
+    validate_responses: bool,
+    /// Error code used for responses to handlers that panic when
+    /// panics are being caught
+    panic_error: Option<u16>,
+    /// Marker for storing the component type
+    _marker: PhantomData<fn() -> C>,
 }
 
 impl<C, S> Default for Router<C, S> {
     fn default() -> Self {
         Self {
             routes: Default::default(),
+            notify_routes: Default::default(),
+            concurrent_routes: Default::default(),
+            guards: Default::default(),
+            hooks: Default::default(),
+            validate_responses: false,
+            panic_error: None,
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Cloning the router only clones the references to the routes
+impl<C, S> Clone for Router<C, S> {
+    fn clone(&self) -> Self {
+        Self {
+            routes: self.routes.clone(),
+            notify_routes: self.notify_routes.clone(),
+            concurrent_routes: self.concurrent_routes.clone(),
+            guards: self.guards.clone(),
+            hooks: self.hooks.clone(),
+            validate_responses: self.validate_responses,
+            panic_error: self.panic_error,
+            _marker: PhantomData,
         }
     }
 }
@@ -254,7 +798,7 @@ impl<C, S> Default for Router<C, S> {
 impl<C, S> Router<C, S>
 where
     C: PacketComponents,
-    S: Send + 'static,
+    S: MaybeSend + 'static,
 {
     /// Creates a new router
     pub fn new() -> Self {
@@ -263,7 +807,10 @@ where
 
     /// Adds a new route to the router where the route is something that implements
     /// the handler type with any lifetime. The value is wrapped with a HandlerRoute
-    /// and stored boxed in the routes map under the component key
+    /// and stored boxed in the routes map under the component key.
+    ///
+    /// Adding a route for a component that already has a route is a mistake
+    /// which fails a debug assertion rather than silently replacing the route
     ///
     /// `component` The component key for the route
     /// `route`     The actual route handler function
@@ -276,45 +823,1040 @@ where
         Res: IntoResponse,
         Format: 'static,
     {
-        self.routes.insert(
-            component,
-            Box::new(HandlerRoute {
+        let (component, command) = component.values();
+        self.route_raw(component, command, route);
+    }
+
+    /// Adds a new route to the router the same as [Router::route] but using
+    /// the raw component and command values. This allows routes to be added
+    /// for components that aren't represented by the component type
+    ///
+    /// `component` The component value for the route
+    /// `command`   The command value for the route
+    /// `route`     The actual route handler function
+    pub fn route_raw<Format, Req, Res>(
+        &mut self,
+        component: u16,
+        command: u16,
+        route: impl for<'a> Handler<'a, S, Format, Req, Res>,
+    ) where
+        Req: FromRequestInternal,
+        Res: IntoResponse,
+        Format: 'static,
+    {
+        insert_route(
+            &mut self.routes,
+            (component, command),
+            Arc::new(HandlerRoute {
+                handler: route,
+                _marker: PhantomData,
+            }),
+        );
+    }
+
+    /// Adds a new route to the router the same as [Router::route] but the
+    /// provided guard is checked before the route is decoded and handled
+    ///
+    /// `component` The component key for the route
+    /// `guard`     The guard to check before the route
+    /// `route`     The actual route handler function
+    pub fn route_guarded<Format, Req, Res>(
+        &mut self,
+        component: C,
+        guard: Guard<S>,
+        route: impl for<'a> Handler<'a, S, Format, Req, Res>,
+    ) where
+        Req: FromRequestInternal,
+        Res: IntoResponse,
+        Format: 'static,
+    {
+        let route = Arc::new(HandlerRoute {
+            handler: route,
+            _marker: PhantomData,
+        });
+        insert_route(
+            &mut self.routes,
+            component.values(),
+            Arc::new(GuardedRoute { guard, route }),
+        );
+    }
+
+    /// Adds a guard that is checked before handling any packet for the
+    /// provided component value (All the commands within the component)
+    ///
+    /// `component` The component value to guard
+    /// `guard`     The guard to check
+    pub fn guard(&mut self, component: u16, guard: Guard<S>) {
+        self.guards.insert(component, Arc::new(guard));
+    }
+
+    /// Adds a new notification route to the router. Notification routes handle
+    /// notify packets which have no packet ID and don't expect a response so the
+    /// handler must not produce one
+    ///
+    /// `component` The notify component key for the route
+    /// `route`     The actual notification handler function
+    pub fn notify<Format, Req>(
+        &mut self,
+        component: C,
+        route: impl for<'a> Handler<'a, S, Format, Req, ()>,
+    ) where
+        Req: FromRequestInternal,
+        Format: 'static,
+    {
+        let (component, command) = component.values();
+        self.notify_raw(component, command, route);
+    }
+
+    /// Adds a new notification route to the router the same as [Router::notify]
+    /// but using the raw component and command values
+    ///
+    /// `component` The component value for the route
+    /// `command`   The command value for the route
+    /// `route`     The actual notification handler function
+    pub fn notify_raw<Format, Req>(
+        &mut self,
+        component: u16,
+        command: u16,
+        route: impl for<'a> Handler<'a, S, Format, Req, ()>,
+    ) where
+        Req: FromRequestInternal,
+        Format: 'static,
+    {
+        insert_route(
+            &mut self.notify_routes,
+            (component, command),
+            Arc::new(HandlerRoute {
+                handler: route,
+                _marker: PhantomData,
+            }),
+        );
+    }
+
+    /// Adds a new concurrent route to the router. Concurrent routes are given
+    /// a clone of the state rather than a mutable borrow so that the state
+    /// should use interior mutability (i.e. `Arc<RwLock<T>>`). This allows
+    /// independent requests to be handled at the same time using
+    /// [Router::handle_concurrent] when their ordering doesn't matter
+    ///
+    /// ```
+    /// use blaze_pk::{router::Router, PacketComponent, PacketComponents};
+    /// use std::sync::{Arc, RwLock};
+    ///
+    /// #[derive(Debug, Hash, PartialEq, Eq, PacketComponents)]
+    /// enum Components {
+    ///     #[component(target = 0x1)]
+    ///     Counter(Counter),
+    /// }
+    ///
+    /// #[derive(Debug, Hash, PartialEq, Eq, PacketComponent)]
+    /// enum Counter {
+    ///     #[command(target = 0x1)]
+    ///     Increment,
+    /// }
+    ///
+    /// #[derive(Default)]
+    /// struct Data {
+    ///     count: u32,
+    /// }
+    ///
+    /// type State = Arc<RwLock<Data>>;
+    ///
+    /// async fn increment(state: State) {
+    ///     state.write().unwrap().count += 1;
+    /// }
+    ///
+    /// let mut router: Router<Components, State> = Router::new();
+    /// router.route_concurrent(Components::Counter(Counter::Increment), increment);
+    /// ```
+    ///
+    /// `component` The component key for the route
+    /// `route`     The actual concurrent route handler function
+    pub fn route_concurrent<Format, Req, Res>(
+        &mut self,
+        component: C,
+        route: impl ConcurrentHandler<S, Format, Req, Res>,
+    ) where
+        Req: FromRequestInternal + 'static,
+        Res: IntoResponse + 'static,
+        Format: 'static,
+    {
+        insert_route(
+            &mut self.concurrent_routes,
+            component.values(),
+            Arc::new(HandlerRoute {
                 handler: route,
                 _marker: PhantomData,
             }),
         );
     }
 
+    /// Adds a hook which is called with every packet handled by the router
+    /// before it is routed
+    ///
+    /// `hook` The hook function
+    pub fn on_request(&mut self, hook: impl Fn(&Packet) + MaybeSend + MaybeSync + 'static) {
+        self.hooks.on_request.push(Arc::new(hook));
+    }
+
+    /// Adds a hook which is called with every packet produced by the router
+    /// (Responses, notifications, and error responses from guards or limits)
+    /// once the future handling the packet has completed
+    ///
+    /// `hook` The hook function
+    pub fn on_response(&mut self, hook: impl Fn(&Packet) + MaybeSend + MaybeSync + 'static) {
+        self.hooks.on_response.push(Arc::new(hook));
+    }
+
+    /// Adds a hook which is called with every error that occurs while
+    /// the router is handling a packet
+    ///
+    /// `hook` The hook function
+    pub fn on_error(&mut self, hook: impl Fn(&HandleError) + MaybeSend + MaybeSync + 'static) {
+        self.hooks.on_error.push(Arc::new(hook));
+    }
+
+    /// Sets whether the router should check that the response packets produced by
+    /// handlers have the same component, command and ID as the request they are
+    /// responding to. Catches mistakes such as responding using the wrong header.
+    ///
+    /// This is disabled by default. When enabled mismatched responses are still
+    /// sent but are reported to the error hooks (See [Router::on_error]) as a
+    /// [HandleError::InvalidResponse]. Without any error hooks mismatches are
+    /// logged as warnings when the `tracing` feature is enabled and otherwise
+    /// fail a debug assertion
+    ///
+    /// `enabled` Whether response validation is enabled
+    pub fn validate_responses(&mut self, enabled: bool) {
+        self.validate_responses = enabled;
+    }
+
+    /// Enables catching panics that occur within handlers and their futures. Rather
+    /// than the panic unwinding through the session task the panic is reported to
+    /// the error hooks as a [HandleError::Panic] (and logged when tracing is enabled)
+    /// and an error response with the provided error code is produced instead.
+    ///
+    /// Only applies to routes that produce responses (Not notification routes)
+    /// and has no effect when the crate is built with `panic = "abort"`
+    ///
+    /// `error` The error code to respond with when a handler panics
+    pub fn catch_panics_with(&mut self, error: u16) {
+        self.panic_error = Some(error);
+    }
+
+    /// Calls the provided route function catching any panics from the route and
+    /// from the future it produces if panic catching is enabled
+    ///
+    /// `packet` The packet being handled
+    /// `route`  The function calling the route
+    fn catch_panics<'a>(
+        &self,
+        packet: &Packet,
+        route: impl FnOnce() -> Result<ResponseFuture<'a>, HandleError>,
+    ) -> Result<ResponseFuture<'a>, HandleError> {
+        let error = match self.panic_error {
+            Some(value) => value,
+            None => return route(),
+        };
+        let header = packet.header;
+        let hooks = self.hooks.on_error.clone();
+        match std::panic::catch_unwind(AssertUnwindSafe(route)) {
+            Ok(Ok(fut)) => Ok(Box::pin(CatchPanic {
+                fut,
+                header,
+                error,
+                hooks,
+            })),
+            Ok(Err(err)) => Err(err),
+            Err(payload) => {
+                let response = panic_response(&header, error, &hooks, payload);
+                Ok(Box::pin(std::future::ready(Responses::new(response))))
+            }
+        }
+    }
+
+    /// Wraps the provided response future for the request with the
+    /// response validation (if enabled) and the response hooks
+    ///
+    /// `req` The header of the request packet
+    /// `fut` The response future
+    fn responses<'a>(&self, req: &PacketHeader, fut: ResponseFuture<'a>) -> ResponseFuture<'a> {
+        let fut = if self.validate_responses {
+            let req = *req;
+            let hooks = self.hooks.on_error.clone();
+            Box::pin(async move {
+                let responses = fut.await;
+                if let Err(err) = validate_response(&req, &responses.response.header) {
+                    report_invalid_response(&hooks, &err);
+                }
+                responses
+            })
+        } else {
+            fut
+        };
+        self.hooks.responses(fut)
+    }
+
     /// Handle function takes the provided packet retrieves the component from its header
     /// and finds the matching route (Returning an empty response immediately if none match)
-    /// and providing the state the route along with the packet awaiting the route future
+    /// and providing the state the route along with the packet awaiting the route future.
+    /// The packet is only borrowed, the future only keeps a copy of its header
     ///
     /// `state`  The provided state
+    /// `ctx`    The context of the session the packet is from
     /// `packet` The packet to handle
     pub fn handle<'a>(
         &self,
         state: &'a mut S,
-        packet: Packet,
-    ) -> Result<PacketFuture<'a>, HandleError> {
+        ctx: &SessionContext,
+        packet: &Packet,
+    ) -> Result<ResponseFuture<'a>, HandleError> {
+        self.hooks
+            .run(packet, || {
+                self.catch_panics(packet, || self.handle_route(state, ctx, packet))
+            })
+            .map(|fut| self.responses(&packet.header, fut))
+    }
+
+    /// Handles the provided packet the same as [Router::handle] but checks the packet
+    /// against the provided session rate limiter first. If the rate limit has been
+    /// exceeded the route is not called and the future instead produces the error
+    /// response from the limiter. Unavailable on wasm32-unknown-unknown where the
+    /// limiter can't read the current time
+    ///
+    /// `state`   The provided state
+    /// `ctx`     The context of the session the packet is from
+    /// `limiter` The rate limiter for the session
+    /// `packet`  The packet to handle
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn handle_limited<'a>(
+        &self,
+        state: &'a mut S,
+        ctx: &SessionContext,
+        limiter: &mut crate::limit::RateLimiter,
+        packet: &Packet,
+    ) -> Result<ResponseFuture<'a>, HandleError> {
+        self.hooks
+            .run(packet, || {
+                if let Err(response) = limiter.check(packet) {
+                    return Ok(Box::pin(std::future::ready(Responses::new(response))) as _);
+                }
+                self.catch_panics(packet, || self.handle_route(state, ctx, packet))
+            })
+            .map(|fut| self.responses(&packet.header, fut))
+    }
+
+    /// Handles the provided packet using the concurrent route registered for its
+    /// component. The state is cloned into the route so the returned future doesn't
+    /// borrow the state or the router allowing it to be spawned or polled alongside
+    /// the futures for other packets from the same session
+    ///
+    /// `state`  The provided state
+    /// `ctx`    The context of the session the packet is from
+    /// `packet` The packet to handle
+    pub fn handle_concurrent(
+        &self,
+        state: &S,
+        ctx: &SessionContext,
+        packet: &Packet,
+    ) -> Result<ResponseFuture<'static>, HandleError>
+    where
+        S: Clone,
+    {
+        self.hooks
+            .run(packet, || {
+                self.catch_panics(packet, || self.handle_concurrent_route(state, ctx, packet))
+            })
+            .map(|fut| self.responses(&packet.header, fut))
+    }
+
+    /// Handles the provided notify packet using the notification route registered
+    /// for its component. The returned future completes once the notification has
+    /// been handled, no response is produced
+    ///
+    /// `state`  The provided state
+    /// `ctx`    The context of the session the packet is from
+    /// `packet` The notify packet to handle
+    pub fn handle_notify<'a>(
+        &self,
+        state: &'a mut S,
+        ctx: &SessionContext,
+        packet: &Packet,
+    ) -> Result<NotifyFuture<'a>, HandleError> {
+        self.hooks
+            .run(packet, || self.handle_notify_route(state, ctx, packet))
+    }
+
+    /// Finds the route for the provided packet checking any component
+    /// guards and calling the route
+    ///
+    /// `state`  The provided state
+    /// `ctx`    The context of the session the packet is from
+    /// `packet` The packet to handle
+    fn handle_route<'a>(
+        &self,
+        state: &'a mut S,
+        ctx: &SessionContext,
+        packet: &Packet,
+    ) -> Result<ResponseFuture<'a>, HandleError> {
+        let key = (packet.header.component, packet.header.command);
+        let route = match self.routes.get(&key) {
+            Some(value) => value,
+            None => return Err(missing_route::<C>(packet)),
+        };
+
+        if let Some(guard) = self.guards.get(&packet.header.component) {
+            if let Err(response) = guard.check(state, packet) {
+                return Ok(Box::pin(std::future::ready(Responses::new(response))));
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            let span = route_span::<C>(packet);
+            let result = span.in_scope(|| route.handle(state, ctx, packet));
+            match result {
+                Ok(fut) => Ok(Box::pin(tracing::Instrument::instrument(fut, span))),
+                Err(err) => {
+                    span.in_scope(|| tracing::warn!(error = ?err, "Failed to handle packet"));
+                    Err(err)
+                }
+            }
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        route.handle(state, ctx, packet)
+    }
+
+    /// Finds the concurrent route for the provided packet checking any
+    /// component guards and calling the route with a clone of the state
+    ///
+    /// `state`  The provided state
+    /// `ctx`    The context of the session the packet is from
+    /// `packet` The packet to handle
+    fn handle_concurrent_route(
+        &self,
+        state: &S,
+        ctx: &SessionContext,
+        packet: &Packet,
+    ) -> Result<ResponseFuture<'static>, HandleError>
+    where
+        S: Clone,
+    {
+        let key = (packet.header.component, packet.header.command);
+        let route = match self.concurrent_routes.get(&key) {
+            Some(value) => value,
+            None => return Err(missing_route::<C>(packet)),
+        };
+
+        if let Some(guard) = self.guards.get(&packet.header.component) {
+            if let Err(response) = guard.check(state, packet) {
+                return Ok(Box::pin(std::future::ready(Responses::new(response))));
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            let span = route_span::<C>(packet);
+            let result = span.in_scope(|| route.handle(state.clone(), ctx, packet));
+            match result {
+                Ok(fut) => Ok(Box::pin(tracing::Instrument::instrument(fut, span))),
+                Err(err) => {
+                    span.in_scope(|| tracing::warn!(error = ?err, "Failed to handle packet"));
+                    Err(err)
+                }
+            }
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        route.handle(state.clone(), ctx, packet)
+    }
+
+    /// Finds the notification route for the provided packet and calls
+    /// the route
+    ///
+    /// `state`  The provided state
+    /// `ctx`    The context of the session the packet is from
+    /// `packet` The notify packet to handle
+    fn handle_notify_route<'a>(
+        &self,
+        state: &'a mut S,
+        ctx: &SessionContext,
+        packet: &Packet,
+    ) -> Result<NotifyFuture<'a>, HandleError> {
+        let key = (packet.header.component, packet.header.command);
+        let route = match self.notify_routes.get(&key) {
+            Some(value) => value,
+            None => return Err(missing_route::<C>(packet)),
+        };
+
+        #[cfg(feature = "tracing")]
+        {
+            let span = route_span::<C>(packet);
+            let result = span.in_scope(|| route.handle(state, ctx, packet));
+            match result {
+                Ok(fut) => Ok(Box::pin(tracing::Instrument::instrument(fut, span))),
+                Err(err) => {
+                    span.in_scope(
+                        || tracing::warn!(error = ?err, "Failed to handle notify packet"),
+                    );
+                    Err(err)
+                }
+            }
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        route.handle(state, ctx, packet)
+    }
+}
+
+/// Creates the unknown component error for the provided packet emitting
+/// a tracing event for the packet when tracing is enabled
+///
+/// `packet` The packet that had an unknown component
+fn unknown_component(packet: &Packet) -> HandleError {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        component = packet.header.component,
+        command = packet.header.command,
+        id = packet.header.id,
+        "Unknown packet component"
+    );
+    HandleError::UnknownComponent(packet.header)
+}
+
+/// Checks that the provided response header is a response or error
+/// for the provided request header
+///
+/// `req` The header of the request packet
+/// `res` The header of the response packet
+fn validate_response(req: &PacketHeader, res: &PacketHeader) -> Result<(), HandleError> {
+    if matches!(res.ty, PacketType::Response | PacketType::Error)
+        && req.component == res.component
+        && req.command == res.command
+        && req.id == res.id
+    {
+        Ok(())
+    } else {
+        Err(HandleError::InvalidResponse {
+            header: *req,
+            response: *res,
+        })
+    }
+}
+
+/// Inserts the provided route into the routes map failing a debug
+/// assertion if there was already a route for the same key
+///
+/// `routes` The routes map to insert into
+/// `key`    The component and command key for the route
+/// `route`  The route to insert
+fn insert_route<R: ?Sized>(routes: &mut HashMap<RouteKey, Arc<R>>, key: RouteKey, route: Arc<R>) {
+    let replaced = routes.insert(key, route).is_some();
+    debug_assert!(
+        !replaced,
+        "Duplicate route for (component: {:#06x}, command: {:#06x})",
+        key.0, key.1
+    );
+}
+
+/// Reports a response that failed validation to the provided error hooks.
+/// When there are no hooks the error is logged as a warning if tracing is
+/// enabled otherwise it fails a debug assertion so that it isn't missed
+///
+/// `hooks` The error hooks to report to
+/// `err`   The validation error
+fn report_invalid_response(hooks: &[Arc<ErrorHook>], err: &HandleError) {
+    hooks.iter().for_each(|hook| hook(err));
+    #[cfg(feature = "tracing")]
+    if hooks.is_empty() {
+        tracing::warn!(error = %err, "Invalid response");
+    }
+    #[cfg(not(feature = "tracing"))]
+    debug_assert!(!hooks.is_empty(), "{err}");
+}
+
+/// Creates the error for a packet that has no matching route. Only
+/// converts the header into the component type once no route is
+/// found in order to describe the missing route
+///
+/// `packet` The packet that was missing a route
+fn missing_route<C: PacketComponents>(packet: &Packet) -> HandleError {
+    match C::from_header(&packet.header) {
+        Some(target) => missing_handler(&target, packet),
+        None => unknown_component(packet),
+    }
+}
+
+/// Creates the missing handler error for the provided packet emitting
+/// a tracing event for the packet when tracing is enabled
+///
+/// `target` The resolved packet component
+/// `packet` The packet that was missing a handler
+fn missing_handler<C: PacketComponents>(target: &C, packet: &Packet) -> HandleError {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(component = ?target, id = packet.header.id, "Missing packet handler");
+    HandleError::MissingHandler {
+        header: packet.header,
+        component: format!("{:?}", target),
+    }
+}
+
+/// Creates the tracing span used for a route invocation. Contains the
+/// resolved component and packet id along with empty fields for the
+/// decode and handle times which are recorded by the route. The
+/// component is only resolved when the span is enabled
+///
+/// `packet` The packet being handled
+#[cfg(feature = "tracing")]
+fn route_span<C: PacketComponents>(packet: &Packet) -> tracing::Span {
+    tracing::debug_span!(
+        "route",
+        component = ?C::from_header(&packet.header),
+        id = packet.header.id,
+        decode_time = tracing::field::Empty,
+        handle_time = tracing::field::Empty,
+    )
+}
+
+/// Trait for erasing the inner types of notification subscribers
+trait Subscriber: MaybeSend + MaybeSync {
+    /// Decodes the request type from the packet and provides it
+    /// to the underlying subscriber function
+    ///
+    /// `packet` The notify packet
+    fn notify(&self, packet: &Packet) -> Result<(), HandleError>;
+}
+
+/// Subscriber wrapper over a function for storing the phantom
+/// request type and implementing Subscriber
+struct FnSubscriber<F, Req> {
+    /// The underlying subscriber function
+    fun: F,
+    /// Marker for storing the request type
+    _marker: PhantomData<fn(Req)>,
+}
+
+impl<F, Req> Subscriber for FnSubscriber<F, Req>
+where
+    F: Fn(Req) + MaybeSend + MaybeSync + 'static,
+    Req: FromRequestInternal,
+{
+    fn notify(&self, packet: &Packet) -> Result<(), HandleError> {
+        let req = match Req::from_request(packet, None) {
+            Ok(value) => value,
+            Err(error) => {
+                return Err(HandleError::Decoding {
+                    header: packet.header,
+                    error,
+                })
+            }
+        };
+        (self.fun)(req);
+        Ok(())
+    }
+}
+
+/// Client-side dispatcher for inbound notify packets. Unlike the [Router]
+/// which produces responses for requests, the dispatcher passes notify
+/// packets along to any number of subscribers for their component and
+/// never produces a response
+pub struct NotifyDispatcher<C> {
+    /// The map of components to their subscribers
+    subscribers: HashMap<C, Vec<Arc<dyn Subscriber>>>,
+}
+
+impl<C> Default for NotifyDispatcher<C> {
+    fn default() -> Self {
+        Self {
+            subscribers: Default::default(),
+        }
+    }
+}
+
+/// Cloning the dispatcher only clones the references to the subscribers
+impl<C> Clone for NotifyDispatcher<C>
+where
+    C: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            subscribers: self.subscribers.clone(),
+        }
+    }
+}
+
+impl<C> NotifyDispatcher<C>
+where
+    C: PacketComponents,
+{
+    /// Creates a new dispatcher
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a new subscriber for the provided notify component. The subscriber
+    /// is provided the decoded request type every time a matching packet is
+    /// dispatched
+    ///
+    /// `component`  The component to subscribe to
+    /// `subscriber` The subscriber function
+    pub fn subscribe<Req, F>(&mut self, component: C, subscriber: F)
+    where
+        F: Fn(Req) + MaybeSend + MaybeSync + 'static,
+        Req: FromRequestInternal,
+    {
+        self.subscribers
+            .entry(component)
+            .or_default()
+            .push(Arc::new(FnSubscriber {
+                fun: subscriber,
+                _marker: PhantomData,
+            }));
+    }
+
+    /// Dispatches the provided packet to all the subscribers for its
+    /// component. Returns a missing handler error if there are no
+    /// subscribers for the packet component
+    ///
+    /// `packet` The notify packet to dispatch
+    pub fn dispatch(&self, packet: &Packet) -> Result<(), HandleError> {
         let target = match C::from_header(&packet.header) {
             Some(value) => value,
-            None => return Err(HandleError::MissingHandler(packet)),
+            None => return Err(unknown_component(packet)),
         };
 
-        let route = match self.routes.get(&target) {
+        let subscribers = match self.subscribers.get(&target) {
             Some(value) => value,
-            None => return Err(HandleError::MissingHandler(packet)),
+            None => return Err(missing_handler(&target, packet)),
         };
 
-        route.handle(state, packet)
+        for subscriber in subscribers {
+            subscriber.notify(packet)?;
+        }
+        Ok(())
     }
 }
 
 /// Error that can occur while handling a packet
 #[derive(Debug)]
 pub enum HandleError {
-    /// There wasn't an available handler for the provided packet
-    MissingHandler(Packet),
+    /// The component and command of the packet with the provided
+    /// header didn't match any known component
+    UnknownComponent(PacketHeader),
+    /// The component was known but there wasn't a handler
+    /// registered for it
+    MissingHandler {
+        /// The header of the packet
+        header: PacketHeader,
+        /// Debug name of the resolved component
+        component: String,
+    },
     /// Decoding error while reading the packet
-    Decoding(DecodeError),
+    Decoding {
+        /// The header of the packet
+        header: PacketHeader,
+        /// The underlying decoding error
+        error: DecodeError,
+    },
+    /// The handler produced a response that wasn't a response or
+    /// error for the request (Only when validating responses)
+    InvalidResponse {
+        /// The header of the request packet
+        header: PacketHeader,
+        /// The header of the response that was produced
+        response: PacketHeader,
+    },
+    /// The handler panicked while panics were being caught
+    Panic {
+        /// The header of the packet
+        header: PacketHeader,
+        /// The panic message
+        message: String,
+    },
+}
+
+impl HandleError {
+    /// Returns the header of the packet that caused this error
+    pub fn header(&self) -> &PacketHeader {
+        match self {
+            HandleError::UnknownComponent(header) => header,
+            HandleError::MissingHandler { header, .. } => header,
+            HandleError::Decoding { header, .. } => header,
+            HandleError::InvalidResponse { header, .. } => header,
+            HandleError::Panic { header, .. } => header,
+        }
+    }
+}
+
+/// Error implementation
+impl Error for HandleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            HandleError::Decoding { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Display formatting implementation
+impl Display for HandleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandleError::UnknownComponent(header) => write!(
+                f,
+                "Unknown component (component: {:#06x}, command: {:#06x}, id: {})",
+                header.component, header.command, header.id
+            ),
+            HandleError::MissingHandler { header, component } => write!(
+                f,
+                "No handler registered for {} (component: {:#06x}, command: {:#06x}, id: {})",
+                component, header.component, header.command, header.id
+            ),
+            HandleError::Decoding { header, error } => write!(
+                f,
+                "Failed to decode request (component: {:#06x}, command: {:#06x}, id: {}): {}",
+                header.component, header.command, header.id, error
+            ),
+            HandleError::InvalidResponse { header, response } => write!(
+                f,
+                "Handler for (component: {:#06x}, command: {:#06x}, id: {}) produced {:?} \
+                 (component: {:#06x}, command: {:#06x}, id: {}) instead of a response",
+                header.component,
+                header.command,
+                header.id,
+                response.ty,
+                response.component,
+                response.command,
+                response.id
+            ),
+            HandleError::Panic { header, message } => write!(
+                f,
+                "Handler panicked (component: {:#06x}, command: {:#06x}, id: {}): {}",
+                header.component, header.command, header.id, message
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "unsend")]
+    use super::{Guard, NotifyDispatcher};
+    use super::{HandleError, Router};
+    use crate::{
+        limit::{RateLimit, RateLimiter},
+        packet::{IntoResponse, Packet, PacketComponents, PacketHeader},
+        session::SessionContext,
+        timer::test::poll_now,
+    };
+    #[cfg(feature = "unsend")]
+    use std::{cell::Cell, rc::Rc};
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    /// Component for testing routing
+    #[derive(Debug, Hash, PartialEq, Eq)]
+    struct TestComponent;
+
+    impl PacketComponents for TestComponent {
+        fn values(&self) -> (u16, u16) {
+            (0x1, 0x2)
+        }
+
+        fn from_values(component: u16, command: u16, _notify: bool) -> Option<Self> {
+            (component == 0x1 && command == 0x2).then_some(TestComponent)
+        }
+    }
+
+    /// Response which is sent using the wrong packet ID
+    struct WrongId;
+
+    impl IntoResponse for WrongId {
+        fn into_response(self, req: &PacketHeader) -> Packet {
+            let mut header = req.response();
+            header.id += 1;
+            Packet::raw_empty(header)
+        }
+    }
+
+    async fn wrong_id(_state: &mut ()) -> WrongId {
+        WrongId
+    }
+
+    /// Tests mismatched responses are only reported to the error
+    /// hooks once response validation is enabled
+    #[test]
+    fn test_validate_responses() {
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let mut router: Router<TestComponent, ()> = Router::new();
+        router.route(TestComponent, wrong_id);
+        router.on_error({
+            let errors = errors.clone();
+            move |err: &HandleError| match err {
+                HandleError::InvalidResponse { header, response } => {
+                    errors.lock().unwrap().push((header.id, response.id))
+                }
+                err => panic!("Unexpected error: {err}"),
+            }
+        });
+
+        let ctx = SessionContext::new(1);
+        let packet = Packet::request_empty(1, TestComponent);
+
+        let responses = poll_now(router.handle(&mut (), &ctx, &packet).unwrap());
+        assert_eq!(responses.response.header.id, 2);
+        assert!(errors.lock().unwrap().is_empty());
+
+        router.validate_responses(true);
+        let responses = poll_now(router.handle(&mut (), &ctx, &packet).unwrap());
+        assert_eq!(responses.response.header.id, 2);
+        assert_eq!(*errors.lock().unwrap(), [(1, 2)]);
+    }
+
+    /// Tests panics caught from handlers are reported to the error hooks
+    #[test]
+    fn test_catch_panics_hooks() {
+        async fn panics(_state: &mut ()) {
+            panic!("Handler failed");
+        }
+
+        let errors = Arc::new(Mutex::new(Vec::new()));
+        let mut router: Router<TestComponent, ()> = Router::new();
+        router.route(TestComponent, panics);
+        router.catch_panics_with(0x5);
+        router.on_error({
+            let errors = errors.clone();
+            move |err: &HandleError| match err {
+                HandleError::Panic { header, message } => {
+                    errors.lock().unwrap().push((header.id, message.clone()))
+                }
+                err => panic!("Unexpected error: {err}"),
+            }
+        });
+
+        let ctx = SessionContext::new(1);
+        let packet = Packet::request_empty(1, TestComponent);
+        let responses = poll_now(router.handle(&mut (), &ctx, &packet).unwrap());
+        assert_eq!(responses.response.header, packet.header.with_error(0x5));
+        assert_eq!(*errors.lock().unwrap(), [(1, "Handler failed".to_string())]);
+    }
+
+    /// Tests adding a second route for the same component is caught
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Duplicate route")]
+    fn test_duplicate_route() {
+        async fn empty(_state: &mut ()) {}
+
+        let mut router: Router<TestComponent, ()> = Router::new();
+        router.route(TestComponent, empty);
+        router.route_raw(0x1, 0x2, empty);
+    }
+
+    /// Tests mismatched responses are still caught when validation is
+    /// enabled without any error hooks
+    #[test]
+    #[cfg_attr(
+        all(debug_assertions, not(feature = "tracing")),
+        should_panic(expected = "instead of a response")
+    )]
+    fn test_validate_responses_without_hooks() {
+        let mut router: Router<TestComponent, ()> = Router::new();
+        router.route(TestComponent, wrong_id);
+        router.validate_responses(true);
+
+        let ctx = SessionContext::new(1);
+        let packet = Packet::request_empty(1, TestComponent);
+        let responses = poll_now(router.handle(&mut (), &ctx, &packet).unwrap());
+        assert_eq!(responses.response.header.id, 2);
+    }
+
+    /// Tests packets exceeding the session rate limit are responded
+    /// to with the limiter error instead of calling the route
+    #[test]
+    fn test_handle_limited() {
+        let calls = Arc::new(Mutex::new(0));
+        let mut router: Router<TestComponent, ()> = Router::new();
+        router.route(TestComponent, {
+            let calls = calls.clone();
+            move |_state: &mut ()| {
+                *calls.lock().unwrap() += 1;
+                async {}
+            }
+        });
+
+        let mut limiter = RateLimiter::new()
+            .session(RateLimit::new(1, Duration::from_secs(60)))
+            .error(0x7);
+        let ctx = SessionContext::new(1);
+        let packet = Packet::request_empty(1, TestComponent);
+
+        let handle = |limiter: &mut RateLimiter| {
+            poll_now(
+                router
+                    .handle_limited(&mut (), &ctx, limiter, &packet)
+                    .unwrap(),
+            )
+        };
+
+        let responses = handle(&mut limiter);
+        assert_eq!(responses.response.header, packet.header.response());
+        let responses = handle(&mut limiter);
+        assert_eq!(responses.response.header, packet.header.with_error(0x7));
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+
+    /// Tests guards, hooks and subscribers that aren't thread safe
+    /// can be registered when the unsend feature is enabled
+    #[cfg(feature = "unsend")]
+    #[test]
+    fn test_unsend_callbacks() {
+        async fn empty(_state: &mut ()) {}
+
+        let calls = Rc::new(Cell::new(0));
+        let count = |calls: &Rc<Cell<u32>>| {
+            let calls = calls.clone();
+            move || calls.set(calls.get() + 1)
+        };
+
+        let mut router: Router<TestComponent, ()> = Router::new();
+        router.route(TestComponent, empty);
+        router.guard(0x1, {
+            let count = count(&calls);
+            Guard::new(0x5, move |_: &(), _: &Packet| {
+                count();
+                true
+            })
+        });
+        router.on_request({
+            let count = count(&calls);
+            move |_| count()
+        });
+        router.on_response({
+            let count = count(&calls);
+            move |_| count()
+        });
+        router.on_error({
+            let count = count(&calls);
+            move |_| count()
+        });
+
+        let ctx = SessionContext::new(1);
+        let packet = Packet::request_empty(1, TestComponent);
+        poll_now(router.handle(&mut (), &ctx, &packet).unwrap());
+        assert_eq!(calls.get(), 3);
+
+        let mut dispatcher: NotifyDispatcher<TestComponent> = NotifyDispatcher::new();
+        dispatcher.subscribe(TestComponent, {
+            let count = count(&calls);
+            move |_: ()| count()
+        });
+        dispatcher
+            .dispatch(&Packet::raw_empty(PacketHeader::notify(0x1, 0x2)))
+            .unwrap();
+        assert_eq!(calls.get(), 4);
+    }
 }
@@ -0,0 +1,469 @@
+//! Runtime schemas ([`Schema`]) describing the components, commands and
+//! fields of a title which can be used to decode packets into named values
+//! ([`NamedField`]) without needing compiled structures for them.
+//!
+//! With the `serde` feature schemas can be loaded from any serde format
+//! such as JSON:
+//!
+//! ```json
+//! {
+//!     "components": [{
+//!         "id": 1,
+//!         "name": "Authentication",
+//!         "commands": [{
+//!             "id": 40,
+//!             "name": "Login",
+//!             "request": [
+//!                 { "tag": "MAIL", "name": "email", "ty": "String" },
+//!                 { "tag": "PASS", "name": "password" }
+//!             ]
+//!         }]
+//!     }]
+//! }
+//! ```
+
+use crate::{
+    error::{DecodeError, DecodeResult},
+    packet::{Packet, PacketHeader, PacketType},
+    reader::TdfReader,
+    tag::{Tag, TdfType},
+    types::UNION_UNSET,
+    value::TdfValue,
+};
+
+/// Schema describing the components of a title
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Schema {
+    /// The components within the schema
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub components: Vec<ComponentSchema>,
+}
+
+/// Schema describing a component and its commands
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComponentSchema {
+    /// The ID of the component
+    pub id: u16,
+    /// The name of the component
+    pub name: String,
+    /// The commands of the component
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub commands: Vec<CommandSchema>,
+    /// The notifications of the component
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub notifications: Vec<NotificationSchema>,
+}
+
+/// Schema describing a command and the fields of its
+/// request, response and error packets
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommandSchema {
+    /// The ID of the command
+    pub id: u16,
+    /// The name of the command
+    pub name: String,
+    /// The fields of request packets
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub request: Vec<FieldSchema>,
+    /// The fields of response packets
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub response: Vec<FieldSchema>,
+    /// The fields of error packets
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub error: Vec<FieldSchema>,
+}
+
+/// Schema describing a notification and its fields
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NotificationSchema {
+    /// The ID of the notification command
+    pub id: u16,
+    /// The name of the notification
+    pub name: String,
+    /// The fields of the notification packets
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fields: Vec<FieldSchema>,
+}
+
+/// Schema describing a tagged field. The nested fields are used
+/// for any groups within the value of this field (The group itself,
+/// groups within lists, map values and union values)
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FieldSchema {
+    /// The tag of the field
+    pub tag: String,
+    /// The name of the field
+    pub name: String,
+    /// The expected type of the field, decoding fails if the
+    /// field has a different type
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ty: Option<TdfType>,
+    /// The fields of groups within this field
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fields: Vec<FieldSchema>,
+}
+
+/// Packet decoded using a schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaDecoded {
+    /// The name of the packet component if known
+    pub component: Option<String>,
+    /// The name of the packet command if known
+    pub command: Option<String>,
+    /// The decoded fields of the packet
+    pub fields: Vec<NamedField>,
+}
+
+/// Field decoded using a schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedField {
+    /// The tag of the field
+    pub tag: Tag,
+    /// The name of the field if it was present in the schema
+    pub name: Option<String>,
+    /// The decoded value
+    pub value: NamedValue,
+}
+
+impl NamedField {
+    /// Returns the name of the field or the tag as a string
+    /// if the field was not in the schema
+    pub fn key(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => self.tag.to_string(),
+        }
+    }
+}
+
+/// Value decoded using a schema
+#[derive(Debug, Clone, PartialEq)]
+pub enum NamedValue {
+    /// Value that cannot contain fields
+    Value(TdfValue),
+    /// Group of named fields
+    Group(Vec<NamedField>),
+    /// List of values
+    List(Vec<NamedValue>),
+    /// Map of key value pairs
+    Map(Vec<(NamedValue, NamedValue)>),
+    /// Union value
+    Union {
+        /// The key of the union
+        key: u8,
+        /// The field of the union (None if unset)
+        value: Option<Box<NamedField>>,
+    },
+}
+
+impl Schema {
+    /// Finds the component with the provided ID
+    ///
+    /// `id` The component ID
+    pub fn component(&self, id: u16) -> Option<&ComponentSchema> {
+        self.components.iter().find(|component| component.id == id)
+    }
+
+    /// Decodes the contents of the provided packet using the fields
+    /// from this schema. Fields not present in the schema are still
+    /// decoded but are left unnamed
+    ///
+    /// `packet` The packet to decode
+    pub fn decode(&self, packet: &Packet) -> DecodeResult<SchemaDecoded> {
+        let header: &PacketHeader = &packet.header;
+        let component = self.component(header.component);
+
+        let (command, fields) = match component {
+            Some(component) => component.fields(header),
+            None => (None, &[] as &[FieldSchema]),
+        };
+
+        let mut reader = TdfReader::new(&packet.contents);
+        let fields = decode_fields(&mut reader, fields, false)?;
+
+        Ok(SchemaDecoded {
+            component: component.map(|component| component.name.clone()),
+            command: command.map(str::to_string),
+            fields,
+        })
+    }
+}
+
+impl ComponentSchema {
+    /// Finds the command with the provided ID
+    ///
+    /// `id` The command ID
+    pub fn command(&self, id: u16) -> Option<&CommandSchema> {
+        self.commands.iter().find(|command| command.id == id)
+    }
+
+    /// Finds the notification with the provided ID
+    ///
+    /// `id` The notification command ID
+    pub fn notification(&self, id: u16) -> Option<&NotificationSchema> {
+        self.notifications
+            .iter()
+            .find(|notification| notification.id == id)
+    }
+
+    /// Finds the name of the command and the schema fields
+    /// for the packet with the provided header
+    ///
+    /// `header` The packet header
+    fn fields(&self, header: &PacketHeader) -> (Option<&str>, &[FieldSchema]) {
+        if let PacketType::Notify = header.ty {
+            return match self.notification(header.command) {
+                Some(value) => (Some(&value.name), &value.fields),
+                None => (None, &[]),
+            };
+        }
+
+        match self.command(header.command) {
+            Some(value) => (
+                Some(&value.name),
+                match header.ty {
+                    PacketType::Request => &value.request,
+                    PacketType::Response => &value.response,
+                    _ => &value.error,
+                },
+            ),
+            None => (None, &[]),
+        }
+    }
+}
+
+/// Decodes fields from the provided reader until the end of the
+/// reader or the end of the group
+///
+/// `reader` The reader to decode from
+/// `schema` The schema for the fields
+/// `group`  Whether the fields are within a group
+fn decode_fields(
+    reader: &mut TdfReader,
+    schema: &[FieldSchema],
+    group: bool,
+) -> DecodeResult<Vec<NamedField>> {
+    let mut fields = Vec::new();
+    loop {
+        if group {
+            if reader.read_byte()? == 0 {
+                break;
+            }
+            reader.cursor -= 1;
+        } else if reader.is_empty() {
+            break;
+        }
+        fields.push(decode_field(reader, schema)?);
+    }
+    Ok(fields)
+}
+
+/// Decodes a single tagged field from the provided reader
+///
+/// `reader` The reader to decode from
+/// `schema` The schema for the fields at this level
+fn decode_field(reader: &mut TdfReader, schema: &[FieldSchema]) -> DecodeResult<NamedField> {
+    let tagged = reader.read_tag()?;
+    let field = schema
+        .iter()
+        .find(|field| Tag::from(field.tag.as_bytes()) == tagged.tag);
+
+    if let Some(expected) = field.and_then(|field| field.ty) {
+        if expected != tagged.ty {
+            return Err(DecodeError::InvalidTagType {
+                tag: tagged.tag,
+                expected,
+                actual: tagged.ty,
+            });
+        }
+    }
+
+    let nested = field.map(|field| field.fields.as_slice()).unwrap_or(&[]);
+    let value = decode_value(reader, tagged.ty, nested)?;
+
+    Ok(NamedField {
+        tag: tagged.tag,
+        name: field.map(|field| field.name.clone()),
+        value,
+    })
+}
+
+/// Decodes a value of the provided type
+///
+/// `reader` The reader to decode from
+/// `ty`     The type of value to decode
+/// `schema` The schema for any groups within the value
+fn decode_value(
+    reader: &mut TdfReader,
+    ty: TdfType,
+    schema: &[FieldSchema],
+) -> DecodeResult<NamedValue> {
+    Ok(match ty {
+        TdfType::Group => {
+            reader.skip_group_2()?;
+            NamedValue::Group(decode_fields(reader, schema, true)?)
+        }
+        TdfType::List => {
+            let ty = reader.read_type()?;
+            let length = reader.read_usize()?;
+            let mut values = Vec::new();
+            for _ in 0..length {
+                values.push(decode_value(reader, ty, schema)?);
+            }
+            NamedValue::List(values)
+        }
+        TdfType::Map => {
+            let key_ty = reader.read_type()?;
+            let value_ty = reader.read_type()?;
+            let length = reader.read_usize()?;
+            let mut entries = Vec::new();
+            for _ in 0..length {
+                let key = decode_value(reader, key_ty, &[])?;
+                let value = decode_value(reader, value_ty, schema)?;
+                entries.push((key, value));
+            }
+            NamedValue::Map(entries)
+        }
+        TdfType::Union => {
+            let key = reader.read_byte()?;
+            let value = if key == UNION_UNSET {
+                None
+            } else {
+                Some(Box::new(decode_field(reader, schema)?))
+            };
+            NamedValue::Union { key, value }
+        }
+        ty => NamedValue::Value(TdfValue::decode_type(reader, ty)?),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CommandSchema, ComponentSchema, FieldSchema, NamedValue, Schema};
+    use crate::{
+        packet::{Packet, PacketHeader},
+        tag::TdfType,
+        value::TdfValue,
+        writer::TdfWriter,
+    };
+
+    /// Creates a schema for testing
+    fn test_schema() -> Schema {
+        Schema {
+            components: vec![ComponentSchema {
+                id: 1,
+                name: "Authentication".to_string(),
+                commands: vec![CommandSchema {
+                    id: 40,
+                    name: "Login".to_string(),
+                    request: vec![
+                        FieldSchema {
+                            tag: "MAIL".to_string(),
+                            name: "email".to_string(),
+                            ty: Some(TdfType::String),
+                            fields: Vec::new(),
+                        },
+                        FieldSchema {
+                            tag: "DATA".to_string(),
+                            name: "data".to_string(),
+                            ty: None,
+                            fields: vec![FieldSchema {
+                                tag: "ID".to_string(),
+                                name: "id".to_string(),
+                                ty: None,
+                                fields: Vec::new(),
+                            }],
+                        },
+                    ],
+                    ..Default::default()
+                }],
+                notifications: Vec::new(),
+            }],
+        }
+    }
+
+    /// Tests decoding a packet using a schema
+    #[test]
+    fn test_schema_decode() {
+        let mut writer = TdfWriter::default();
+        writer.tag_str(b"MAIL", "test@example.com");
+        writer.group(b"DATA", |writer| {
+            writer.tag_u32(b"ID", 5);
+            writer.tag_u32(b"UNK", 6);
+        });
+
+        let packet = Packet::raw(PacketHeader::request(1, 1, 40), writer.buffer);
+        let decoded = test_schema().decode(&packet).unwrap();
+
+        assert_eq!(decoded.component.as_deref(), Some("Authentication"));
+        assert_eq!(decoded.command.as_deref(), Some("Login"));
+        assert_eq!(decoded.fields[0].key(), "email");
+
+        let NamedValue::Group(fields) = &decoded.fields[1].value else {
+            panic!("Expected group value");
+        };
+        assert_eq!(fields[0].key(), "id");
+        assert_eq!(fields[0].value, NamedValue::Value(TdfValue::VarInt(5)));
+        assert_eq!(fields[1].name, None);
+        assert_eq!(fields[1].key(), "UNK");
+    }
+
+    /// Tests that fields with the wrong type are rejected
+    #[test]
+    fn test_schema_type_mismatch() {
+        let mut writer = TdfWriter::default();
+        writer.tag_u32(b"MAIL", 1);
+
+        let packet = Packet::raw(PacketHeader::request(1, 1, 40), writer.buffer);
+        assert!(test_schema().decode(&packet).is_err());
+    }
+
+    /// Tests loading a schema from JSON
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_schema_json() {
+        let schema: Schema = serde_json::from_str(
+            r#"{
+                "components": [{
+                    "id": 1,
+                    "name": "Authentication",
+                    "commands": [{
+                        "id": 40,
+                        "name": "Login",
+                        "request": [
+                            { "tag": "MAIL", "name": "email", "ty": "String" },
+                            { "tag": "DATA", "name": "data", "fields": [
+                                { "tag": "ID", "name": "id" }
+                            ]}
+                        ]
+                    }]
+                }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(schema, test_schema());
+
+        let mut writer = TdfWriter::default();
+        writer.tag_str(b"MAIL", "test@example.com");
+        writer.group(b"DATA", |writer| writer.tag_u32(b"ID", 5));
+
+        let packet = Packet::raw(PacketHeader::request(1, 1, 40), writer.buffer);
+        let decoded = schema.decode(&packet).unwrap();
+        assert_eq!(
+            serde_json::to_value(&decoded).unwrap(),
+            serde_json::json!({
+                "component": "Authentication",
+                "command": "Login",
+                "fields": {
+                    "email": "test@example.com",
+                    "data": { "id": 5 }
+                }
+            })
+        );
+    }
+}
@@ -1,9 +1,27 @@
-//! This module contains the serde Serialize implementations for the
-//! structures created by Pocket Relay
+//! This module contains the serde Serialize and Deserialize implementations
+//! for the structures created by Pocket Relay
 
-use crate::types::{Blob, TdfMap, VarIntList};
-use serde::ser::SerializeMap;
-use serde::Serialize;
+pub mod base64;
+pub mod de;
+pub mod hex;
+pub mod ser;
+
+use crate::codec::{Decodable, Encodable};
+use crate::error::{DecodeError, DecodeResult};
+use crate::packet::{Packet, PacketHeader, PacketType};
+use crate::reader::TdfReader;
+use crate::schema::{NamedField, NamedValue, SchemaDecoded};
+use crate::types::{Blob, ObjectId, ObjectType, TdfMap, TdfPair, TdfTriple, Union, VarIntList};
+use crate::value::{TdfField, TdfFields, TdfValue};
+use crate::writer::TdfWriter;
+use bytes::Bytes;
+use de::DeserializeError;
+use ser::SerializeResult;
+use serde::de::{DeserializeOwned, MapAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeStruct};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt::Formatter;
+use std::marker::PhantomData;
 
 impl<K, V> Serialize for TdfMap<K, V>
 where
@@ -22,6 +40,49 @@ where
     }
 }
 
+/// Deserializes maps into a TdfMap retaining the order of the
+/// entries from the deserializer
+impl<'de, K, V> Deserialize<'de> for TdfMap<K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(TdfMapVisitor(PhantomData))
+    }
+}
+
+/// Visitor for deserializing [TdfMap]s
+struct TdfMapVisitor<K, V>(PhantomData<fn() -> TdfMap<K, V>>);
+
+impl<'de, K, V> Visitor<'de> for TdfMapVisitor<K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = TdfMap<K, V>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        // Size hint is capped to prevent large allocations from untrusted input
+        let capacity = access.size_hint().unwrap_or_default().min(4096);
+        let mut map = TdfMap::with_capacity(capacity);
+        while let Some((key, value)) = access.next_entry::<K, V>()? {
+            map.insert(key, value);
+        }
+        Ok(map)
+    }
+}
+
 impl<T> Serialize for VarIntList<T>
 where
     T: Serialize,
@@ -34,11 +95,756 @@ where
     }
 }
 
+/// Blobs are serialized as a list of bytes in human-readable formats
+/// and as raw bytes otherwise. Use the [hex] or [base64] helpers for
+/// more compact human-readable representations
 impl Serialize for Blob {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        self.0.serialize(serializer)
+        if serializer.is_human_readable() {
+            self.0.serialize(serializer)
+        } else {
+            serializer.serialize_bytes(&self.0)
+        }
+    }
+}
+
+impl<'de, T> Deserialize<'de> for VarIntList<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::deserialize(deserializer).map(VarIntList)
+    }
+}
+
+impl<'de> Deserialize<'de> for Blob {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_byte_buf(BytesVisitor).map(Blob)
+    }
+}
+
+/// Serde representation of a set [Union]
+#[derive(Serialize, Deserialize)]
+struct UnionRepr<T> {
+    key: u8,
+    tag: String,
+    value: T,
+}
+
+/// Unions are serialized as none when unset or the key, tag
+/// and value when set
+impl<C> Serialize for Union<C>
+where
+    C: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Union::Set { key, tag, value } => serializer.serialize_some(&UnionRepr {
+                key: *key,
+                tag: tag.to_string(),
+                value,
+            }),
+            Union::Unset => serializer.serialize_none(),
+        }
+    }
+}
+
+impl<'de, C> Deserialize<'de> for Union<C>
+where
+    C: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value: Option<UnionRepr<C>> = Option::deserialize(deserializer)?;
+        Ok(match value {
+            Some(UnionRepr { key, tag, value }) => Union::set(key, tag.as_bytes(), value),
+            None => Union::Unset,
+        })
+    }
+}
+
+/// Serde representation of [TdfPair]
+#[derive(Serialize, Deserialize)]
+struct PairRepr<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A, B> Serialize for TdfPair<A, B>
+where
+    A: Serialize,
+    B: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        PairRepr {
+            first: &self.first,
+            second: &self.second,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, A, B> Deserialize<'de> for TdfPair<A, B>
+where
+    A: Deserialize<'de>,
+    B: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let PairRepr { first, second } = PairRepr::deserialize(deserializer)?;
+        Ok(TdfPair::new(first, second))
+    }
+}
+
+/// Serde representation of [TdfTriple]
+#[derive(Serialize, Deserialize)]
+struct TripleRepr<A, B, C> {
+    first: A,
+    second: B,
+    third: C,
+}
+
+impl<A, B, C> Serialize for TdfTriple<A, B, C>
+where
+    A: Serialize,
+    B: Serialize,
+    C: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        TripleRepr {
+            first: &self.first,
+            second: &self.second,
+            third: &self.third,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, A, B, C> Deserialize<'de> for TdfTriple<A, B, C>
+where
+    A: Deserialize<'de>,
+    B: Deserialize<'de>,
+    C: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let TripleRepr {
+            first,
+            second,
+            third,
+        } = TripleRepr::deserialize(deserializer)?;
+        Ok(TdfTriple::new(first, second, third))
+    }
+}
+
+impl Serialize for ObjectType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut value = serializer.serialize_struct("ObjectType", 2)?;
+        value.serialize_field("component", &self.component)?;
+        value.serialize_field("ty", &self.ty)?;
+        value.end()
+    }
+}
+
+impl Serialize for ObjectId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut value = serializer.serialize_struct("ObjectId", 3)?;
+        value.serialize_field("component", &self.component)?;
+        value.serialize_field("ty", &self.ty)?;
+        value.serialize_field("id", &self.id)?;
+        value.end()
+    }
+}
+
+/// Dynamic values are serialized as their natural structure. Groups
+/// are serialized as maps of tags to values, unions are serialized as
+/// a map of the union key and its tagged value (or none when unset) and
+/// generics are serialized the same with their type id as the "id" key
+impl Serialize for TdfValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            TdfValue::VarInt(value) => serializer.serialize_u64(*value),
+            TdfValue::String(value) => serializer.serialize_str(value),
+            TdfValue::Blob(value) => value.serialize(serializer),
+            TdfValue::Group { fields, .. } => {
+                let mut map = serializer.serialize_map(Some(fields.len()))?;
+                for field in fields {
+                    map.serialize_entry(&field.tag.to_string(), &field.value)?;
+                }
+                map.end()
+            }
+            TdfValue::List { values, .. } => values.serialize(serializer),
+            TdfValue::Map { entries, .. } => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            TdfValue::Union { key, value } => match value {
+                Some(value) => {
+                    let mut map = serializer.serialize_map(Some(2))?;
+                    map.serialize_entry("key", key)?;
+                    map.serialize_entry(&value.tag.to_string(), &value.value)?;
+                    map.end()
+                }
+                None => serializer.serialize_none(),
+            },
+            TdfValue::VarIntList(values) => values.serialize(serializer),
+            TdfValue::Pair(a, b) => (a, b).serialize(serializer),
+            TdfValue::Triple(a, b, c) => (a, b, c).serialize(serializer),
+            TdfValue::Float(value) => serializer.serialize_f32(*value),
+            TdfValue::TimeValue(value) => serializer.serialize_u64(*value),
+            TdfValue::Generic(value) => match value {
+                Some((id, value)) => {
+                    let mut map = serializer.serialize_map(Some(2))?;
+                    map.serialize_entry("id", id)?;
+                    map.serialize_entry(&value.tag.to_string(), &value.value)?;
+                    map.end()
+                }
+                None => serializer.serialize_none(),
+            },
+        }
+    }
+}
+
+impl Serialize for TdfField {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry(&self.tag.to_string(), &self.value)?;
+        map.end()
+    }
+}
+
+/// Fields are serialized as a map of tags to values
+impl Serialize for TdfFields {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for field in &self.0 {
+            map.serialize_entry(&field.tag.to_string(), &field.value)?;
+        }
+        map.end()
+    }
+}
+
+/// Serializes the provided named fields as a map of their
+/// names (or tags when unnamed) to their values
+///
+/// `fields`     The fields to serialize
+/// `serializer` The serializer to use
+fn serialize_named_fields<S>(fields: &[NamedField], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let mut map = serializer.serialize_map(Some(fields.len()))?;
+    for field in fields {
+        map.serialize_entry(&field.key(), &field.value)?;
+    }
+    map.end()
+}
+
+/// Named values are serialized the same as [TdfValue]s with
+/// group fields keyed by their names
+impl Serialize for NamedValue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            NamedValue::Value(value) => value.serialize(serializer),
+            NamedValue::Group(fields) => serialize_named_fields(fields, serializer),
+            NamedValue::List(values) => values.serialize(serializer),
+            NamedValue::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            NamedValue::Union { key, value } => match value {
+                Some(value) => {
+                    let mut map = serializer.serialize_map(Some(2))?;
+                    map.serialize_entry("key", key)?;
+                    map.serialize_entry(&value.key(), &value.value)?;
+                    map.end()
+                }
+                None => serializer.serialize_none(),
+            },
+        }
+    }
+}
+
+impl Serialize for SchemaDecoded {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        /// Wrapper for serializing the fields as a map
+        struct Fields<'a>(&'a [NamedField]);
+
+        impl Serialize for Fields<'_> {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serialize_named_fields(self.0, serializer)
+            }
+        }
+
+        let mut value = serializer.serialize_struct("SchemaDecoded", 3)?;
+        value.serialize_field("component", &self.component)?;
+        value.serialize_field("command", &self.command)?;
+        value.serialize_field("fields", &Fields(&self.fields))?;
+        value.end()
+    }
+}
+
+/// Packet types are serialized as their byte value
+impl Serialize for PacketType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+impl<'de> Deserialize<'de> for PacketType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = u8::deserialize(deserializer)?;
+        match value {
+            0x00 => Ok(PacketType::Request),
+            0x10 => Ok(PacketType::Response),
+            0x20 => Ok(PacketType::Notify),
+            0x30 => Ok(PacketType::Error),
+            value => Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Unsigned(value as u64),
+                &"a packet type (0x00, 0x10, 0x20, 0x30)",
+            )),
+        }
+    }
+}
+
+/// Serde definition for [PacketHeader]
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "PacketHeader")]
+struct PacketHeaderDef {
+    component: u16,
+    command: u16,
+    error: u16,
+    ty: PacketType,
+    id: u16,
+}
+
+impl Serialize for PacketHeader {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        PacketHeaderDef::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for PacketHeader {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        PacketHeaderDef::deserialize(deserializer)
+    }
+}
+
+/// Serde definition for [Packet] where the contents are
+/// stored as a hex string
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "Packet")]
+struct PacketDef {
+    header: PacketHeader,
+    #[serde(with = "hex")]
+    contents: Bytes,
+}
+
+/// Packets are serialized as their header along with their
+/// contents as a hex string
+impl Serialize for Packet {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        PacketDef::serialize(self, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Packet {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        PacketDef::deserialize(deserializer)
+    }
+}
+
+/// Visitor for deserializing raw bytes which accepts byte
+/// buffers along with sequences of bytes
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("bytes")
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(v)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        let mut out = Vec::with_capacity(seq.size_hint().unwrap_or_default().min(4096));
+        while let Some(value) = seq.next_element()? {
+            out.push(value);
+        }
+        Ok(out)
+    }
+}
+
+/// Adapter for using [Serialize] types as [Encodable] values. The value
+/// is serialized when the adapter is created so that any serialization
+/// errors can be handled up front
+///
+/// ```
+/// use blaze_pk::{codec::Encodable, serialize::SerdeEncodable};
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Login {
+///     #[serde(rename = "MAIL")]
+///     email: String,
+/// }
+///
+/// let value = Login { email: "test@example.com".to_string() };
+/// let encodable = SerdeEncodable::new(&value).unwrap();
+/// let bytes = encodable.encode_bytes();
+/// ```
+pub struct SerdeEncodable<T> {
+    /// The serialized fields of the value
+    fields: TdfFields,
+    /// Marker for the serialized type
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<T> SerdeEncodable<T>
+where
+    T: Serialize,
+{
+    /// Serializes the provided value creating a new adapter
+    ///
+    /// `value` The value to serialize
+    pub fn new(value: &T) -> SerializeResult<Self> {
+        Ok(Self {
+            fields: ser::to_fields(value)?,
+            _marker: PhantomData,
+        })
+    }
+}
+
+impl<T> Encodable for SerdeEncodable<T> {
+    fn encode(&self, output: &mut TdfWriter) {
+        self.fields.encode(output);
+    }
+
+    fn try_encode(&self, output: &mut TdfWriter) -> crate::error::EncodeResult<()> {
+        self.fields.try_encode(output)
+    }
+}
+
+/// Adapter for using [Deserialize] types as [Decodable] values
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerdeDecodable<T>(pub T);
+
+impl<T> Decodable for SerdeDecodable<T>
+where
+    T: DeserializeOwned,
+{
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        de::from_reader_owned(reader)
+            .map(SerdeDecodable)
+            .map_err(|err| match err {
+                DeserializeError::Decode(err) => err,
+                DeserializeError::Custom(err) => DecodeError::Custom(err),
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        codec::Decodable,
+        error::DecodeError,
+        reader::TdfReader,
+        types::{TdfMap, Union},
+        value::TdfFields,
+        writer::TdfWriter,
+    };
+    use serde_json::{json, Value};
+
+    /// Creates contents with each kind of value along with the
+    /// JSON representation of those contents
+    fn test_contents() -> (Vec<u8>, Value) {
+        let mut map = TdfMap::<String, u32>::new();
+        map.insert("A", 1u32);
+
+        let mut writer = TdfWriter::default();
+        writer.tag_u32(b"INT", 12);
+        writer.group(b"GRP", |writer| writer.tag_str(b"STR", "Test"));
+        writer.tag_value(b"MAP", &map);
+        writer.tag_value(b"UNI", &Union::set(0x2, b"VALU", 5u32));
+        writer.tag_pair(b"PAIR", (1u32, 2u32));
+
+        let expected = json!({
+            "INT": 12,
+            "GRP": { "STR": "Test" },
+            "MAP": { "A": 1 },
+            "UNI": { "key": 2, "VALU": 5 },
+            "PAIR": [1, 2]
+        });
+
+        (writer.buffer, expected)
+    }
+
+    /// Tests serializing dynamic values as JSON
+    #[test]
+    fn test_value_json() {
+        let (contents, expected) = test_contents();
+        let fields = TdfFields::decode(&mut TdfReader::new(&contents)).unwrap();
+        assert_eq!(serde_json::to_value(&fields).unwrap(), expected);
+    }
+
+    /// Tests using serde types as packet contents through the adapters
+    #[test]
+    fn test_serde_adapters() {
+        use super::{SerdeDecodable, SerdeEncodable};
+        use crate::packet::{Packet, PacketHeader};
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Login {
+            #[serde(rename = "MAIL")]
+            email: String,
+        }
+
+        let value = Login {
+            email: "test@example.com".to_string(),
+        };
+        let request = Packet::raw_empty(PacketHeader::request(1, 1, 40));
+        let packet = Packet::response(&request, SerdeEncodable::new(&value).unwrap());
+
+        let SerdeDecodable(decoded) = packet.decode::<SerdeDecodable<Login>>().unwrap();
+        assert_eq!(decoded, value);
+
+        let empty = Packet::response_empty(&request);
+        let err = empty.decode::<SerdeDecodable<Login>>().unwrap_err();
+        assert!(matches!(err, DecodeError::Custom(_)));
+        assert_eq!(err.to_string(), "missing field `MAIL`");
+    }
+
+    /// Tests round tripping wire types through JSON
+    #[test]
+    fn test_wire_types_json() {
+        use crate::types::{Blob, TdfPair, TdfTriple, VarIntList};
+
+        let union = Union::set(0x1, b"VALU", 5u32);
+        let value = serde_json::to_value(&union).unwrap();
+        assert_eq!(value, json!({ "key": 1, "tag": "VALU", "value": 5 }));
+        assert_eq!(serde_json::from_value::<Union<u32>>(value).unwrap(), union);
+
+        let unset = serde_json::to_value(Union::<u32>::Unset).unwrap();
+        assert_eq!(unset, Value::Null);
+        assert!(serde_json::from_value::<Union<u32>>(unset)
+            .unwrap()
+            .is_unset());
+
+        let pair = TdfPair::new(1u16, 2u32);
+        let value = serde_json::to_value(pair).unwrap();
+        assert_eq!(value, json!({ "first": 1, "second": 2 }));
+        assert_eq!(
+            serde_json::from_value::<TdfPair<u16, u32>>(value).unwrap(),
+            pair
+        );
+
+        let triple = TdfTriple::new(1u16, 2u16, 3u64);
+        let value = serde_json::to_value(triple).unwrap();
+        assert_eq!(
+            serde_json::from_value::<TdfTriple<u16, u16, u64>>(value).unwrap(),
+            triple
+        );
+
+        let list: VarIntList<u32> = serde_json::from_value(json!([1, 2])).unwrap();
+        assert_eq!(list, VarIntList(vec![1, 2]));
+        let blob: Blob = serde_json::from_value(json!([1, 2])).unwrap();
+        assert_eq!(blob, Blob(vec![1, 2]));
+    }
+
+    /// Tests the hex and base64 helpers use strings for JSON while
+    /// keeping blobs raw in the Tdf format
+    #[test]
+    fn test_blob_helpers() {
+        use super::{de::from_bytes, ser::to_bytes};
+        use crate::types::Blob;
+        use serde::{Deserialize, Serialize};
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Ticket {
+            #[serde(rename = "HEX", with = "super::hex")]
+            hex: Blob,
+            #[serde(rename = "BASE", with = "super::base64")]
+            base64: Vec<u8>,
+        }
+
+        let value = Ticket {
+            hex: Blob(vec![0x01, 0xAB]),
+            base64: b"foo".to_vec(),
+        };
+        let json = serde_json::to_value(&value).unwrap();
+        assert_eq!(json, json!({ "HEX": "01ab", "BASE": "Zm9v" }));
+        assert_eq!(serde_json::from_value::<Ticket>(json).unwrap(), value);
+
+        let bytes = to_bytes(&value).unwrap();
+        let mut writer = TdfWriter::default();
+        writer.tag_value(b"HEX", &Blob(vec![0x01, 0xAB]));
+        writer.tag_value(b"BASE", &Blob(b"foo".to_vec()));
+        assert_eq!(bytes, writer.buffer);
+        assert_eq!(from_bytes::<Ticket>(&bytes).unwrap(), value);
+    }
+
+    /// Tests round tripping packets through JSON
+    #[test]
+    fn test_packet_json() {
+        use crate::packet::{Packet, PacketHeader};
+
+        let packet = Packet::raw(PacketHeader::request(1, 2, 3), vec![0x01, 0xAB, 0xFF]);
+        let value = serde_json::to_value(&packet).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "header": { "component": 2, "command": 3, "error": 0, "ty": 0, "id": 1 },
+                "contents": "01abff"
+            })
+        );
+        let decoded: Packet = serde_json::from_value(value).unwrap();
+        assert_eq!(decoded, packet);
+    }
+
+    /// Tests converting packet contents to JSON
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_contents_as_json() {
+        use crate::packet::{Packet, PacketHeader};
+
+        let (contents, expected) = test_contents();
+        let packet = Packet::raw(PacketHeader::notify(0, 0), contents);
+        assert_eq!(packet.contents_as_json().unwrap(), expected);
+    }
+
+    /// Tests the JSON debug formatting of packets
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_packet_json_debug() {
+        use crate::packet::{Packet, PacketHeader, PacketJsonDebug};
+
+        #[derive(Debug, Hash, PartialEq, Eq)]
+        struct Component;
+
+        impl crate::packet::PacketComponents for Component {
+            fn values(&self) -> (u16, u16) {
+                (1, 2)
+            }
+
+            fn from_values(_component: u16, _command: u16, _notify: bool) -> Option<Self> {
+                Some(Component)
+            }
+        }
+
+        let (contents, expected) = test_contents();
+        let packet = Packet::raw(PacketHeader::request(3, 1, 2), contents);
+        let debug = PacketJsonDebug {
+            packet: &packet,
+            component: Some(&Component),
+            minified: false,
+        };
+        assert_eq!(
+            debug.to_json(),
+            json!({
+                "component": "Component",
+                "type": "Request",
+                "id": 3,
+                "contents": expected
+            })
+        );
+
+        let packet = Packet::raw(PacketHeader::request(3, 1, 2), vec![0xFF]);
+        let debug = PacketJsonDebug::<Component> {
+            packet: &packet,
+            component: None,
+            minified: false,
+        };
+        let value = debug.to_json();
+        assert_eq!(value["raw"], "ff");
+        assert!(format!("{:?}", debug).contains("\"malformed\""));
     }
 }
@@ -0,0 +1,132 @@
+//! Serde helpers for serializing bytes as standard padded base64 strings
+//! in human-readable formats (e.g. JSON) while keeping them as raw bytes
+//! in binary formats such as the Tdf serializer.
+//!
+//! ```
+//! use blaze_pk::types::Blob;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Ticket {
+//!     #[serde(rename = "DATA", with = "blaze_pk::serialize::base64")]
+//!     data: Blob,
+//! }
+//! ```
+
+use super::BytesVisitor;
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+/// The standard base64 alphabet
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes the provided bytes as a padded base64 string
+///
+/// `bytes` The bytes to encode
+pub fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let value = (chunk[0] as u32) << 16
+            | (chunk.get(1).copied().unwrap_or_default() as u32) << 8
+            | chunk.get(2).copied().unwrap_or_default() as u32;
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (value >> (18 - index * 6)) & 0x3F;
+                out.push(ALPHABET[sextet as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes the provided padded base64 string into bytes returning
+/// None if the string is not valid base64
+///
+/// `value` The base64 string to decode
+pub fn decode(value: &str) -> Option<Vec<u8>> {
+    let bytes = value.as_bytes();
+    if !bytes.len().is_multiple_of(4) {
+        return None;
+    }
+
+    let mut out = Vec::with_capacity(bytes.len() / 4 * 3);
+    for (chunk_index, chunk) in bytes.chunks(4).enumerate() {
+        let last = chunk_index == bytes.len() / 4 - 1;
+        let padding = chunk.iter().rev().take_while(|byte| **byte == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+
+        let mut value: u32 = 0;
+        for byte in &chunk[..4 - padding] {
+            let sextet = ALPHABET.iter().position(|value| value == byte)? as u32;
+            value = value << 6 | sextet;
+        }
+        value <<= 6 * padding as u32;
+
+        let decoded = [(value >> 16) as u8, (value >> 8) as u8, value as u8];
+        out.extend_from_slice(&decoded[..3 - padding]);
+    }
+    Some(out)
+}
+
+/// Serializes the provided bytes as a base64 string for human-readable
+/// formats or as raw bytes otherwise
+///
+/// `value`      The bytes to serialize
+/// `serializer` The serializer to use
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<[u8]>,
+    S: Serializer,
+{
+    let value = value.as_ref();
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&encode(value))
+    } else {
+        serializer.serialize_bytes(value)
+    }
+}
+
+/// Deserializes bytes from a base64 string for human-readable formats
+/// or from raw bytes otherwise
+///
+/// `deserializer` The deserializer to use
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: From<Vec<u8>>,
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        let value = String::deserialize(deserializer)?;
+        decode(&value).map(T::from).ok_or_else(|| {
+            de::Error::invalid_value(de::Unexpected::Str(&value), &"a base64 string")
+        })
+    } else {
+        deserializer.deserialize_byte_buf(BytesVisitor).map(T::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, encode};
+
+    /// Tests encoding and decoding base64 with each amount of padding
+    #[test]
+    fn test_base64() {
+        let values: [(&[u8], &str); 4] = [
+            (b"", ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foobar", "Zm9vYmFy"),
+        ];
+        for (bytes, text) in values {
+            assert_eq!(encode(bytes), text);
+            assert_eq!(decode(text).as_deref(), Some(bytes));
+        }
+        assert!(decode("Zg=a").is_none());
+        assert!(decode("Zm9").is_none());
+    }
+}
@@ -0,0 +1,822 @@
+//! Serde [Deserializer] implementation for reading any [Deserialize] type
+//! from tagged Tdf values. This is the inverse of the [super::ser] module.
+//!
+//! Tags are matched to struct field names ignoring case so fields named
+//! after their tags or renamed using `#[serde(rename = "TAG")]` will be
+//! found. Fields that are missing are left to serde (Option fields are
+//! None) and fields with unknown tags are ignored unless the struct
+//! denies unknown fields.
+//!
+//! Strings and blobs are borrowed from the input buffer so types with
+//! `&'de str` or `&'de [u8]` fields can be deserialized without copying
+//! when using [from_bytes] or [from_reader].
+
+use crate::{error::DecodeError, reader::TdfReader, tag::TdfType, types::UNION_UNSET};
+use serde::de::{
+    self, value::StringDeserializer, DeserializeOwned, DeserializeSeed, EnumAccess,
+    IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor,
+};
+use serde::{forward_to_deserialize_any, Deserialize};
+use std::{borrow::Cow, error::Error, fmt::Display};
+
+/// Error type for errors that can occur while deserializing
+/// a value from Tdf
+#[derive(Debug)]
+pub enum DeserializeError {
+    /// Error from decoding the underlying Tdf values
+    Decode(DecodeError),
+    /// Custom error from the value being deserialized
+    Custom(String),
+}
+
+impl Error for DeserializeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DeserializeError::Decode(err) => Some(err),
+            DeserializeError::Custom(_) => None,
+        }
+    }
+}
+
+impl Display for DeserializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeserializeError::Decode(err) => Display::fmt(err, f),
+            DeserializeError::Custom(err) => f.write_str(err),
+        }
+    }
+}
+
+impl de::Error for DeserializeError {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Self::Custom(msg.to_string())
+    }
+}
+
+impl From<DecodeError> for DeserializeError {
+    fn from(value: DecodeError) -> Self {
+        Self::Decode(value)
+    }
+}
+
+/// Type alias for result which could result in a Deserialize Error
+pub type DeserializeResult<T> = Result<T, DeserializeError>;
+
+/// Deserializes a value from the tagged fields in the provided bytes
+///
+/// `bytes` The bytes to deserialize from
+pub fn from_bytes<'de, T>(bytes: &'de [u8]) -> DeserializeResult<T>
+where
+    T: Deserialize<'de>,
+{
+    let mut reader = TdfReader::new(bytes);
+    from_reader(&mut reader)
+}
+
+/// Deserializes a value from the remaining tagged fields in the
+/// provided reader
+///
+/// `reader` The reader to deserialize from
+pub fn from_reader<'de, T>(reader: &mut TdfReader<'de>) -> DeserializeResult<T>
+where
+    T: Deserialize<'de>,
+{
+    T::deserialize(Deserializer { reader })
+}
+
+/// Deserializes an owned value from the remaining tagged fields in
+/// the provided reader regardless of the reader lifetime
+///
+/// `reader` The reader to deserialize from
+pub fn from_reader_owned<T>(reader: &mut TdfReader) -> DeserializeResult<T>
+where
+    T: DeserializeOwned,
+{
+    T::deserialize(Deserializer { reader })
+}
+
+/// Deserializer for the top level tagged fields of a reader
+pub struct Deserializer<'a, 'de> {
+    /// The reader to read from
+    reader: &'a mut TdfReader<'de>,
+}
+
+impl<'a, 'de> Deserializer<'a, 'de> {
+    /// Creates a new deserializer over the remaining fields
+    /// of the provided reader
+    ///
+    /// `reader` The reader to read from
+    pub fn new(reader: &'a mut TdfReader<'de>) -> Self {
+        Self { reader }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'_, 'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(FieldsAccess::new(self.reader, &[], false))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(FieldsAccess::new(self.reader, fields, false))
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Deserializer for a single value of a known type
+struct ValueDeserializer<'a, 'de> {
+    /// The reader to read from
+    reader: &'a mut TdfReader<'de>,
+    /// The type of the value
+    ty: TdfType,
+}
+
+impl<'a, 'de> ValueDeserializer<'a, 'de> {
+    /// Ensures the value is of the provided type
+    ///
+    /// `expected` The expected type
+    fn expect(&self, expected: TdfType) -> DeserializeResult<()> {
+        if self.ty != expected {
+            return Err(DecodeError::InvalidType {
+                expected,
+                actual: self.ty,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Reads a var int value as a signed value
+    fn read_i64(self) -> DeserializeResult<i64> {
+        self.expect(TdfType::VarInt)?;
+        Ok(self.reader.read_u64()? as i64)
+    }
+}
+
+/// Implements the deserialize functions for signed integers which are
+/// stored as unsigned VarInts and must be cast back to be in range
+macro_rules! deserialize_signed {
+    ($($fn:ident => $visit:ident $ty:ty),*) => {
+        $(
+            fn $fn<V>(self, visitor: V) -> DeserializeResult<V::Value>
+            where
+                V: Visitor<'de>,
+            {
+                let value = self.read_i64()?;
+                visitor.$visit(value as $ty)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'_, 'de> {
+    type Error = DeserializeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let reader = self.reader;
+        match self.ty {
+            TdfType::VarInt | TdfType::TimeValue => visitor.visit_u64(reader.read_u64()?),
+            TdfType::String => match reader.read_str()? {
+                Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+                Cow::Owned(value) => visitor.visit_string(value),
+            },
+            TdfType::Blob => visitor.visit_borrowed_bytes(reader.read_blob()?),
+            TdfType::Group => {
+                reader.skip_group_2()?;
+                visitor.visit_map(FieldsAccess::new(reader, &[], true))
+            }
+            TdfType::List => {
+                let ty = reader.read_type()?;
+                let length = reader.read_usize()?;
+                visitor.visit_seq(ListAccess {
+                    reader,
+                    ty,
+                    remaining: length,
+                })
+            }
+            TdfType::Map => {
+                let key_ty = reader.read_type()?;
+                let value_ty = reader.read_type()?;
+                let length = reader.read_usize()?;
+                visitor.visit_map(EntriesAccess {
+                    reader,
+                    key_ty,
+                    value_ty,
+                    remaining: length,
+                })
+            }
+            TdfType::Union => {
+                let key = reader.read_byte()?;
+                if key == UNION_UNSET {
+                    return visitor.visit_none();
+                }
+                let tag = reader.read_tag()?;
+                visitor.visit_some(ValueDeserializer { reader, ty: tag.ty })
+            }
+            TdfType::VarIntList => {
+                let length = reader.read_usize()?;
+                visitor.visit_seq(ListAccess {
+                    reader,
+                    ty: TdfType::VarInt,
+                    remaining: length,
+                })
+            }
+            TdfType::Pair => visitor.visit_seq(ListAccess {
+                reader,
+                ty: TdfType::VarInt,
+                remaining: 2,
+            }),
+            TdfType::Triple => visitor.visit_seq(ListAccess {
+                reader,
+                ty: TdfType::VarInt,
+                remaining: 3,
+            }),
+            TdfType::Float => visitor.visit_f32(reader.read_f32()?),
+            TdfType::Generic => {
+                if reader.read_byte()? == 0 {
+                    reader.skip_group_end()?;
+                    return visitor.visit_none();
+                }
+                reader.skip_var_int();
+                let tag = reader.read_tag()?;
+                let value = visitor.visit_some(ValueDeserializer {
+                    reader: &mut *reader,
+                    ty: tag.ty,
+                })?;
+                reader.skip_group_end()?;
+                Ok(value)
+            }
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.expect(TdfType::VarInt)?;
+        visitor.visit_bool(self.reader.read_u64()? != 0)
+    }
+
+    deserialize_signed!(
+        deserialize_i8 => visit_i8 i8,
+        deserialize_i16 => visit_i16 i16,
+        deserialize_i32 => visit_i32 i32,
+        deserialize_i64 => visit_i64 i64
+    );
+
+    fn deserialize_option<V>(self, visitor: V) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        // Unset unions are treated as None values
+        if self.ty == TdfType::Union {
+            let reader = &mut *self.reader;
+            if reader.buffer.get(reader.cursor) == Some(&UNION_UNSET) {
+                reader.cursor += 1;
+                return visitor.visit_none();
+            }
+        }
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.reader.skip_type(&self.ty)?;
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.expect(TdfType::Group)?;
+        self.reader.skip_group_2()?;
+        visitor.visit_map(FieldsAccess::new(self.reader, fields, true))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        match self.ty {
+            TdfType::VarInt => {
+                let index = self.reader.read_u32()?;
+                visitor.visit_enum(VariantIndexAccess { index })
+            }
+            TdfType::Union => {
+                let key = self.reader.read_byte()?;
+                if key == UNION_UNSET {
+                    return Err(de::Error::custom("Unset union cannot be an enum variant"));
+                }
+                visitor.visit_enum(UnionAccess {
+                    reader: self.reader,
+                    key,
+                })
+            }
+            actual => Err(DecodeError::InvalidType {
+                expected: TdfType::Union,
+                actual,
+            }
+            .into()),
+        }
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        self.reader.skip_type(&self.ty)?;
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit seq tuple tuple_struct map identifier
+    }
+}
+
+/// Access to tagged fields as map entries with the tags as keys
+struct FieldsAccess<'a, 'de> {
+    /// The reader to read from
+    reader: &'a mut TdfReader<'de>,
+    /// The names of the struct fields to match tags against
+    fields: &'static [&'static str],
+    /// Whether the fields are within a group
+    group: bool,
+    /// The type of the value for the last read tag
+    ty: Option<TdfType>,
+}
+
+impl<'a, 'de> FieldsAccess<'a, 'de> {
+    /// Creates a new access over the fields in the reader
+    ///
+    /// `reader` The reader to read from
+    /// `fields` The struct field names
+    /// `group`  Whether the fields are within a group
+    fn new(reader: &'a mut TdfReader<'de>, fields: &'static [&'static str], group: bool) -> Self {
+        Self {
+            reader,
+            fields,
+            group,
+            ty: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for FieldsAccess<'_, 'de> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> DeserializeResult<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.group {
+            if self.reader.read_byte()? == 0 {
+                return Ok(None);
+            }
+            self.reader.cursor -= 1;
+        } else if self.reader.is_empty() {
+            return Ok(None);
+        }
+
+        let tagged = self.reader.read_tag()?;
+        self.ty = Some(tagged.ty);
+
+        let tag = tagged.tag.to_string();
+        let key = match self
+            .fields
+            .iter()
+            .find(|field| field.eq_ignore_ascii_case(&tag))
+        {
+            Some(field) => field.to_string(),
+            None => tag,
+        };
+
+        let key: StringDeserializer<DeserializeError> = key.into_deserializer();
+        seed.deserialize(key).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> DeserializeResult<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let ty = self
+            .ty
+            .take()
+            .ok_or_else(|| DeserializeError::Custom("Value read before key".to_string()))?;
+        seed.deserialize(ValueDeserializer {
+            reader: self.reader,
+            ty,
+        })
+    }
+}
+
+/// Access to the values of a list
+struct ListAccess<'a, 'de> {
+    /// The reader to read from
+    reader: &'a mut TdfReader<'de>,
+    /// The type of the list values
+    ty: TdfType,
+    /// The number of values remaining
+    remaining: usize,
+}
+
+impl<'de> SeqAccess<'de> for ListAccess<'_, 'de> {
+    type Error = DeserializeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> DeserializeResult<Option<T::Value>>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(ValueDeserializer {
+            reader: self.reader,
+            ty: self.ty,
+        })
+        .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Access to the entries of a map
+struct EntriesAccess<'a, 'de> {
+    /// The reader to read from
+    reader: &'a mut TdfReader<'de>,
+    /// The type of the map keys
+    key_ty: TdfType,
+    /// The type of the map values
+    value_ty: TdfType,
+    /// The number of entries remaining
+    remaining: usize,
+}
+
+impl<'de> MapAccess<'de> for EntriesAccess<'_, 'de> {
+    type Error = DeserializeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> DeserializeResult<Option<K::Value>>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(ValueDeserializer {
+            reader: self.reader,
+            ty: self.key_ty,
+        })
+        .map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> DeserializeResult<V::Value>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(ValueDeserializer {
+            reader: self.reader,
+            ty: self.value_ty,
+        })
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+/// Access to an enum variant encoded as a VarInt of its index
+struct VariantIndexAccess {
+    /// The variant index
+    index: u32,
+}
+
+impl<'de> EnumAccess<'de> for VariantIndexAccess {
+    type Error = DeserializeError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> DeserializeResult<(V::Value, Self::Variant)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let index: de::value::U32Deserializer<DeserializeError> = self.index.into_deserializer();
+        let value = seed.deserialize(index)?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for VariantIndexAccess {
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> DeserializeResult<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, _seed: T) -> DeserializeResult<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"newtype variant",
+        ))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"tuple variant",
+        ))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        Err(de::Error::invalid_type(
+            de::Unexpected::UnitVariant,
+            &"struct variant",
+        ))
+    }
+}
+
+/// Access to an enum variant encoded as a union keyed by
+/// the variant index
+struct UnionAccess<'a, 'de> {
+    /// The reader to read from
+    reader: &'a mut TdfReader<'de>,
+    /// The union key
+    key: u8,
+}
+
+impl<'a, 'de> EnumAccess<'de> for UnionAccess<'a, 'de> {
+    type Error = DeserializeError;
+    type Variant = ValueDeserializer<'a, 'de>;
+
+    fn variant_seed<V>(self, seed: V) -> DeserializeResult<(V::Value, Self::Variant)>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let key: de::value::U32Deserializer<DeserializeError> =
+            (self.key as u32).into_deserializer();
+        let value = seed.deserialize(key)?;
+        let tag = self.reader.read_tag()?;
+        Ok((
+            value,
+            ValueDeserializer {
+                reader: self.reader,
+                ty: tag.ty,
+            },
+        ))
+    }
+}
+
+impl<'de> VariantAccess<'de> for ValueDeserializer<'_, 'de> {
+    type Error = DeserializeError;
+
+    fn unit_variant(self) -> DeserializeResult<()> {
+        self.reader.skip_type(&self.ty)?;
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> DeserializeResult<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> DeserializeResult<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        de::Deserializer::deserialize_struct(self, "", fields, visitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{from_bytes, DeserializeError};
+    use crate::{
+        error::DecodeError,
+        serialize::ser::to_bytes,
+        types::{Blob, TdfMap, Union},
+        writer::TdfWriter,
+    };
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Target {
+        Name(String),
+        Id { id: u32 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Mode {
+        A,
+        B,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Inner {
+        #[serde(rename = "VALU")]
+        value: i32,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Test {
+        name: String,
+        #[serde(rename = "OPT")]
+        optional: Option<u8>,
+        list: Vec<u32>,
+        pair: (u16, u16),
+        map: TdfMap<String, u32>,
+        #[serde(rename = "GRP")]
+        inner: Inner,
+        #[serde(rename = "TGT")]
+        target: Target,
+        mode: Mode,
+        flag: bool,
+    }
+
+    /// Tests deserializing values that were encoded by hand
+    #[test]
+    fn test_deserialize() {
+        let mut map = TdfMap::<String, u32>::new();
+        map.insert("A", 1u32);
+
+        let mut writer = TdfWriter::default();
+        writer.tag_str(b"NAME", "Test");
+        writer.tag_u32(b"UNK", 1);
+        writer.tag_value(b"LIST", &vec![1u32, 2, 3]);
+        writer.tag_pair(b"PAIR", (1u16, 2u16));
+        writer.tag_value(b"MAP", &map);
+        writer.group(b"GRP", |writer| writer.tag_u64(b"VALU", -5i32 as u64));
+        writer.tag_value(b"TGT", &Union::set(0x0, b"VALU", "Test".to_string()));
+        writer.tag_u8(b"MODE", 1);
+        writer.tag_bool(b"FLAG", true);
+
+        let value: Test = from_bytes(&writer.buffer).unwrap();
+        assert_eq!(
+            value,
+            Test {
+                name: "Test".to_string(),
+                optional: None,
+                list: vec![1, 2, 3],
+                pair: (1, 2),
+                map,
+                inner: Inner { value: -5 },
+                target: Target::Name("Test".to_string()),
+                mode: Mode::B,
+                flag: true,
+            }
+        );
+    }
+
+    /// Tests round tripping values through the serializer
+    /// and deserializer
+    #[test]
+    fn test_round_trip() {
+        let value = Test {
+            name: "Test".to_string(),
+            optional: Some(5),
+            list: Vec::new(),
+            pair: (3, 4),
+            map: TdfMap::new(),
+            inner: Inner { value: 12 },
+            target: Target::Id { id: 7 },
+            mode: Mode::A,
+            flag: false,
+        };
+
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(from_bytes::<Test>(&bytes).unwrap(), value);
+    }
+
+    /// Tests decode failures are displayed and chained as the
+    /// error source
+    #[test]
+    fn test_error_source() {
+        use std::error::Error;
+
+        let err = from_bytes::<Test>(&[0x00]).unwrap_err();
+        assert!(matches!(err, DeserializeError::Decode(_)));
+        let source = err.source().unwrap();
+        assert_eq!(err.to_string(), source.to_string());
+        assert!(source.downcast_ref::<DecodeError>().is_some());
+    }
+
+    /// Tests strings and blobs are borrowed from the input buffer
+    #[test]
+    fn test_borrowed() {
+        #[derive(Deserialize)]
+        struct Borrowed<'a> {
+            #[serde(rename = "NAME")]
+            name: &'a str,
+            #[serde(rename = "DATA")]
+            data: &'a [u8],
+        }
+
+        let mut writer = TdfWriter::default();
+        writer.tag_str(b"NAME", "Test");
+        writer.tag_value(b"DATA", &Blob(vec![1, 2, 3]));
+
+        let bytes = writer.buffer;
+        let value: Borrowed = from_bytes(&bytes).unwrap();
+        assert_eq!(value.name, "Test");
+        assert_eq!(value.data, &[1, 2, 3]);
+        assert!(bytes.as_ptr_range().contains(&value.name.as_ptr()));
+        assert!(bytes.as_ptr_range().contains(&value.data.as_ptr()));
+    }
+}
@@ -0,0 +1,80 @@
+//! Serde helpers for serializing bytes as lowercase hex strings in
+//! human-readable formats (e.g. JSON) while keeping them as raw bytes
+//! in binary formats such as the Tdf serializer.
+//!
+//! ```
+//! use blaze_pk::types::Blob;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Ticket {
+//!     #[serde(rename = "DATA", with = "blaze_pk::serialize::hex")]
+//!     data: Blob,
+//! }
+//! ```
+
+use super::BytesVisitor;
+use serde::{de, Deserialize, Deserializer, Serializer};
+use std::fmt::Write;
+
+/// Encodes the provided bytes as a lowercase hex string
+///
+/// `bytes` The bytes to encode
+pub fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(out, "{:02x}", byte);
+    }
+    out
+}
+
+/// Decodes the provided hex string into bytes returning None
+/// if the string is not valid hex
+///
+/// `value` The hex string to decode
+pub fn decode(value: &str) -> Option<Vec<u8>> {
+    if !value.len().is_multiple_of(2) || !value.is_ascii() {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&value[index..index + 2], 16).ok())
+        .collect()
+}
+
+/// Serializes the provided bytes as a hex string for human-readable
+/// formats or as raw bytes otherwise
+///
+/// `value`      The bytes to serialize
+/// `serializer` The serializer to use
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<[u8]>,
+    S: Serializer,
+{
+    let value = value.as_ref();
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&encode(value))
+    } else {
+        serializer.serialize_bytes(value)
+    }
+}
+
+/// Deserializes bytes from a hex string for human-readable formats
+/// or from raw bytes otherwise
+///
+/// `deserializer` The deserializer to use
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: From<Vec<u8>>,
+    D: Deserializer<'de>,
+{
+    if deserializer.is_human_readable() {
+        let value = String::deserialize(deserializer)?;
+        decode(&value)
+            .map(T::from)
+            .ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(&value), &"a hex string"))
+    } else {
+        deserializer.deserialize_byte_buf(BytesVisitor).map(T::from)
+    }
+}
@@ -0,0 +1,693 @@
+//! Serde [Serializer] implementation for writing any [Serialize] type
+//! as tagged Tdf values.
+//!
+//! Struct field names are used as the tags for their values so they must
+//! be 1 to 4 alphanumeric characters (they are converted to uppercase).
+//! Use `#[serde(rename = "TAG")]` to map descriptive field names to tags.
+//!
+//! Values are mapped to the following Tdf types:
+//! - bool and integers are VarInts
+//! - f32 and f64 are Floats
+//! - chars and strings are Strings
+//! - byte slices (e.g. serde_bytes) are Blobs
+//! - structs are Groups (the top level struct is written as the fields)
+//! - sequences are Lists, 2 and 3 element tuples of integers are Pairs
+//!   and Triples
+//! - maps are Maps
+//! - unit enum variants are VarInts of the variant index
+//! - other enum variants are Unions keyed by the variant index with the
+//!   value under the `VALU` tag
+//! - None struct fields are omitted and Some values are written as the
+//!   inner value
+//!
+//! The type of empty lists and maps cannot be known so their values are
+//! written as VarInts
+
+use crate::{
+    codec::Encodable,
+    tag::{Tag, TdfType},
+    value::{TdfField, TdfFields, TdfValue},
+    writer::TdfWriter,
+};
+use serde::{
+    ser::{
+        SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+        SerializeTupleStruct, SerializeTupleVariant,
+    },
+    Serialize, Serializer,
+};
+use std::{error::Error, fmt::Display};
+
+/// Error type for errors that can occur while serializing
+/// a value to Tdf
+#[derive(Debug)]
+pub struct SerializeError(String);
+
+impl Error for SerializeError {}
+
+impl Display for SerializeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl serde::ser::Error for SerializeError {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        Self(msg.to_string())
+    }
+}
+
+/// Type alias for result which could result in a Serialize Error
+pub type SerializeResult<T> = Result<T, SerializeError>;
+
+/// Serializes the provided value into the tagged fields it
+/// is made up of. The value must be a struct
+///
+/// `value` The value to serialize
+pub fn to_fields<T>(value: &T) -> SerializeResult<TdfFields>
+where
+    T: Serialize + ?Sized,
+{
+    match value.serialize(ValueSerializer)? {
+        Some(TdfValue::Group { fields, .. }) => Ok(TdfFields(fields)),
+        _ => Err(SerializeError(
+            "Only structs can be serialized as top level values".to_string(),
+        )),
+    }
+}
+
+/// Serializes the provided value writing its tagged fields
+/// to the provided writer. The value must be a struct
+///
+/// `writer` The writer to write to
+/// `value`  The value to serialize
+pub fn to_writer<T>(writer: &mut TdfWriter, value: &T) -> SerializeResult<()>
+where
+    T: Serialize + ?Sized,
+{
+    let fields = to_fields(value)?;
+    fields.encode(writer);
+    Ok(())
+}
+
+/// Serializes the provided value into the bytes of its
+/// tagged fields. The value must be a struct
+///
+/// `value` The value to serialize
+pub fn to_bytes<T>(value: &T) -> SerializeResult<Vec<u8>>
+where
+    T: Serialize + ?Sized,
+{
+    let mut writer = TdfWriter::default();
+    to_writer(&mut writer, value)?;
+    Ok(writer.into())
+}
+
+/// Creates a tag from the provided struct field name
+///
+/// `name` The field name
+fn field_tag(name: &str) -> SerializeResult<Tag> {
+    let bytes = name.as_bytes();
+    if bytes.is_empty()
+        || bytes.len() > 4
+        || !bytes.iter().all(|value| value.is_ascii_alphanumeric())
+    {
+        return Err(SerializeError(format!(
+            "Field name '{}' is not a valid tag (1 to 4 alphanumeric characters)",
+            name
+        )));
+    }
+    Ok(Tag::from(bytes.to_ascii_uppercase().as_slice()))
+}
+
+/// Converts the provided enum variant index into a union key
+///
+/// `variant_index` The index of the variant
+fn union_key(variant_index: u32) -> SerializeResult<u8> {
+    match u8::try_from(variant_index) {
+        Ok(key) if key < crate::types::UNION_UNSET => Ok(key),
+        _ => Err(SerializeError(format!(
+            "Variant index {} is too large for a union key",
+            variant_index
+        ))),
+    }
+}
+
+/// Creates a union value for the provided enum variant
+///
+/// `variant_index` The index of the variant
+/// `value`         The value of the variant
+fn union_value(variant_index: u32, value: TdfValue) -> SerializeResult<TdfValue> {
+    Ok(TdfValue::Union {
+        key: union_key(variant_index)?,
+        value: Some(Box::new(TdfField {
+            tag: Tag::from(b"VALU"),
+            value,
+        })),
+    })
+}
+
+/// Unwraps the provided serialized value erroring if the value
+/// was None (Optional values are only allowed as struct fields)
+fn required(value: Option<TdfValue>) -> SerializeResult<TdfValue> {
+    value.ok_or_else(|| {
+        SerializeError("Optional values are only supported as struct fields".to_string())
+    })
+}
+
+/// Serializer which serializes values into dynamic [TdfValue]s. None
+/// values are serialized as None so that they can be omitted
+#[derive(Debug, Clone, Copy)]
+pub struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+    type Ok = Option<TdfValue>;
+    type Error = SerializeError;
+
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeVariant<SerializeList>;
+    type SerializeMap = SerializeTdfMap;
+    type SerializeStruct = SerializeGroup;
+    type SerializeStructVariant = SerializeVariant<SerializeGroup>;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, v: bool) -> SerializeResult<Self::Ok> {
+        Ok(Some(TdfValue::VarInt(v as u64)))
+    }
+
+    fn serialize_i8(self, v: i8) -> SerializeResult<Self::Ok> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> SerializeResult<Self::Ok> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> SerializeResult<Self::Ok> {
+        self.serialize_i64(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> SerializeResult<Self::Ok> {
+        Ok(Some(TdfValue::VarInt(v as u64)))
+    }
+
+    fn serialize_u8(self, v: u8) -> SerializeResult<Self::Ok> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u16(self, v: u16) -> SerializeResult<Self::Ok> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u32(self, v: u32) -> SerializeResult<Self::Ok> {
+        self.serialize_u64(v as u64)
+    }
+
+    fn serialize_u64(self, v: u64) -> SerializeResult<Self::Ok> {
+        Ok(Some(TdfValue::VarInt(v)))
+    }
+
+    fn serialize_f32(self, v: f32) -> SerializeResult<Self::Ok> {
+        Ok(Some(TdfValue::Float(v)))
+    }
+
+    fn serialize_f64(self, v: f64) -> SerializeResult<Self::Ok> {
+        self.serialize_f32(v as f32)
+    }
+
+    fn serialize_char(self, v: char) -> SerializeResult<Self::Ok> {
+        self.serialize_str(v.encode_utf8(&mut [0u8; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> SerializeResult<Self::Ok> {
+        Ok(Some(TdfValue::String(v.to_string())))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> SerializeResult<Self::Ok> {
+        Ok(Some(TdfValue::Blob(v.to_vec())))
+    }
+
+    fn serialize_none(self) -> SerializeResult<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> SerializeResult<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> SerializeResult<Self::Ok> {
+        Err(SerializeError(
+            "Unit values cannot be serialized".to_string(),
+        ))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> SerializeResult<Self::Ok> {
+        Ok(Some(TdfValue::Group {
+            two: false,
+            fields: Vec::new(),
+        }))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> SerializeResult<Self::Ok> {
+        self.serialize_u32(variant_index)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> SerializeResult<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> SerializeResult<Self::Ok>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = required(value.serialize(self)?)?;
+        union_value(variant_index, value).map(Some)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> SerializeResult<Self::SerializeSeq> {
+        Ok(SerializeList {
+            values: Vec::with_capacity(len.unwrap_or_default().min(4096)),
+            tuple: false,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> SerializeResult<Self::SerializeTuple> {
+        Ok(SerializeList {
+            values: Vec::with_capacity(len),
+            tuple: true,
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> SerializeResult<Self::SerializeTupleStruct> {
+        self.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> SerializeResult<Self::SerializeTupleVariant> {
+        Ok(SerializeVariant {
+            variant_index,
+            inner: self.serialize_tuple(len)?,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> SerializeResult<Self::SerializeMap> {
+        Ok(SerializeTdfMap {
+            entries: Vec::with_capacity(len.unwrap_or_default().min(4096)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> SerializeResult<Self::SerializeStruct> {
+        Ok(SerializeGroup {
+            fields: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        len: usize,
+    ) -> SerializeResult<Self::SerializeStructVariant> {
+        Ok(SerializeVariant {
+            variant_index,
+            inner: self.serialize_struct(name, len)?,
+        })
+    }
+}
+
+/// Serializer for sequences and tuples which produces lists
+/// or pairs and triples for tuples of VarInts
+pub struct SerializeList {
+    /// The serialized values
+    values: Vec<TdfValue>,
+    /// Whether the values are from a tuple
+    tuple: bool,
+}
+
+impl SerializeList {
+    /// Serializes the provided element adding it to the values
+    ///
+    /// `value` The element to serialize
+    fn push<T>(&mut self, value: &T) -> SerializeResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value = required(value.serialize(ValueSerializer)?)?;
+        self.values.push(value);
+        Ok(())
+    }
+
+    /// Creates the value from the serialized values
+    fn finish(self) -> SerializeResult<TdfValue> {
+        if self.tuple {
+            let values: Option<Vec<u64>> = self
+                .values
+                .iter()
+                .map(|value| match value {
+                    TdfValue::VarInt(value) => Some(*value),
+                    _ => None,
+                })
+                .collect();
+
+            match values.as_deref() {
+                Some(&[a, b]) => return Ok(TdfValue::Pair(a, b)),
+                Some(&[a, b, c]) => return Ok(TdfValue::Triple(a, b, c)),
+                _ => {}
+            }
+        }
+
+        let ty = values_type(&self.values)?;
+        Ok(TdfValue::List {
+            ty,
+            values: self.values,
+        })
+    }
+}
+
+/// Finds the type of the provided list of values ensuring that
+/// all the values are the same type. Empty lists use VarInt
+///
+/// `values` The values to find the type of
+fn values_type<'a, I>(values: I) -> SerializeResult<TdfType>
+where
+    I: IntoIterator<Item = &'a TdfValue>,
+{
+    let mut values = values.into_iter();
+    let ty = match values.next() {
+        Some(value) => value.ty(),
+        None => return Ok(TdfType::VarInt),
+    };
+    if values.any(|value| value.ty() != ty) {
+        return Err(SerializeError(
+            "All values in a list or map must be the same type".to_string(),
+        ));
+    }
+    Ok(ty)
+}
+
+impl SerializeSeq for SerializeList {
+    type Ok = Option<TdfValue>;
+    type Error = SerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> SerializeResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> SerializeResult<Self::Ok> {
+        self.finish().map(Some)
+    }
+}
+
+impl SerializeTuple for SerializeList {
+    type Ok = Option<TdfValue>;
+    type Error = SerializeError;
+
+    fn serialize_element<T>(&mut self, value: &T) -> SerializeResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> SerializeResult<Self::Ok> {
+        self.finish().map(Some)
+    }
+}
+
+impl SerializeTupleStruct for SerializeList {
+    type Ok = Option<TdfValue>;
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> SerializeResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> SerializeResult<Self::Ok> {
+        self.finish().map(Some)
+    }
+}
+
+/// Serializer for maps which produces Tdf maps
+pub struct SerializeTdfMap {
+    /// The serialized entries
+    entries: Vec<(TdfValue, TdfValue)>,
+    /// The key waiting for its value
+    key: Option<TdfValue>,
+}
+
+impl SerializeMap for SerializeTdfMap {
+    type Ok = Option<TdfValue>;
+    type Error = SerializeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> SerializeResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = required(key.serialize(ValueSerializer)?)?;
+        if !matches!(key.ty(), TdfType::VarInt | TdfType::String) {
+            return Err(SerializeError(
+                "Map keys must be strings or integers".to_string(),
+            ));
+        }
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> SerializeResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| SerializeError("Map value serialized before its key".to_string()))?;
+        let value = required(value.serialize(ValueSerializer)?)?;
+        self.entries.push((key, value));
+        Ok(())
+    }
+
+    fn end(self) -> SerializeResult<Self::Ok> {
+        let key_ty = values_type(self.entries.iter().map(|(key, _)| key))?;
+        let value_ty = values_type(self.entries.iter().map(|(_, value)| value))?;
+        Ok(Some(TdfValue::Map {
+            key_ty,
+            value_ty,
+            entries: self.entries,
+        }))
+    }
+}
+
+/// Serializer for structs which produces groups
+pub struct SerializeGroup {
+    /// The serialized fields
+    fields: Vec<TdfField>,
+}
+
+impl SerializeGroup {
+    /// Creates the group value from the serialized fields
+    fn finish(self) -> TdfValue {
+        TdfValue::Group {
+            two: false,
+            fields: self.fields,
+        }
+    }
+}
+
+impl SerializeStruct for SerializeGroup {
+    type Ok = Option<TdfValue>;
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> SerializeResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        let tag = field_tag(key)?;
+        // None values are omitted
+        if let Some(value) = value.serialize(ValueSerializer)? {
+            self.fields.push(TdfField { tag, value });
+        }
+        Ok(())
+    }
+
+    fn end(self) -> SerializeResult<Self::Ok> {
+        Ok(Some(self.finish()))
+    }
+}
+
+/// Serializer for enum variants with fields which wraps the
+/// serialized variant value in a union
+pub struct SerializeVariant<S> {
+    /// The index of the variant
+    variant_index: u32,
+    /// The serializer for the variant value
+    inner: S,
+}
+
+impl SerializeTupleVariant for SerializeVariant<SerializeList> {
+    type Ok = Option<TdfValue>;
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, value: &T) -> SerializeResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.inner.push(value)
+    }
+
+    fn end(self) -> SerializeResult<Self::Ok> {
+        union_value(self.variant_index, self.inner.finish()?).map(Some)
+    }
+}
+
+impl SerializeStructVariant for SerializeVariant<SerializeGroup> {
+    type Ok = Option<TdfValue>;
+    type Error = SerializeError;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> SerializeResult<()>
+    where
+        T: Serialize + ?Sized,
+    {
+        SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn end(self) -> SerializeResult<Self::Ok> {
+        union_value(self.variant_index, self.inner.finish()).map(Some)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::to_bytes;
+    use crate::{
+        reader::TdfReader,
+        types::{TdfMap, Union},
+        writer::TdfWriter,
+    };
+    use serde::Serialize;
+
+    #[derive(Serialize)]
+    enum Target {
+        Name(String),
+    }
+
+    #[derive(Serialize)]
+    struct Inner {
+        #[serde(rename = "VALU")]
+        value: u32,
+    }
+
+    #[derive(Serialize)]
+    struct Test {
+        #[serde(rename = "NAME")]
+        name: String,
+        #[serde(rename = "OPT")]
+        optional: Option<u8>,
+        #[serde(rename = "LIST")]
+        list: Vec<u32>,
+        #[serde(rename = "PAIR")]
+        pair: (u16, u16),
+        #[serde(rename = "MAP")]
+        map: TdfMap<String, u32>,
+        #[serde(rename = "GRP")]
+        inner: Inner,
+        #[serde(rename = "TGT")]
+        target: Target,
+        flag: bool,
+    }
+
+    /// Tests that serializing with serde produces the same bytes
+    /// as encoding the values by hand
+    #[test]
+    fn test_serialize() {
+        let mut map = TdfMap::<String, u32>::new();
+        map.insert("A", 1u32);
+
+        let value = Test {
+            name: "Test".to_string(),
+            optional: None,
+            list: vec![1, 2, 3],
+            pair: (1, 2),
+            map: map.clone(),
+            inner: Inner { value: 5 },
+            target: Target::Name("Test".to_string()),
+            flag: true,
+        };
+
+        let mut writer = TdfWriter::default();
+        writer.tag_str(b"NAME", "Test");
+        writer.tag_value(b"LIST", &vec![1u32, 2, 3]);
+        writer.tag_pair(b"PAIR", (1u16, 2u16));
+        writer.tag_value(b"MAP", &map);
+        writer.group(b"GRP", |writer| writer.tag_u32(b"VALU", 5));
+        writer.tag_value(b"TGT", &Union::set(0x0, b"VALU", "Test".to_string()));
+        writer.tag_bool(b"FLAG", true);
+
+        let bytes = to_bytes(&value).unwrap();
+        assert_eq!(bytes, writer.buffer);
+
+        let mut reader = TdfReader::new(&bytes);
+        assert_eq!(reader.tag::<String>(b"NAME").unwrap(), "Test");
+        assert!(reader.try_tag::<u8>(b"OPT").unwrap().is_none());
+    }
+
+    /// Tests that invalid field names are rejected
+    #[test]
+    fn test_invalid_tag() {
+        #[derive(Serialize)]
+        struct Invalid {
+            too_long: u32,
+        }
+
+        assert!(to_bytes(&Invalid { too_long: 1 }).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Integration with the tower ecosystem allowing a [`Router`] to be used
+//! as a tower [`Service`] so existing middleware (timeouts, load shedding,
+//! retries, etc) can be applied to Blaze routing
+//!
+//! ```
+//! use blaze_pk::{router::Router, session::SessionContext, PacketComponent, PacketComponents};
+//! use std::sync::{Arc, RwLock};
+//! use tower_layer::Identity;
+//!
+//! #[derive(Debug, Hash, PartialEq, Eq, PacketComponents)]
+//! enum Components {
+//!     #[component(target = 0x1)]
+//!     Counter(Counter),
+//! }
+//!
+//! #[derive(Debug, Hash, PartialEq, Eq, PacketComponent)]
+//! enum Counter {
+//!     #[command(target = 0x1)]
+//!     Increment,
+//! }
+//!
+//! type State = Arc<RwLock<u32>>;
+//!
+//! async fn increment(state: State) {
+//!     *state.write().unwrap() += 1;
+//! }
+//!
+//! let mut router: Router<Components, State> = Router::new();
+//! router.route_concurrent(Components::Counter(Counter::Increment), increment);
+//!
+//! let service = router
+//!     .into_service(State::default(), SessionContext::new(1))
+//!     .layer(Identity::new());
+//! ```
+//!
+//! [`Router`]: crate::router::Router
+
+use crate::{
+    marker::{BoxFuture, MaybeSend},
+    packet::{Packet, PacketComponents},
+    router::{HandleError, Router},
+    session::SessionContext,
+};
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Future produced by the router service which resolves to the
+/// response packet for the request
+pub type RouterServiceFuture = BoxFuture<'static, Result<Packet, HandleError>>;
+
+/// Tower service which handles packets using the concurrent routes of
+/// a router (See [Router::route_concurrent]). The state is cloned into
+/// each handler so it should use interior mutability.
+///
+/// Only the response packet is produced by the service, any additional
+/// notification packets produced by the handler are discarded. Use
+/// [Router::handle_concurrent] directly if these are required
+pub struct RouterService<C, S> {
+    /// The shared router
+    router: Arc<Router<C, S>>,
+    /// The state provided to the handlers
+    state: S,
+    /// The context of the session the service is for
+    ctx: SessionContext,
+}
+
+impl<C, S> RouterService<C, S> {
+    /// Creates a new router service from the provided
+    /// router, state and session context
+    ///
+    /// `router` The shared router
+    /// `state`  The state provided to the handlers
+    /// `ctx`    The context of the session the service is for
+    pub fn new(router: Arc<Router<C, S>>, state: S, ctx: SessionContext) -> Self {
+        Self { router, state, ctx }
+    }
+
+    /// Wraps this service with the provided tower layer
+    /// producing the layered service
+    ///
+    /// `layer` The layer to apply
+    pub fn layer<L>(self, layer: L) -> L::Service
+    where
+        L: Layer<Self>,
+    {
+        layer.layer(self)
+    }
+}
+
+impl<C, S> Clone for RouterService<C, S>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            router: self.router.clone(),
+            state: self.state.clone(),
+            ctx: self.ctx.clone(),
+        }
+    }
+}
+
+impl<C, S> Service<Packet> for RouterService<C, S>
+where
+    C: PacketComponents,
+    S: Clone + MaybeSend + 'static,
+{
+    type Response = Packet;
+    type Error = HandleError;
+    type Future = RouterServiceFuture;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, packet: Packet) -> Self::Future {
+        let result = self
+            .router
+            .handle_concurrent(&self.state, &self.ctx, &packet);
+        Box::pin(async move {
+            let responses = result?.await;
+            Ok(responses.response)
+        })
+    }
+}
+
+impl<C, S> Router<C, S>
+where
+    C: PacketComponents,
+    S: Clone + MaybeSend + 'static,
+{
+    /// Converts this router into a tower service which handles
+    /// packets using the concurrent routes with the provided state
+    /// and session context
+    ///
+    /// `state` The state provided to the handlers
+    /// `ctx`   The context of the session the service is for
+    pub fn into_service(self, state: S, ctx: SessionContext) -> RouterService<C, S> {
+        RouterService::new(Arc::new(self), state, ctx)
+    }
+}
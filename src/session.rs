@@ -0,0 +1,229 @@
+//! Per-session context provided to routes by the [`Router`] allowing
+//! handlers to identify the session a packet was received from without
+//! needing to store it within the state
+//!
+//! [`Router`]: crate::router::Router
+
+use crate::{
+    error::{DecodeError, DecodeResult},
+    packet::{FromRequest, Packet},
+};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt::Debug,
+    net::SocketAddr,
+    ops::Deref,
+    sync::{Arc, RwLock},
+};
+
+/// Type of the map used for storing user data on the context
+type DataMap = HashMap<TypeId, Box<dyn Any + Send + Sync>>;
+
+/// Context for the session a packet was received from. Contains the
+/// session ID, the peer address and a map of user data keyed by type.
+/// Cloning the context is cheap and clones share the same user data.
+///
+/// Handlers can access the context by taking it as their request
+/// type or by using [WithContext] to access it alongside a request
+///
+/// ```
+/// use blaze_pk::session::SessionContext;
+///
+/// struct PlayerId(u32);
+///
+/// let ctx = SessionContext::new(1).with_addr(([127, 0, 0, 1], 14219).into());
+/// ctx.insert(PlayerId(5));
+///
+/// assert_eq!(ctx.id(), 1);
+/// assert_eq!(ctx.with(|player: &PlayerId| player.0), Some(5));
+/// ```
+#[derive(Clone)]
+pub struct SessionContext {
+    /// The unique ID of the session
+    id: u32,
+    /// The address of the session peer if known
+    addr: Option<SocketAddr>,
+    /// The user data stored on the session
+    data: Arc<RwLock<DataMap>>,
+}
+
+impl SessionContext {
+    /// Creates a new session context for the session
+    /// with the provided ID
+    ///
+    /// `id` The unique ID of the session
+    pub fn new(id: u32) -> Self {
+        Self {
+            id,
+            addr: None,
+            data: Default::default(),
+        }
+    }
+
+    /// Sets the peer address of the session
+    ///
+    /// `addr` The address of the session peer
+    pub fn with_addr(mut self, addr: SocketAddr) -> Self {
+        self.addr = Some(addr);
+        self
+    }
+
+    /// Returns the unique ID of the session
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns the address of the session peer if known
+    pub fn addr(&self) -> Option<SocketAddr> {
+        self.addr
+    }
+
+    /// Inserts the provided value into the user data replacing
+    /// and returning any existing value of the same type
+    ///
+    /// `value` The value to insert
+    pub fn insert<T>(&self, value: T) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let mut data = self.data.write().unwrap_or_else(|err| err.into_inner());
+        data.insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|value| value.downcast().ok())
+            .map(|value| *value)
+    }
+
+    /// Removes and returns the value of the provided type from
+    /// the user data
+    pub fn remove<T>(&self) -> Option<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let mut data = self.data.write().unwrap_or_else(|err| err.into_inner());
+        data.remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok())
+            .map(|value| *value)
+    }
+
+    /// Checks whether the user data contains a value of the
+    /// provided type
+    pub fn contains<T>(&self) -> bool
+    where
+        T: Send + Sync + 'static,
+    {
+        let data = self.data.read().unwrap_or_else(|err| err.into_inner());
+        data.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Calls the provided function with a reference to the value of
+    /// the provided type from the user data returning the result of
+    /// the function or None if there is no value of that type
+    ///
+    /// `action` The function to call with the value
+    pub fn with<T, O>(&self, action: impl FnOnce(&T) -> O) -> Option<O>
+    where
+        T: Send + Sync + 'static,
+    {
+        let data = self.data.read().unwrap_or_else(|err| err.into_inner());
+        data.get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+            .map(action)
+    }
+
+    /// Returns a clone of the value of the provided type from the
+    /// user data
+    pub fn get<T>(&self) -> Option<T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        self.with(T::clone)
+    }
+}
+
+impl Debug for SessionContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionContext")
+            .field("id", &self.id)
+            .field("addr", &self.addr)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Request wrapper for accessing the session context alongside
+/// the request type for handlers
+///
+/// ```
+/// use blaze_pk::session::WithContext;
+///
+/// struct Req;
+/// struct Res;
+///
+/// async fn test(req: WithContext<Req>) -> Res {
+///     let session_id = req.ctx.id();
+///     Res {}
+/// }
+/// ```
+pub struct WithContext<T> {
+    /// The context of the session the request is from
+    pub ctx: SessionContext,
+    /// The decoded request type
+    pub req: T,
+}
+
+/// Deref implementation so that the request fields can be
+/// directly accessed
+impl<T> Deref for WithContext<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.req
+    }
+}
+
+/// From request implementation for taking the context of the session
+/// the request is from. Fails if the context is not available
+impl FromRequest for SessionContext {
+    fn from_request(_req: &Packet) -> DecodeResult<Self> {
+        Err(DecodeError::Other("Missing session context"))
+    }
+
+    fn from_request_context(_req: &Packet, ctx: Option<&SessionContext>) -> DecodeResult<Self> {
+        ctx.cloned()
+            .ok_or(DecodeError::Other("Missing session context"))
+    }
+}
+
+/// From request implementation for taking the context of the session
+/// alongside the request type
+impl<T: FromRequest> FromRequest for WithContext<T> {
+    fn from_request(req: &Packet) -> DecodeResult<Self> {
+        Self::from_request_context(req, None)
+    }
+
+    fn from_request_context(req: &Packet, ctx: Option<&SessionContext>) -> DecodeResult<Self> {
+        let inner = T::from_request_context(req, ctx)?;
+        let ctx = SessionContext::from_request_context(req, ctx)?;
+        Ok(Self { ctx, req: inner })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SessionContext;
+
+    /// Tests inserting, reading and removing user data from
+    /// a session context and its clones
+    #[test]
+    fn test_user_data() {
+        let ctx = SessionContext::new(1);
+        let other = ctx.clone();
+
+        assert_eq!(ctx.insert(5u32), None);
+        assert_eq!(other.get::<u32>(), Some(5));
+        assert_eq!(ctx.insert(6u32), Some(5));
+        assert!(!ctx.contains::<String>());
+
+        assert_eq!(other.remove::<u32>(), Some(6));
+        assert!(!ctx.contains::<u32>());
+    }
+}
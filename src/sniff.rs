@@ -0,0 +1,215 @@
+//! Sniffing of the protocol used by a connection ([`sniff`]) so servers can
+//! accept both Blaze packets and plain HTTP on the same listener such as the
+//! shared redirector port. The bytes read while sniffing are replayed by the
+//! returned [`SniffedStream`] so the connection can be handed to either
+//! protocol unconsumed
+//!
+//! ```
+//! use blaze_pk::sniff::{sniff, Protocol};
+//! use tokio::io::{AsyncRead, AsyncWrite};
+//!
+//! async fn accept<T: AsyncRead + AsyncWrite + Unpin>(io: T) -> std::io::Result<()> {
+//!     let (protocol, stream) = sniff(io).await?;
+//!     match protocol {
+//!         Protocol::Blaze => { /* Frame the stream with PacketCodec */ }
+//!         Protocol::Http => { /* Pass the stream to the HTTP server */ }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use bytes::{Buf, BytesMut};
+use std::{
+    future::poll_fn,
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// HTTP request methods which HTTP connections start with
+const HTTP_METHODS: &[&[u8]] = &[
+    b"GET ",
+    b"POST ",
+    b"PUT ",
+    b"HEAD ",
+    b"DELETE ",
+    b"OPTIONS ",
+    b"PATCH ",
+    b"CONNECT ",
+    b"TRACE ",
+];
+
+/// The number of bytes to read from the connection at a time
+const READ_CHUNK: usize = 16;
+
+/// Protocol used by a connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    /// Blaze packets
+    Blaze,
+    /// Plain HTTP requests
+    Http,
+}
+
+/// Classifies the protocol from the initial bytes of a connection
+/// returning None if more bytes are needed to tell
+///
+/// `bytes` The initial bytes of the connection
+pub fn classify(bytes: &[u8]) -> Option<Protocol> {
+    let mut partial = false;
+    for method in HTTP_METHODS {
+        if bytes.starts_with(method) {
+            return Some(Protocol::Http);
+        }
+        partial |= method.starts_with(bytes);
+    }
+    if partial {
+        None
+    } else {
+        Some(Protocol::Blaze)
+    }
+}
+
+/// Reads the initial bytes of the provided connection until the protocol
+/// can be classified. The bytes read are replayed by the returned stream.
+/// Connections that end before the protocol is known result in an error
+///
+/// `io` The connection to sniff
+pub async fn sniff<T>(mut io: T) -> io::Result<(Protocol, SniffedStream<T>)>
+where
+    T: AsyncRead + Unpin,
+{
+    let mut prefix = BytesMut::new();
+    let mut chunk = [0u8; READ_CHUNK];
+    loop {
+        if let Some(protocol) = classify(&prefix) {
+            return Ok((protocol, SniffedStream { prefix, io }));
+        }
+
+        let mut buf = ReadBuf::new(&mut chunk);
+        poll_fn(|cx| Pin::new(&mut io).poll_read(cx, &mut buf)).await?;
+        if buf.filled().is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Connection ended before protocol was known",
+            ));
+        }
+        prefix.extend_from_slice(buf.filled());
+    }
+}
+
+/// Connection which replays the bytes read while sniffing
+/// before reading from the underlying connection
+pub struct SniffedStream<T> {
+    /// The bytes read while sniffing that haven't been replayed
+    prefix: BytesMut,
+    /// The underlying connection
+    io: T,
+}
+
+impl<T> SniffedStream<T> {
+    /// Returns the bytes read while sniffing that haven't been replayed
+    pub fn prefix(&self) -> &[u8] {
+        &self.prefix
+    }
+
+    /// Returns a reference to the underlying connection
+    pub fn get_ref(&self) -> &T {
+        &self.io
+    }
+
+    /// Returns a mutable reference to the underlying connection
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.io
+    }
+
+    /// Consumes the stream returning the bytes that haven't been
+    /// replayed and the underlying connection
+    pub fn into_parts(self) -> (BytesMut, T) {
+        (self.prefix, self.io)
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for SniffedStream<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if this.prefix.is_empty() {
+            return Pin::new(&mut this.io).poll_read(cx, buf);
+        }
+        let length = buf.remaining().min(this.prefix.len());
+        buf.put_slice(&this.prefix[..length]);
+        this.prefix.advance(length);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for SniffedStream<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().io).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().io).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{classify, sniff, Protocol};
+    use crate::{
+        packet::{Packet, PacketHeader},
+        timer::test::poll_now,
+    };
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Tests classifying the initial bytes of connections
+    #[test]
+    fn test_classify() {
+        assert_eq!(classify(b"GET /redirector HTTP/1.1"), Some(Protocol::Http));
+        assert_eq!(classify(b"POST "), Some(Protocol::Http));
+        assert_eq!(classify(b""), None);
+        assert_eq!(classify(b"PO"), None);
+        assert_eq!(classify(b"OPTIONS"), None);
+        assert_eq!(classify(b"GETX"), Some(Protocol::Blaze));
+        assert_eq!(classify(&[0, 0, 0, 5]), Some(Protocol::Blaze));
+    }
+
+    /// Tests sniffed streams replay the bytes read while sniffing
+    #[test]
+    fn test_sniff() {
+        let request = b"GET /redirector/getServerInstance HTTP/1.1\r\n\r\n";
+        let (client, mut server) = tokio::io::duplex(1024);
+        poll_now(server.write_all(request)).unwrap();
+        let (protocol, mut stream) = poll_now(sniff(client)).unwrap();
+        assert_eq!(protocol, Protocol::Http);
+        assert!(!stream.prefix().is_empty());
+        let mut read = vec![0u8; request.len()];
+        poll_now(stream.read_exact(&mut read)).unwrap();
+        assert_eq!(&read, request);
+
+        let packet = Packet::raw(PacketHeader::request(1, 0x5, 0x1), vec![1, 2, 3]);
+        let (client, mut server) = tokio::io::duplex(1024);
+        poll_now(packet.write_async(&mut server)).unwrap();
+        let (protocol, mut stream) = poll_now(sniff(client)).unwrap();
+        assert_eq!(protocol, Protocol::Blaze);
+        assert_eq!(poll_now(Packet::read_async(&mut stream)).unwrap(), packet);
+
+        let (client, mut server) = tokio::io::duplex(1024);
+        poll_now(server.write_all(b"PO")).unwrap();
+        drop(server);
+        assert!(poll_now(sniff(client)).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+//! Traffic statistics ([`PacketStats`]) aggregated for each component and
+//! command. Stats can be fed packets from anywhere packets pass through
+//! such as the [`Router`] hooks and read as a [`StatsSnapshot`] for dashboards
+//!
+//! ```
+//! use blaze_pk::{packet::{Packet, PacketHeader}, stats::PacketStats};
+//! use std::sync::Arc;
+//!
+//! let stats = Arc::new(PacketStats::new());
+//!
+//! // Stats can be shared with router hooks
+//! // router.on_request({ let stats = stats.clone(); move |packet| stats.record(packet) });
+//! // router.on_response({ let stats = stats.clone(); move |packet| stats.record(packet) });
+//!
+//! let request = Packet::raw_empty(PacketHeader::request(1, 0x1, 0x28));
+//! stats.record(&request);
+//! stats.record(&Packet::raw_empty(request.header.with_error(0x2)));
+//!
+//! let snapshot = stats.snapshot();
+//! let command = snapshot.get(0x1, 0x28).unwrap();
+//! assert_eq!(command.requests, 1);
+//! assert_eq!(command.errors, 1);
+//! assert_eq!(command.error_rate(), 1.0);
+//! ```
+//!
+//! [`Router`]: crate::router::Router
+
+use crate::packet::{Packet, PacketType};
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Collector aggregating statistics about the packets it is
+/// fed. Recording only requires a shared reference so the
+/// collector can be shared between threads and hooks
+#[derive(Debug)]
+pub struct PacketStats {
+    /// The time the stats were created or last reset
+    started: Mutex<Instant>,
+    /// The stats for each component and command
+    commands: Mutex<HashMap<(u16, u16), CommandStats>>,
+}
+
+impl Default for PacketStats {
+    fn default() -> Self {
+        Self {
+            started: Mutex::new(Instant::now()),
+            commands: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl PacketStats {
+    /// Creates a new empty stats collector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the provided packet in the stats for its
+    /// component and command
+    ///
+    /// `packet` The packet to record
+    pub fn record(&self, packet: &Packet) {
+        let header = &packet.header;
+        let mut commands = self.commands.lock().unwrap_or_else(|err| err.into_inner());
+        let stats = commands
+            .entry((header.component, header.command))
+            .or_insert_with(|| CommandStats::new(header.component, header.command));
+
+        match header.ty {
+            PacketType::Request => stats.requests += 1,
+            PacketType::Response => stats.responses += 1,
+            PacketType::Notify => stats.notifies += 1,
+            PacketType::Error => stats.errors += 1,
+        }
+        stats.bytes += frame_length(packet) as u64;
+    }
+
+    /// Takes a snapshot of the current stats
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let elapsed = self
+            .started
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .elapsed();
+        let commands = self.commands.lock().unwrap_or_else(|err| err.into_inner());
+        let mut commands: Vec<CommandStats> = commands.values().cloned().collect();
+        commands.sort_by_key(|stats| (stats.component, stats.command));
+        StatsSnapshot { elapsed, commands }
+    }
+
+    /// Clears all the recorded stats
+    pub fn reset(&self) {
+        *self.started.lock().unwrap_or_else(|err| err.into_inner()) = Instant::now();
+        self.commands
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .clear();
+    }
+}
+
+/// Returns the length of the complete frame for a packet
+/// including the header
+///
+/// `packet` The packet
+fn frame_length(packet: &Packet) -> usize {
+    let length = packet.contents.len();
+    if length > 0xFFFF {
+        length + 14
+    } else {
+        length + 12
+    }
+}
+
+/// Statistics for a specific component and command
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CommandStats {
+    /// The component of the packets
+    pub component: u16,
+    /// The command of the packets
+    pub command: u16,
+    /// The number of request packets
+    pub requests: u64,
+    /// The number of response packets
+    pub responses: u64,
+    /// The number of notify packets
+    pub notifies: u64,
+    /// The number of error packets
+    pub errors: u64,
+    /// The total bytes of all the packet frames
+    pub bytes: u64,
+}
+
+impl CommandStats {
+    /// Creates new empty stats for the provided component and command
+    ///
+    /// `component` The component of the packets
+    /// `command`   The command of the packets
+    fn new(component: u16, command: u16) -> Self {
+        Self {
+            component,
+            command,
+            requests: 0,
+            responses: 0,
+            notifies: 0,
+            errors: 0,
+            bytes: 0,
+        }
+    }
+
+    /// Returns the total number of packets
+    pub fn packets(&self) -> u64 {
+        self.requests + self.responses + self.notifies + self.errors
+    }
+
+    /// Returns the fraction of replies (responses and errors) that
+    /// were errors. Zero when there are no replies
+    pub fn error_rate(&self) -> f64 {
+        let replies = self.responses + self.errors;
+        if replies == 0 {
+            0.0
+        } else {
+            self.errors as f64 / replies as f64
+        }
+    }
+
+    /// Adds the counts from other stats to these stats
+    ///
+    /// `other` The stats to add
+    fn add(&mut self, other: &CommandStats) {
+        self.requests += other.requests;
+        self.responses += other.responses;
+        self.notifies += other.notifies;
+        self.errors += other.errors;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Snapshot of the stats at a specific point in time
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StatsSnapshot {
+    /// The time elapsed since the stats were created or reset
+    pub elapsed: Duration,
+    /// The stats for each component and command ordered by
+    /// component then command
+    pub commands: Vec<CommandStats>,
+}
+
+impl StatsSnapshot {
+    /// Finds the stats for the provided component and command
+    ///
+    /// `component` The component of the packets
+    /// `command`   The command of the packets
+    pub fn get(&self, component: u16, command: u16) -> Option<&CommandStats> {
+        self.commands
+            .binary_search_by_key(&(component, command), |stats| {
+                (stats.component, stats.command)
+            })
+            .ok()
+            .map(|index| &self.commands[index])
+    }
+
+    /// Returns the combined stats for all the commands of a component
+    ///
+    /// `component` The component of the packets
+    pub fn component(&self, component: u16) -> CommandStats {
+        let mut total = CommandStats::new(component, 0);
+        self.commands
+            .iter()
+            .filter(|stats| stats.component == component)
+            .for_each(|stats| total.add(stats));
+        total
+    }
+
+    /// Returns the combined stats for all the commands. The component
+    /// and command of the returned stats are zero
+    pub fn total(&self) -> CommandStats {
+        let mut total = CommandStats::new(0, 0);
+        self.commands.iter().for_each(|stats| total.add(stats));
+        total
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PacketStats;
+    use crate::packet::{Packet, PacketHeader};
+
+    /// Tests packets are aggregated for each component and command
+    #[test]
+    fn test_packet_stats() {
+        let stats = PacketStats::new();
+        let request = PacketHeader::request(1, 0x1, 0x28);
+        stats.record(&Packet::raw(request, vec![0; 8]));
+        stats.record(&Packet::raw_empty(request.response()));
+        stats.record(&Packet::raw_empty(request.with_error(0x2)));
+        stats.record(&Packet::raw_empty(request.response()));
+        stats.record(&Packet::raw_empty(PacketHeader::notify(0x1, 0x2)));
+        stats.record(&Packet::raw_empty(PacketHeader::notify(0x4, 0x1)));
+
+        let snapshot = stats.snapshot();
+        let login = snapshot.get(0x1, 0x28).unwrap();
+        assert_eq!(login.packets(), 4);
+        assert_eq!(login.bytes, 20 + 12 * 3);
+        assert!((login.error_rate() - 1.0 / 3.0).abs() < f64::EPSILON);
+        assert!(snapshot.get(0x1, 0x3).is_none());
+
+        assert_eq!(snapshot.component(0x1).packets(), 5);
+        assert_eq!(snapshot.total().packets(), 6);
+        assert_eq!(snapshot.total().notifies, 2);
+
+        stats.reset();
+        assert!(stats.snapshot().commands.is_empty());
+    }
+}
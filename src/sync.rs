@@ -0,0 +1,216 @@
+//! Blocking framing of packets over [`std::io`] streams for simple tools,
+//! scripts and servers that don't use an async runtime. Packets can be read
+//! and written directly ([`read_packet`], [`write_packet`]) or through a
+//! buffered [`BlazeStream`]
+//!
+//! ```no_run
+//! use blaze_pk::{packet::{Packet, PacketHeader}, sync::BlazeStream};
+//! use std::net::TcpStream;
+//!
+//! let stream = TcpStream::connect("127.0.0.1:14219").unwrap();
+//! let mut stream = BlazeStream::new(stream);
+//! stream
+//!     .send(&Packet::raw_empty(PacketHeader::request(1, 0x9, 0x2)))
+//!     .unwrap();
+//! let response = stream.read_packet().unwrap();
+//! ```
+
+use crate::{
+    error::CodecError,
+    packet::{Packet, PacketCodec, PacketHeader, EXTENDED_FLAG, HEADER_LENGTH},
+};
+use bytes::{Bytes, BytesMut};
+use std::io::{self, Read, Write};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// The number of bytes to read from the stream at a time
+const READ_CHUNK: usize = 8 * 1024;
+
+/// Reads a single packet from the provided reader blocking until
+/// the whole packet has been read. Reaching the end of the reader
+/// before a whole packet is read results in an error
+///
+/// `reader` The reader to read from
+pub fn read_packet(reader: &mut impl Read) -> io::Result<Packet> {
+    let mut header = [0u8; HEADER_LENGTH + 2];
+    reader.read_exact(&mut header[..HEADER_LENGTH])?;
+    let header_length = if header[9] == EXTENDED_FLAG {
+        reader.read_exact(&mut header[HEADER_LENGTH..])?;
+        HEADER_LENGTH + 2
+    } else {
+        HEADER_LENGTH
+    };
+
+    let mut src = BytesMut::from(&header[..header_length]);
+    let (header, length) = PacketHeader::read(&mut src)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Incomplete packet header"))?;
+
+    let mut contents = vec![0u8; length];
+    reader.read_exact(&mut contents)?;
+    Ok(Packet {
+        header,
+        contents: Bytes::from(contents),
+    })
+}
+
+/// Writes the provided packet to the writer. Packets with contents
+/// too long to be described by the header result in an error
+///
+/// `writer` The writer to write to
+/// `packet` The packet to write
+pub fn write_packet(writer: &mut impl Write, packet: &Packet) -> io::Result<()> {
+    let mut dst = BytesMut::new();
+    packet.try_write(&mut dst).map_err(CodecError::from)?;
+    writer.write_all(&dst)
+}
+
+/// Buffered blocking stream of packets over an underlying
+/// stream such as a [`std::net::TcpStream`]. Written packets
+/// are buffered until the stream is flushed
+pub struct BlazeStream<S> {
+    /// The underlying stream
+    stream: S,
+    /// Buffer of bytes read but not yet decoded
+    read_buffer: BytesMut,
+    /// Buffer of encoded packets not yet written
+    write_buffer: BytesMut,
+}
+
+impl<S> BlazeStream<S> {
+    /// Creates a new stream over the provided underlying stream
+    ///
+    /// `stream` The underlying stream
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            read_buffer: BytesMut::new(),
+            write_buffer: BytesMut::new(),
+        }
+    }
+
+    /// Returns a reference to the underlying stream
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the underlying stream
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Consumes the stream returning the underlying stream. Any
+    /// buffered bytes that haven't been read or written are lost
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S: Read> BlazeStream<S> {
+    /// Reads the next packet from the stream blocking until a whole
+    /// packet is available. Reaching the end of the stream before a
+    /// whole packet is read results in an error
+    pub fn read_packet(&mut self) -> io::Result<Packet> {
+        self.try_read_packet()?.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::UnexpectedEof, "Stream ended before packet")
+        })
+    }
+
+    /// Reads the next packet from the stream blocking until a whole
+    /// packet is available. Returns None if the stream ended cleanly
+    /// between packets
+    pub fn try_read_packet(&mut self) -> io::Result<Option<Packet>> {
+        let mut chunk = [0u8; READ_CHUNK];
+        loop {
+            if let Some(packet) = PacketCodec.decode(&mut self.read_buffer)? {
+                return Ok(Some(packet));
+            }
+
+            let count = self.stream.read(&mut chunk)?;
+            if count == 0 {
+                if self.read_buffer.is_empty() {
+                    return Ok(None);
+                }
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Stream ended within packet",
+                ));
+            }
+            self.read_buffer.extend_from_slice(&chunk[..count]);
+        }
+    }
+}
+
+impl<S: Write> BlazeStream<S> {
+    /// Buffers the provided packet to be written when the
+    /// stream is next flushed
+    ///
+    /// `packet` The packet to write
+    pub fn write_packet(&mut self, packet: &Packet) -> io::Result<()> {
+        PacketCodec.encode(packet, &mut self.write_buffer)?;
+        Ok(())
+    }
+
+    /// Writes all the buffered packets to the underlying stream
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.stream.write_all(&self.write_buffer)?;
+        self.write_buffer.clear();
+        self.stream.flush()
+    }
+
+    /// Writes the provided packet and flushes the stream
+    ///
+    /// `packet` The packet to send
+    pub fn send(&mut self, packet: &Packet) -> io::Result<()> {
+        self.write_packet(packet)?;
+        self.flush()
+    }
+}
+
+impl<S: Read> Iterator for BlazeStream<S> {
+    type Item = io::Result<Packet>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.try_read_packet().transpose()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_packet, write_packet, BlazeStream};
+    use crate::packet::test::framing_packets;
+    use std::io::{Cursor, ErrorKind};
+
+    /// Tests packets written with write_packet can be read back
+    #[test]
+    fn test_read_write_packet() {
+        let packets = framing_packets();
+        let mut buffer = Vec::new();
+        for packet in &packets {
+            write_packet(&mut buffer, packet).unwrap();
+        }
+
+        let mut reader = Cursor::new(&buffer[..buffer.len() - 1]);
+        for packet in &packets[..packets.len() - 1] {
+            assert_eq!(&read_packet(&mut reader).unwrap(), packet);
+        }
+        let err = read_packet(&mut reader).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+
+    /// Tests the buffered stream reads and writes packets
+    #[test]
+    fn test_blaze_stream() {
+        let packets = framing_packets();
+        let mut stream = BlazeStream::new(Vec::new());
+        for packet in &packets {
+            stream.write_packet(packet).unwrap();
+        }
+        assert!(stream.get_ref().is_empty());
+        stream.flush().unwrap();
+
+        let written = stream.into_inner();
+        let stream = BlazeStream::new(Cursor::new(written));
+        let read: Vec<_> = stream.collect::<Result<_, _>>().unwrap();
+        assert_eq!(read, packets);
+    }
+}
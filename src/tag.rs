@@ -13,7 +13,7 @@ pub struct Tagged {
 }
 
 /// Decoded tag bytes type
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Tag(pub [u8; 4]);
 
 impl From<&[u8]> for Tag {
@@ -52,6 +52,7 @@ impl Display for Tag {
 /// Types from the Blaze packet system which are used to describe
 /// what data needs to be decoded.
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum TdfType {
     /// Variable length integer value
@@ -76,6 +77,12 @@ pub enum TdfType {
     Triple = 0x9,
     /// f32 value
     Float = 0xA,
+    /// Time value in microseconds encoded as a variable length
+    /// integer. Used by newer Blaze SDKs
+    TimeValue = 0xB,
+    /// Generic value tagged with its type id which may be unset.
+    /// Used by newer Blaze SDKs
+    Generic = 0xC,
 }
 
 /// Convert bytes back to tdf types
@@ -95,6 +102,8 @@ impl TryFrom<u8> for TdfType {
             0x8 => TdfType::Pair,
             0x9 => TdfType::Triple,
             0xA => TdfType::Float,
+            0xB => TdfType::TimeValue,
+            0xC => TdfType::Generic,
             ty => return Err(DecodeError::UnknownType { ty }),
         })
     }
@@ -0,0 +1,288 @@
+//! Registry of known field names for tags ([`TagNameRegistry`]) used when
+//! converting packet contents to strings (See [`TdfReader::stringify_with_names`]
+//! and [`PacketDebug`]) so that output can include meaningful names.
+//!
+//! Names are registered for each component and command at runtime which
+//! allows the names for titles with schemas that are only known at runtime
+//! to be loaded from data files
+//!
+//! ```
+//! use blaze_pk::{reader::TdfReader, tag_names::TagNames, writer::TdfWriter};
+//!
+//! let mut names = TagNames::new();
+//! names.insert(b"PNAM", "playerName");
+//!
+//! let mut writer = TdfWriter::default();
+//! writer.tag_str(b"PNAM", "Test");
+//!
+//! let mut out = String::new();
+//! TdfReader::new(&writer.buffer)
+//!     .stringify_with_names(&mut out, Some(&names))
+//!     .unwrap();
+//! assert_eq!(out, "  \"PNAM\" (playerName): \"Test\",\n");
+//! ```
+//!
+//! [`TdfReader::stringify_with_names`]: crate::reader::TdfReader::stringify_with_names
+//! [`PacketDebug`]: crate::packet::PacketDebug
+
+use crate::tag::Tag;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Arc, OnceLock, RwLock},
+};
+
+/// Collection of the known field names for tags
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagNames {
+    /// The field names for each tag
+    names: HashMap<Tag, Cow<'static, str>>,
+}
+
+impl TagNames {
+    /// Creates a new empty collection of names
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts the name for the provided tag replacing
+    /// any existing name
+    ///
+    /// `tag`  The tag to name
+    /// `name` The field name for the tag
+    pub fn insert(&mut self, tag: &[u8], name: impl Into<Cow<'static, str>>) {
+        self.names.insert(Tag::from(tag), name.into());
+    }
+
+    /// Looks up the name for the provided tag
+    ///
+    /// `tag` The tag to find the name of
+    pub fn get(&self, tag: &Tag) -> Option<&str> {
+        self.names.get(tag).map(|name| name.as_ref())
+    }
+
+    /// Returns the number of named tags
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Checks whether there are no named tags
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+impl<'a, N> FromIterator<(&'a [u8], N)> for TagNames
+where
+    N: Into<Cow<'static, str>>,
+{
+    fn from_iter<T: IntoIterator<Item = (&'a [u8], N)>>(iter: T) -> Self {
+        let mut names = Self::new();
+        for (tag, name) in iter {
+            names.insert(tag, name);
+        }
+        names
+    }
+}
+
+/// Registry mapping components and commands to the
+/// names of the tags used in their contents along with
+/// the names of the components and commands themselves
+#[derive(Debug, Clone, Default)]
+pub struct TagNameRegistry {
+    /// The tag names for each component and command
+    commands: HashMap<(u16, u16), Arc<TagNames>>,
+    /// The names of each component
+    component_names: HashMap<u16, Cow<'static, str>>,
+    /// The names of each component, command and whether the
+    /// command is a notification
+    command_names: HashMap<(u16, u16, bool), Cow<'static, str>>,
+}
+
+impl TagNameRegistry {
+    /// Creates a new empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the tag names for a component and command replacing
+    /// any existing names for the same component and command
+    ///
+    /// `component` The component of the packets
+    /// `command`   The command of the packets
+    /// `names`     The tag names
+    pub fn register(&mut self, component: u16, command: u16, names: TagNames) {
+        self.commands.insert((component, command), Arc::new(names));
+    }
+
+    /// Looks up the tag names for the provided component and command
+    ///
+    /// `component` The component of the packets
+    /// `command`   The command of the packets
+    pub fn get(&self, component: u16, command: u16) -> Option<Arc<TagNames>> {
+        self.commands.get(&(component, command)).cloned()
+    }
+
+    /// Merges the tag names for a component and command into any
+    /// existing names for the same component and command replacing
+    /// the names of tags that are already named
+    ///
+    /// `component` The component of the packets
+    /// `command`   The command of the packets
+    /// `names`     The tag names
+    pub fn merge(&mut self, component: u16, command: u16, names: TagNames) {
+        let existing = self.commands.entry((component, command)).or_default();
+        Arc::make_mut(existing).names.extend(names.names);
+    }
+
+    /// Sets the name of a component
+    ///
+    /// `component` The component ID
+    /// `name`      The name of the component
+    pub fn register_component(&mut self, component: u16, name: impl Into<Cow<'static, str>>) {
+        self.component_names.insert(component, name.into());
+    }
+
+    /// Sets the name of a command or notification
+    ///
+    /// `component` The component of the command
+    /// `command`   The command ID
+    /// `notify`    Whether the command is a notification
+    /// `name`      The name of the command
+    pub fn register_command(
+        &mut self,
+        component: u16,
+        command: u16,
+        notify: bool,
+        name: impl Into<Cow<'static, str>>,
+    ) {
+        self.command_names
+            .insert((component, command, notify), name.into());
+    }
+
+    /// Looks up the name of a component
+    ///
+    /// `component` The component ID
+    pub fn component_name(&self, component: u16) -> Option<&str> {
+        self.component_names
+            .get(&component)
+            .map(|name| name.as_ref())
+    }
+
+    /// Looks up the name of a command or notification
+    ///
+    /// `component` The component of the command
+    /// `command`   The command ID
+    /// `notify`    Whether the command is a notification
+    pub fn command_name(&self, component: u16, command: u16, notify: bool) -> Option<&str> {
+        self.command_names
+            .get(&(component, command, notify))
+            .map(|name| name.as_ref())
+    }
+}
+
+/// Returns the global tag name registry
+pub fn global_registry() -> &'static RwLock<TagNameRegistry> {
+    static REGISTRY: OnceLock<RwLock<TagNameRegistry>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Registers tag names for a component and command with the global registry
+///
+/// `component` The component of the packets
+/// `command`   The command of the packets
+/// `names`     The tag names
+pub fn register_tag_names(component: u16, command: u16, names: TagNames) {
+    let mut registry = global_registry()
+        .write()
+        .unwrap_or_else(|err| err.into_inner());
+    registry.register(component, command, names);
+}
+
+/// Looks up the tag names for a component and command in the global registry
+///
+/// `component` The component of the packets
+/// `command`   The command of the packets
+pub fn lookup_tag_names(component: u16, command: u16) -> Option<Arc<TagNames>> {
+    let registry = global_registry()
+        .read()
+        .unwrap_or_else(|err| err.into_inner());
+    registry.get(component, command)
+}
+
+/// Looks up the names of a component and command in the global
+/// registry returning the names that are known
+///
+/// `component` The component ID
+/// `command`   The command ID
+/// `notify`    Whether the command is a notification
+pub fn lookup_command_names(
+    component: u16,
+    command: u16,
+    notify: bool,
+) -> (Option<String>, Option<String>) {
+    let registry = global_registry()
+        .read()
+        .unwrap_or_else(|err| err.into_inner());
+    (
+        registry.component_name(component).map(str::to_string),
+        registry
+            .command_name(component, command, notify)
+            .map(str::to_string),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{lookup_tag_names, register_tag_names, TagNames};
+    use crate::{
+        packet::{Packet, PacketComponents, PacketDebug, PacketHeader},
+        tag::Tag,
+        writer::TdfWriter,
+    };
+
+    /// Component type without any known components
+    #[derive(Debug, Hash, PartialEq, Eq)]
+    struct Unknown;
+
+    impl PacketComponents for Unknown {
+        fn values(&self) -> (u16, u16) {
+            (0, 0)
+        }
+
+        fn from_values(_component: u16, _command: u16, _notify: bool) -> Option<Self> {
+            None
+        }
+    }
+
+    /// Tests registered names are included in the packet debug output
+    /// including the names of nested tags
+    #[test]
+    fn test_packet_debug_names() {
+        let names: TagNames = [(&b"USER"[..], "user"), (&b"NAME"[..], "name")]
+            .into_iter()
+            .collect();
+        assert_eq!(names.get(&Tag::from(b"USER")), Some("user"));
+
+        register_tag_names(0x7FFE, 0x1, names);
+        assert!(lookup_tag_names(0x7FFE, 0x1).is_some());
+        assert!(lookup_tag_names(0x7FFE, 0x2).is_none());
+
+        let mut writer = TdfWriter::default();
+        writer.group(b"USER", |writer| {
+            writer.tag_str(b"NAME", "Test");
+            writer.tag_u32(b"ID", 1);
+        });
+        let packet = Packet::raw(PacketHeader::notify(0x7FFE, 0x1), writer.into());
+
+        let debug = PacketDebug::<Unknown> {
+            packet: &packet,
+            component: None,
+            minified: false,
+        };
+        let output = format!("{:?}", debug);
+        assert!(output.contains("\"USER\" (user): {"));
+        assert!(output.contains("\"NAME\" (name): \"Test\""));
+        assert!(output.contains("\"ID\": 1"));
+    }
+}
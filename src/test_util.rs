@@ -0,0 +1,664 @@
+//! Utilities for testing encoding and decoding of packet definitions
+//! along with [proptest] strategies for generating wire values so that
+//! downstream definitions can be property tested in a single line.
+//!
+//! ```
+//! use blaze_pk::test_util::{assert_roundtrip, strategy};
+//! use proptest::prelude::*;
+//!
+//! assert_roundtrip(String::from("Test"));
+//!
+//! proptest!(|(value in strategy::var_int(), name in strategy::string())| {
+//!     assert_roundtrip(value);
+//!     assert_roundtrip(name);
+//! });
+//! ```
+
+use crate::{
+    annotate::{annotate, AnnotationKind},
+    codec::{Decodable, Encodable},
+    hexdump::parse_hex,
+    reader::TdfReader,
+    tag::TdfType,
+};
+use std::fmt::{Debug, Write};
+
+/// Asserts that the provided value encodes and decodes back into an
+/// equal value consuming all of the encoded bytes, and that encoding
+/// the decoded value produces the same bytes
+///
+/// `value` The value to round-trip
+#[track_caller]
+pub fn assert_roundtrip<T>(value: T)
+where
+    T: Encodable + Decodable + PartialEq + Debug,
+{
+    let bytes = value.encode_bytes();
+    let mut reader = TdfReader::new(&bytes);
+    let decoded = match T::decode(&mut reader) {
+        Ok(value) => value,
+        Err(err) => panic!("Failed to decode {:?} from {:02x?}: {}", value, bytes, err),
+    };
+    assert_eq!(decoded, value, "Decoded value differs from {:02x?}", bytes);
+    assert!(
+        reader.is_empty(),
+        "{} bytes remaining after decoding {:?}",
+        bytes.len() - reader.cursor,
+        value
+    );
+    assert_eq!(
+        decoded.encode_bytes(),
+        bytes,
+        "Decoded value encoded differently"
+    );
+}
+
+/// Asserts that a value encodes to the bytes described by the provided
+/// hex (See [`parse_hex`] for the accepted formats). On mismatch the
+/// fields of both encodings are listed with the differing fields marked
+///
+/// ```
+/// use blaze_pk::{assert_encodes_to, types::Blob};
+///
+/// assert_encodes_to!(String::from("Test"), "05 54 65 73 74 00");
+/// assert_encodes_to!(Blob(vec![1, 2]), "02 01 02");
+/// ```
+///
+/// [`parse_hex`]: crate::hexdump::parse_hex
+#[macro_export]
+macro_rules! assert_encodes_to {
+    ($value:expr, $hex:expr $(,)?) => {
+        $crate::test_util::assert_encodes_to(&$value, $hex)
+    };
+}
+
+/// Asserts that the provided value encodes to the bytes described by
+/// the provided hex (See [`assert_encodes_to!`])
+///
+/// `value` The value to encode
+/// `hex`   The hex of the expected bytes
+#[track_caller]
+pub fn assert_encodes_to<T: Encodable>(value: &T, hex: &str) {
+    let expected = match parse_hex(hex) {
+        Ok(value) => value,
+        Err(err) => panic!("Invalid expected hex: {}", err),
+    };
+    let actual = value.encode_bytes();
+    if actual[..] == expected[..] {
+        return;
+    }
+
+    let offset = actual
+        .iter()
+        .zip(expected.iter())
+        .position(|(a, b)| a != b)
+        .unwrap_or(actual.len().min(expected.len()));
+
+    let mut message = String::from("Encoded bytes don't match expected\n");
+    let _ = writeln!(message, "Expected: {}", hex_string(&expected));
+    let _ = writeln!(message, "Actual:   {}", hex_string(&actual));
+    let _ = writeln!(message, "First difference at offset {:#x}", offset);
+
+    let expected_fields = leaf_fields(&expected);
+    let actual_fields = leaf_fields(&actual);
+    if !expected_fields.is_empty() || !actual_fields.is_empty() {
+        message.push_str("Fields:\n");
+        let width = expected_fields
+            .iter()
+            .chain(actual_fields.iter())
+            .map(|(path, _)| path.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut paths: Vec<&String> = expected_fields.iter().map(|(path, _)| path).collect();
+        for (path, _) in &actual_fields {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+
+        let find = |fields: &[(String, String)], path: &String| {
+            fields
+                .iter()
+                .find(|(other, _)| other == path)
+                .map(|(_, hex)| hex.clone())
+        };
+
+        for path in paths {
+            match (find(&expected_fields, path), find(&actual_fields, path)) {
+                (Some(a), Some(b)) if a == b => {
+                    let _ = writeln!(message, "  {:width$} {}", path, a);
+                }
+                (Some(a), Some(b)) => {
+                    let _ = writeln!(message, "~ {:width$} - {}", path, a);
+                    let _ = writeln!(message, "  {:width$} + {}", "", b);
+                }
+                (Some(a), None) => {
+                    let _ = writeln!(message, "- {:width$} {}", path, a);
+                }
+                (None, Some(b)) => {
+                    let _ = writeln!(message, "+ {:width$} {}", path, b);
+                }
+                (None, None) => {}
+            }
+        }
+    }
+
+    panic!("{}", message);
+}
+
+/// Annotates the provided bytes returning the path and value hex of
+/// each field that doesn't contain other fields
+///
+/// `bytes` The encoded bytes
+fn leaf_fields(bytes: &[u8]) -> Vec<(String, String)> {
+    let annotated = annotate(bytes);
+    annotated
+        .annotations
+        .iter()
+        .filter_map(|annotation| {
+            if let AnnotationKind::Value(ty) = annotation.kind {
+                let leaf = !matches!(
+                    ty,
+                    TdfType::Group
+                        | TdfType::List
+                        | TdfType::Map
+                        | TdfType::Union
+                        | TdfType::Generic
+                ) || !annotation.complete;
+                if leaf {
+                    let path = annotation.path.to_string();
+                    return Some((path, hex_string(&bytes[annotation.range.clone()])));
+                }
+            }
+            None
+        })
+        .collect()
+}
+
+/// Formats the provided bytes as space separated hex
+///
+/// `bytes` The bytes to format
+fn hex_string(bytes: &[u8]) -> String {
+    let hex: Vec<String> = bytes.iter().map(|value| format!("{:02x}", value)).collect();
+    hex.join(" ")
+}
+
+/// Strategies for generating wire values
+pub mod strategy {
+    use crate::{
+        packet::{PacketHeader, PacketType},
+        tag::{Tag, TdfType},
+        types::UNION_UNSET,
+        value::{TdfField, TdfValue},
+    };
+    use proptest::{collection::vec, prelude::*};
+
+    /// Strategy for var int values of any size
+    pub fn var_int() -> impl Strategy<Value = u64> {
+        any::<u64>()
+    }
+
+    /// Strategy for strings
+    pub fn string() -> impl Strategy<Value = String> {
+        any::<String>()
+    }
+
+    /// Strategy for blobs of bytes
+    pub fn blob() -> impl Strategy<Value = Vec<u8>> {
+        vec(any::<u8>(), 0..64)
+    }
+
+    /// Strategy for floats
+    pub fn float() -> impl Strategy<Value = f32> {
+        any::<f32>()
+    }
+
+    /// Strategy for pairs of var ints
+    pub fn pair() -> impl Strategy<Value = (u64, u64)> {
+        (var_int(), var_int())
+    }
+
+    /// Strategy for triples of var ints
+    pub fn triple() -> impl Strategy<Value = (u64, u64, u64)> {
+        (var_int(), var_int(), var_int())
+    }
+
+    /// Strategy for lists of var ints
+    pub fn var_int_list() -> impl Strategy<Value = Vec<u64>> {
+        vec(var_int(), 0..16)
+    }
+
+    /// Strategy for tags of 1 to 4 uppercase letters which
+    /// are the tags that can be encoded
+    pub fn tag() -> impl Strategy<Value = Tag> {
+        "[A-Z]{1,4}".prop_map(|value| Tag::from(value.as_bytes()))
+    }
+
+    /// Strategy for value types
+    pub fn tdf_type() -> impl Strategy<Value = TdfType> {
+        prop_oneof![
+            Just(TdfType::VarInt),
+            Just(TdfType::String),
+            Just(TdfType::Blob),
+            Just(TdfType::Group),
+            Just(TdfType::List),
+            Just(TdfType::Map),
+            Just(TdfType::Union),
+            Just(TdfType::VarIntList),
+            Just(TdfType::Pair),
+            Just(TdfType::Triple),
+            Just(TdfType::Float),
+            Just(TdfType::TimeValue),
+            Just(TdfType::Generic),
+        ]
+    }
+
+    /// Strategy for packet headers
+    pub fn packet_header() -> impl Strategy<Value = PacketHeader> {
+        let ty = prop_oneof![
+            Just(PacketType::Request),
+            Just(PacketType::Response),
+            Just(PacketType::Notify),
+            Just(PacketType::Error),
+        ];
+        (any::<u16>(), any::<u16>(), any::<u16>(), ty, any::<u16>()).prop_map(
+            |(component, command, error, ty, id)| PacketHeader {
+                component,
+                command,
+                error,
+                ty,
+                id,
+            },
+        )
+    }
+
+    /// Strategy for values that don't contain nested values
+    pub fn scalar_value() -> impl Strategy<Value = TdfValue> {
+        prop_oneof![
+            var_int().prop_map(TdfValue::VarInt),
+            string().prop_map(TdfValue::String),
+            blob().prop_map(TdfValue::Blob),
+            var_int_list().prop_map(TdfValue::VarIntList),
+            pair().prop_map(|(a, b)| TdfValue::Pair(a, b)),
+            triple().prop_map(|(a, b, c)| TdfValue::Triple(a, b, c)),
+            float().prop_map(TdfValue::Float),
+            var_int().prop_map(TdfValue::TimeValue),
+        ]
+    }
+
+    /// Strategy for dynamic values including nested groups, lists,
+    /// maps, unions and generics. Lists and map values always match their
+    /// declared types
+    pub fn tdf_value() -> impl Strategy<Value = TdfValue> {
+        scalar_value().prop_recursive(4, 64, 8, |inner| {
+            let field = (tag(), inner.clone())
+                .prop_map(|(tag, value)| TdfField { tag, value })
+                .boxed();
+            prop_oneof![
+                (any::<bool>(), vec(field.clone(), 0..8))
+                    .prop_map(|(two, fields)| TdfValue::Group { two, fields }),
+                vec(inner.clone(), 0..8).prop_map(|values| {
+                    let (ty, values) = same_type(values);
+                    TdfValue::List { ty, values }
+                }),
+                vec((string(), inner), 0..8).prop_map(|entries| {
+                    let (keys, values): (Vec<_>, Vec<_>) = entries.into_iter().unzip();
+                    let (value_ty, values) = same_type(values);
+                    TdfValue::Map {
+                        key_ty: TdfType::String,
+                        value_ty,
+                        entries: keys.into_iter().map(TdfValue::String).zip(values).collect(),
+                    }
+                }),
+                (any::<u8>(), field.clone()).prop_map(|(key, field)| TdfValue::Union {
+                    key,
+                    value: (key != UNION_UNSET).then(|| Box::new(field)),
+                }),
+                (any::<Option<u64>>(), field).prop_map(|(id, field)| {
+                    TdfValue::Generic(id.map(|id| (id, Box::new(field))))
+                }),
+            ]
+        })
+    }
+
+    /// Strategy for tagged dynamic fields
+    pub fn tdf_field() -> impl Strategy<Value = TdfField> {
+        (tag(), tdf_value()).prop_map(|(tag, value)| TdfField { tag, value })
+    }
+
+    /// Filters the provided values to only those with the same type
+    /// as the first value returning the type and the values
+    ///
+    /// `values` The values to filter
+    fn same_type(mut values: Vec<TdfValue>) -> (TdfType, Vec<TdfValue>) {
+        let ty = values.first().map(TdfValue::ty).unwrap_or(TdfType::VarInt);
+        values.retain(|value| value.ty() == ty);
+        (ty, values)
+    }
+}
+
+/// Golden tests for directories of packet frame fixtures. Each fixture is a
+/// `.bin` file containing the raw frame bytes or a `.hex` file containing
+/// a hex dump of the frame (See [`parse_hex`]) alongside a `.json` file with
+/// the same name containing the expected output of [`decode_frame_to_json`].
+///
+/// Setting the `BLAZE_PK_BLESS` environment variable writes the actual
+/// output to the `.json` files instead of comparing them, which is used to
+/// create the expected output for new fixtures.
+///
+/// ```no_run
+/// blaze_pk::test_util::fixtures::assert_fixtures("tests/fixtures");
+/// ```
+///
+/// [`parse_hex`]: crate::hexdump::parse_hex
+/// [`decode_frame_to_json`]: crate::json::decode_frame_to_json
+#[cfg(feature = "json")]
+pub mod fixtures {
+    use crate::{hexdump::parse_hex, json::decode_frame_to_json};
+    use serde_json::Value;
+    use std::{
+        fmt::Display,
+        fs, io,
+        path::{Path, PathBuf},
+    };
+
+    /// Environment variable which enables writing the expected output
+    pub const BLESS_VAR: &str = "BLAZE_PK_BLESS";
+
+    /// Frame fixture loaded from a fixture directory
+    #[derive(Debug, Clone)]
+    pub struct Fixture {
+        /// The name of the fixture (The file name without the extension)
+        pub name: String,
+        /// The bytes of the frame
+        pub frame: Vec<u8>,
+        /// The path to the expected output
+        pub expected_path: PathBuf,
+        /// The expected output if the file exists
+        pub expected: Option<Value>,
+    }
+
+    /// Reason a fixture failed
+    #[derive(Debug)]
+    pub enum FixtureFailure {
+        /// The frame could not be decoded
+        Decode {
+            /// The name of the fixture
+            name: String,
+            /// The decoding error message
+            error: String,
+        },
+        /// There was no expected output for the fixture
+        MissingExpected {
+            /// The name of the fixture
+            name: String,
+        },
+        /// The decoded output didn't match the expected output
+        Mismatch {
+            /// The name of the fixture
+            name: String,
+            /// The expected output
+            expected: Value,
+            /// The actual output
+            actual: Value,
+        },
+    }
+
+    impl Display for FixtureFailure {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            let pretty = |value: &Value| serde_json::to_string_pretty(value).unwrap_or_default();
+            match self {
+                FixtureFailure::Decode { name, error } => {
+                    write!(f, "{}: Failed to decode frame: {}", name, error)
+                }
+                FixtureFailure::MissingExpected { name } => write!(
+                    f,
+                    "{}: Missing expected output (Set {} to create it)",
+                    name, BLESS_VAR
+                ),
+                FixtureFailure::Mismatch {
+                    name,
+                    expected,
+                    actual,
+                } => write!(
+                    f,
+                    "{}: Output doesn't match\nExpected: {}\nActual: {}",
+                    name,
+                    pretty(expected),
+                    pretty(actual)
+                ),
+            }
+        }
+    }
+
+    /// Loads the fixtures from the provided directory ordered by name
+    ///
+    /// `dir` The fixture directory
+    pub fn load_fixtures(dir: impl AsRef<Path>) -> io::Result<Vec<Fixture>> {
+        let mut fixtures = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let extension = path.extension().and_then(|value| value.to_str());
+            let frame = match extension {
+                Some("bin") => fs::read(&path)?,
+                Some("hex") => parse_hex(&fs::read_to_string(&path)?)
+                    .map_err(|err| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("{}: {}", path.display(), err),
+                        )
+                    })?
+                    .to_vec(),
+                _ => continue,
+            };
+
+            let name = path
+                .file_stem()
+                .map(|value| value.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let expected_path = path.with_extension("json");
+            let expected = match fs::read_to_string(&expected_path) {
+                Ok(value) => Some(serde_json::from_str(&value).map_err(|err| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{}: {}", expected_path.display(), err),
+                    )
+                })?),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+                Err(err) => return Err(err),
+            };
+
+            fixtures.push(Fixture {
+                name,
+                frame,
+                expected_path,
+                expected,
+            });
+        }
+        fixtures.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(fixtures)
+    }
+
+    /// Decodes each of the fixtures in the provided directory comparing
+    /// the output with the expected output returning the failures. When
+    /// [`BLESS_VAR`] is set the expected output is written instead
+    ///
+    /// `dir` The fixture directory
+    pub fn check_fixtures(dir: impl AsRef<Path>) -> io::Result<Vec<FixtureFailure>> {
+        let bless = std::env::var_os(BLESS_VAR).is_some();
+        let mut failures = Vec::new();
+        for fixture in load_fixtures(dir)? {
+            let actual = match decode_frame_to_json(&fixture.frame) {
+                Ok(value) => value,
+                Err(err) => {
+                    failures.push(FixtureFailure::Decode {
+                        name: fixture.name,
+                        error: err.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            if bless {
+                let json = serde_json::to_string_pretty(&actual).map_err(io::Error::other)?;
+                fs::write(&fixture.expected_path, json + "\n")?;
+                continue;
+            }
+
+            match fixture.expected {
+                Some(expected) if expected == actual => {}
+                Some(expected) => failures.push(FixtureFailure::Mismatch {
+                    name: fixture.name,
+                    expected,
+                    actual,
+                }),
+                None => failures.push(FixtureFailure::MissingExpected { name: fixture.name }),
+            }
+        }
+        Ok(failures)
+    }
+
+    /// Asserts that all the fixtures in the provided directory
+    /// decode to their expected output (See [`check_fixtures`])
+    ///
+    /// `dir` The fixture directory
+    #[track_caller]
+    pub fn assert_fixtures(dir: impl AsRef<Path>) {
+        let dir = dir.as_ref();
+        let failures = match check_fixtures(dir) {
+            Ok(value) => value,
+            Err(err) => panic!("Failed to load fixtures from {}: {}", dir.display(), err),
+        };
+        if !failures.is_empty() {
+            let messages: Vec<String> = failures.iter().map(ToString::to_string).collect();
+            panic!(
+                "{} fixtures failed in {}:\n{}",
+                failures.len(),
+                dir.display(),
+                messages.join("\n")
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{assert_roundtrip, strategy};
+    use crate::{
+        codec::{Decodable, Encodable},
+        reader::TdfReader,
+        value::TdfValue,
+    };
+    use proptest::prelude::*;
+
+    proptest! {
+        /// Tests generated primitive values round-trip
+        #[test]
+        fn test_primitive_roundtrip(
+            value in strategy::var_int(),
+            name in strategy::string(),
+            pair in strategy::pair(),
+        ) {
+            assert_roundtrip(value);
+            assert_roundtrip(name);
+            assert_roundtrip(pair);
+        }
+
+        /// Tests generated dynamic values encode to bytes
+        /// that decode to the same bytes
+        #[test]
+        fn test_value_roundtrip(value in strategy::tdf_value()) {
+            let bytes = value.try_encode_bytes().unwrap();
+            let decoded = TdfValue::decode_type(&mut TdfReader::new(&bytes), value.ty()).unwrap();
+            prop_assert_eq!(decoded.encode_bytes(), bytes);
+        }
+    }
+
+    /// Tests checking a directory of fixtures
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_fixtures() {
+        use super::fixtures::{check_fixtures, FixtureFailure};
+        use crate::packet::{Packet, PacketHeader};
+        use bytes::BytesMut;
+        use std::fs;
+
+        let dir = std::env::temp_dir().join(format!("blaze-pk-fixtures-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut writer = crate::writer::TdfWriter::default();
+        writer.tag_str(b"NAME", "Test");
+        let mut frame = BytesMut::new();
+        Packet::raw(PacketHeader::notify(0x1, 0x2), writer.into()).write(&mut frame);
+
+        fs::write(dir.join("a.bin"), &frame).unwrap();
+        let expected = crate::json::decode_frame_to_json(&frame).unwrap();
+        fs::write(dir.join("a.json"), expected.to_string()).unwrap();
+
+        let hex: Vec<String> = frame.iter().map(|value| format!("{:02x}", value)).collect();
+        fs::write(dir.join("b.hex"), hex.join(" ")).unwrap();
+        fs::write(dir.join("b.json"), "{}").unwrap();
+        fs::write(dir.join("c.bin"), &frame[..4]).unwrap();
+        fs::write(dir.join("d.hex"), hex.join(" ")).unwrap();
+
+        let failures = check_fixtures(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(failures.len(), 3, "{:?}", failures);
+        assert!(matches!(&failures[0], FixtureFailure::Mismatch { name, .. } if name == "b"));
+        assert!(matches!(&failures[1], FixtureFailure::Decode { name, .. } if name == "c"));
+        assert!(matches!(&failures[2], FixtureFailure::MissingExpected { name } if name == "d"));
+    }
+
+    /// Tests the encoded bytes are compared against the hex and that
+    /// mismatches list the differing fields
+    #[test]
+    fn test_assert_encodes_to() {
+        use crate::writer::TdfWriter;
+
+        let mut writer = TdfWriter::default();
+        writer.tag_u8(b"ID", 1);
+        writer.tag_str(b"NAME", "A");
+        let contents =
+            crate::value::TdfFields::decode(&mut TdfReader::new(&writer.buffer)).unwrap();
+        crate::assert_encodes_to!(contents, "a6 40 00 00 01 ba 1b 65 01 02 41 00");
+
+        let result = std::panic::catch_unwind(|| {
+            crate::assert_encodes_to!(contents, "a6 40 00 00 02 ba 1b 65 01 02 42 00")
+        });
+        let message = result.unwrap_err();
+        let message = message.downcast_ref::<String>().unwrap();
+        assert!(
+            message.contains("First difference at offset 0x4"),
+            "{}",
+            message
+        );
+        assert!(message.contains("~ ID   - 02\n"), "{}", message);
+        assert!(message.contains("~ NAME - 02 42 00\n"), "{}", message);
+    }
+
+    /// Tests values that don't round-trip are caught
+    #[test]
+    #[should_panic]
+    fn test_roundtrip_mismatch() {
+        /// Value which always decodes as zero
+        #[derive(Debug, PartialEq)]
+        struct Broken(u32);
+
+        impl Encodable for Broken {
+            fn encode(&self, writer: &mut crate::writer::TdfWriter) {
+                writer.write_u32(self.0);
+            }
+        }
+
+        impl Decodable for Broken {
+            fn decode(reader: &mut TdfReader) -> crate::error::DecodeResult<Self> {
+                reader.read_u32()?;
+                Ok(Broken(0))
+            }
+        }
+
+        assert_roundtrip(Broken(5));
+    }
+}
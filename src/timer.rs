@@ -0,0 +1,172 @@
+//! Runtime agnostic timers ([`Timer`]) used for timeouts and expiry so the
+//! parts of the crate that need to wait aren't tied to a specific runtime.
+//! Implementations are provided for tokio ([`TokioTimer`]) with the
+//! `tokio-time` feature and for async-std and smol ([`AsyncIoTimer`]) with
+//! the `async-io` feature
+//!
+//! ```
+//! use blaze_pk::{marker::BoxFuture, timer::{timeout, Timer}};
+//! use std::time::Duration;
+//!
+//! /// Timer which never completes
+//! struct NeverTimer;
+//!
+//! impl Timer for NeverTimer {
+//!     fn sleep(&self, _duration: Duration) -> BoxFuture<'static, ()> {
+//!         Box::pin(std::future::pending())
+//!     }
+//! }
+//!
+//! async fn run() {
+//!     let value = timeout(&NeverTimer, Duration::from_secs(5), async { 5 }).await;
+//!     assert_eq!(value, Ok(5));
+//! }
+//! ```
+
+use crate::marker::{BoxFuture, MaybeSend, MaybeSync};
+use std::{
+    error::Error,
+    fmt::Display,
+    future::{poll_fn, Future},
+    pin::pin,
+    sync::Arc,
+    task::Poll,
+    time::Duration,
+};
+
+/// Timer for waiting on durations within a specific runtime
+pub trait Timer: MaybeSend + MaybeSync {
+    /// Returns a future which completes once the provided
+    /// duration has passed
+    ///
+    /// `duration` The duration to wait for
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+impl<T: Timer + ?Sized> Timer for Arc<T> {
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        (**self).sleep(duration)
+    }
+}
+
+/// Error for futures that didn't complete before their timeout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl Error for Elapsed {}
+
+impl Display for Elapsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Timed out")
+    }
+}
+
+/// Waits for the provided future to complete within the duration
+/// using the provided timer. Futures that take longer result in
+/// an [`Elapsed`] error and are dropped
+///
+/// `timer`    The timer to wait with
+/// `duration` The duration the future must complete within
+/// `fut`      The future to wait for
+pub async fn timeout<T, F>(timer: &T, duration: Duration, fut: F) -> Result<F::Output, Elapsed>
+where
+    T: Timer + ?Sized,
+    F: Future,
+{
+    let mut fut = pin!(fut);
+    let mut sleep = timer.sleep(duration);
+    poll_fn(|cx| {
+        if let Poll::Ready(value) = fut.as_mut().poll(cx) {
+            return Poll::Ready(Ok(value));
+        }
+        sleep.as_mut().poll(cx).map(|_| Err(Elapsed))
+    })
+    .await
+}
+
+/// Timer using the tokio runtime
+#[cfg(feature = "tokio-time")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokioTimer;
+
+#[cfg(feature = "tokio-time")]
+impl Timer for TokioTimer {
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+/// Timer using async-io which drives the timers
+/// for the async-std and smol runtimes
+#[cfg(feature = "async-io")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AsyncIoTimer;
+
+#[cfg(feature = "async-io")]
+impl Timer for AsyncIoTimer {
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(async move {
+            async_io::Timer::after(duration).await;
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{timeout, Elapsed, Timer};
+    use crate::marker::BoxFuture;
+    use std::{
+        future::{pending, ready, Future},
+        pin::pin,
+        task::{Context, Poll, Waker},
+        time::Duration,
+    };
+
+    /// Timer for tests which either completes
+    /// immediately or never completes
+    pub(crate) struct TestTimer {
+        /// Whether sleeping completes immediately
+        pub(crate) elapsed: bool,
+    }
+
+    impl Timer for TestTimer {
+        fn sleep(&self, _duration: Duration) -> BoxFuture<'static, ()> {
+            if self.elapsed {
+                Box::pin(ready(()))
+            } else {
+                Box::pin(pending())
+            }
+        }
+    }
+
+    /// Polls the provided future which must complete without waiting
+    ///
+    /// `fut` The future to poll
+    pub(crate) fn poll_now<F: Future>(fut: F) -> F::Output {
+        let mut cx = Context::from_waker(Waker::noop());
+        match pin!(fut).poll(&mut cx) {
+            Poll::Ready(value) => value,
+            Poll::Pending => panic!("Future wasn't ready"),
+        }
+    }
+
+    /// Tests futures complete or time out depending on the timer
+    #[test]
+    fn test_timeout() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let duration = Duration::from_secs(1);
+
+        let timer = TestTimer { elapsed: false };
+        let fut = pin!(timeout(&timer, duration, ready(5)));
+        assert_eq!(fut.poll(&mut cx), Poll::Ready(Ok(5)));
+        let mut fut = pin!(timeout(&timer, duration, pending::<u32>()));
+        assert!(fut.as_mut().poll(&mut cx).is_pending());
+
+        let timer = TestTimer { elapsed: true };
+        let fut = pin!(timeout(&timer, duration, pending::<u32>()));
+        assert_eq!(fut.poll(&mut cx), Poll::Ready(Err(Elapsed)));
+        // Completed futures take priority over the timer
+        let fut = pin!(timeout(&timer, duration, ready(5)));
+        assert_eq!(fut.poll(&mut cx), Poll::Ready(Ok(5)));
+    }
+}
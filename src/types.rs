@@ -1,19 +1,25 @@
 //! Types implementation for custom types used while encoding values
 //! with Blaze packets
 
-use crate::codec::{Decodable, Encodable, ValueType};
-use crate::error::{DecodeError, DecodeResult};
+use crate::codec::{BorrowDecodable, Decodable, Encodable, ValueType};
+use crate::error::{DecodeError, DecodeResult, EncodeResult};
 use crate::reader::TdfReader;
 use crate::tag::{Tag, TdfType};
 use crate::value_type;
-use crate::writer::TdfWriter;
+use crate::writer::{var_int_length, TdfWriter};
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
+use std::hash::Hash;
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::num::{NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8};
+use std::ops::{Deref, DerefMut, Index, IndexMut};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::{slice, vec};
 
 /// List of Var ints
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct VarIntList<T>(pub Vec<T>);
 
 impl<T> Default for VarIntList<T> {
@@ -64,9 +70,68 @@ impl<T> VarIntList<T> {
     /// a borrow if one is there
     ///
     /// `index` The index to get the value at
-    pub fn get(&mut self, index: usize) -> Option<&T> {
+    pub fn get(&self, index: usize) -> Option<&T> {
         self.0.get(index)
     }
+
+    /// Creates a new iterator over the values in the list
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.0.iter()
+    }
+}
+
+/// Deref implementation so that the list can be used like
+/// the underlying vec
+impl<T> Deref for VarIntList<T> {
+    type Target = Vec<T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for VarIntList<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> From<Vec<T>> for VarIntList<T> {
+    fn from(value: Vec<T>) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> From<VarIntList<T>> for Vec<T> {
+    fn from(value: VarIntList<T>) -> Self {
+        value.0
+    }
+}
+
+impl<T> FromIterator<T> for VarIntList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self(Vec::from_iter(iter))
+    }
+}
+
+/// Into iterator implementation for owned lists
+impl<T> IntoIterator for VarIntList<T> {
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// Into iterator implementation for borrowed lists
+impl<'a, T> IntoIterator for &'a VarIntList<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
 }
 
 impl<C> Encodable for VarIntList<C>
@@ -79,6 +144,10 @@ where
             value.encode(output);
         }
     }
+
+    fn size_hint(&self) -> usize {
+        var_int_length(self.0.len() as u64) + self.0.iter().map(C::size_hint).sum::<usize>()
+    }
 }
 
 impl<C> Decodable for VarIntList<C>
@@ -103,7 +172,7 @@ impl<C> ValueType for VarIntList<C> {
 
 /// Type that can be unset or contain a pair of key
 /// values
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Union<C> {
     /// Set variant of a union value
     Set { key: u8, tag: Tag, value: C },
@@ -145,17 +214,74 @@ impl<C> Union<C> {
             Self::Set { value, .. } => value,
         }
     }
+
+    /// Takes the underlying value stored in this union returning
+    /// None if the union is unset
+    pub fn into_value(self) -> Option<C> {
+        match self {
+            Self::Set { value, .. } => Some(value),
+            Self::Unset => None,
+        }
+    }
+
+    /// Returns a reference to the underlying value stored in this
+    /// union or None if the union is unset
+    pub fn value(&self) -> Option<&C> {
+        match self {
+            Self::Set { value, .. } => Some(value),
+            Self::Unset => None,
+        }
+    }
+
+    /// Converts from `&Union<C>` to `Union<&C>` keeping the
+    /// key and tag of the union
+    pub fn as_ref(&self) -> Union<&C> {
+        match self {
+            Self::Set { key, tag, value } => Union::Set {
+                key: *key,
+                tag: *tag,
+                value,
+            },
+            Self::Unset => Union::Unset,
+        }
+    }
+
+    /// Maps the value stored in this union using the provided
+    /// function keeping the key and tag of the union
+    ///
+    /// `f` The function to map the value with
+    pub fn map<U, F>(self, f: F) -> Union<U>
+    where
+        F: FnOnce(C) -> U,
+    {
+        match self {
+            Self::Set { key, tag, value } => Union::Set {
+                key,
+                tag,
+                value: f(value),
+            },
+            Self::Unset => Union::Unset,
+        }
+    }
 }
 
-impl<C> From<Union<C>> for Option<C> {
-    fn from(value: Union<C>) -> Self {
+/// Creates a set union from the key, tag and value or
+/// an unset union if there is no value
+impl<C> From<Option<(u8, &str, C)>> for Union<C> {
+    fn from(value: Option<(u8, &str, C)>) -> Self {
         match value {
-            Union::Set { value, .. } => Some(value),
-            Union::Unset => None,
+            Some((key, tag, value)) => Self::set(key, tag.as_bytes(), value),
+            None => Self::Unset,
         }
     }
 }
 
+impl<C> From<Union<C>> for Option<C> {
+    fn from(value: Union<C>) -> Self {
+        value.into_value()
+    }
+}
+
 impl<C> ValueType for Union<C> {
     fn value_type() -> TdfType {
         TdfType::Union
@@ -176,6 +302,28 @@ where
             Union::Unset => output.write_byte(UNION_UNSET),
         }
     }
+
+    fn try_encode(&self, output: &mut TdfWriter) -> EncodeResult<()> {
+        match self {
+            Union::Set { key, tag, value } => {
+                output.write_byte(*key);
+                output.try_tag(&tag.0, C::value_type())?;
+                value.try_encode(output)
+            }
+            Union::Unset => {
+                output.write_byte(UNION_UNSET);
+                Ok(())
+            }
+        }
+    }
+
+    fn size_hint(&self) -> usize {
+        match self {
+            // Key, tag and value
+            Union::Set { value, .. } => 5 + value.size_hint(),
+            Union::Unset => 1,
+        }
+    }
 }
 
 impl<C> Decodable for Union<C>
@@ -209,11 +357,125 @@ where
 /// Key value for unions that are unset
 pub const UNION_UNSET: u8 = 0x7F;
 
+/// Macro for creating union types where each union key maps to its own
+/// value type. Generates an enum with a variant for each key along with
+/// an additional `Unset` variant and implements [Encodable], [Decodable]
+/// and [ValueType] for it. Decoding a key that isn't listed produces an
+/// error
+///
+/// ```
+/// use blaze_pk::{tdf_union, types::VarIntList};
+///
+/// tdf_union! {
+///     /// Union of either a name or a list of IDs
+///     #[derive(Debug, PartialEq)]
+///     pub enum Target {
+///         /// Target by name
+///         0x0 => Name(String) = b"NAME",
+///         /// Target by list of IDs
+///         0x1 => Ids(VarIntList<u32>) = b"IDS",
+///     }
+/// }
+///
+/// let value = Target::Name("Test".to_string());
+/// let unset = Target::Unset;
+/// ```
+#[macro_export]
+macro_rules! tdf_union {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $key:literal => $variant:ident($ty:ty) = $tag:literal
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant($ty),
+            )*
+            /// Unset variant of the union
+            Unset,
+        }
+
+        impl $crate::codec::Encodable for $name {
+            fn encode(&self, output: &mut $crate::writer::TdfWriter) {
+                match self {
+                    $(
+                        Self::$variant(value) => {
+                            output.write_byte($key);
+                            output.tag($tag, <$ty as $crate::codec::ValueType>::value_type());
+                            $crate::codec::Encodable::encode(value, output);
+                        }
+                    )*
+                    Self::Unset => output.write_byte($crate::types::UNION_UNSET),
+                }
+            }
+
+            fn size_hint(&self) -> usize {
+                match self {
+                    $(
+                        Self::$variant(value) => 5 + $crate::codec::Encodable::size_hint(value),
+                    )*
+                    Self::Unset => 1,
+                }
+            }
+        }
+
+        impl $crate::codec::Decodable for $name {
+            fn decode(
+                reader: &mut $crate::reader::TdfReader,
+            ) -> $crate::error::DecodeResult<Self> {
+                let key = reader.read_byte()?;
+                if key == $crate::types::UNION_UNSET {
+                    return Ok(Self::Unset);
+                }
+                let tag = reader.read_tag()?;
+                match key {
+                    $(
+                        $key => {
+                            let expected = <$ty as $crate::codec::ValueType>::value_type();
+                            if tag.ty != expected {
+                                return Err($crate::error::DecodeError::InvalidType {
+                                    expected,
+                                    actual: tag.ty,
+                                });
+                            }
+                            let value = <$ty as $crate::codec::Decodable>::decode(reader)?;
+                            Ok(Self::$variant(value))
+                        }
+                    )*
+                    _ => Err($crate::error::DecodeError::Other("Unknown union key")),
+                }
+            }
+        }
+
+        impl $crate::codec::ValueType for $name {
+            fn value_type() -> $crate::tag::TdfType {
+                $crate::tag::TdfType::Union
+            }
+        }
+    };
+}
+
 /// Trait implemented by VarInt types
 pub trait VarInt: PartialEq + Eq + Debug + Encodable + Decodable {}
 
 /// Trait that must be implemented on a type for it to
-/// be considered a map key
+/// be considered a map key. Map keys should only ever be
+/// strings or VarInts, custom key types that encode as
+/// either can implement this trait to be used as keys
+///
+/// ```compile_fail
+/// use blaze_pk::{codec::Encodable, types::TdfMap};
+///
+/// // Floats are not valid map keys
+/// let map = TdfMap::<f32, u32>::new();
+/// map.encode_bytes();
+/// ```
 pub trait MapKey: PartialEq + Eq + Debug {}
 
 impl MapKey for &'_ str {}
@@ -225,17 +487,22 @@ macro_rules! impl_var_int {
     ($($ty:ty),*) => { $(impl VarInt for $ty {})* };
 }
 
-impl_var_int!(u8, i8, u16, i16, u32, i32, u64, i64, usize, isize);
+impl_var_int!(
+    u8, i8, u16, i16, u32, i32, u64, i64, usize, isize, NonZeroU8, NonZeroU16, NonZeroU32,
+    NonZeroU64
+);
 
 /// Structure for maps used in the protocol. These maps have a special
 /// order that is usually required and they retain the order of insertion
 /// because it uses two vecs as the underlying structure
+#[derive(PartialEq, Eq, Hash)]
 pub struct TdfMap<K, V> {
     /// The entries stored in this map
     entries: Vec<MapEntry<K, V>>,
 }
 
 /// Entry within a TdfMap storing a key value pair
+#[derive(PartialEq, Eq, Hash)]
 struct MapEntry<K, V> {
     /// Entry key
     key: K,
@@ -325,6 +592,22 @@ impl<K, V> TdfMap<K, V> {
         }
     }
 
+    /// Consumes the map returning an iterator over its keys
+    /// in order
+    pub fn into_keys(self) -> MapIntoKeys<K, V> {
+        MapIntoKeys {
+            inner: self.entries.into_iter(),
+        }
+    }
+
+    /// Consumes the map returning an iterator over its values
+    /// in order
+    pub fn into_values(self) -> MapIntoValues<K, V> {
+        MapIntoValues {
+            inner: self.entries.into_iter(),
+        }
+    }
+
     /// Returns the key and value stored at the provided index
     /// will return None if there is nothing at the provided index
     pub fn index(&self, index: usize) -> Option<(&'_ K, &'_ V)> {
@@ -332,6 +615,44 @@ impl<K, V> TdfMap<K, V> {
         Some((&entry.key, &entry.value))
     }
 
+    /// Returns the key and a mutable reference to the value stored
+    /// at the provided index will return None if there is nothing at
+    /// the provided index
+    ///
+    /// `index` The index of the entry
+    pub fn get_index_mut(&mut self, index: usize) -> Option<(&'_ K, &'_ mut V)> {
+        let entry = self.entries.get_mut(index)?;
+        Some((&entry.key, &mut entry.value))
+    }
+
+    /// Inserts a new key value pair at the provided index shifting
+    /// all the entries after it to the right.
+    ///
+    /// Panics if the index is greater than the length of the map
+    ///
+    /// `index` The index to insert at
+    /// `key`   The entry key
+    /// `value` The entry value
+    pub fn insert_at<A: Into<K>, B: Into<V>>(&mut self, index: usize, key: A, value: B) {
+        self.entries.insert(
+            index,
+            MapEntry {
+                key: key.into(),
+                value: value.into(),
+            },
+        );
+    }
+
+    /// Swaps the positions of the entries at the two provided indexes.
+    ///
+    /// Panics if either index is out of bounds
+    ///
+    /// `a` The index of the first entry
+    /// `b` The index of the second entry
+    pub fn swap(&mut self, a: usize, b: usize) {
+        self.entries.swap(a, b);
+    }
+
     /// Inserts a new key value pair into the underlying structure.
     ///
     /// This function does NOT maintain order of the entires, use
@@ -357,6 +678,53 @@ impl<K, V> TdfMap<K, V> {
     pub fn clear(&mut self) {
         self.entries.clear();
     }
+
+    /// Retains only the entries that the provided function returns
+    /// true for removing all other entries. The order of the retained
+    /// entries is preserved
+    ///
+    /// `keep` The function deciding whether to keep an entry
+    pub fn retain<F>(&mut self, mut keep: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.entries
+            .retain_mut(|entry| keep(&entry.key, &mut entry.value));
+    }
+
+    /// Removes all the entries from the map returning an iterator
+    /// over the removed keys and values in order. The map's capacity
+    /// is kept for reuse
+    pub fn drain(&mut self) -> MapDrain<'_, K, V> {
+        MapDrain {
+            inner: self.entries.drain(..),
+        }
+    }
+
+    /// Sorts the entries in this map using the provided compare
+    /// function which is given the key and value of both entries
+    ///
+    /// `compare` The function comparing two entries
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&K, &V, &K, &V) -> Ordering,
+    {
+        self.entries
+            .sort_by(|a, b| compare(&a.key, &a.value, &b.key, &b.value));
+    }
+
+    /// Sorts the entries in this map using the key extracted
+    /// from each entry by the provided function
+    ///
+    /// `extract` The function extracting the sort key from an entry
+    pub fn sort_by_key<T, F>(&mut self, mut extract: F)
+    where
+        T: Ord,
+        F: FnMut(&K, &V) -> T,
+    {
+        self.entries
+            .sort_by_key(|entry| extract(&entry.key, &entry.value));
+    }
 }
 
 impl<K, V> TdfMap<K, V>
@@ -364,20 +732,13 @@ where
     K: PartialOrd + Ord,
 {
     /// Orders this map based on its keys by ordering keys that
-    /// are greater further up in the map
+    /// are greater further up in the map which is the order
+    /// required when encoding maps
     ///
-    /// This function is quite slow compared to using `insert_ordered`
-    /// for all the inserted entries. This is only for if you inserted
-    /// with `insert` instead
+    /// Only required if entries were inserted using `insert`
+    /// rather than `insert_ordered`
     pub fn order(&mut self) {
-        let entries = &mut self.entries;
-        let length = entries.len();
-        // If empty or 1 item no need to order
-        if length <= 1 {
-            return;
-        }
-
-        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        self.entries.sort_by(|a, b| a.key.cmp(&b.key));
     }
 }
 
@@ -405,10 +766,10 @@ where
     /// the key was not present
     ///
     /// `key` The key to find the index of
-    fn index_of_key<Q: ?Sized>(&self, key: &Q) -> Option<usize>
+    fn index_of_key<Q>(&self, key: &Q) -> Option<usize>
     where
         K: Borrow<Q>,
-        Q: Eq,
+        Q: Eq + ?Sized,
     {
         for index in 0..self.entries.len() {
             let entry_at = &self.entries[index];
@@ -435,10 +796,10 @@ where
     ///
     /// `key` The key to retrieve the value for
     #[inline]
-    pub fn get<Q: ?Sized>(&self, key: &Q) -> Option<&V>
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
     where
         K: Borrow<Q>,
-        Q: Eq,
+        Q: Eq + ?Sized,
     {
         let index = self.index_of_key(key)?;
         let entry = self.entries.get(index)?;
@@ -450,10 +811,10 @@ where
     ///
     /// `key` The key to retrieve the value for
     #[inline]
-    pub fn get_mut<Q: ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
     where
         K: Borrow<Q>,
-        Q: Eq,
+        Q: Eq + ?Sized,
     {
         let index = self.index_of_key(key)?;
         let entry = self.entries.get_mut(index)?;
@@ -463,10 +824,10 @@ where
 
     /// Takes the value stored at the provided key out of
     /// the map taking ownership this also removes the key.
-    pub fn get_owned<Q: ?Sized>(&mut self, key: &Q) -> Option<V>
+    pub fn get_owned<Q>(&mut self, key: &Q) -> Option<V>
     where
         K: Borrow<Q>,
-        Q: Eq,
+        Q: Eq + ?Sized,
     {
         let index = self.index_of_key(key)?;
         let entry = self.entries.remove(index);
@@ -474,6 +835,24 @@ where
     }
 }
 
+/// Positional access to the value of the entry at the provided
+/// index. Panics if the index is out of bounds
+impl<K, V> Index<usize> for TdfMap<K, V> {
+    type Output = V;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.entries[index].value
+    }
+}
+
+/// Mutable positional access to the value of the entry at the
+/// provided index. Panics if the index is out of bounds
+impl<K, V> IndexMut<usize> for TdfMap<K, V> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.entries[index].value
+    }
+}
+
 /// Iterator implementation for iterating over TdfMap
 pub struct MapEntryIter<'a, K, V> {
     /// The underlying map entry iterator
@@ -507,6 +886,60 @@ impl<K, V> Iterator for OwnedMapEntryIter<K, V> {
     }
 }
 
+/// Owned iterator over the keys of a map created using
+/// [TdfMap::into_keys]
+pub struct MapIntoKeys<K, V> {
+    /// The underlying entry iterator
+    inner: vec::IntoIter<MapEntry<K, V>>,
+}
+
+impl<K, V> Iterator for MapIntoKeys<K, V> {
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| entry.key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Owned iterator over the values of a map created using
+/// [TdfMap::into_values]
+pub struct MapIntoValues<K, V> {
+    /// The underlying entry iterator
+    inner: vec::IntoIter<MapEntry<K, V>>,
+}
+
+impl<K, V> Iterator for MapIntoValues<K, V> {
+    type Item = V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|entry| entry.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+/// Iterator over the entries removed from a map using
+/// [TdfMap::drain]
+pub struct MapDrain<'a, K, V> {
+    /// The underlying entry drain
+    inner: vec::Drain<'a, MapEntry<K, V>>,
+}
+
+impl<K, V> Iterator for MapDrain<'_, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let MapEntry { key, value } = self.inner.next()?;
+        Some((key, value))
+    }
+}
+
 /// Into iterator implementation for owned map
 impl<K, V> IntoIterator for TdfMap<K, V> {
     type Item = (K, V);
@@ -544,9 +977,10 @@ impl<K, V, B: Into<K>, A: Into<V>> FromIterator<(B, A)> for TdfMap<K, V> {
     }
 }
 
+/// Only maps with valid key types ([MapKey]) can be encoded
 impl<K, V> Encodable for TdfMap<K, V>
 where
-    K: Encodable + ValueType,
+    K: MapKey + Encodable + ValueType,
     V: Encodable + ValueType,
 {
     fn encode(&self, output: &mut TdfWriter) {
@@ -557,6 +991,24 @@ where
             value.encode(output);
         }
     }
+
+    fn try_encode(&self, output: &mut TdfWriter) -> EncodeResult<()> {
+        output.write_map_header(K::value_type(), V::value_type(), self.len());
+
+        for MapEntry { key, value } in &self.entries {
+            key.try_encode(output)?;
+            value.try_encode(output)?;
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> usize {
+        let entries: usize = (self.entries.iter())
+            .map(|MapEntry { key, value }| key.size_hint() + value.size_hint())
+            .sum();
+        // Key and value types followed by the length
+        2 + var_int_length(self.len() as u64) + entries
+    }
 }
 
 impl<K, V> Decodable for TdfMap<K, V>
@@ -590,11 +1042,45 @@ impl<K, V> From<HashMap<K, V>> for TdfMap<K, V> {
     }
 }
 
+impl<K, V> TdfMap<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Converts this map into a HashMap. If the map contains
+    /// duplicate keys the last value for the key is kept
+    pub fn into_hashmap(self) -> HashMap<K, V> {
+        self.into_iter().collect()
+    }
+}
+
+impl<K, V> From<TdfMap<K, V>> for HashMap<K, V>
+where
+    K: Hash + Eq,
+{
+    fn from(map: TdfMap<K, V>) -> Self {
+        map.into_hashmap()
+    }
+}
+
+impl<K, V> From<TdfMap<K, V>> for BTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn from(map: TdfMap<K, V>) -> Self {
+        map.into_iter().collect()
+    }
+}
+
 impl Encodable for f32 {
     #[inline]
     fn encode(&self, output: &mut TdfWriter) {
         output.write_f32(*self)
     }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        4
+    }
 }
 
 impl Decodable for f32 {
@@ -611,6 +1097,11 @@ impl Encodable for bool {
     fn encode(&self, output: &mut TdfWriter) {
         output.write_bool(*self)
     }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        1
+    }
 }
 
 impl Decodable for bool {
@@ -641,6 +1132,11 @@ macro_rules! forward_codec {
             fn encode(&self, output: &mut TdfWriter) {
                 $b::encode(&(*self as $b), output)
             }
+
+            #[inline]
+            fn size_hint(&self) -> usize {
+                $b::size_hint(&(*self as $b))
+            }
         }
 
         impl $crate::codec::ValueType for $a {
@@ -652,6 +1148,41 @@ macro_rules! forward_codec {
     };
 }
 
+/// Macro for implementing the codec for non zero integer types by
+/// forwarding to the underlying integer type. Decoding a zero value
+/// produces an error
+///
+/// `$a` The non zero type
+/// `$b` The underlying integer type
+macro_rules! non_zero_codec {
+    ($($a:ident => $b:ident),*) => {
+        $(
+            impl Encodable for $a {
+                #[inline]
+                fn encode(&self, output: &mut TdfWriter) {
+                    self.get().encode(output)
+                }
+
+                #[inline]
+                fn size_hint(&self) -> usize {
+                    self.get().size_hint()
+                }
+            }
+
+            impl Decodable for $a {
+                fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+                    $a::new($b::decode(reader)?)
+                        .ok_or(DecodeError::Other("Unexpected zero value for non zero integer"))
+                }
+            }
+
+            value_type!($a, TdfType::VarInt);
+        )*
+    };
+}
+
+non_zero_codec!(NonZeroU8 => u8, NonZeroU16 => u16, NonZeroU32 => u32, NonZeroU64 => u64);
+
 // Encoding for u8 values
 
 impl Encodable for u8 {
@@ -659,6 +1190,11 @@ impl Encodable for u8 {
     fn encode(&self, output: &mut TdfWriter) {
         output.write_u8(*self)
     }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        var_int_length(*self as u64)
+    }
 }
 
 impl Decodable for u8 {
@@ -673,6 +1209,15 @@ impl Encodable for u16 {
     fn encode(&self, output: &mut TdfWriter) {
         output.write_u16(*self)
     }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        if *self < 64 {
+            1
+        } else {
+            3
+        }
+    }
 }
 
 impl Decodable for u16 {
@@ -687,6 +1232,11 @@ impl Encodable for u32 {
     fn encode(&self, output: &mut TdfWriter) {
         output.write_u32(*self)
     }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        var_int_length(*self as u64)
+    }
 }
 
 impl Decodable for u32 {
@@ -701,6 +1251,11 @@ impl Encodable for u64 {
     fn encode(&self, output: &mut TdfWriter) {
         output.write_u64(*self)
     }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        var_int_length(*self)
+    }
 }
 
 impl Decodable for u64 {
@@ -715,6 +1270,11 @@ impl Encodable for usize {
     fn encode(&self, output: &mut TdfWriter) {
         output.write_usize(*self)
     }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        var_int_length(*self as u64)
+    }
 }
 
 impl Decodable for usize {
@@ -736,20 +1296,55 @@ forward_codec!(i32, u32);
 forward_codec!(i64, u64);
 forward_codec!(isize, usize);
 
+/// Returns the encoded size of the provided string including
+/// its length and null terminator
+///
+/// `value` The string value
+pub(crate) fn str_size(value: &str) -> usize {
+    let length = match value.as_bytes().last() {
+        Some(0) => value.len(),
+        _ => value.len() + 1,
+    };
+    var_int_length(length as u64) + length
+}
+
 impl Encodable for &'_ str {
     #[inline]
     fn encode(&self, output: &mut TdfWriter) {
         output.write_str(self)
     }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        str_size(self)
+    }
 }
 
 value_type!(&'_ str, TdfType::String);
 
+/// Borrowed strings must be valid UTF-8 as invalid characters
+/// can't be replaced without allocating
+impl<'a> BorrowDecodable<'a> for &'a str {
+    fn decode_borrowed(reader: &mut TdfReader<'a>) -> DecodeResult<Self> {
+        let mut bytes: &'a [u8] = reader.read_blob()?;
+        // Remove null terminator
+        if let Some((0, rest)) = bytes.split_last() {
+            bytes = rest;
+        }
+        std::str::from_utf8(bytes).map_err(|err| DecodeError::Source(Box::new(err)))
+    }
+}
+
 impl Encodable for String {
     #[inline]
     fn encode(&self, output: &mut TdfWriter) {
         output.write_str(self);
     }
+
+    #[inline]
+    fn size_hint(&self) -> usize {
+        str_size(self)
+    }
 }
 
 impl Decodable for String {
@@ -761,29 +1356,245 @@ impl Decodable for String {
 
 value_type!(String, TdfType::String);
 
-/// Blob structure wrapping a vec of bytes. This implementation is
-/// to differenciate between a list of VarInts and a Blob of straight
-/// bytes
-#[derive(Default, Debug, Clone)]
-pub struct Blob(pub Vec<u8>);
-
-impl Encodable for Blob {
+/// UUIDs are encoded as their hyphenated string form
+#[cfg(feature = "uuid")]
+impl Encodable for uuid::Uuid {
     fn encode(&self, output: &mut TdfWriter) {
-        output.write_usize(self.0.len());
-        output.write_slice(&self.0);
+        let mut buffer = uuid::Uuid::encode_buffer();
+        output.write_str(self.hyphenated().encode_lower(&mut buffer));
     }
 }
 
-impl Decodable for Blob {
+#[cfg(feature = "uuid")]
+impl Decodable for uuid::Uuid {
     fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
-        let length = reader.read_usize()?;
-        let bytes = reader.read_slice(length)?;
+        let value = reader.read_string()?;
+        uuid::Uuid::parse_str(&value).map_err(|err| DecodeError::Source(Box::new(err)))
+    }
+}
+
+#[cfg(feature = "uuid")]
+value_type!(uuid::Uuid, TdfType::String);
+
+/// Blob structure wrapping a vec of bytes. This implementation is
+/// to differenciate between a list of VarInts and a Blob of straight
+/// bytes
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Blob(pub Vec<u8>);
+
+impl Blob {
+    /// Creates a blob from a static slice of bytes. The bytes
+    /// are copied into the blob
+    ///
+    /// `bytes` The static bytes
+    pub fn from_static(bytes: &'static [u8]) -> Self {
+        Self(bytes.to_vec())
+    }
+}
+
+/// Deref implementation so that blobs can be used as
+/// a slice of bytes
+impl Deref for Blob {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for Blob {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Vec<u8>> for Blob {
+    fn from(value: Vec<u8>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&[u8]> for Blob {
+    fn from(value: &[u8]) -> Self {
+        Self(value.to_vec())
+    }
+}
+
+impl From<Blob> for Vec<u8> {
+    fn from(value: Blob) -> Self {
+        value.0
+    }
+}
+
+impl Encodable for Blob {
+    fn encode(&self, output: &mut TdfWriter) {
+        output.write_usize(self.0.len());
+        output.write_slice(&self.0);
+    }
+
+    fn size_hint(&self) -> usize {
+        var_int_length(self.0.len() as u64) + self.0.len()
+    }
+}
+
+impl Decodable for Blob {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        let length = reader.read_usize()?;
+        let bytes = reader.read_slice(length)?;
         Ok(Blob(bytes.to_vec()))
     }
 }
 
 value_type!(Blob, TdfType::Blob);
 
+/// Blob borrowing its bytes from the buffer it was decoded
+/// from. Borrowed counterpart of [`Blob`]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlobRef<'a>(pub &'a [u8]);
+
+impl Deref for BlobRef<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+impl<'a> From<&'a [u8]> for BlobRef<'a> {
+    fn from(value: &'a [u8]) -> Self {
+        Self(value)
+    }
+}
+
+impl From<BlobRef<'_>> for Blob {
+    fn from(value: BlobRef<'_>) -> Self {
+        Blob(value.0.to_vec())
+    }
+}
+
+impl Encodable for BlobRef<'_> {
+    fn encode(&self, output: &mut TdfWriter) {
+        output.write_usize(self.0.len());
+        output.write_slice(self.0);
+    }
+
+    fn size_hint(&self) -> usize {
+        var_int_length(self.0.len() as u64) + self.0.len()
+    }
+}
+
+impl<'a> BorrowDecodable<'a> for BlobRef<'a> {
+    fn decode_borrowed(reader: &mut TdfReader<'a>) -> DecodeResult<Self> {
+        reader.read_blob().map(BlobRef)
+    }
+}
+
+value_type!(BlobRef<'_>, TdfType::Blob);
+
+/// Duration encoded as a VarInt number of whole seconds. Any
+/// sub-second precision is truncated when encoding
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DurationSecs(pub Duration);
+
+impl Encodable for DurationSecs {
+    fn encode(&self, output: &mut TdfWriter) {
+        output.write_u64(self.0.as_secs());
+    }
+}
+
+impl Decodable for DurationSecs {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        let secs = reader.read_u64()?;
+        Ok(Self(Duration::from_secs(secs)))
+    }
+}
+
+value_type!(DurationSecs, TdfType::VarInt);
+
+/// Duration encoded as a VarInt number of whole milliseconds. Any
+/// sub-millisecond precision is truncated when encoding
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DurationMillis(pub Duration);
+
+impl Encodable for DurationMillis {
+    fn encode(&self, output: &mut TdfWriter) {
+        output.write_u64(self.0.as_millis() as u64);
+    }
+}
+
+impl Decodable for DurationMillis {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        let millis = reader.read_u64()?;
+        Ok(Self(Duration::from_millis(millis)))
+    }
+}
+
+value_type!(DurationMillis, TdfType::VarInt);
+
+/// Point in time encoded as a VarInt number of seconds since the
+/// Unix epoch. Times before the epoch are encoded as zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UnixTimestamp(pub SystemTime);
+
+impl UnixTimestamp {
+    /// Creates a timestamp for the current system time
+    pub fn now() -> Self {
+        Self(SystemTime::now())
+    }
+}
+
+impl Encodable for UnixTimestamp {
+    fn encode(&self, output: &mut TdfWriter) {
+        let secs = self
+            .0
+            .duration_since(UNIX_EPOCH)
+            .map(|value| value.as_secs())
+            .unwrap_or_default();
+        output.write_u64(secs);
+    }
+}
+
+impl Decodable for UnixTimestamp {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        let secs = reader.read_u64()?;
+        Ok(Self(UNIX_EPOCH + Duration::from_secs(secs)))
+    }
+}
+
+value_type!(UnixTimestamp, TdfType::VarInt);
+
+/// Point in time encoded as a VarInt number of milliseconds since
+/// the Unix epoch. Times before the epoch are encoded as zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UnixTimestampMillis(pub SystemTime);
+
+impl UnixTimestampMillis {
+    /// Creates a timestamp for the current system time
+    pub fn now() -> Self {
+        Self(SystemTime::now())
+    }
+}
+
+impl Encodable for UnixTimestampMillis {
+    fn encode(&self, output: &mut TdfWriter) {
+        let millis = self
+            .0
+            .duration_since(UNIX_EPOCH)
+            .map(|value| value.as_millis() as u64)
+            .unwrap_or_default();
+        output.write_u64(millis);
+    }
+}
+
+impl Decodable for UnixTimestampMillis {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        let millis = reader.read_u64()?;
+        Ok(Self(UNIX_EPOCH + Duration::from_millis(millis)))
+    }
+}
+
+value_type!(UnixTimestampMillis, TdfType::VarInt);
+
 /// Vec List encoding for encodable items items are required
 /// to have the ValueType trait in order to write the list header
 impl<C> Encodable for Vec<C>
@@ -797,6 +1608,14 @@ where
             value.encode(writer);
         }
     }
+
+    fn try_encode(&self, writer: &mut TdfWriter) -> EncodeResult<()> {
+        self.as_slice().try_encode(writer)
+    }
+
+    fn size_hint(&self) -> usize {
+        self.as_slice().size_hint()
+    }
 }
 
 /// Support for encoding slices of encodable items as lists
@@ -811,6 +1630,20 @@ where
             value.encode(writer);
         }
     }
+
+    fn try_encode(&self, writer: &mut TdfWriter) -> EncodeResult<()> {
+        writer.write_type(C::value_type());
+        writer.write_usize(self.len());
+        for value in self.iter() {
+            value.try_encode(writer)?;
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> usize {
+        // Value type followed by the length
+        1 + var_int_length(self.len() as u64) + self.iter().map(C::size_hint).sum::<usize>()
+    }
 }
 
 impl<C> ValueType for &[C]
@@ -851,10 +1684,77 @@ impl<C> ValueType for Vec<C> {
     }
 }
 
-/// Pair type alias. (Note Pairs should only ever be used with VarInts)
-pub type Pair<A, B> = (A, B);
+/// Pair of two VarInt values
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TdfPair<A, B> {
+    /// The first value of the pair
+    pub first: A,
+    /// The second value of the pair
+    pub second: B,
+}
 
-impl<A, B> Encodable for Pair<A, B>
+impl<A, B> TdfPair<A, B> {
+    /// Creates a new pair from the provided values
+    ///
+    /// `first`  The first value
+    /// `second` The second value
+    pub const fn new(first: A, second: B) -> Self {
+        Self { first, second }
+    }
+
+    /// Converts this pair into a tuple of its values
+    pub fn into_tuple(self) -> (A, B) {
+        (self.first, self.second)
+    }
+}
+
+impl<A, B> From<(A, B)> for TdfPair<A, B> {
+    fn from((first, second): (A, B)) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<A, B> From<TdfPair<A, B>> for (A, B) {
+    fn from(value: TdfPair<A, B>) -> Self {
+        value.into_tuple()
+    }
+}
+
+impl<A, B> Encodable for TdfPair<A, B>
+where
+    A: VarInt,
+    B: VarInt,
+{
+    fn encode(&self, output: &mut TdfWriter) {
+        self.first.encode(output);
+        self.second.encode(output);
+    }
+
+    fn size_hint(&self) -> usize {
+        self.first.size_hint() + self.second.size_hint()
+    }
+}
+
+impl<A, B> Decodable for TdfPair<A, B>
+where
+    A: VarInt,
+    B: VarInt,
+{
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        let first = A::decode(reader)?;
+        let second = B::decode(reader)?;
+        Ok(Self { first, second })
+    }
+}
+
+impl<A, B> ValueType for TdfPair<A, B> {
+    fn value_type() -> TdfType {
+        TdfType::Pair
+    }
+}
+
+/// Tuple pair encoding (Note Pairs should only ever be used with VarInts)
+impl<A, B> Encodable for (A, B)
 where
     A: VarInt,
     B: VarInt,
@@ -863,9 +1763,13 @@ where
         self.0.encode(output);
         self.1.encode(output);
     }
+
+    fn size_hint(&self) -> usize {
+        self.0.size_hint() + self.1.size_hint()
+    }
 }
 
-impl<A, B> Decodable for Pair<A, B>
+impl<A, B> Decodable for (A, B)
 where
     A: VarInt,
     B: VarInt,
@@ -877,16 +1781,102 @@ where
     }
 }
 
-impl<A, B> ValueType for Pair<A, B> {
+impl<A, B> ValueType for (A, B) {
     fn value_type() -> TdfType {
         TdfType::Pair
     }
 }
 
-/// Triple type alias. (Note Triples should only ever be used with VarInts)
-pub type Triple<A, B, C> = (A, B, C);
+/// Triple of three VarInt values
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TdfTriple<A, B, C> {
+    /// The first value of the triple
+    pub first: A,
+    /// The second value of the triple
+    pub second: B,
+    /// The third value of the triple
+    pub third: C,
+}
+
+impl<A, B, C> TdfTriple<A, B, C> {
+    /// Creates a new triple from the provided values
+    ///
+    /// `first`  The first value
+    /// `second` The second value
+    /// `third`  The third value
+    pub const fn new(first: A, second: B, third: C) -> Self {
+        Self {
+            first,
+            second,
+            third,
+        }
+    }
+
+    /// Converts this triple into a tuple of its values
+    pub fn into_tuple(self) -> (A, B, C) {
+        (self.first, self.second, self.third)
+    }
+}
+
+impl<A, B, C> From<(A, B, C)> for TdfTriple<A, B, C> {
+    fn from((first, second, third): (A, B, C)) -> Self {
+        Self {
+            first,
+            second,
+            third,
+        }
+    }
+}
 
-impl<A, B, C> Encodable for Triple<A, B, C>
+impl<A, B, C> From<TdfTriple<A, B, C>> for (A, B, C) {
+    fn from(value: TdfTriple<A, B, C>) -> Self {
+        value.into_tuple()
+    }
+}
+
+impl<A, B, C> Encodable for TdfTriple<A, B, C>
+where
+    A: VarInt,
+    B: VarInt,
+    C: VarInt,
+{
+    fn encode(&self, output: &mut TdfWriter) {
+        self.first.encode(output);
+        self.second.encode(output);
+        self.third.encode(output);
+    }
+
+    fn size_hint(&self) -> usize {
+        self.first.size_hint() + self.second.size_hint() + self.third.size_hint()
+    }
+}
+
+impl<A, B, C> Decodable for TdfTriple<A, B, C>
+where
+    A: VarInt,
+    B: VarInt,
+    C: VarInt,
+{
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        let first = A::decode(reader)?;
+        let second = B::decode(reader)?;
+        let third = C::decode(reader)?;
+        Ok(Self {
+            first,
+            second,
+            third,
+        })
+    }
+}
+
+impl<A, B, C> ValueType for TdfTriple<A, B, C> {
+    fn value_type() -> TdfType {
+        TdfType::Triple
+    }
+}
+
+/// Tuple triple encoding (Note Triples should only ever be used with VarInts)
+impl<A, B, C> Encodable for (A, B, C)
 where
     A: VarInt,
     B: VarInt,
@@ -897,8 +1887,13 @@ where
         self.1.encode(output);
         self.2.encode(output);
     }
+
+    fn size_hint(&self) -> usize {
+        self.0.size_hint() + self.1.size_hint() + self.2.size_hint()
+    }
 }
-impl<A, B, C> Decodable for Triple<A, B, C>
+
+impl<A, B, C> Decodable for (A, B, C)
 where
     A: VarInt,
     B: VarInt,
@@ -912,18 +1907,351 @@ where
     }
 }
 
-impl<A, B, C> ValueType for Triple<A, B, C> {
+impl<A, B, C> ValueType for (A, B, C) {
+    fn value_type() -> TdfType {
+        TdfType::Triple
+    }
+}
+
+/// Wrapper for encoding the fields of any value as a group. The
+/// wrapped value should only encode its tagged fields as the group
+/// end is written by this wrapper. Decoding accepts groups with or
+/// without the leading 0x02 byte
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Group<T>(pub T);
+
+impl<T: Encodable> Encodable for Group<T> {
+    fn encode(&self, output: &mut TdfWriter) {
+        self.0.encode(output);
+        output.tag_group_end();
+    }
+
+    fn try_encode(&self, output: &mut TdfWriter) -> EncodeResult<()> {
+        self.0.try_encode(output)?;
+        output.tag_group_end();
+        Ok(())
+    }
+
+    fn size_hint(&self) -> usize {
+        // Fields followed by the group end
+        self.0.size_hint() + 1
+    }
+}
+
+impl<T: Decodable> Decodable for Group<T> {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        reader.skip_group_2()?;
+        let value = T::decode(reader)?;
+        reader.skip_group_end()?;
+        Ok(Self(value))
+    }
+}
+
+impl<T> ValueType for Group<T> {
+    fn value_type() -> TdfType {
+        TdfType::Group
+    }
+}
+
+/// Wrapper for encoding the fields of any value as a group which
+/// starts with the 0x02 byte. Behaves the same as [Group] other
+/// than the leading byte
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Group2<T>(pub T);
+
+impl<T: Encodable> Encodable for Group2<T> {
+    fn encode(&self, output: &mut TdfWriter) {
+        output.write_byte(2);
+        self.0.encode(output);
+        output.tag_group_end();
+    }
+
+    fn try_encode(&self, output: &mut TdfWriter) -> EncodeResult<()> {
+        output.write_byte(2);
+        self.0.try_encode(output)?;
+        output.tag_group_end();
+        Ok(())
+    }
+
+    fn size_hint(&self) -> usize {
+        // Leading 0x02, fields and the group end
+        self.0.size_hint() + 2
+    }
+}
+
+impl<T: Decodable> Decodable for Group2<T> {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        let Group(value) = Group::<T>::decode(reader)?;
+        Ok(Self(value))
+    }
+}
+
+impl<T> ValueType for Group2<T> {
+    fn value_type() -> TdfType {
+        TdfType::Group
+    }
+}
+
+/// Type of a Blaze object consisting of the component the object
+/// belongs to and the type of the object within that component.
+/// Encoded as a [TdfPair]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectType {
+    /// The component the object belongs to
+    pub component: u16,
+    /// The type of the object within the component
+    pub ty: u16,
+}
+
+impl ObjectType {
+    /// Creates a new object type from the provided
+    /// component and type
+    ///
+    /// `component` The component the object belongs to
+    /// `ty`        The type of the object
+    pub const fn new(component: u16, ty: u16) -> Self {
+        Self { component, ty }
+    }
+}
+
+impl From<(u16, u16)> for ObjectType {
+    fn from((component, ty): (u16, u16)) -> Self {
+        Self { component, ty }
+    }
+}
+
+impl From<ObjectType> for (u16, u16) {
+    fn from(value: ObjectType) -> Self {
+        (value.component, value.ty)
+    }
+}
+
+impl Encodable for ObjectType {
+    fn encode(&self, output: &mut TdfWriter) {
+        self.component.encode(output);
+        self.ty.encode(output);
+    }
+}
+
+impl Decodable for ObjectType {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        let component = u16::decode(reader)?;
+        let ty = u16::decode(reader)?;
+        Ok(Self { component, ty })
+    }
+}
+
+impl ValueType for ObjectType {
+    fn value_type() -> TdfType {
+        TdfType::Pair
+    }
+}
+
+/// ID of a Blaze object consisting of the type of the object along
+/// with its unique ID. Encoded as a [TdfTriple]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectId {
+    /// The component the object belongs to
+    pub component: u16,
+    /// The type of the object within the component
+    pub ty: u16,
+    /// The unique ID of the object
+    pub id: u64,
+}
+
+impl ObjectId {
+    /// Creates a new object ID for the object with the
+    /// provided type and ID
+    ///
+    /// `ty` The type of the object
+    /// `id` The unique ID of the object
+    pub const fn new(ty: ObjectType, id: u64) -> Self {
+        Self {
+            component: ty.component,
+            ty: ty.ty,
+            id,
+        }
+    }
+
+    /// Returns the type of the object
+    pub const fn object_type(&self) -> ObjectType {
+        ObjectType::new(self.component, self.ty)
+    }
+}
+
+impl From<(u16, u16, u64)> for ObjectId {
+    fn from((component, ty, id): (u16, u16, u64)) -> Self {
+        Self { component, ty, id }
+    }
+}
+
+impl From<ObjectId> for (u16, u16, u64) {
+    fn from(value: ObjectId) -> Self {
+        (value.component, value.ty, value.id)
+    }
+}
+
+impl Encodable for ObjectId {
+    fn encode(&self, output: &mut TdfWriter) {
+        self.component.encode(output);
+        self.ty.encode(output);
+        self.id.encode(output);
+    }
+}
+
+impl Decodable for ObjectId {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        let component = u16::decode(reader)?;
+        let ty = u16::decode(reader)?;
+        let id = u64::decode(reader)?;
+        Ok(Self { component, ty, id })
+    }
+}
+
+impl ValueType for ObjectId {
     fn value_type() -> TdfType {
         TdfType::Triple
     }
 }
 
+/// IPv4 address and port group used by network addresses. The
+/// IP address is stored as a big endian u32
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpAddress {
+    /// The IPv4 address
+    pub ip: u32,
+    /// The port
+    pub port: u16,
+}
+
+impl From<SocketAddrV4> for IpAddress {
+    fn from(value: SocketAddrV4) -> Self {
+        Self {
+            ip: u32::from(*value.ip()),
+            port: value.port(),
+        }
+    }
+}
+
+impl From<IpAddress> for SocketAddrV4 {
+    fn from(value: IpAddress) -> Self {
+        SocketAddrV4::new(Ipv4Addr::from(value.ip), value.port)
+    }
+}
+
+impl Encodable for IpAddress {
+    fn encode(&self, output: &mut TdfWriter) {
+        output.tag_u32(b"IP", self.ip);
+        output.tag_u16(b"PORT", self.port);
+        output.tag_group_end();
+    }
+}
+
+impl Decodable for IpAddress {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        reader.skip_group_2()?;
+        let ip: u32 = reader.tag(b"IP")?;
+        let port: u16 = reader.tag(b"PORT")?;
+        reader.skip_group_end()?;
+        Ok(Self { ip, port })
+    }
+}
+
+value_type!(IpAddress, TdfType::Group);
+
+/// Pair of the external and internal addresses of a client
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IpPairAddress {
+    /// The external address of the client
+    pub external: IpAddress,
+    /// The internal address of the client
+    pub internal: IpAddress,
+}
+
+impl Encodable for IpPairAddress {
+    fn encode(&self, output: &mut TdfWriter) {
+        output.tag_value(b"EXIP", &self.external);
+        output.tag_value(b"INIP", &self.internal);
+        output.tag_group_end();
+    }
+}
+
+impl Decodable for IpPairAddress {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        reader.skip_group_2()?;
+        let external: IpAddress = reader.tag(b"EXIP")?;
+        let internal: IpAddress = reader.tag(b"INIP")?;
+        reader.skip_group_end()?;
+        Ok(Self { external, internal })
+    }
+}
+
+value_type!(IpPairAddress, TdfType::Group);
+
+/// Host name and port group used by network addresses
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct HostNameAddress {
+    /// The host name
+    pub name: String,
+    /// The port
+    pub port: u16,
+}
+
+impl Encodable for HostNameAddress {
+    fn encode(&self, output: &mut TdfWriter) {
+        output.tag_str(b"NAME", &self.name);
+        output.tag_u16(b"PORT", self.port);
+        output.tag_group_end();
+    }
+}
+
+impl Decodable for HostNameAddress {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        reader.skip_group_2()?;
+        let name: String = reader.tag(b"NAME")?;
+        let port: u16 = reader.tag(b"PORT")?;
+        reader.skip_group_end()?;
+        Ok(Self { name, port })
+    }
+}
+
+value_type!(HostNameAddress, TdfType::Group);
+
+crate::tdf_union! {
+    /// Network address union used throughout the session and game
+    /// manager components. The Xbox address variants are not supported
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub enum NetworkAddress {
+        /// Pair of external and internal addresses
+        0x2 => Pair(IpPairAddress) = b"VALU",
+        /// Single IP address
+        0x3 => Ip(IpAddress) = b"VALU",
+        /// Host name address
+        0x4 => HostName(HostNameAddress) = b"VALU",
+    }
+}
+
 #[cfg(test)]
 mod test {
 
-    use std::time::Instant;
+    use std::time::{Duration, Instant, UNIX_EPOCH};
 
-    use crate::types::TdfMap;
+    use super::{
+        Blob, BlobRef, DurationMillis, DurationSecs, Group, Group2, IpPairAddress, NetworkAddress,
+        ObjectId, ObjectType, TdfPair, TdfTriple, Union, UnixTimestamp, UnixTimestampMillis,
+        VarIntList,
+    };
+    use crate::{
+        codec::{BorrowDecodable, Decodable, Encodable},
+        error::DecodeResult,
+        reader::TdfReader,
+        tag::Tag,
+        types::TdfMap,
+        value::{TdfField, TdfFields, TdfValue},
+        writer::TdfWriter,
+    };
+    use std::net::{Ipv4Addr, SocketAddrV4};
+    use std::num::NonZeroU32;
 
     /// Tests ordering a map
     #[test]
@@ -959,6 +2287,52 @@ mod test {
         assert_eq!(map.entries[3].key, "key2");
         assert_eq!(map.entries[4].key, "key24");
         assert_eq!(map.entries[5].key, "key4");
+
+        // Entries with duplicate keys keep their insertion order
+        let mut map = TdfMap::<String, u32>::new();
+        for (index, key) in ["b", "a", "b", "a", "b"].into_iter().enumerate() {
+            map.insert(key, index as u32);
+        }
+        map.order();
+        let values: Vec<u32> = map.iter().map(|(_, value)| *value).collect();
+        assert_eq!(values, [1, 3, 0, 2, 4]);
+    }
+
+    /// Tests sorting a map by its values
+    #[test]
+    fn test_map_sort_by() {
+        let mut map = TdfMap::<String, u32>::new();
+        map.insert("a", 3u32);
+        map.insert("b", 1u32);
+        map.insert("c", 2u32);
+
+        map.sort_by(|_, a, _, b| a.cmp(b));
+        let keys: Vec<&String> = map.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["b", "c", "a"]);
+
+        map.sort_by_key(|key, _| key.clone());
+        let keys: Vec<&String> = map.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, ["a", "b", "c"]);
+    }
+
+    /// Tests retaining and draining map entries
+    #[test]
+    fn test_map_retain_drain() {
+        let mut map = TdfMap::<String, u32>::new();
+        map.insert("a", 1u32);
+        map.insert("b", 2u32);
+        map.insert("c", 3u32);
+
+        map.retain(|key, value| {
+            *value *= 10;
+            key != "b"
+        });
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get("c"), Some(&30));
+
+        let drained: Vec<(String, u32)> = map.drain().collect();
+        assert_eq!(drained, [("a".to_string(), 10), ("c".to_string(), 30)]);
+        assert!(map.is_empty());
     }
 
     /// Tests extending an existing map
@@ -984,6 +2358,283 @@ mod test {
         println!("{mapa:?}")
     }
 
+    /// Tests that object types and IDs are encoded the same
+    /// as their equivalent pair and triple
+    #[test]
+    fn test_object_encoding() {
+        let ty = ObjectType::new(0x4, 0x1);
+        let id = ObjectId::new(ty, 0x1234);
+
+        let mut writer = TdfWriter::default();
+        writer.tag_value(b"TYPE", &ty);
+        writer.tag_value(b"ID", &id);
+
+        let mut expected = TdfWriter::default();
+        expected.tag_value(b"TYPE", &(0x4u16, 0x1u16));
+        expected.tag_value(b"ID", &(0x4u16, 0x1u16, 0x1234u64));
+        assert_eq!(writer.buffer, expected.buffer);
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        assert_eq!(reader.tag::<ObjectType>(b"TYPE").unwrap(), ty);
+        assert_eq!(reader.tag::<ObjectId>(b"ID").unwrap(), id);
+        assert_eq!(id.object_type(), ty);
+    }
+
+    crate::tdf_union! {
+        #[derive(Debug, PartialEq)]
+        enum TestUnion {
+            0x0 => Name(String) = b"NAME",
+            0x1 => Id(u32) = b"ID",
+        }
+    }
+
+    /// Tests encoding and decoding unions with different
+    /// value types for each key
+    #[test]
+    fn test_tdf_union() {
+        let mut writer = TdfWriter::default();
+        writer.tag_value(b"A", &TestUnion::Name("Test".to_string()));
+        writer.tag_value(b"B", &TestUnion::Id(5));
+        writer.tag_value(b"C", &TestUnion::Unset);
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        assert_eq!(
+            reader.tag::<TestUnion>(b"A").unwrap(),
+            TestUnion::Name("Test".to_string())
+        );
+        assert_eq!(reader.tag::<TestUnion>(b"B").unwrap(), TestUnion::Id(5));
+        assert_eq!(reader.tag::<TestUnion>(b"C").unwrap(), TestUnion::Unset);
+
+        // Union with a key that doesn't exist
+        let mut writer = TdfWriter::default();
+        writer.tag_union_value(b"A", 0x5, b"ID", &5u32);
+        let mut reader = TdfReader::new(&writer.buffer);
+        assert!(reader.tag::<TestUnion>(b"A").is_err());
+    }
+
+    /// Tests encoding and decoding network address unions
+    #[test]
+    fn test_network_address() {
+        let address = NetworkAddress::Pair(IpPairAddress {
+            external: SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), 3659).into(),
+            internal: SocketAddrV4::new(Ipv4Addr::new(192, 168, 0, 2), 3659).into(),
+        });
+
+        let mut writer = TdfWriter::default();
+        writer.tag_value(b"ADDR", &address);
+        writer.tag_value(b"NEXT", &1u8);
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        assert_eq!(reader.tag::<NetworkAddress>(b"ADDR").unwrap(), address);
+        assert_eq!(reader.tag::<u8>(b"NEXT").unwrap(), 1);
+    }
+
+    /// Tests using a var int list as a collection
+    #[test]
+    fn test_var_int_list() {
+        let mut list: VarIntList<u32> = (1..=3).collect();
+        list.push(4u32);
+
+        assert_eq!(list.len(), 4);
+        assert_eq!(list.get(0), Some(&1));
+        assert_eq!(list.iter().sum::<u32>(), 10);
+
+        let values: Vec<u32> = list.into_iter().collect();
+        assert_eq!(VarIntList::from(values), VarIntList(vec![1, 2, 3, 4]));
+    }
+
+    /// Tests the union conversions and combinators
+    #[test]
+    fn test_union_combinators() {
+        let union: Union<u32> = Some((0x1, "VALU", 5u32)).into();
+        assert_eq!(union.value(), Some(&5));
+        assert_eq!(union.as_ref().map(|value| value * 2).into_value(), Some(10));
+        assert_eq!(Option::<u32>::from(union), Some(5));
+
+        let unset: Union<u32> = None.into();
+        assert!(unset.is_unset());
+        assert_eq!(unset.into_value(), None);
+    }
+
+    /// Tests encoding and decoding blobs
+    #[test]
+    fn test_blob() {
+        let blob = Blob::from_static(&[1, 2, 3]);
+        assert_eq!(blob.len(), 3);
+        assert_eq!(blob.as_ref(), &[1, 2, 3]);
+
+        let mut writer = TdfWriter::default();
+        writer.tag_value(b"BLOB", &blob);
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        let value: Blob = reader.tag(b"BLOB").unwrap();
+        assert_eq!(value, Blob::from(vec![1, 2, 3]));
+        assert_eq!(Vec::from(value), vec![1, 2, 3]);
+    }
+
+    /// Tests wrapping values to encode them as groups
+    #[test]
+    fn test_group_wrappers() {
+        #[derive(Debug, PartialEq)]
+        struct Test {
+            value: u32,
+        }
+
+        impl Encodable for Test {
+            fn encode(&self, output: &mut TdfWriter) {
+                output.tag_u32(b"VALU", self.value);
+            }
+        }
+
+        impl Decodable for Test {
+            fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+                let value = reader.tag(b"VALU")?;
+                Ok(Self { value })
+            }
+        }
+
+        let mut writer = TdfWriter::default();
+        writer.tag_value(b"LIST", &vec![Group(Test { value: 1 })]);
+        writer.tag_value(b"TWO", &Group2(Test { value: 2 }));
+        writer.tag_u8(b"NEXT", 3);
+
+        assert_eq!(
+            TdfFields::decode(&mut TdfReader::new(&writer.buffer))
+                .unwrap()
+                .get(b"TWO"),
+            Some(&TdfValue::Group {
+                two: true,
+                fields: vec![TdfField {
+                    tag: Tag::from(b"VALU".as_slice()),
+                    value: TdfValue::VarInt(2)
+                }]
+            })
+        );
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        let list: Vec<Group<Test>> = reader.tag(b"LIST").unwrap();
+        assert_eq!(list, vec![Group(Test { value: 1 })]);
+        let Group2(two) = reader.tag::<Group2<Test>>(b"TWO").unwrap();
+        assert_eq!(two, Test { value: 2 });
+        assert_eq!(reader.tag::<u8>(b"NEXT").unwrap(), 3);
+    }
+
+    /// Tests encoding and decoding non zero integers
+    #[test]
+    fn test_non_zero() {
+        let mut writer = TdfWriter::default();
+        writer.tag_value(b"A", &NonZeroU32::new(5).unwrap());
+        writer.tag_u32(b"B", 0);
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        assert_eq!(reader.tag::<NonZeroU32>(b"A").unwrap().get(), 5);
+        assert!(reader.tag::<NonZeroU32>(b"B").is_err());
+    }
+
+    /// Tests that pair and triple structs encode the same as tuples
+    #[test]
+    fn test_pair_triple() {
+        let mut writer = TdfWriter::default();
+        writer.tag_value(b"PAIR", &TdfPair::new(1u16, 2u32));
+        writer.tag_value(b"TRIP", &TdfTriple::new(1u16, 2u16, 3u64));
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        let pair: (u16, u32) = reader.tag(b"PAIR").unwrap();
+        assert_eq!(TdfPair::from(pair), TdfPair::new(1, 2));
+        let triple: TdfTriple<u16, u16, u64> = reader.tag(b"TRIP").unwrap();
+        assert_eq!(triple.into_tuple(), (1, 2, 3));
+    }
+
+    /// Tests encoding and decoding durations and timestamps
+    #[test]
+    fn test_time_codecs() {
+        let time = UNIX_EPOCH + Duration::from_millis(1_650_000_000_123);
+
+        let mut writer = TdfWriter::default();
+        writer.tag_value(b"SECS", &DurationSecs(Duration::from_millis(2500)));
+        writer.tag_value(b"MILS", &DurationMillis(Duration::from_millis(2500)));
+        writer.tag_value(b"TIME", &UnixTimestamp(time));
+        writer.tag_value(b"TMLS", &UnixTimestampMillis(time));
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        assert_eq!(reader.tag::<u64>(b"SECS").unwrap(), 2);
+        assert_eq!(
+            reader.tag::<DurationMillis>(b"MILS").unwrap(),
+            DurationMillis(Duration::from_millis(2500))
+        );
+        assert_eq!(
+            reader.tag::<UnixTimestamp>(b"TIME").unwrap(),
+            UnixTimestamp(UNIX_EPOCH + Duration::from_secs(1_650_000_000))
+        );
+        assert_eq!(
+            reader.tag::<UnixTimestampMillis>(b"TMLS").unwrap(),
+            UnixTimestampMillis(time)
+        );
+    }
+
+    /// Tests encoding and decoding UUIDs as strings
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_uuid() {
+        let value = uuid::Uuid::from_u128(0x1234_5678_9abc_def0_1234_5678_9abc_def0);
+
+        let mut writer = TdfWriter::default();
+        writer.tag_value(b"UUID", &value);
+        writer.tag_str(b"BAD", "not-a-uuid");
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        assert_eq!(reader.tag::<uuid::Uuid>(b"UUID").unwrap(), value);
+        assert!(matches!(
+            reader.tag::<uuid::Uuid>(b"BAD"),
+            Err(crate::error::DecodeError::Source(_))
+        ));
+    }
+
+    /// Tests that map equality takes the entry order into account
+    #[test]
+    fn test_map_eq() {
+        let a: TdfMap<String, u32> = [("A", 1u32), ("B", 2u32)].into_iter().collect();
+        let b: TdfMap<String, u32> = [("B", 2u32), ("A", 1u32)].into_iter().collect();
+        assert_eq!(a, a.clone());
+        assert_ne!(a, b);
+    }
+
+    /// Tests consuming a map into its keys and values
+    #[test]
+    fn test_map_into_keys_values() {
+        let map: TdfMap<String, u32> = [("A", 1u32), ("B", 2u32)].into_iter().collect();
+        assert_eq!(map.clone().into_keys().collect::<Vec<_>>(), vec!["A", "B"]);
+        assert_eq!(map.into_values().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    /// Tests positional access and editing of map entries
+    #[test]
+    fn test_map_index() {
+        let mut map = TdfMap::<String, u32>::new();
+        map.insert("A", 1u32);
+        map.insert("C", 3u32);
+        map.insert_at(1, "B", 2u32);
+
+        assert_eq!(map.index(1), Some((&"B".to_string(), &2)));
+        assert_eq!(map[2], 3);
+
+        map[0] = 10;
+        if let Some((_, value)) = map.get_index_mut(1) {
+            *value = 20;
+        }
+        map.swap(0, 2);
+
+        let entries: Vec<(String, u32)> = map.into_iter().collect();
+        assert_eq!(
+            entries,
+            vec![
+                ("C".to_string(), 3),
+                ("B".to_string(), 20),
+                ("A".to_string(), 10)
+            ]
+        );
+    }
+
     /// Tests inserting into a map
     #[test]
     fn test_map_insert() {
@@ -996,4 +2647,82 @@ mod test {
 
         println!("{value:?}")
     }
+
+    /// Tests decoding strings and blobs borrowed from the buffer
+    #[test]
+    fn test_borrowed_decode() {
+        let mut writer = TdfWriter::default();
+        writer.tag_value(b"DATA", &BlobRef(&[1, 2, 3]));
+        writer.tag_str(b"NAME", "Test");
+        writer.tag_u32(b"ID", 5);
+        let bytes: Vec<u8> = writer.into();
+
+        let mut reader = TdfReader::new(&bytes);
+        let data: BlobRef = reader.tag_borrowed(b"DATA").unwrap();
+        let name: &str = reader.tag_borrowed(b"NAME").unwrap();
+        let missing: Option<&str> = reader.try_tag_borrowed(b"MISS").unwrap();
+        let id: u32 = reader.tag_borrowed(b"ID").unwrap();
+        assert_eq!(&*data, &[1, 2, 3]);
+        assert_eq!(name, "Test");
+        assert!(missing.is_none());
+        assert_eq!(id, 5);
+
+        // Borrowed strings must be valid UTF-8
+        let bytes = [0x2, 0xFF, 0x0];
+        let mut reader = TdfReader::new(&bytes);
+        assert!(<&str>::decode_borrowed(&mut reader).is_err());
+    }
+
+    /// Tests the size hints of values match their encoded length
+    #[test]
+    fn test_size_hint() {
+        fn check<C: Encodable>(value: C) {
+            assert_eq!(value.size_hint(), value.encode_bytes().len());
+        }
+
+        check(0u8);
+        check(200u8);
+        check(u16::MAX);
+        check(u32::MAX);
+        check(u64::MAX);
+        check(-1i32);
+        check(NonZeroU32::new(70).unwrap());
+        check(true);
+        check(1.5f32);
+        check("Test");
+        check("Test\0".to_string());
+        check(Blob(vec![0; 300]));
+        check(BlobRef(&[1, 2, 3]));
+        check(vec!["A".to_string(), "BC".to_string()]);
+        check(VarIntList(vec![1u32, 100, 100000]));
+        check(TdfPair::new(1u32, 1000u32));
+        check((1u8, 2u16, 3u32));
+        check(Union::Set {
+            key: 1,
+            tag: Tag::from(b"TEST"),
+            value: 500u32,
+        });
+        check(Union::<u32>::Unset);
+
+        let mut map = TdfMap::<String, u32>::new();
+        map.insert("A", 1u32);
+        map.insert("B", 1000u32);
+        check(map);
+
+        let fields = TdfFields(vec![TdfField {
+            tag: Tag::from(b"DATA"),
+            value: TdfValue::Group {
+                two: true,
+                fields: vec![TdfField {
+                    tag: Tag::from(b"LIST"),
+                    value: TdfValue::List {
+                        ty: crate::tag::TdfType::String,
+                        values: vec![TdfValue::String("Test".to_string())],
+                    },
+                }],
+            },
+        }]);
+        check(Group(fields.clone()));
+        check(Group2(fields));
+    }
 }
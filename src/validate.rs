@@ -0,0 +1,485 @@
+//! Wire format conformance validation ([`validate`]) which walks encoded
+//! contents checking structural rules and reporting every violation along
+//! with its offset and path. Useful for checking packet builders in CI and
+//! for vetting the output of third party servers.
+//!
+//! The following rules are checked:
+//! - Groups are terminated before the end of the contents
+//! - Var ints use the shortest encoding and fit within 64 bits
+//! - Tags only contain uppercase letters
+//! - Strings are null terminated
+//! - Map keys (var int and string keys) are sorted without duplicates
+//!
+//! Violations that prevent the rest of the contents from being read (such
+//! as unknown types or missing bytes) stop the validation
+//!
+//! ```
+//! use blaze_pk::{validate::{validate, ViolationKind}, writer::TdfWriter};
+//!
+//! let mut writer = TdfWriter::default();
+//! writer.tag_str(b"NAME", "Test");
+//! assert!(validate(&writer.buffer).is_empty());
+//!
+//! // Zero encoded using two bytes
+//! writer.tag(b"ID", blaze_pk::tag::TdfType::VarInt);
+//! writer.buffer.extend_from_slice(&[0x80, 0x00]);
+//!
+//! let violations = validate(&writer.buffer);
+//! assert_eq!(violations[0].kind, ViolationKind::NonCanonicalVarInt);
+//! assert_eq!(violations[0].path.to_string(), "ID");
+//! ```
+
+use crate::{
+    diff::{DiffPath, PathSegment},
+    packet::Packet,
+    reader::TdfReader,
+    tag::{Tag, TdfType},
+    types::UNION_UNSET,
+    value::TdfValue,
+};
+use std::{cmp::Ordering, fmt::Display};
+
+/// Kind of rule that was violated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ViolationKind {
+    /// The contents ended before the value was complete
+    UnexpectedEof,
+    /// Value type byte that isn't a known type
+    UnknownType(u8),
+    /// Group without a terminator before the end of the contents
+    UnterminatedGroup,
+    /// Var int that could have been encoded using fewer bytes
+    NonCanonicalVarInt,
+    /// Var int with a value that doesn't fit within 64 bits
+    VarIntOverflow,
+    /// Tag containing characters other than uppercase letters
+    InvalidTag(Tag),
+    /// String without a null terminator
+    UnterminatedString,
+    /// Map key that is less than the key before it
+    UnsortedMapKey,
+    /// Map key that is equal to the key before it
+    DuplicateMapKey,
+}
+
+impl Display for ViolationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ViolationKind::UnexpectedEof => f.write_str("Unexpected end of contents"),
+            ViolationKind::UnknownType(ty) => write!(f, "Unknown value type {:#04x}", ty),
+            ViolationKind::UnterminatedGroup => f.write_str("Group is not terminated"),
+            ViolationKind::NonCanonicalVarInt => f.write_str("Var int is not canonically encoded"),
+            ViolationKind::VarIntOverflow => f.write_str("Var int does not fit within 64 bits"),
+            ViolationKind::InvalidTag(tag) => write!(f, "Invalid tag characters {:02x?}", tag.0),
+            ViolationKind::UnterminatedString => f.write_str("String is not null terminated"),
+            ViolationKind::UnsortedMapKey => f.write_str("Map key is not sorted"),
+            ViolationKind::DuplicateMapKey => f.write_str("Map key is duplicated"),
+        }
+    }
+}
+
+/// Violation of a wire format rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// The offset within the contents of the value violating the rule
+    pub offset: usize,
+    /// The path to the value violating the rule
+    pub path: DiffPath,
+    /// The rule that was violated
+    pub kind: ViolationKind,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:#06x}", self.offset)?;
+        if !self.path.0.is_empty() {
+            write!(f, " {}", self.path)?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+/// Validates the provided encoded contents returning all the violations
+///
+/// `bytes` The encoded contents
+pub fn validate(bytes: &[u8]) -> Vec<Violation> {
+    let mut validator = Validator {
+        bytes,
+        cursor: 0,
+        path: Vec::new(),
+        violations: Vec::new(),
+    };
+    // Stopping violations are already included in the violations
+    let _ = validator.fields();
+    validator.violations
+}
+
+/// Validates the contents of the provided packet returning all the violations
+///
+/// `packet` The packet to validate
+pub fn validate_packet(packet: &Packet) -> Vec<Violation> {
+    validate(&packet.contents)
+}
+
+/// Marker for violations that prevent validation from continuing
+struct Stop;
+
+/// Result type for validation steps
+type ValidateResult<T> = Result<T, Stop>;
+
+/// Walker over encoded contents collecting violations
+struct Validator<'a> {
+    /// The contents being validated
+    bytes: &'a [u8],
+    /// The cursor position within the contents
+    cursor: usize,
+    /// The path to the current value
+    path: Vec<PathSegment>,
+    /// The collected violations
+    violations: Vec<Violation>,
+}
+
+impl Validator<'_> {
+    /// Adds a violation at the provided offset
+    ///
+    /// `offset` The offset of the violating value
+    /// `kind`   The violated rule
+    fn violation(&mut self, offset: usize, kind: ViolationKind) {
+        self.violations.push(Violation {
+            offset,
+            path: DiffPath(self.path.clone()),
+            kind,
+        });
+    }
+
+    /// Adds a violation that stops the validation
+    ///
+    /// `offset` The offset of the violating value
+    /// `kind`   The violated rule
+    fn stop<T>(&mut self, offset: usize, kind: ViolationKind) -> ValidateResult<T> {
+        self.violation(offset, kind);
+        Err(Stop)
+    }
+
+    /// Reads the provided number of bytes
+    ///
+    /// `length` The number of bytes to read
+    fn slice(&mut self, length: usize) -> ValidateResult<&[u8]> {
+        let start = self.cursor;
+        if self.bytes.len() - start < length {
+            return self.stop(start, ViolationKind::UnexpectedEof);
+        }
+        self.cursor += length;
+        Ok(&self.bytes[start..self.cursor])
+    }
+
+    /// Reads a single byte
+    fn byte(&mut self) -> ValidateResult<u8> {
+        self.slice(1).map(|bytes| bytes[0])
+    }
+
+    /// Reads a value type
+    fn ty(&mut self) -> ValidateResult<TdfType> {
+        let offset = self.cursor;
+        let value = self.byte()?;
+        match TdfType::try_from(value) {
+            Ok(ty) => Ok(ty),
+            Err(_) => self.stop(offset, ViolationKind::UnknownType(value)),
+        }
+    }
+
+    /// Reads a var int checking that its encoding is canonical
+    fn var_int(&mut self) -> ValidateResult<u64> {
+        let offset = self.cursor;
+        let first = self.byte()?;
+        let mut result = (first & 63) as u128;
+        if first < 128 {
+            return Ok(result as u64);
+        }
+
+        let mut shift: u32 = 6;
+        let mut overflow = false;
+        let byte = loop {
+            let byte = self.byte()?;
+            let value = (byte & 127) as u128;
+            if shift < 121 {
+                result |= value << shift;
+            } else if value != 0 {
+                overflow = true;
+            }
+            if byte < 128 {
+                break byte;
+            }
+            shift += 7;
+        };
+
+        // The last byte only contains zero bits when it wasn't required
+        if byte == 0 {
+            self.violation(offset, ViolationKind::NonCanonicalVarInt);
+        }
+        if overflow || result > u64::MAX as u128 {
+            self.violation(offset, ViolationKind::VarIntOverflow);
+        }
+        Ok(result as u64)
+    }
+
+    /// Reads a length prefixed slice of bytes
+    fn blob(&mut self) -> ValidateResult<&[u8]> {
+        let length = self.var_int()?;
+        let length = usize::try_from(length).unwrap_or(usize::MAX);
+        self.slice(length)
+    }
+
+    /// Reads fields until the end of the contents
+    fn fields(&mut self) -> ValidateResult<()> {
+        while self.cursor < self.bytes.len() {
+            self.field()?;
+        }
+        Ok(())
+    }
+
+    /// Reads a tagged field
+    fn field(&mut self) -> ValidateResult<()> {
+        let offset = self.cursor;
+        let header = self.slice(4)?;
+        let raw_ty = header[3];
+        let Ok(ty) = TdfType::try_from(raw_ty) else {
+            return self.stop(offset + 3, ViolationKind::UnknownType(raw_ty));
+        };
+        let tag = match TdfReader::new(header).read_tag() {
+            Ok(tagged) => tagged.tag,
+            Err(_) => return self.stop(offset + 3, ViolationKind::UnknownType(raw_ty)),
+        };
+
+        self.path.push(PathSegment::Tag(tag));
+        if !is_valid_tag(&tag) {
+            self.violation(offset, ViolationKind::InvalidTag(tag));
+        }
+        let result = self.value(ty).map(|_| ());
+        self.path.pop();
+        result
+    }
+
+    /// Reads a value of the provided type returning the value
+    /// when its a var int or string so map keys can be compared
+    ///
+    /// `ty` The type of value
+    fn value(&mut self, ty: TdfType) -> ValidateResult<Option<TdfValue>> {
+        let offset = self.cursor;
+        match ty {
+            TdfType::VarInt | TdfType::TimeValue => {
+                return self.var_int().map(|value| Some(TdfValue::VarInt(value)))
+            }
+            TdfType::String => {
+                let bytes = self.blob()?;
+                let value = match bytes.split_last() {
+                    Some((0, bytes)) => String::from_utf8_lossy(bytes).into_owned(),
+                    _ => {
+                        let value = String::from_utf8_lossy(bytes).into_owned();
+                        self.violation(offset, ViolationKind::UnterminatedString);
+                        value
+                    }
+                };
+                return Ok(Some(TdfValue::String(value)));
+            }
+            TdfType::Blob => {
+                self.blob()?;
+            }
+            TdfType::Group => {
+                if self.bytes.get(self.cursor) == Some(&2) {
+                    self.cursor += 1;
+                }
+                loop {
+                    match self.bytes.get(self.cursor) {
+                        Some(0) => {
+                            self.cursor += 1;
+                            break;
+                        }
+                        Some(_) => self.field()?,
+                        None => return self.stop(offset, ViolationKind::UnterminatedGroup),
+                    }
+                }
+            }
+            TdfType::List => {
+                let ty = self.ty()?;
+                let length = self.var_int()?;
+                for index in 0..length {
+                    self.path.push(PathSegment::Index(index as usize));
+                    let result = self.value(ty);
+                    self.path.pop();
+                    result?;
+                }
+            }
+            TdfType::Map => {
+                let key_ty = self.ty()?;
+                let value_ty = self.ty()?;
+                let length = self.var_int()?;
+                let mut previous: Option<TdfValue> = None;
+                for index in 0..length {
+                    let key_offset = self.cursor;
+                    self.path.push(PathSegment::Index(index as usize));
+                    let key = self.value(key_ty);
+                    self.path.pop();
+                    let key = key?;
+
+                    let segment = match &key {
+                        Some(key) => PathSegment::Key(key.clone()),
+                        None => PathSegment::Index(index as usize),
+                    };
+                    self.path.push(segment);
+                    if let (Some(previous), Some(key)) = (&previous, &key) {
+                        match compare_keys(previous, key) {
+                            Some(Ordering::Greater) => {
+                                self.violation(key_offset, ViolationKind::UnsortedMapKey)
+                            }
+                            Some(Ordering::Equal) => {
+                                self.violation(key_offset, ViolationKind::DuplicateMapKey)
+                            }
+                            _ => {}
+                        }
+                    }
+                    let result = self.value(value_ty);
+                    self.path.pop();
+                    result?;
+                    previous = key;
+                }
+            }
+            TdfType::Union => {
+                if self.byte()? != UNION_UNSET {
+                    self.field()?;
+                }
+            }
+            TdfType::VarIntList => {
+                let length = self.var_int()?;
+                for _ in 0..length {
+                    self.var_int()?;
+                }
+            }
+            TdfType::Pair => {
+                self.var_int()?;
+                self.var_int()?;
+            }
+            TdfType::Triple => {
+                self.var_int()?;
+                self.var_int()?;
+                self.var_int()?;
+            }
+            TdfType::Float => {
+                self.slice(4)?;
+            }
+            TdfType::Generic => {
+                if self.byte()? != 0 {
+                    self.var_int()?;
+                    self.field()?;
+                }
+                match self.bytes.get(self.cursor) {
+                    Some(0) => self.cursor += 1,
+                    _ => return self.stop(offset, ViolationKind::UnterminatedGroup),
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Checks whether the tag is made of uppercase letters
+/// followed only by padding
+///
+/// `tag` The tag to check
+fn is_valid_tag(tag: &Tag) -> bool {
+    let length = tag.0.iter().take_while(|value| **value != 0).count();
+    length > 0
+        && tag.0[..length].iter().all(u8::is_ascii_uppercase)
+        && tag.0[length..].iter().all(|value| *value == 0)
+}
+
+/// Compares two map keys when they are comparable
+///
+/// `a` The first key
+/// `b` The second key
+fn compare_keys(a: &TdfValue, b: &TdfValue) -> Option<Ordering> {
+    match (a, b) {
+        (TdfValue::VarInt(a), TdfValue::VarInt(b)) => Some(a.cmp(b)),
+        (TdfValue::String(a), TdfValue::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{validate, ViolationKind};
+    use crate::{tag::TdfType, writer::TdfWriter};
+
+    /// Tests that contents produced by the writer are valid
+    #[test]
+    fn test_valid_contents() {
+        let mut writer = TdfWriter::default();
+        writer.tag_str(b"NAME", "Test");
+        writer.tag_u64(b"ID", u64::MAX);
+        writer.group(b"USER", |writer| {
+            writer.tag_pair(b"PAIR", (1, 2));
+            writer.tag_union_unset(b"UNON");
+        });
+        writer.tag_map_start(b"MAP", TdfType::String, TdfType::VarInt, 2);
+        writer.write_str("A");
+        writer.write_u8(1);
+        writer.write_str("B");
+        writer.write_u8(2);
+        assert_eq!(validate(&writer.buffer), Vec::new());
+    }
+
+    /// Tests that each kind of violation is reported at the right offset
+    #[test]
+    fn test_violations() {
+        let kinds = |bytes: &[u8]| -> Vec<(usize, ViolationKind)> {
+            validate(bytes)
+                .into_iter()
+                .map(|violation| (violation.offset, violation.kind))
+                .collect()
+        };
+
+        let mut writer = TdfWriter::default();
+        writer.tag(b"ID", TdfType::VarInt);
+        writer.buffer.extend_from_slice(&[0x81, 0x80, 0x00]);
+        writer.tag(b"NAME", TdfType::String);
+        writer.buffer.extend_from_slice(&[0x01, b'A']);
+        writer.tag(b"BIG", TdfType::VarInt);
+        writer.buffer.extend_from_slice(&[0xFF; 11]);
+        writer.buffer.push(0x01);
+        assert_eq!(
+            kinds(&writer.buffer),
+            vec![
+                (4, ViolationKind::NonCanonicalVarInt),
+                (11, ViolationKind::UnterminatedString),
+                (17, ViolationKind::VarIntOverflow)
+            ]
+        );
+
+        let mut writer = TdfWriter::default();
+        writer.tag_map_start(b"MAP", TdfType::VarInt, TdfType::VarInt, 3);
+        writer.buffer.extend_from_slice(&[2, 0, 1, 0, 1, 0]);
+        writer.tag(b"A_", TdfType::VarInt);
+        writer.buffer.push(0);
+        let violations = validate(&writer.buffer);
+        assert_eq!(violations[0].kind, ViolationKind::UnsortedMapKey);
+        assert_eq!(violations[0].path.to_string(), "MAP[1]");
+        assert_eq!(violations[1].kind, ViolationKind::DuplicateMapKey);
+        assert!(matches!(violations[2].kind, ViolationKind::InvalidTag(_)));
+
+        let mut writer = TdfWriter::default();
+        writer.tag_group(b"GRP");
+        writer.tag_u8(b"ID", 1);
+        assert_eq!(
+            kinds(&writer.buffer),
+            vec![(4, ViolationKind::UnterminatedGroup)]
+        );
+
+        let mut writer = TdfWriter::default();
+        writer.tag(b"ID", TdfType::VarInt);
+        writer.buffer[3] = 0x7F;
+        assert_eq!(
+            kinds(&writer.buffer),
+            vec![(3, ViolationKind::UnknownType(0x7F))]
+        );
+    }
+}
@@ -0,0 +1,545 @@
+//! Schema-less dynamic representation of Tdf values ([`TdfValue`]) which
+//! can decode, manipulate and re-encode arbitrary packet contents without
+//! needing structure definitions
+
+use crate::{
+    codec::{Decodable, Encodable},
+    error::{DecodeResult, EncodeError, EncodeResult},
+    reader::TdfReader,
+    tag::{Tag, TdfType},
+    types::{str_size, UNION_UNSET},
+    writer::{var_int_length, TdfWriter},
+};
+
+/// Dynamic Tdf value which can represent any of the values
+/// from the Blaze packet system
+#[derive(Debug, Clone, PartialEq)]
+pub enum TdfValue {
+    /// Variable length integer value
+    VarInt(u64),
+    /// String value
+    String(String),
+    /// Blob of bytes
+    Blob(Vec<u8>),
+    /// Group of tagged fields
+    Group {
+        /// Whether the group starts with the 2 byte
+        two: bool,
+        /// The fields within the group
+        fields: Vec<TdfField>,
+    },
+    /// List of values
+    List {
+        /// The type of the values in the list
+        ty: TdfType,
+        /// The values in the list
+        values: Vec<TdfValue>,
+    },
+    /// Map of key value pairs
+    Map {
+        /// The type of the keys in the map
+        key_ty: TdfType,
+        /// The type of the values in the map
+        value_ty: TdfType,
+        /// The entries in the map
+        entries: Vec<(TdfValue, TdfValue)>,
+    },
+    /// Union value
+    Union {
+        /// The key of the union
+        key: u8,
+        /// The tagged value of the union (None if unset)
+        value: Option<Box<TdfField>>,
+    },
+    /// List of variable length integers
+    VarIntList(Vec<u64>),
+    /// Pair of two var int values
+    Pair(u64, u64),
+    /// Three var int values
+    Triple(u64, u64, u64),
+    /// f32 value
+    Float(f32),
+    /// Time value in microseconds
+    TimeValue(u64),
+    /// Generic value with its type id and tagged value (None if unset)
+    Generic(Option<(u64, Box<TdfField>)>),
+}
+
+impl TdfValue {
+    /// Sorts the fields of any groups within this value by tag
+    /// (See [TdfFields::normalize])
+    pub fn normalize(&mut self) {
+        match self {
+            TdfValue::Group { fields, .. } => normalize_fields(fields),
+            TdfValue::List { values, .. } => values.iter_mut().for_each(TdfValue::normalize),
+            TdfValue::Map { entries, .. } => entries.iter_mut().for_each(|(key, value)| {
+                key.normalize();
+                value.normalize();
+            }),
+            TdfValue::Union {
+                value: Some(field), ..
+            }
+            | TdfValue::Generic(Some((_, field))) => field.value.normalize(),
+            _ => {}
+        }
+    }
+
+    /// Returns the type of this value
+    pub fn ty(&self) -> TdfType {
+        match self {
+            TdfValue::VarInt(_) => TdfType::VarInt,
+            TdfValue::String(_) => TdfType::String,
+            TdfValue::Blob(_) => TdfType::Blob,
+            TdfValue::Group { .. } => TdfType::Group,
+            TdfValue::List { .. } => TdfType::List,
+            TdfValue::Map { .. } => TdfType::Map,
+            TdfValue::Union { .. } => TdfType::Union,
+            TdfValue::VarIntList(_) => TdfType::VarIntList,
+            TdfValue::Pair(..) => TdfType::Pair,
+            TdfValue::Triple(..) => TdfType::Triple,
+            TdfValue::Float(_) => TdfType::Float,
+            TdfValue::TimeValue(_) => TdfType::TimeValue,
+            TdfValue::Generic(_) => TdfType::Generic,
+        }
+    }
+
+    /// Decodes a value of the provided type from the reader
+    ///
+    /// `reader` The reader to decode from
+    /// `ty`     The type of value to decode
+    pub fn decode_type(reader: &mut TdfReader, ty: TdfType) -> DecodeResult<Self> {
+        Ok(match ty {
+            TdfType::VarInt => TdfValue::VarInt(reader.read_u64()?),
+            TdfType::String => TdfValue::String(reader.read_string()?),
+            TdfType::Blob => TdfValue::Blob(reader.read_blob()?.to_vec()),
+            TdfType::Group => {
+                let mut two = false;
+                if reader.buffer.get(reader.cursor) == Some(&2) {
+                    two = true;
+                    reader.cursor += 1;
+                }
+                let mut fields = Vec::new();
+                loop {
+                    if reader.read_byte()? == 0 {
+                        break;
+                    }
+                    reader.cursor -= 1;
+                    fields.push(TdfField::decode(reader)?);
+                }
+                TdfValue::Group { two, fields }
+            }
+            TdfType::List => {
+                let ty = reader.read_type()?;
+                let length = reader.read_usize()?;
+                let mut values = Vec::new();
+                for _ in 0..length {
+                    values.push(TdfValue::decode_type(reader, ty)?);
+                }
+                TdfValue::List { ty, values }
+            }
+            TdfType::Map => {
+                let key_ty = reader.read_type()?;
+                let value_ty = reader.read_type()?;
+                let length = reader.read_usize()?;
+                let mut entries = Vec::new();
+                for _ in 0..length {
+                    let key = TdfValue::decode_type(reader, key_ty)?;
+                    let value = TdfValue::decode_type(reader, value_ty)?;
+                    entries.push((key, value));
+                }
+                TdfValue::Map {
+                    key_ty,
+                    value_ty,
+                    entries,
+                }
+            }
+            TdfType::Union => {
+                let key = reader.read_byte()?;
+                let value = if key == UNION_UNSET {
+                    None
+                } else {
+                    Some(Box::new(TdfField::decode(reader)?))
+                };
+                TdfValue::Union { key, value }
+            }
+            TdfType::VarIntList => {
+                let length = reader.read_usize()?;
+                let mut values = Vec::new();
+                for _ in 0..length {
+                    values.push(reader.read_u64()?);
+                }
+                TdfValue::VarIntList(values)
+            }
+            TdfType::Pair => TdfValue::Pair(reader.read_u64()?, reader.read_u64()?),
+            TdfType::Triple => {
+                TdfValue::Triple(reader.read_u64()?, reader.read_u64()?, reader.read_u64()?)
+            }
+            TdfType::Float => TdfValue::Float(reader.read_f32()?),
+            TdfType::TimeValue => TdfValue::TimeValue(reader.read_u64()?),
+            TdfType::Generic => {
+                let value = if reader.read_byte()? == 0 {
+                    None
+                } else {
+                    let id = reader.read_u64()?;
+                    Some((id, Box::new(TdfField::decode(reader)?)))
+                };
+                reader.skip_group_end()?;
+                TdfValue::Generic(value)
+            }
+        })
+    }
+}
+
+impl Encodable for TdfValue {
+    fn encode(&self, output: &mut TdfWriter) {
+        match self {
+            TdfValue::VarInt(value) => output.write_u64(*value),
+            TdfValue::String(value) => output.write_str(value),
+            TdfValue::Blob(value) => {
+                output.write_usize(value.len());
+                output.write_slice(value);
+            }
+            TdfValue::Group { two, fields } => {
+                if *two {
+                    output.write_byte(2);
+                }
+                fields.iter().for_each(|field| field.encode(output));
+                output.tag_group_end();
+            }
+            TdfValue::List { ty, values } => {
+                output.write_type(*ty);
+                output.write_usize(values.len());
+                values.iter().for_each(|value| value.encode(output));
+            }
+            TdfValue::Map {
+                key_ty,
+                value_ty,
+                entries,
+            } => {
+                output.write_map_header(*key_ty, *value_ty, entries.len());
+                for (key, value) in entries {
+                    key.encode(output);
+                    value.encode(output);
+                }
+            }
+            TdfValue::Union { key, value } => match value {
+                Some(value) => {
+                    output.write_byte(*key);
+                    value.encode(output);
+                }
+                None => output.write_byte(UNION_UNSET),
+            },
+            TdfValue::VarIntList(values) => {
+                output.write_usize(values.len());
+                values.iter().for_each(|value| output.write_u64(*value));
+            }
+            TdfValue::Pair(a, b) => {
+                output.write_u64(*a);
+                output.write_u64(*b);
+            }
+            TdfValue::Triple(a, b, c) => {
+                output.write_u64(*a);
+                output.write_u64(*b);
+                output.write_u64(*c);
+            }
+            TdfValue::Float(value) => output.write_f32(*value),
+            TdfValue::TimeValue(value) => output.write_u64(*value),
+            TdfValue::Generic(value) => {
+                match value {
+                    Some((id, value)) => {
+                        output.write_byte(1);
+                        output.write_u64(*id);
+                        value.encode(output);
+                    }
+                    None => output.write_byte(0),
+                }
+                output.tag_group_end();
+            }
+        }
+    }
+
+    /// Checks that list and map values match their declared types
+    /// and that group and union tags can be encoded
+    fn try_encode(&self, output: &mut TdfWriter) -> EncodeResult<()> {
+        match self {
+            TdfValue::Group { two, fields } => {
+                if *two {
+                    output.write_byte(2);
+                }
+                for field in fields {
+                    field.try_encode(output)?;
+                }
+                output.tag_group_end();
+            }
+            TdfValue::List { ty, values } => {
+                output.write_type(*ty);
+                output.write_usize(values.len());
+                for value in values {
+                    expect_type(*ty, value)?;
+                    value.try_encode(output)?;
+                }
+            }
+            TdfValue::Map {
+                key_ty,
+                value_ty,
+                entries,
+            } => {
+                output.write_map_header(*key_ty, *value_ty, entries.len());
+                for (key, value) in entries {
+                    expect_type(*key_ty, key)?;
+                    key.try_encode(output)?;
+                    expect_type(*value_ty, value)?;
+                    value.try_encode(output)?;
+                }
+            }
+            TdfValue::Union {
+                key,
+                value: Some(value),
+            } => {
+                output.write_byte(*key);
+                value.try_encode(output)?;
+            }
+            TdfValue::Generic(Some((id, value))) => {
+                output.write_byte(1);
+                output.write_u64(*id);
+                value.try_encode(output)?;
+                output.tag_group_end();
+            }
+            value => value.encode(output),
+        }
+        Ok(())
+    }
+
+    fn size_hint(&self) -> usize {
+        match self {
+            TdfValue::VarInt(value) => var_int_length(*value),
+            TdfValue::String(value) => str_size(value),
+            TdfValue::Blob(value) => var_int_length(value.len() as u64) + value.len(),
+            TdfValue::Group { two, fields } => {
+                *two as usize + fields.iter().map(TdfField::size_hint).sum::<usize>() + 1
+            }
+            TdfValue::List { values, .. } => {
+                1 + var_int_length(values.len() as u64)
+                    + values.iter().map(TdfValue::size_hint).sum::<usize>()
+            }
+            TdfValue::Map { entries, .. } => {
+                2 + var_int_length(entries.len() as u64)
+                    + (entries.iter())
+                        .map(|(key, value)| key.size_hint() + value.size_hint())
+                        .sum::<usize>()
+            }
+            TdfValue::Union { value, .. } => {
+                1 + value.as_ref().map_or(0, |value| value.size_hint())
+            }
+            TdfValue::VarIntList(values) => {
+                var_int_length(values.len() as u64)
+                    + values.iter().copied().map(var_int_length).sum::<usize>()
+            }
+            TdfValue::Pair(a, b) => var_int_length(*a) + var_int_length(*b),
+            TdfValue::Triple(a, b, c) => {
+                var_int_length(*a) + var_int_length(*b) + var_int_length(*c)
+            }
+            TdfValue::Float(_) => 4,
+            TdfValue::TimeValue(value) => var_int_length(*value),
+            TdfValue::Generic(value) => {
+                // Set byte, optional id and value, and the end byte
+                2 + value
+                    .as_ref()
+                    .map_or(0, |(id, value)| var_int_length(*id) + value.size_hint())
+            }
+        }
+    }
+}
+
+/// Ensures the provided value is of the expected type
+///
+/// `expected` The expected value type
+/// `value`    The value to check
+fn expect_type(expected: TdfType, value: &TdfValue) -> EncodeResult<()> {
+    let actual = value.ty();
+    if actual != expected {
+        return Err(EncodeError::InvalidType { expected, actual });
+    }
+    Ok(())
+}
+
+/// Tagged dynamic value
+#[derive(Debug, Clone, PartialEq)]
+pub struct TdfField {
+    /// The tag of the field
+    pub tag: Tag,
+    /// The value of the field
+    pub value: TdfValue,
+}
+
+impl Encodable for TdfField {
+    fn encode(&self, output: &mut TdfWriter) {
+        output.tag(&self.tag.0, self.value.ty());
+        self.value.encode(output);
+    }
+
+    fn try_encode(&self, output: &mut TdfWriter) -> EncodeResult<()> {
+        output.try_tag(&self.tag.0, self.value.ty())?;
+        self.value.try_encode(output)
+    }
+
+    fn size_hint(&self) -> usize {
+        // Tag followed by the value
+        4 + self.value.size_hint()
+    }
+}
+
+impl Decodable for TdfField {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        let tag = reader.read_tag()?;
+        let value = TdfValue::decode_type(reader, tag.ty)?;
+        Ok(Self {
+            tag: tag.tag,
+            value,
+        })
+    }
+}
+
+/// Collection of dynamic fields making up the contents of a packet.
+/// Decoding reads fields until the end of the reader
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TdfFields(pub Vec<TdfField>);
+
+impl TdfFields {
+    /// Finds the value of the field with the provided tag
+    ///
+    /// `tag` The tag of the field
+    pub fn get(&self, tag: &[u8]) -> Option<&TdfValue> {
+        let tag = Tag::from(tag);
+        self.0
+            .iter()
+            .find(|field| field.tag == tag)
+            .map(|field| &field.value)
+    }
+
+    /// Finds a mutable reference to the value of the field with
+    /// the provided tag
+    ///
+    /// `tag` The tag of the field
+    pub fn get_mut(&mut self, tag: &[u8]) -> Option<&mut TdfValue> {
+        let tag = Tag::from(tag);
+        self.0
+            .iter_mut()
+            .find(|field| field.tag == tag)
+            .map(|field| &mut field.value)
+    }
+
+    /// Sorts the fields by tag, including the fields of any nested
+    /// groups, so that contents with the same fields in a different
+    /// order compare as equal
+    pub fn normalize(&mut self) {
+        normalize_fields(&mut self.0);
+    }
+}
+
+/// Sorts the provided fields by tag along with the fields
+/// nested within their values
+///
+/// `fields` The fields to sort
+fn normalize_fields(fields: &mut [TdfField]) {
+    fields.sort_by_key(|field| field.tag.0);
+    fields.iter_mut().for_each(|field| field.value.normalize());
+}
+
+impl Encodable for TdfFields {
+    fn encode(&self, output: &mut TdfWriter) {
+        self.0.iter().for_each(|field| field.encode(output));
+    }
+
+    fn try_encode(&self, output: &mut TdfWriter) -> EncodeResult<()> {
+        self.0.iter().try_for_each(|field| field.try_encode(output))
+    }
+
+    fn size_hint(&self) -> usize {
+        self.0.iter().map(TdfField::size_hint).sum()
+    }
+}
+
+impl Decodable for TdfFields {
+    fn decode(reader: &mut TdfReader) -> DecodeResult<Self> {
+        let mut fields = Vec::new();
+        while !reader.is_empty() {
+            fields.push(TdfField::decode(reader)?);
+        }
+        Ok(Self(fields))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TdfField, TdfFields, TdfValue};
+    use crate::{
+        codec::{Decodable, Encodable},
+        error::EncodeError,
+        reader::TdfReader,
+        tag::{Tag, TdfType},
+        types::{NetworkAddress, TdfMap, Union},
+        writer::TdfWriter,
+    };
+
+    /// Tests decoding arbitrary contents into dynamic values
+    /// and re-encoding them to the same bytes
+    #[test]
+    fn test_round_trip() {
+        let mut map = TdfMap::<String, u32>::new();
+        map.insert("A", 1u32);
+        map.insert("B", 2u32);
+
+        let mut writer = TdfWriter::default();
+        writer.tag_u32(b"INT", 12);
+        writer.tag_str(b"STR", "Test");
+        writer.group(b"GRP", |writer| {
+            writer.tag_value(b"ADDR", &NetworkAddress::Unset);
+            writer.tag_pair(b"PAIR", (1u32, 2u32));
+        });
+        writer.tag_value(b"MAP", &map);
+        writer.tag_value(b"UNI", &Union::set(0x2, b"VALU", 5u32));
+        writer.tag_value(b"LIST", &vec![1u32, 2, 3]);
+        writer.tag_list_empty(b"EMPT", TdfType::String);
+
+        let fields = TdfFields::decode(&mut TdfReader::new(&writer.buffer)).unwrap();
+        assert_eq!(fields.get(b"INT"), Some(&TdfValue::VarInt(12)));
+        assert_eq!(
+            fields.get(b"STR"),
+            Some(&TdfValue::String("Test".to_string()))
+        );
+
+        assert_eq!(fields.encode_bytes(), writer.buffer);
+    }
+    /// Tests the fallible encoding rejects values that don't match
+    /// their declared type along with invalid tags
+    #[test]
+    fn test_try_encode() {
+        let list = TdfValue::List {
+            ty: TdfType::VarInt,
+            values: vec![TdfValue::VarInt(1), TdfValue::String("A".to_string())],
+        };
+        assert!(matches!(
+            list.try_encode_bytes(),
+            Err(EncodeError::InvalidType {
+                expected: TdfType::VarInt,
+                actual: TdfType::String
+            })
+        ));
+
+        let fields = TdfFields(vec![TdfField {
+            tag: Tag::from(b"test"),
+            value: TdfValue::VarInt(1),
+        }]);
+        assert!(matches!(
+            fields.try_encode_bytes(),
+            Err(EncodeError::InvalidTag { .. })
+        ));
+
+        let fields = TdfFields(vec![TdfField {
+            tag: Tag::from(b"TEST"),
+            value: TdfValue::VarInt(1),
+        }]);
+        assert_eq!(fields.try_encode_bytes().unwrap(), fields.encode_bytes());
+    }
+}
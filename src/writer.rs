@@ -3,8 +3,9 @@
 
 use crate::{
     codec::{Encodable, ValueType},
+    error::{EncodeError, EncodeResult},
     tag::TdfType,
-    types::{VarInt, UNION_UNSET},
+    types::{MapKey, VarInt, UNION_UNSET},
 };
 
 /// Writer implementation for writing values to an underlying buffer
@@ -16,27 +17,34 @@ pub struct TdfWriter {
     pub buffer: Vec<u8>,
 }
 
-/// Macro for implementing VarInt encoding for a specific number type
-/// to prevent the need for casting up to a u64 for the u16 and u32 types
-macro_rules! impl_encode_var {
-    ($value:ident, $output:ident) => {
-        if $value < 64 {
-            $output.write_byte($value as u8);
-            return;
-        }
-        let mut byte: u8 = (($value & 63) as u8) | 128;
-        $output.write_byte(byte);
-        let mut cur_shift = $value >> 6;
-        while cur_shift >= 128 {
-            byte = ((cur_shift & 127) | 128) as u8;
-            cur_shift >>= 7;
-            $output.write_byte(byte);
-        }
-        $output.write_byte(cur_shift as u8)
-    };
+/// The maximum number of bytes a u64 uses when encoded
+/// using the VarInt encoding (6 bits + 9 * 7 bits)
+const MAX_VAR_INT_LENGTH: usize = 10;
+
+/// Returns the number of bytes the provided value uses
+/// when encoded using the VarInt encoding
+///
+/// `value` The value to get the length of
+pub const fn var_int_length(value: u64) -> usize {
+    let bits = u64::BITS - value.leading_zeros();
+    if bits <= 6 {
+        1
+    } else {
+        1 + (bits - 6).div_ceil(7) as usize
+    }
 }
 
 impl TdfWriter {
+    /// Creates a new writer with a buffer that can hold at
+    /// least the provided number of bytes without reallocating
+    ///
+    /// `capacity` The number of bytes to allocate
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: Vec::with_capacity(capacity),
+        }
+    }
+
     /// Writes a single byte to the underlying buffer. This just
     /// appends the byte to the buffer.
     ///
@@ -63,6 +71,32 @@ impl TdfWriter {
         self.write_byte(ty as u8);
     }
 
+    /// Writes a tag value to the underlying buffer after checking
+    /// that the tag can be represented. Tags must be 1 to 4 bytes
+    /// (ignoring trailing zero bytes) in the range 0x40 to 0x5F which
+    /// covers the uppercase letters. Other bytes lose information
+    /// when encoded
+    ///
+    /// `tag`        The tag bytes to write
+    /// `value_type` The value type for the tag
+    pub fn try_tag(&mut self, tag: &[u8], value_type: TdfType) -> EncodeResult<()> {
+        let length = tag
+            .iter()
+            .rposition(|byte| *byte != 0)
+            .map_or(0, |index| index + 1);
+        let label = &tag[..length];
+        if label.is_empty()
+            || label.len() > 4
+            || !label.iter().all(|byte| matches!(byte, 0x40..=0x5F))
+        {
+            return Err(EncodeError::InvalidTag {
+                tag: String::from_utf8_lossy(tag).to_string(),
+            });
+        }
+        self.tag(label, value_type);
+        Ok(())
+    }
+
     /// Writes a tag vvalue to the underlying buffer
     ///
     /// `tag`        The tag bytes to write
@@ -196,6 +230,15 @@ impl TdfWriter {
         self.tag(tag, TdfType::Group);
     }
 
+    /// Writes a group opening tag followed by the 0x02 byte that
+    /// some encoders use to prefix the group fields
+    ///
+    /// `tag` The tag to write
+    pub fn tag_group_2(&mut self, tag: &[u8]) {
+        self.tag(tag, TdfType::Group);
+        self.buffer.push(2);
+    }
+
     /// Writes the zero value that indicates the end of a group
     pub fn tag_group_end(&mut self) {
         self.buffer.push(0);
@@ -216,6 +259,21 @@ impl TdfWriter {
         self.tag_group_end();
     }
 
+    /// Version of [TdfWriter::group] for groups with fields
+    /// prefixed by the 0x02 byte
+    ///
+    /// `tag` The tag to write
+    /// `gr`  The group closure
+    #[inline]
+    pub fn group_2<F>(&mut self, tag: &[u8], gr: F)
+    where
+        F: FnOnce(&mut Self),
+    {
+        self.tag_group_2(tag);
+        gr(self);
+        self.tag_group_end();
+    }
+
     /// Writes a new tag indicating that a list is begining and writes the list
     /// type and length
     ///
@@ -263,6 +321,46 @@ impl TdfWriter {
         self.tag_union_start(tag, UNION_UNSET);
     }
 
+    /// Writes a new tag where the value is a time value
+    /// in microseconds
+    ///
+    /// `tag`   The tag to write
+    /// `value` The number of microseconds
+    pub fn tag_time_value(&mut self, tag: &[u8], value: u64) {
+        self.tag(tag, TdfType::TimeValue);
+        self.write_u64(value);
+    }
+
+    /// Writes a new generic tag with its type id and value
+    ///
+    /// `tag`       The tag to write
+    /// `id`        The type id of the value
+    /// `value_tag` The tag for the value
+    /// `value`     The value to write
+    pub fn tag_generic_value<C: Encodable + ValueType>(
+        &mut self,
+        tag: &[u8],
+        id: u64,
+        value_tag: &[u8],
+        value: &C,
+    ) {
+        self.tag(tag, TdfType::Generic);
+        self.buffer.push(1);
+        self.write_u64(id);
+        self.tag(value_tag, C::value_type());
+        value.encode(self);
+        self.tag_group_end();
+    }
+
+    /// Writes a new tag indicating a generic with no value
+    ///
+    /// `tag` The tag to write
+    pub fn tag_generic_unset(&mut self, tag: &[u8]) {
+        self.tag(tag, TdfType::Generic);
+        self.buffer.push(0);
+        self.tag_group_end();
+    }
+
     /// Writes a tag and its value where the value implements ValueType
     ///
     /// `tag`   The tag to write
@@ -272,6 +370,36 @@ impl TdfWriter {
         value.encode(self);
     }
 
+    /// Fallible version of [TdfWriter::tag_value] which validates the
+    /// tag and uses the fallible encoding of the value
+    ///
+    /// `tag`   The tag to write
+    /// `value` The value to write
+    pub fn try_tag_value<C: Encodable + ValueType>(
+        &mut self,
+        tag: &[u8],
+        value: &C,
+    ) -> EncodeResult<()> {
+        self.try_tag(tag, C::value_type())?;
+        value.try_encode(self)
+    }
+
+    /// Writes a tag and its value if the provided optional value is
+    /// present. Optional values are represented by the absence of
+    /// their tag so nothing is written if the value is None.
+    ///
+    /// Optional values can be read using [`TdfReader::try_tag`]
+    ///
+    /// `tag`   The tag to write
+    /// `value` The optional value to write
+    ///
+    /// [`TdfReader::try_tag`]: crate::reader::TdfReader::try_tag
+    pub fn tag_option<C: Encodable + ValueType>(&mut self, tag: &[u8], value: &Option<C>) {
+        if let Some(value) = value {
+            self.tag_value(tag, value);
+        }
+    }
+
     /// Writes a tag for indiciating a list with no contents
     ///
     /// `tag` The tag to write
@@ -317,7 +445,7 @@ impl TdfWriter {
     /// `values` The tuples of key value pairs to write
     pub fn tag_map_tuples<K, V>(&mut self, tag: &[u8], values: &[(K, V)])
     where
-        K: Encodable + ValueType,
+        K: MapKey + Encodable + ValueType,
         V: Encodable + ValueType,
     {
         self.tag_map_start(tag, K::value_type(), V::value_type(), values.len());
@@ -373,16 +501,11 @@ impl TdfWriter {
     ///
     /// `value` The value to write
     pub fn write_u8(&mut self, value: u8) {
-        // Values < 64 are directly appended to buffer
-        if value < 64 {
-            self.buffer.push(value);
-            return;
-        }
-        self.buffer.push((value & 63) | 128);
-        self.buffer.push(value >> 6);
+        self.write_u64(value as u64);
     }
 
-    /// Writes a u16 value using the VarInt encoding
+    /// Writes a u16 value using the VarInt encoding. Values
+    /// above 63 are always written using three bytes
     ///
     /// `value` The value to write
     pub fn write_u16(&mut self, value: u16) {
@@ -390,34 +513,49 @@ impl TdfWriter {
             self.buffer.push(value as u8);
             return;
         }
-        let mut byte: u8 = ((value & 63) as u8) | 128;
-        let mut shift: u16 = value >> 6;
-        self.buffer.push(byte);
-        byte = ((shift & 127) | 128) as u8;
-        shift >>= 7;
-        self.buffer.push(byte);
-        self.buffer.push(shift as u8);
+        self.buffer.extend_from_slice(&[
+            ((value & 63) as u8) | 128,
+            (((value >> 6) & 127) as u8) | 128,
+            (value >> 13) as u8,
+        ]);
     }
 
     /// Writes a u32 value using the VarInt encoding
     ///
     /// `value` The value to write
     pub fn write_u32(&mut self, value: u32) {
-        impl_encode_var!(value, self);
+        self.write_u64(value as u64);
     }
 
-    /// Writes a u64 value using the VarInt encoding
+    /// Writes a u64 value using the VarInt encoding. The value is
+    /// encoded onto the stack and appended to the buffer at once
     ///
     /// `value` The value to write
     pub fn write_u64(&mut self, value: u64) {
-        impl_encode_var!(value, self);
+        // Values < 64 are directly appended to buffer
+        if value < 64 {
+            self.buffer.push(value as u8);
+            return;
+        }
+
+        let mut bytes = [0u8; MAX_VAR_INT_LENGTH];
+        bytes[0] = ((value & 63) as u8) | 128;
+        let mut rest: u64 = value >> 6;
+        let mut length: usize = 1;
+        while rest >= 128 {
+            bytes[length] = ((rest & 127) as u8) | 128;
+            rest >>= 7;
+            length += 1;
+        }
+        bytes[length] = rest as u8;
+        self.buffer.extend_from_slice(&bytes[..=length]);
     }
 
     /// Writes a usize value using the VarInt encoding
     ///
     /// `value` The value to write
     pub fn write_usize(&mut self, value: usize) {
-        impl_encode_var!(value, self);
+        self.write_u64(value as u64);
     }
 
     /// Writes a string to the underlying buffer. The bytes
@@ -465,6 +603,13 @@ impl TdfWriter {
     }
 }
 
+/// Creates a writer which appends to the provided buffer
+impl From<Vec<u8>> for TdfWriter {
+    fn from(buffer: Vec<u8>) -> Self {
+        Self { buffer }
+    }
+}
+
 /// Implementation for converting tdf writer into its underlying buffer with from
 impl From<TdfWriter> for Vec<u8> {
     fn from(value: TdfWriter) -> Self {
@@ -474,8 +619,15 @@ impl From<TdfWriter> for Vec<u8> {
 
 #[cfg(test)]
 mod test {
-    use super::TdfWriter;
-    use crate::{codec::Encodable, reader::TdfReader, tag::TdfType, types::UNION_UNSET};
+    use super::{var_int_length, TdfWriter};
+    use crate::{
+        codec::{Decodable, Encodable},
+        error::EncodeError,
+        reader::TdfReader,
+        tag::TdfType,
+        types::UNION_UNSET,
+        value::{TdfFields, TdfValue},
+    };
 
     /// Test for ensuring some common tags of different
     /// length are encoded to the correct values. The tags
@@ -542,7 +694,7 @@ mod test {
     /// Tests writing all the type values
     #[test]
     fn test_write_type() {
-        const TYPES: [TdfType; 11] = [
+        const TYPES: [TdfType; 13] = [
             TdfType::VarInt,
             TdfType::String,
             TdfType::Blob,
@@ -554,6 +706,8 @@ mod test {
             TdfType::Pair,
             TdfType::Triple,
             TdfType::Float,
+            TdfType::TimeValue,
+            TdfType::Generic,
         ];
         let mut writer = TdfWriter::default();
         for ty in TYPES {
@@ -734,6 +888,62 @@ mod test {
         assert_eq!(writer.buffer[4], 0);
     }
 
+    /// Tests groups prefixed with the 0x02 byte can be written
+    /// and are read the same as other groups
+    #[test]
+    fn test_tag_group_2() {
+        let mut writer = TdfWriter::default();
+        writer.group_2(b"TEST", |writer| writer.tag_u8(b"VAL", 5));
+        writer.tag_u8(b"NEXT", 1);
+
+        assert_eq!(writer.buffer[3], TdfType::Group as u8);
+        assert_eq!(writer.buffer[4], 2);
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        reader.until_tag(b"TEST", TdfType::Group).unwrap();
+        reader.skip_group_2().unwrap();
+        assert_eq!(reader.tag::<u8>(b"VAL").unwrap(), 5);
+        reader.skip_group_end().unwrap();
+        assert_eq!(reader.tag::<u8>(b"NEXT").unwrap(), 1);
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        reader.skip().unwrap();
+        assert_eq!(reader.tag::<u8>(b"NEXT").unwrap(), 1);
+    }
+
+    /// Tests the newer time value and generic types can be
+    /// written and then skipped or decoded
+    #[test]
+    fn test_extended_types() {
+        let mut writer = TdfWriter::default();
+        writer.tag_time_value(b"TIME", 1500);
+        writer.tag_generic_value(b"GEN", 12, b"VAL", &String::from("Test"));
+        writer.tag_generic_unset(b"NONE");
+        writer.tag_u8(b"NEXT", 1);
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        for _ in 0..3 {
+            reader.skip().unwrap();
+        }
+        assert_eq!(reader.tag::<u8>(b"NEXT").unwrap(), 1);
+
+        let fields = TdfFields::decode(&mut TdfReader::new(&writer.buffer)).unwrap();
+        assert_eq!(fields.get(b"TIME"), Some(&TdfValue::TimeValue(1500)));
+        let Some(TdfValue::Generic(Some((id, value)))) = fields.get(b"GEN") else {
+            panic!("Expected generic value");
+        };
+        assert_eq!(*id, 12);
+        assert_eq!(value.value, TdfValue::String("Test".to_string()));
+        assert_eq!(fields.get(b"NONE"), Some(&TdfValue::Generic(None)));
+        assert_eq!(fields.encode_bytes(), writer.buffer);
+        assert_eq!(fields.size_hint(), writer.buffer.len());
+
+        let mut out = String::new();
+        TdfReader::new(&writer.buffer).stringify(&mut out).unwrap();
+        assert!(out.contains("1500us"));
+        assert!(out.contains("Generic(\"VAL\", 12, \"Test\")"));
+    }
+
     /// Tests tagging a union
     #[test]
     fn test_tag_union() {
@@ -758,6 +968,21 @@ mod test {
         assert_eq!(writer.buffer[9], 15);
     }
 
+    /// Tests writing optional values and reading them back
+    #[test]
+    fn test_tag_option() {
+        let mut writer = TdfWriter::default();
+        writer.tag_option(b"A", &Some(12u8));
+        writer.tag_option::<u8>(b"B", &None);
+        writer.tag_value(b"C", &1u8);
+        assert_eq!(writer.buffer.len(), 10);
+
+        let mut reader = TdfReader::new(&writer.buffer);
+        assert_eq!(reader.try_tag::<u8>(b"A").unwrap(), Some(12));
+        assert_eq!(reader.try_tag::<u8>(b"B").unwrap(), None);
+        assert_eq!(reader.tag::<u8>(b"C").unwrap(), 1);
+    }
+
     /// Tests tagging for value types
     #[test]
     fn test_tag_value() {
@@ -768,6 +993,27 @@ mod test {
         assert_eq!(writer.buffer[4], 12);
     }
 
+    /// Tests the tag validation of the fallible tag writing
+    #[test]
+    fn test_try_tag() {
+        let mut writer = TdfWriter::default();
+        writer.try_tag(b"TEST", TdfType::VarInt).unwrap();
+        writer.try_tag(b"IP\0\0", TdfType::VarInt).unwrap();
+
+        let mut expected = TdfWriter::default();
+        expected.tag(b"TEST", TdfType::VarInt);
+        expected.tag(b"IP", TdfType::VarInt);
+        assert_eq!(writer.buffer, expected.buffer);
+
+        for tag in [&b""[..], b"test", b"B64", b"TOOLONG"] {
+            assert!(matches!(
+                writer.try_tag(tag, TdfType::VarInt),
+                Err(EncodeError::InvalidTag { .. })
+            ));
+        }
+        assert_eq!(writer.buffer, expected.buffer);
+    }
+
     /// Tests writing an empty list
     #[test]
     fn test_tag_list_empty() {
@@ -969,4 +1215,63 @@ mod test {
         writer.clear();
         assert_eq!(writer.buffer.len(), 0)
     }
+
+    /// Tests VarInt values around every bit width boundary are
+    /// encoded using the minimum number of bytes and decode back
+    /// to the same value for every width they fit in
+    #[test]
+    fn test_var_int_widths() {
+        let mut writer = TdfWriter::default();
+        for bits in 0..=u64::BITS {
+            let boundary: u64 = 1u64.checked_shl(bits).unwrap_or(0).wrapping_sub(1);
+            for value in [
+                boundary.saturating_sub(1),
+                boundary,
+                boundary.saturating_add(1),
+            ] {
+                writer.write_u64(value);
+                let significant = u64::BITS - value.leading_zeros();
+                let expected = 1 + significant.saturating_sub(6).div_ceil(7) as usize;
+                assert_eq!(writer.buffer.len(), expected, "Length of {value}");
+                assert_eq!(var_int_length(value), expected);
+
+                let mut reader = TdfReader::new(&writer.buffer);
+                assert_eq!(reader.read_u64().unwrap(), value);
+                assert!(reader.is_empty());
+                if let Ok(value) = u32::try_from(value) {
+                    let mut reader = TdfReader::new(&writer.buffer);
+                    assert_eq!(reader.read_u32().unwrap(), value);
+                }
+                if let Ok(value) = u16::try_from(value) {
+                    let mut reader = TdfReader::new(&writer.buffer);
+                    assert_eq!(reader.read_u16().unwrap(), value);
+                    writer.clear();
+                    writer.write_u16(value);
+                    let expected = if value < 64 { 1 } else { 3 };
+                    assert_eq!(writer.buffer.len(), expected, "Length of u16 {value}");
+                    assert_eq!(value.size_hint(), expected);
+                    let mut reader = TdfReader::new(&writer.buffer);
+                    assert_eq!(reader.read_u16().unwrap(), value);
+                }
+                writer.clear();
+            }
+        }
+    }
+
+    /// Pins the encoded bytes for the maximum values of each
+    /// VarInt width
+    #[test]
+    fn test_var_int_max_bytes() {
+        let mut writer = TdfWriter::default();
+        writer.write_u16(u16::MAX);
+        assert_eq!(writer.buffer, [0xBF, 0xFF, 0x07]);
+
+        writer.clear();
+        writer.write_u16(64);
+        assert_eq!(writer.buffer, [0x80, 0x81, 0x00]);
+
+        writer.clear();
+        writer.write_u32(u32::MAX);
+        assert_eq!(writer.buffer, [0xBF, 0xFF, 0xFF, 0xFF, 0x1F]);
+    }
 }